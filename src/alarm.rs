@@ -0,0 +1,107 @@
+use tokio::process::Command;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+use crate::timer::Phase;
+
+/// Name of the transient systemd timer showing the end of the current phase
+const UNIT: &str = "tomat-phase-end";
+
+/// Arguments for `systemd-run` to register a timer firing in `seconds`
+fn register_args(phase: &Phase, seconds: u64) -> Vec<String> {
+    vec![
+        "--user".to_string(),
+        format!("--unit={}", UNIT),
+        format!(
+            "--description=Tomat: {} ends",
+            phase.to_string().replace('_', " ")
+        ),
+        format!("--on-active={}s", seconds.max(1)),
+        "--timer-property=AccuracySec=1s".to_string(),
+        "--collect".to_string(),
+        "--quiet".to_string(),
+        "true".to_string(),
+    ]
+}
+
+/// Keeps the registered phase end up to date from a single long-lived task
+///
+/// Updates are applied one at a time, in order, and an update that arrives
+/// while an earlier one is still running replaces any other pending one, so a
+/// stale registration never overtakes a newer one.
+pub struct Alarm {
+    sender: watch::Sender<Option<(Phase, u64)>>,
+    task: JoinHandle<()>,
+}
+
+impl Alarm {
+    pub fn spawn() -> Self {
+        let (sender, mut receiver) = watch::channel(None);
+        let task = tokio::spawn(async move {
+            let mut registered = false;
+            while receiver.changed().await.is_ok() {
+                let alarm = receiver.borrow_and_update().clone();
+                if alarm.is_some() || registered {
+                    registered = alarm.is_some();
+                    sync(alarm).await;
+                }
+            }
+        });
+        Self { sender, task }
+    }
+
+    /// Register the phase ending at the given timestamp, or remove the
+    /// registration with None
+    pub fn set(&self, alarm: Option<(Phase, u64)>) {
+        self.sender.send_replace(alarm);
+    }
+
+    /// Remove the registration and wait until the task is done
+    pub async fn shutdown(self) {
+        self.sender.send_replace(None);
+        drop(self.sender);
+        let _ = self.task.await;
+    }
+}
+
+/// Register the end of the running phase with the systemd user manager, so
+/// that it shows up in `systemctl --user list-timers` and desktop tools that
+/// list timers, replacing any earlier registration
+///
+/// With None the registration is only removed. Systems without systemd are
+/// skipped silently.
+async fn sync(alarm: Option<(Phase, u64)>) {
+    let _ = Command::new("systemctl")
+        .args(["--user", "stop", &format!("{}.timer", UNIT)])
+        .output()
+        .await;
+
+    let Some((phase, finish)) = alarm else {
+        return;
+    };
+    let seconds = finish.saturating_sub(crate::timer::current_timestamp());
+    let _ = Command::new("systemd-run")
+        .args(register_args(&phase, seconds))
+        .output()
+        .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register_args() {
+        let args = register_args(&Phase::Work, 1500);
+        assert!(args.contains(&"--unit=tomat-phase-end".to_string()));
+        assert!(args.contains(&"--description=Tomat: work ends".to_string()));
+        assert!(args.contains(&"--on-active=1500s".to_string()));
+        let args = register_args(&Phase::LongBreak, 900);
+        assert!(args.contains(&"--description=Tomat: long break ends".to_string()));
+        assert_eq!(args.last().unwrap(), "true");
+
+        // A phase that is already over still gets a valid timer
+        let args = register_args(&Phase::Break, 0);
+        assert!(args.contains(&"--on-active=1s".to_string()));
+    }
+}
@@ -3,36 +3,272 @@ use rodio::{Decoder, Source};
 #[cfg(feature = "audio")]
 use std::io::Cursor;
 
-// Embed sound files at compile time (only when audio feature is enabled)
-#[cfg(feature = "audio")]
+use crate::config::{SoundConfig, SoundMode, SynthConfig};
+use crate::error::TomatError;
+
+// Embed sound files at compile time (only when embedded-sounds feature is enabled)
+#[cfg(feature = "embedded-sounds")]
 const WORK_TO_BREAK_SOUND: &[u8] = include_bytes!("../assets/sounds/work-to-break.wav");
-#[cfg(feature = "audio")]
+#[cfg(feature = "embedded-sounds")]
 const BREAK_TO_WORK_SOUND: &[u8] = include_bytes!("../assets/sounds/break-to-work.wav");
-#[cfg(feature = "audio")]
+#[cfg(feature = "embedded-sounds")]
 const WORK_TO_LONG_BREAK_SOUND: &[u8] = include_bytes!("../assets/sounds/work-to-long-break.wav");
 
+/// Sample rate of synthesized sounds
+#[cfg(any(feature = "audio", test))]
+const SYNTH_SAMPLE_RATE: u32 = 44100;
+
+/// Output settings from the `[sound]` section, applied to every sound
+#[cfg(feature = "audio")]
+#[derive(Debug, Clone, Default)]
+struct OutputSettings {
+    backend: Option<String>,
+    device: Option<String>,
+    buffer_size: Option<u32>,
+}
+
+#[cfg(feature = "audio")]
+static OUTPUT: std::sync::Mutex<Option<OutputSettings>> = std::sync::Mutex::new(None);
+
+/// How often to try opening the output for a sound before giving up on it
+#[cfg(feature = "audio")]
+const OPEN_ATTEMPTS: u64 = 3;
+
+/// Why the output could not be opened for the last sound, if it could not
+#[cfg(feature = "audio")]
+static LAST_OUTPUT_ERROR: std::sync::Mutex<Option<String>> = std::sync::Mutex::new(None);
+
 #[derive(Debug, Clone, Copy)]
 pub enum SoundType {
+    PrepareToWork,
     WorkToBreak,
     BreakToWork,
     WorkToLongBreak,
 }
 
+impl SoundType {
+    /// Name of the freedesktop theme sound played with `theme_sounds`
+    pub fn theme_name(self) -> &'static str {
+        match self {
+            SoundType::PrepareToWork => "message",
+            SoundType::WorkToBreak => "complete",
+            SoundType::BreakToWork => "bell",
+            SoundType::WorkToLongBreak => "alarm-clock-elapsed",
+        }
+    }
+}
+
+/// Use the backend, device and buffer size from the sound configuration for
+/// all sounds played from now on
+#[cfg(feature = "audio")]
+pub fn configure(config: &SoundConfig) {
+    let settings = OutputSettings {
+        backend: config.backend.clone(),
+        device: config.device.clone(),
+        buffer_size: config.buffer_size,
+    };
+    if let Ok(mut output) = OUTPUT.lock() {
+        *output = Some(settings);
+    }
+}
+
+/// Open an output sink, retrying with a growing delay
+///
+/// The output is opened afresh for every sound, so an audio server that is
+/// not up yet (e.g. PipeWire right after login) only costs the sounds played
+/// before it starts, and sound works again once it is running.
 #[cfg(feature = "audio")]
-pub fn play_embedded_sound(
-    sound_type: SoundType,
-    volume: f32,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let sound_data = match sound_type {
+fn open_sink() -> Result<rodio::stream::MixerDeviceSink, TomatError> {
+    let mut attempt = 1;
+    loop {
+        let result = try_open_sink();
+        if (result.is_ok() || attempt >= OPEN_ATTEMPTS)
+            && let Ok(mut last_error) = LAST_OUTPUT_ERROR.lock()
+        {
+            *last_error = result.as_ref().err().map(|e| e.to_string());
+        }
+        match result {
+            Ok(sink) => return Ok(sink),
+            Err(e) if attempt >= OPEN_ATTEMPTS => {
+                eprintln!("Failed to open audio output: {}", e);
+                return Err(e);
+            }
+            Err(_) => {
+                std::thread::sleep(std::time::Duration::from_secs(attempt));
+                attempt += 1;
+            }
+        }
+    }
+}
+
+/// Open an output sink, honouring the configured output settings
+#[cfg(feature = "audio")]
+fn try_open_sink() -> Result<rodio::stream::MixerDeviceSink, TomatError> {
+    let settings = OUTPUT
+        .lock()
+        .ok()
+        .and_then(|output| output.clone())
+        .unwrap_or_default();
+
+    if settings.backend.is_none() && settings.device.is_none() && settings.buffer_size.is_none() {
+        return rodio::stream::DeviceSinkBuilder::open_default_sink()
+            .map_err(|e| TomatError::Other(e.to_string()));
+    }
+
+    open_configured_sink(&settings)
+}
+
+#[cfg(feature = "audio")]
+fn open_configured_sink(
+    settings: &OutputSettings,
+) -> Result<rodio::stream::MixerDeviceSink, TomatError> {
+    use rodio::cpal::traits::HostTrait;
+
+    let host = match &settings.backend {
+        Some(name) => {
+            let id = rodio::cpal::available_hosts()
+                .into_iter()
+                .find(|id| id.name().eq_ignore_ascii_case(name))
+                .ok_or_else(|| format!("Unknown audio backend '{}'", name))?;
+            rodio::cpal::host_from_id(id).map_err(|e| TomatError::Other(e.to_string()))?
+        }
+        None => rodio::cpal::default_host(),
+    };
+
+    let device = match &settings.device {
+        Some(name) => host
+            .output_devices()
+            .map_err(|e| TomatError::Other(e.to_string()))?
+            .find(|device| device_matches(device, name))
+            .ok_or_else(|| format!("No audio output device named '{}'", name))?,
+        None => host
+            .default_output_device()
+            .ok_or("No default audio output device")?,
+    };
+
+    let mut builder = rodio::stream::DeviceSinkBuilder::from_device(device)
+        .map_err(|e| TomatError::Other(e.to_string()))?;
+    if let Some(frames) = settings.buffer_size {
+        builder = builder.with_buffer_size(rodio::cpal::BufferSize::Fixed(frames));
+    }
+    builder
+        .open_stream()
+        .map_err(|e| TomatError::Other(e.to_string()))
+}
+
+/// Match a device by its name or ID, e.g. "pipewire" or "alsa:pipewire"
+#[cfg(feature = "audio")]
+fn device_matches(device: &rodio::Device, name: &str) -> bool {
+    use rodio::DeviceTrait;
+
+    let described = device
+        .description()
+        .is_ok_and(|description| description.name().eq_ignore_ascii_case(name));
+    let identified = device
+        .id()
+        .is_ok_and(|id| id.1 == name || id.to_string() == name);
+    described || identified
+}
+
+#[cfg(not(feature = "audio"))]
+pub fn configure(_config: &SoundConfig) {
+    // Audio feature not enabled, do nothing
+}
+
+#[cfg(feature = "embedded-sounds")]
+fn embedded_sound_data(sound_type: SoundType) -> &'static [u8] {
+    match sound_type {
         SoundType::WorkToBreak => WORK_TO_BREAK_SOUND,
-        SoundType::BreakToWork => BREAK_TO_WORK_SOUND,
+        SoundType::PrepareToWork | SoundType::BreakToWork => BREAK_TO_WORK_SOUND,
         SoundType::WorkToLongBreak => WORK_TO_LONG_BREAK_SOUND,
+    }
+}
+
+#[cfg(all(feature = "audio", not(feature = "embedded-sounds")))]
+fn embedded_sound_data(_sound_type: SoundType) -> &'static [u8] {
+    &[]
+}
+
+/// Notes played for a transition: descending into a break, ascending back
+/// to work, twice through for a long break, and just the first
+/// note after preparing
+#[cfg(any(feature = "audio", test))]
+fn synth_melody(sound_type: SoundType, frequencies: &[f32]) -> Vec<f32> {
+    match sound_type {
+        SoundType::PrepareToWork => frequencies.iter().take(1).copied().collect(),
+        SoundType::WorkToBreak => frequencies.to_vec(),
+        SoundType::BreakToWork => frequencies.iter().rev().copied().collect(),
+        SoundType::WorkToLongBreak => frequencies.repeat(2),
+    }
+}
+
+/// Render mono samples for a sequence of notes, each shaped by the
+/// attack/release envelope and softened with a quiet octave overtone
+#[cfg(any(feature = "audio", test))]
+fn synth_samples(notes: &[f32], config: &SynthConfig, sample_rate: u32) -> Vec<f32> {
+    let to_samples = |ms: u64| (ms * u64::from(sample_rate) / 1000) as usize;
+    let note_len = to_samples(config.note_ms);
+    let attack = to_samples(config.attack_ms).min(note_len);
+    let release = to_samples(config.release_ms).min(note_len - attack);
+
+    let mut samples = Vec::with_capacity(notes.len() * note_len);
+    for &frequency in notes {
+        for i in 0..note_len {
+            let mut gain = 1.0;
+            if i < attack {
+                gain = i as f32 / attack as f32;
+            }
+            if note_len - i <= release {
+                gain *= (note_len - i - 1) as f32 / release as f32;
+            }
+            let t = i as f32 / sample_rate as f32;
+            let phase = 2.0 * std::f32::consts::PI * frequency * t;
+            let tone = (phase.sin() + 0.25 * (2.0 * phase).sin()) / 1.25;
+            samples.push(0.5 * gain * tone);
+        }
+    }
+    samples
+}
+
+/// Play a procedurally generated chime for a transition
+#[cfg(feature = "audio")]
+pub fn play_synth(sound_type: SoundType, config: &SynthConfig, volume: f32) {
+    let notes = synth_melody(sound_type, &config.frequencies);
+    let samples = synth_samples(&notes, config, SYNTH_SAMPLE_RATE);
+    let length = std::time::Duration::from_millis(config.note_ms * notes.len() as u64);
+
+    let play = move || {
+        if let Ok(handle) = open_sink() {
+            let mixer = handle.mixer();
+
+            let source = rodio::buffer::SamplesBuffer::new(
+                std::num::NonZero::<u16>::MIN,
+                std::num::NonZero::new(SYNTH_SAMPLE_RATE).unwrap(),
+                samples,
+            )
+            .amplify(volume);
+
+            mixer.add(source);
+            std::thread::sleep(length + std::time::Duration::from_millis(100));
+        }
     };
 
-    // Check if the sound data is just a placeholder (empty/minimal WAV)
+    if tokio::runtime::Handle::try_current().is_ok() {
+        tokio::task::spawn_blocking(play);
+    } else {
+        std::thread::spawn(play);
+    }
+}
+
+#[cfg(feature = "audio")]
+pub fn play_embedded_sound(sound_type: SoundType, volume: f32) -> Result<(), TomatError> {
+    let sound_data = embedded_sound_data(sound_type);
+
+    // Check if the sound data is just a placeholder (empty/minimal WAV), which
+    // is also the case when built without embedded sounds
     if sound_data.len() <= 44 {
-        // Fallback to system beep for placeholder files
-        play_system_beep();
+        // Fallback to synthesized chimes for placeholder files
+        play_synth(sound_type, &SynthConfig::default(), volume);
         return Ok(());
     }
 
@@ -41,7 +277,7 @@ pub fn play_embedded_sound(
     let sound_data = sound_data.to_vec();
     if tokio::runtime::Handle::try_current().is_ok() {
         tokio::task::spawn_blocking(move || {
-            if let Ok(handle) = rodio::stream::DeviceSinkBuilder::open_default_sink() {
+            if let Ok(handle) = open_sink() {
                 let mixer = handle.mixer();
 
                 let cursor = Cursor::new(sound_data);
@@ -57,7 +293,7 @@ pub fn play_embedded_sound(
     } else {
         // Fallback to std::thread if not in tokio runtime (e.g., tests without runtime)
         std::thread::spawn(move || {
-            if let Ok(handle) = rodio::stream::DeviceSinkBuilder::open_default_sink() {
+            if let Ok(handle) = open_sink() {
                 let mixer = handle.mixer();
 
                 let cursor = Cursor::new(sound_data);
@@ -78,7 +314,7 @@ pub fn play_system_beep() {
     // Use tokio::spawn_blocking for beep playback
     if tokio::runtime::Handle::try_current().is_ok() {
         tokio::task::spawn_blocking(|| {
-            if let Ok(handle) = rodio::stream::DeviceSinkBuilder::open_default_sink() {
+            if let Ok(handle) = open_sink() {
                 let mixer = handle.mixer();
 
                 // Generate a simple beep tone
@@ -94,7 +330,7 @@ pub fn play_system_beep() {
     } else {
         // Fallback to std::thread if not in tokio runtime
         std::thread::spawn(|| {
-            if let Ok(handle) = rodio::stream::DeviceSinkBuilder::open_default_sink() {
+            if let Ok(handle) = open_sink() {
                 let mixer = handle.mixer();
 
                 let source = rodio::source::SineWave::new(800.0)
@@ -108,11 +344,32 @@ pub fn play_system_beep() {
     }
 }
 
+/// Play a short, quiet chime as a reminder that time is passing
 #[cfg(feature = "audio")]
-pub fn play_custom_file<P: AsRef<std::path::Path>>(
-    path: P,
-    volume: f32,
-) -> Result<(), Box<dyn std::error::Error>> {
+pub fn play_chime(volume: f32) {
+    let play = move || {
+        if let Ok(handle) = open_sink() {
+            let mixer = handle.mixer();
+
+            let source = rodio::source::SineWave::new(1320.0)
+                .take_duration(std::time::Duration::from_millis(120))
+                .fade_in(std::time::Duration::from_millis(20))
+                .amplify(0.2 * volume);
+
+            mixer.add(source);
+            std::thread::sleep(std::time::Duration::from_millis(200));
+        }
+    };
+
+    if tokio::runtime::Handle::try_current().is_ok() {
+        tokio::task::spawn_blocking(play);
+    } else {
+        std::thread::spawn(play);
+    }
+}
+
+#[cfg(feature = "audio")]
+pub fn play_custom_file<P: AsRef<std::path::Path>>(path: P, volume: f32) -> Result<(), TomatError> {
     // Load file data before spawning task
     let file = std::fs::File::open(path)?;
     let mut reader = std::io::BufReader::new(file);
@@ -122,7 +379,7 @@ pub fn play_custom_file<P: AsRef<std::path::Path>>(
     // Use tokio::spawn_blocking for audio playback
     if tokio::runtime::Handle::try_current().is_ok() {
         tokio::task::spawn_blocking(move || {
-            if let Ok(handle) = rodio::stream::DeviceSinkBuilder::open_default_sink() {
+            if let Ok(handle) = open_sink() {
                 let mixer = handle.mixer();
 
                 let cursor = Cursor::new(buffer);
@@ -138,7 +395,7 @@ pub fn play_custom_file<P: AsRef<std::path::Path>>(
     } else {
         // Fallback to std::thread if not in tokio runtime
         std::thread::spawn(move || {
-            if let Ok(handle) = rodio::stream::DeviceSinkBuilder::open_default_sink() {
+            if let Ok(handle) = open_sink() {
                 let mixer = handle.mixer();
 
                 let cursor = Cursor::new(buffer);
@@ -154,11 +411,36 @@ pub fn play_custom_file<P: AsRef<std::path::Path>>(
     Ok(())
 }
 
+/// Describe how sounds are played, for `tomat daemon status`
+pub fn describe(config: &SoundConfig) -> String {
+    if !cfg!(feature = "audio") {
+        return "unavailable (built without audio support)".to_string();
+    }
+    let mut description = match config.effective_mode() {
+        SoundMode::None => return "off".to_string(),
+        SoundMode::Embedded if config.theme_sounds => format!("theme sounds ({})", config.theme),
+        SoundMode::Embedded => "embedded sounds".to_string(),
+        SoundMode::SystemBeep => "system beep".to_string(),
+        SoundMode::Synth => "synthesized chimes".to_string(),
+    };
+    match (&config.backend, &config.device) {
+        (Some(backend), Some(device)) => {
+            description.push_str(&format!(" on {} ({})", device, backend))
+        }
+        (Some(output), None) | (None, Some(output)) => {
+            description.push_str(&format!(" on {}", output))
+        }
+        (None, None) => {}
+    }
+    #[cfg(feature = "audio")]
+    if let Some(error) = LAST_OUTPUT_ERROR.lock().ok().and_then(|e| e.clone()) {
+        description.push_str(&format!(", last sound failed: {}", error));
+    }
+    description
+}
+
 #[cfg(not(feature = "audio"))]
-pub fn play_embedded_sound(
-    _sound_type: SoundType,
-    _volume: f32,
-) -> Result<(), Box<dyn std::error::Error>> {
+pub fn play_embedded_sound(_sound_type: SoundType, _volume: f32) -> Result<(), TomatError> {
     // Audio feature not enabled, do nothing
     Ok(())
 }
@@ -168,11 +450,21 @@ pub fn play_system_beep() {
     // Audio feature not enabled, do nothing
 }
 
+#[cfg(not(feature = "audio"))]
+pub fn play_synth(_sound_type: SoundType, _config: &SynthConfig, _volume: f32) {
+    // Audio feature not enabled, do nothing
+}
+
+#[cfg(not(feature = "audio"))]
+pub fn play_chime(_volume: f32) {
+    // Audio feature not enabled, do nothing
+}
+
 #[cfg(not(feature = "audio"))]
 pub fn play_custom_file<P: AsRef<std::path::Path>>(
     _path: P,
     _volume: f32,
-) -> Result<(), Box<dyn std::error::Error>> {
+) -> Result<(), TomatError> {
     // Audio feature not enabled, do nothing
     Ok(())
 }
@@ -192,8 +484,8 @@ mod tests {
     #[test]
     #[allow(clippy::const_is_empty)]
     fn test_embedded_sounds_exist() {
-        // Only test when audio feature is enabled
-        #[cfg(feature = "audio")]
+        // Only test when embedded sounds are compiled in
+        #[cfg(feature = "embedded-sounds")]
         {
             // Test that embedded sound data exists (even if placeholder)
             assert!(!WORK_TO_BREAK_SOUND.is_empty());
@@ -201,4 +493,44 @@ mod tests {
             assert!(!WORK_TO_LONG_BREAK_SOUND.is_empty());
         }
     }
+
+    #[test]
+    fn test_synth_melody_direction() {
+        let notes = [880.0, 659.25, 523.25];
+        assert_eq!(synth_melody(SoundType::WorkToBreak, &notes), notes);
+        assert_eq!(
+            synth_melody(SoundType::BreakToWork, &notes),
+            [523.25, 659.25, 880.0]
+        );
+        assert_eq!(synth_melody(SoundType::WorkToLongBreak, &notes).len(), 6);
+        assert_eq!(synth_melody(SoundType::PrepareToWork, &notes), [880.0]);
+    }
+
+    #[test]
+    fn test_synth_samples_envelope() {
+        let config = SynthConfig::default();
+        let samples = synth_samples(&[440.0, 660.0], &config, SYNTH_SAMPLE_RATE);
+
+        let note_len = (config.note_ms * SYNTH_SAMPLE_RATE as u64 / 1000) as usize;
+        assert_eq!(samples.len(), 2 * note_len);
+        // Each note starts and ends silent, so notes join without clicks
+        assert_eq!(samples[0], 0.0);
+        assert_eq!(samples[note_len - 1], 0.0);
+        assert_eq!(samples[note_len], 0.0);
+        assert!(samples.iter().all(|s| s.abs() <= 0.5));
+        assert!(samples.iter().any(|s| s.abs() > 0.25));
+    }
+
+    #[test]
+    fn test_synth_samples_envelope_longer_than_note() {
+        let config = SynthConfig {
+            note_ms: 10,
+            attack_ms: 50,
+            release_ms: 50,
+            ..SynthConfig::default()
+        };
+        let samples = synth_samples(&[440.0], &config, SYNTH_SAMPLE_RATE);
+        assert_eq!(samples.len(), 441);
+        assert!(samples.iter().all(|s| s.is_finite()));
+    }
 }
@@ -1,4 +1,86 @@
-use clap::{ArgAction, Args, Parser, Subcommand};
+use clap::{ArgAction, Args, Parser, Subcommand, ValueEnum};
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq)]
+pub enum Switch {
+    On,
+    Off,
+}
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq)]
+pub enum StatsGroup {
+    /// Group sessions by their task
+    Task,
+    /// Group sessions by their tags
+    Tag,
+}
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq)]
+pub enum PlotFormat {
+    /// Scalable vector graphics
+    Svg,
+    /// Portable network graphics
+    Png,
+}
+
+impl PlotFormat {
+    /// File extension of images in this format
+    pub fn extension(self) -> &'static str {
+        match self {
+            PlotFormat::Svg => "svg",
+            PlotFormat::Png => "png",
+        }
+    }
+}
+
+#[derive(Subcommand)]
+pub enum HistoryAction {
+    /// List the sessions recorded on a day
+    #[command(
+        long_about = "List the sessions recorded on a day (today by default) with their ids, \
+        which are used by 'history edit' and 'history delete'."
+    )]
+    List {
+        /// Day to list, as YYYY-MM-DD
+        #[arg(long)]
+        date: Option<String>,
+    },
+    /// Delete a session
+    Delete {
+        /// Id of the session, as shown by 'tomat history list'
+        id: i64,
+    },
+    /// Change the duration or task of a session
+    #[command(
+        long_about = "Change the duration or task of a recorded session. The start time is \
+        kept and the end time moves with the new duration. Pass an empty task to clear it."
+    )]
+    Edit {
+        /// Id of the session, as shown by 'tomat history list'
+        id: i64,
+        /// New duration, e.g. 20m, 1h30m or 90s (plain numbers are minutes)
+        #[arg(long)]
+        duration: Option<String>,
+        /// New task name
+        #[arg(long)]
+        task: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum PlanAction {
+    /// Add a task to the end of the plan
+    Add {
+        /// Name of the task
+        task: String,
+        /// Number of pomodoros to spend on the task
+        #[arg(default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+        pomodoros: u32,
+    },
+    /// Show the planned tasks and their progress
+    Show,
+    /// Remove all tasks from the plan
+    Clear,
+}
 
 #[derive(Subcommand)]
 pub enum DaemonAction {
@@ -15,12 +97,29 @@ pub enum DaemonAction {
         any active timer session. The daemon will clean up its socket and PID files."
     )]
     Stop,
+    /// Restart the daemon
+    #[command(
+        long_about = "Stop the running tomat daemon, wait for it to release its socket, \
+        and start a new one, e.g. to pick up configuration changes. The timer state is \
+        kept across the restart. If no daemon is running, one is started."
+    )]
+    Restart,
     /// Check daemon status
     #[command(
         long_about = "Check if the tomat daemon is currently running and report its \
-        process ID."
+        process ID, uptime, number of handled requests, time of the last phase change, \
+        config file in use, and whether sounds and notifications work. With --verbose, \
+        also show status cache hits and per-command handling times."
     )]
-    Status,
+    Status {
+        /// Show status cache statistics and per-command timings
+        #[arg(short, long)]
+        verbose: bool,
+        /// Output format: text or json
+        #[arg(short, long, default_value = "text")]
+        #[arg(value_parser = ["text", "json"])]
+        output: String,
+    },
     /// Install systemd user service
     #[command(
         long_about = "Install and enable the tomat systemd user service. This allows \
@@ -31,22 +130,72 @@ pub enum DaemonAction {
         after_help = "After installation, manage the service with systemctl:\n    \
         systemctl --user start tomat.service\n    \
         systemctl --user status tomat.service\n    \
-        systemctl --user stop tomat.service"
+        systemctl --user stop tomat.service\n\n\
+        Without systemd, install an XDG autostart entry instead:\n    \
+        tomat daemon install --autostart"
     )]
     Install {
         /// Force overwrite existing service file without prompting
         #[arg(short, long)]
         force: bool,
+        /// Install an XDG autostart entry instead of a systemd service
+        #[arg(
+            long,
+            long_help = "Write an XDG autostart entry to ~/.config/autostart/tomat.desktop \
+            that runs 'tomat daemon start' when the graphical session starts. Use this on \
+            systems without systemd user services, or where the user service starts before \
+            the graphical session is ready."
+        )]
+        autostart: bool,
+        /// Print the generated file instead of installing it
+        #[arg(long)]
+        print: bool,
+        /// Merge a unit snippet into the generated file
+        #[arg(
+            long,
+            value_name = "PATH",
+            long_help = "Merge a unit snippet into the generated file before installing it. \
+            Keys in the snippet replace keys of the same name in the same section, an empty \
+            assignment (e.g. 'UMask=') removes the key, and new sections are appended."
+        )]
+        template: Option<std::path::PathBuf>,
+        /// Init system to generate a user service for
+        #[arg(long, value_enum, default_value_t = InitSystem::Systemd, conflicts_with = "autostart")]
+        init: InitSystem,
     },
     /// Uninstall systemd user service
     #[command(
         long_about = "Stop and remove the tomat systemd user service. This removes \
         the service file and disables automatic startup."
     )]
-    Uninstall,
+    Uninstall {
+        /// Remove the XDG autostart entry instead of the systemd service
+        #[arg(long)]
+        autostart: bool,
+        /// Init system to remove the user service from
+        #[arg(long, value_enum, default_value_t = InitSystem::Systemd, conflicts_with = "autostart")]
+        init: InitSystem,
+    },
     /// Run the daemon in the foreground (internal use)
     #[command(hide = true)]
-    Run,
+    Run {
+        /// Log every change of the timer state with its cause and a timestamp
+        #[arg(long)]
+        trace_transitions: bool,
+    },
+}
+
+/// Init system for `tomat daemon install`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum InitSystem {
+    /// systemd user service (~/.config/systemd/user/tomat.service)
+    Systemd,
+    /// OpenRC user service (~/.config/rc/init.d/tomat)
+    Openrc,
+    /// runit service directory for turnstile (~/.config/service/tomat)
+    Runit,
+    /// dinit user service (~/.config/dinit.d/tomat)
+    Dinit,
 }
 
 #[derive(Parser)]
@@ -75,11 +224,31 @@ EXAMPLES:
     # Check status (outputs JSON for waybar)
     tomat status
 
-    # Toggle pause/resume
+    # Start, pause or resume
     tomat toggle
 
 For more information, visit: https://github.com/jolars/tomat")]
 pub struct Cli {
+    /// Print strict key=value lines without emoji, for scripts
+    #[arg(long, global = true)]
+    #[arg(
+        long_help = "Print strict, line-oriented key=value output without emoji, for \
+        automation frontends such as Shortcuts over SSH or termux scripts. The first line is \
+        result=ok or result=error; errors add error=<code> and exit with status 1. Timer \
+        commands print the new status after their message. Supported by start, stop, \
+        away, pause, resume, toggle, skip, postpone-long-break, break, privacy, annotate, \
+        note, status, watch, plan and log."
+    )]
+    pub porcelain: bool,
+    /// Start the daemon if it is not running
+    #[arg(long, global = true)]
+    #[arg(
+        long_help = "Start the daemon in the background if it is not running, and then \
+        run the command, e.g. for status bar modules that may run before the daemon is up. \
+        The same as '[daemon] auto_spawn = true' in the config file. Has no effect on the \
+        'daemon' commands, stop and away."
+    )]
+    pub spawn_daemon: bool,
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -129,13 +298,15 @@ pub struct TimerArgs {
         to-work  - Auto-advance from break to work only\n\n\
         If not specified, uses the value from ~/.config/tomat/config.toml or the \
         built-in default of 'none'.")]
+    #[arg(value_parser = ["all", "none", "to-break", "to-work"])]
     pub auto_advance: Option<String>,
     /// Sound notification mode
     #[arg(long)]
-    #[arg(help = "Sound mode: embedded, system-beep, none (default: from config)")]
+    #[arg(help = "Sound mode: embedded, system-beep, synth, none (default: from config)")]
     #[arg(long_help = "Control sound notifications:\n  \
         embedded    - Use built-in audio files (default)\n  \
         system-beep - Use system beep (terminal bell)\n  \
+        synth       - Generate chimes procedurally\n  \
         none        - No sound notifications\n\n\
         If not specified, uses the value from ~/.config/tomat/config.toml or the \
         built-in default of 'embedded'.")]
@@ -155,6 +326,14 @@ pub struct TimerArgs {
         value from ~/.config/tomat/config.toml or the built-in default of 0.5."
     )]
     pub volume: Option<f32>,
+    /// What you are working on
+    #[arg(long, visible_alias = "label")]
+    #[arg(help = "Task label shown in the tooltip and recorded in the history")]
+    pub task: Option<String>,
+    /// Tags recorded with the work sessions, e.g. a project
+    #[arg(long = "tag", value_name = "TAG", visible_alias = "project")]
+    #[arg(help = "Tag recorded with the work sessions in the history (repeatable)")]
+    pub tags: Vec<String>,
 }
 
 #[derive(Subcommand)]
@@ -173,7 +352,9 @@ pub enum Commands {
         long_about = "Start a new Pomodoro timer session with the specified durations. \
         If no options are provided, uses defaults from ~/.config/tomat/config.toml or \
         built-in defaults (25min work, 5min break, 15min long break, 4 sessions). \
-        Custom durations only apply to the current session."
+        Custom durations only apply to the current session. While a session is in \
+        progress, starting again is refused unless --force is given, since it resets \
+        the cycle."
     )]
     #[command(after_help = "\
 EXAMPLES:
@@ -185,14 +366,44 @@ EXAMPLES:
     tomat start --work 45 --break 15
 
     # Auto-advance between phases
-    tomat start --auto-advance")]
+    tomat start --auto-advance all
+
+    # Label the sessions for 'tomat stats --by'
+    tomat start --task \"chapter 2\" --tag thesis --tag writing
+
+    # The same with the label and project aliases
+    tomat start --label writing --project client-x
+
+    # Restart the cycle while a session is in progress
+    tomat start --force")]
     Start {
         #[command(flatten)]
         timer: TimerArgs,
+        /// Restart even if a session is in progress, resetting the cycle
+        #[arg(long)]
+        force: bool,
     },
     /// Stop the current session
     #[command(long_about = "Stop the current Pomodoro session and return the timer to idle state.")]
     Stop,
+    /// Stop the timer because you have been away, e.g. from swayidle
+    #[command(
+        long_about = "Stop the timer like 'stop', for an idle daemon such as swayidle or \
+        hypridle to run when you have been away for a while. Work while you were away does \
+        not count: the work session in progress is recorded as abandoned, without the time \
+        since you went away, and so are sessions that ended while you were away. Give the \
+        idle timeout with --idle, so that tomat knows when you left."
+    )]
+    #[command(after_help = "\
+EXAMPLES:
+
+    # Stop after 30 minutes away
+    swayidle -w timeout 1800 'tomat away --idle 30m'")]
+    Away {
+        /// How long you have been away, e.g. 30m or 1h (plain numbers are minutes)
+        #[arg(long, value_name = "DURATION")]
+        idle: Option<String>,
+    },
     /// Get current timer status
     #[command(
         long_about = "Display the current timer status. Output format can be customized \
@@ -211,6 +422,18 @@ OUTPUT FORMATS:
 `plain`
   : Plain text output
 
+`zellij`
+  : Plain text colored with ANSI escapes, for zellij status plugins
+
+`display`
+  : Compact JSON with the phase, MM:SS and percentage, for desk displays
+
+`i3blocks`
+  : Full text, short text and color on three lines, for i3blocks
+
+`dwmblocks`
+  : Plain text on a single line, for dwmblocks
+
 FORMAT PLACEHOLDERS:
 
 `{icon}`
@@ -233,11 +456,14 @@ EXAMPLES:
     tomat status
     tomat status --output plain
     tomat status --format \"{time}\"
-    tomat status --format \"{phase}: {time} {state}\"")]
+    tomat status --format \"{phase}: {time} {state}\"
+    tomat status --output i3status-rs --schema
+    tomat status --profile minimal
+    tomat status --follow --interval 0.5")]
     Status {
-        /// Output format: waybar, i3status-rs, or plain
+        /// Output format: waybar, i3status-rs, plain, zellij, display, i3blocks, or dwmblocks
         #[arg(short, long, default_value = "waybar")]
-        #[arg(value_parser = ["waybar", "i3status-rs", "plain"])]
+        #[arg(value_parser = ["waybar", "i3status-rs", "plain", "zellij", "display", "i3blocks", "dwmblocks"])]
         output: String,
         /// Text format template
         #[arg(short = 'f', long)]
@@ -247,14 +473,52 @@ EXAMPLES:
             {time}    - Remaining time (MM:SS)\n\
             {state}   - Play/pause symbol\n\
             {phase}   - Phase name\n\
-            {session} - Session progress")]
+            {session} - Session progress\n\
+            {sessions_total} - Work sessions before a long break\n\
+            {percentage} - Progress of the phase in percent\n\
+            {task}    - Task label\n\
+            {completed} - Work sessions completed today\n\
+            {goal}    - Daily goal\n\n\
+            Modifiers: {phase:upper}, {phase:lower}, {time:pad6}, {time:lpad6}, and\n\
+            {task:?( ({task}))} to show text only when the value is not empty.")]
         format: Option<String>,
+        /// Display profile from [display.profiles] (e.g. minimal, full)
+        #[arg(
+            short,
+            long,
+            value_name = "NAME",
+            long_help = "Use a display profile configured under [display.profiles.NAME], so \
+            that several bars can show different formats and tooltips from the same daemon. \
+            Without a configured profile of that name, minimal, normal and full set only the \
+            tooltip detail. Without --profile, a profile named after the output format (e.g. \
+            [display.profiles.i3status-rs]) is used if configured."
+        )]
+        profile: Option<String>,
+        /// Print the JSON Schema of the selected output format and exit
+        #[arg(long)]
+        schema: bool,
+        /// Print only the tooltip text
+        #[arg(long, conflicts_with_all = ["output", "schema", "class_only"])]
+        tooltip_only: bool,
+        /// Print only the CSS class (e.g. work, break-paused, idle)
+        #[arg(long, conflicts_with_all = ["output", "schema"])]
+        class_only: bool,
+        /// Keep printing the status until the daemon stops
+        #[arg(long, conflicts_with = "schema")]
+        follow: bool,
+        /// Update interval in seconds for --follow, fractions allowed (e.g. 0.1)
+        #[arg(short, long, default_value = "0.25", requires = "follow")]
+        #[arg(value_parser = parse_interval)]
+        interval: f64,
+        /// With --follow, read single-key commands from stdin: space toggles, s skips, q quits
+        #[arg(long, requires = "follow")]
+        interactive: bool,
     },
     /// Continuously output status updates
     #[command(
-        long_about = "Continuously watch and output timer status updates. This maintains \
-        a single connection to the daemon and updates at the specified interval. Automatically \
-        exits when the daemon stops. More efficient than polling with 'status' command."
+        long_about = "Continuously watch and output timer status updates at the specified \
+        interval, which may be a fraction of a second. Automatically exits when the daemon \
+        stops. More efficient than polling with 'status' command. Same as 'status --follow'."
     )]
     #[command(after_help = "\
 EXAMPLES:
@@ -266,27 +530,266 @@ EXAMPLES:
     tomat watch --interval 5
 
     # Watch with plain text output
-    tomat watch --output plain")]
+    tomat watch --output plain
+
+    # Control the timer with single keys (space, s, q)
+    tomat watch --output plain --interactive
+
+    # Publish a compact payload to MQTT twice a minute
+    tomat watch --output display --interval 30 | mosquitto_pub -l -t tomat/status")]
     Watch {
-        /// Output format: waybar, i3status-rs, or plain
+        /// Output format: waybar, i3status-rs, plain, zellij, display, i3blocks, or dwmblocks
         #[arg(short, long, default_value = "waybar")]
-        #[arg(value_parser = ["waybar", "i3status-rs", "plain"])]
+        #[arg(value_parser = ["waybar", "i3status-rs", "plain", "zellij", "display", "i3blocks", "dwmblocks"])]
         output: String,
         /// Text format template
         #[arg(short = 'f', long)]
         #[arg(help = "Custom text format (e.g. \"{icon} {time}\")")]
         format: Option<String>,
-        /// Update interval in seconds
+        /// Display profile from [display.profiles] (e.g. minimal, full)
+        #[arg(
+            short,
+            long,
+            value_name = "NAME",
+            long_help = "Use a display profile configured under [display.profiles.NAME], so \
+            that several bars can show different formats and tooltips from the same daemon. \
+            Without a configured profile of that name, minimal, normal and full set only the \
+            tooltip detail."
+        )]
+        profile: Option<String>,
+        /// Update interval in seconds, fractions allowed (e.g. 0.1)
+        #[arg(short, long, default_value = "0.25")]
+        #[arg(value_parser = parse_interval)]
+        interval: f64,
+        /// Read single-key commands from stdin: space toggles, s skips, q quits
+        #[arg(long)]
+        interactive: bool,
+    },
+    /// Print timer events as they happen
+    #[command(
+        long_about = "Subscribe to the daemon's events and print each one as a line of JSON \
+        when it happens: phase_change, pause, resume, stop, and a tick each second while the \
+        timer runs. Each event carries the full timer status. Unlike 'watch', nothing is \
+        polled. Exits when the daemon stops."
+    )]
+    #[command(after_help = "\
+EXAMPLES:
+
+    # Print all events
+    tomat subscribe
+
+    # React to phase changes in a script
+    tomat subscribe | jq --unbuffered -r 'select(.event == \"phase_change\") | .status.phase'")]
+    Subscribe,
+    /// Print a short status for shell prompts and terminal status bars
+    #[command(
+        long_about = "Print the timer status for shell prompts and terminal status bars, such \
+        as wezterm's. Unlike 'status', this prints nothing while the timer is idle or the \
+        daemon is not running, and never fails, so it can be called from a prompt without \
+        further checks. With --json, the text is printed with its CSS class and its color \
+        from [display.colors]."
+    )]
+    #[command(after_help = "\
+EXAMPLES:
+
+    # In a bash prompt
+    PS1='$(tomat prompt) \\$ '
+
+    # For scripts that style the text themselves
+    tomat prompt --json")]
+    Prompt {
+        /// Text format template
+        #[arg(short = 'f', long)]
+        #[arg(help = "Custom text format (e.g. \"{icon} {time}\")")]
+        format: Option<String>,
+        /// Print JSON with the text, class and color
+        #[arg(long)]
+        json: bool,
+    },
+    /// Act as a rofi-blocks mode, for a live timer popup with actions
+    #[command(
+        long_about = "Speak the rofi-blocks protocol on stdin and stdout, so that rofi shows \
+        the live timer status with entries to start, pause, resume, skip or stop the timer. \
+        The status is updated every interval until rofi is closed. Requires the rofi-blocks \
+        plugin."
+    )]
+    #[command(after_help = "\
+EXAMPLES:
+
+    # Open the popup
+    rofi -modi blocks -show blocks -blocks-wrap \"tomat rofi-blocks\"
+
+    # With a compact tooltip
+    rofi -modi blocks -show blocks -blocks-wrap \"tomat rofi-blocks --profile minimal\"")]
+    RofiBlocks {
+        /// Display profile from [display.profiles] (e.g. minimal, full)
+        #[arg(short, long, value_name = "NAME")]
+        profile: Option<String>,
+        /// Update interval in seconds, fractions allowed (e.g. 0.1)
+        #[arg(short, long, default_value = "1")]
+        #[arg(value_parser = parse_interval)]
+        interval: f64,
+    },
+    /// Stream the status to a waybar module, printing only changes
+    #[command(
+        long_about = "Keep running as the exec command of a waybar custom module and print a \
+        line of waybar JSON whenever the status changes: once a second while the timer runs, \
+        and right after it is paused, resumed or stopped. Unlike 'status' with waybar's \
+        interval, no process is started for each update, and unlike 'watch', unchanged lines \
+        are not repeated. While the daemon is not running, the text is empty, which hides the \
+        module, and the module picks the timer up again when the daemon starts. Exits when \
+        waybar closes the pipe."
+    )]
+    #[command(after_help = "\
+EXAMPLES:
+
+    # In ~/.config/waybar/config, without an interval
+    \"custom/tomat\": {
+      \"exec\": \"tomat waybar\",
+      \"return-type\": \"json\"
+    }")]
+    Waybar {
+        /// Text format template
+        #[arg(short = 'f', long)]
+        #[arg(help = "Custom text format (e.g. \"{icon} {time}\")")]
+        format: Option<String>,
+        /// Display profile from [display.profiles] (e.g. minimal, full)
+        #[arg(short, long, value_name = "NAME")]
+        profile: Option<String>,
+        /// How often to check for changes in seconds, fractions allowed (e.g. 0.1)
         #[arg(short, long, default_value = "0.25")]
+        #[arg(value_parser = parse_interval)]
         interval: f64,
     },
+    /// Act as an i3bar status command, with click handling
+    #[command(
+        long_about = "Speak the i3bar protocol on stdout, so that plain i3bar shows the timer \
+        without i3status or i3status-rs. A status line is printed whenever the status changes, \
+        with the text, a short text and the phase color from [display.colors]. Clicks on the \
+        block are read from stdin: a left click toggles the timer, a middle click stops it and \
+        a right click skips to the next phase. While the daemon is not running, the block is \
+        hidden. Exits when i3bar closes the pipe."
+    )]
+    #[command(after_help = "\
+EXAMPLES:
+
+    # In ~/.config/i3/config
+    bar {
+        status_command tomat i3bar
+    }")]
+    I3bar {
+        /// Text format template
+        #[arg(short = 'f', long)]
+        #[arg(help = "Custom text format (e.g. \"{icon} {time}\")")]
+        format: Option<String>,
+        /// Display profile from [display.profiles] (e.g. minimal, full)
+        #[arg(short, long, value_name = "NAME")]
+        profile: Option<String>,
+        /// How often to check for changes in seconds, fractions allowed (e.g. 0.1)
+        #[arg(short, long, default_value = "0.25")]
+        #[arg(value_parser = parse_interval)]
+        interval: f64,
+    },
+    /// Show the status in dwm's bar by setting the X root window name
+    #[command(
+        long_about = "Keep the name of the X root window, which dwm shows as its status text, \
+        set to the timer status. The name is updated every interval when it changes, using \
+        xsetroot. Output of other commands, such as the date, can be shown next to the timer \
+        with --with, so that this can replace a status script. While the daemon is not \
+        running, only the output of those commands is shown."
+    )]
+    #[command(after_help = "\
+EXAMPLES:
+
+    # Only the timer
+    tomat dwm &
+
+    # The timer and the date, e.g. in ~/.xinitrc
+    tomat dwm --with \"date '+%a %H:%M'\" --separator \" | \" &")]
+    Dwm {
+        /// Text format template
+        #[arg(short = 'f', long)]
+        #[arg(help = "Custom text format (e.g. \"{icon} {time}\")")]
+        format: Option<String>,
+        /// Display profile from [display.profiles] (e.g. minimal, full)
+        #[arg(short, long, value_name = "NAME")]
+        profile: Option<String>,
+        /// Shell command whose output is shown after the timer, may be repeated
+        #[arg(short, long, value_name = "COMMAND")]
+        with: Vec<String>,
+        /// Text between the timer and the output of each command
+        #[arg(short, long, default_value = " | ")]
+        separator: String,
+        /// Update interval in seconds, fractions allowed (e.g. 0.5)
+        #[arg(short, long, default_value = "1")]
+        #[arg(value_parser = parse_interval)]
+        interval: f64,
+        /// Print each status line instead of setting the root window name
+        #[arg(long)]
+        print: bool,
+    },
+    /// Act as an Elgato Stream Deck plugin
+    #[command(
+        long_about = "Run as the plugin of the Stream Deck application, which starts it with \
+        the -port, -pluginUUID, -registerEvent and -info arguments. The plugin connects to \
+        the application's WebSocket and offers three actions: a key that shows the countdown \
+        and toggles the timer when pressed (io.github.jolars.tomat.timer), a key that \
+        toggles the timer (io.github.jolars.tomat.toggle), and a key that skips to the next \
+        phase (io.github.jolars.tomat.skip). A key shows an alert when its command fails, \
+        for example because the daemon is not running."
+    )]
+    #[command(after_help = "\
+EXAMPLES:
+
+    # Plugin executable (CodePath in manifest.json) passing a format
+    #!/bin/sh
+    exec tomat streamdeck-bridge --format \"{time}\" \"$@\"")]
+    StreamdeckBridge {
+        /// Text format template of the countdown
+        #[arg(short = 'f', long)]
+        #[arg(help = "Custom text format (e.g. \"{icon} {time}\")")]
+        format: Option<String>,
+        /// Display profile from [display.profiles] (e.g. minimal, full)
+        #[arg(short, long, value_name = "NAME")]
+        profile: Option<String>,
+        /// Arguments passed by the Stream Deck application
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true, hide = true)]
+        launch: Vec<String>,
+    },
     /// Skip to the next phase
     #[command(
         long_about = "Skip the current phase and immediately transition to the next phase \
         (work → break → work → ... → long break). The timer will start in the new phase if \
-        auto-advance is enabled, otherwise it will be paused."
+        auto-advance is enabled, otherwise it will be paused.\n\n\
+        With --merge during a break, the break is cancelled and the next work session starts \
+        right away. The remaining break time is added to the next long break, unless it is \
+        spent earlier with 'tomat break --banked'."
+    )]
+    Skip {
+        /// Start working now and add the rest of the break to the next long break
+        #[arg(long)]
+        merge: bool,
+    },
+    /// Take a normal break now and the long break one session later
+    #[command(
+        long_about = "Postpone the pending long break by one work session, for when you are \
+        in flow at the end of a cycle. During the work session before a long break, that \
+        session ends with a normal break instead. During a long break, the long break turns \
+        into a normal break. Either way, the long break follows the next work session, and \
+        banked break time is kept for it."
     )]
-    Skip,
+    PostponeLongBreak,
+    /// Spend banked break time
+    #[command(
+        long_about = "Spend the break time banked with 'skip --merge' now instead of with \
+        the next long break. During a work session, the session ends early and a break as \
+        long as the banked time starts. During a break, the break is extended by it."
+    )]
+    Break {
+        /// Take the break from the banked break time
+        #[arg(long, required = true)]
+        banked: bool,
+    },
     /// Pause the current timer
     #[command(
         long_about = "Pause the currently running timer. Use 'resume' or 'toggle' to \
@@ -296,10 +799,289 @@ EXAMPLES:
     /// Resume a paused timer
     #[command(long_about = "Resume a paused timer from where it left off.")]
     Resume,
-    /// Toggle timer pause/resume
+    /// Start, pause or resume the timer
     #[command(
-        long_about = "Toggle the timer state: pause if running, resume if paused. This is \
-        useful for waybar click handlers."
+        long_about = "Toggle the timer state: start a new cycle if idle, pause if running, \
+        resume if paused. Starting from idle works exactly like `tomat start` without \
+        options, using the config defaults and the day plan, so a status bar click handler \
+        needs no other command."
     )]
     Toggle,
+    /// Run a single timer in the foreground, without the daemon
+    #[command(
+        long_about = "Run a single work timer in the foreground without the daemon. Progress \
+        is printed to stderr, and the command exits with status 0 once the timer completes, \
+        or 1 if it is interrupted, so it can be chained in shell scripts. The completed \
+        session is recorded in the history."
+    )]
+    #[command(after_help = "\
+EXAMPLES:
+
+    # Work for 25 minutes, then deploy
+    tomat oneshot 25 && make deploy
+
+    # Use the configured work duration and the [focus] blocklist
+    tomat oneshot --focus")]
+    Oneshot {
+        /// Work duration in minutes (default: from config or 25)
+        #[arg(conflicts_with = "work")]
+        minutes: Option<f32>,
+        /// Work duration in minutes (same as the positional argument)
+        #[arg(short, long)]
+        work: Option<f32>,
+        /// Warn about or minimize the applications in [focus] block when starting
+        #[arg(long)]
+        focus: bool,
+        /// Wait in the foreground until the timer completes, which oneshot always
+        /// does; accepted so that scripts can spell it out
+        #[arg(long)]
+        block: bool,
+    },
+    /// Log a work session done away from the timer
+    #[command(
+        long_about = "Record a completed work session in the history without running the \
+        timer, so that reports and statistics include work done away from the computer. \
+        The session ends now unless a start time is given with --at."
+    )]
+    #[command(after_help = "\
+EXAMPLES:
+
+    # A 50-minute session that just ended
+    tomat log 50m
+
+    # Name the task
+    tomat log 1h30m --task reading
+
+    # Session that started at 14:00 today
+    tomat log 25m --at 14:00
+
+    # Tag the session
+    tomat log 45m --tag thesis --tag reading")]
+    Log {
+        /// Duration of the session, e.g. 50m, 1h30m or 90s (plain numbers are minutes)
+        duration: String,
+        /// Name of the task worked on
+        #[arg(long, visible_alias = "label")]
+        task: Option<String>,
+        /// Tag of the session (repeatable)
+        #[arg(long = "tag", value_name = "TAG", visible_alias = "project")]
+        tags: Vec<String>,
+        /// When the session started: HH:MM today or YYYY-MM-DD HH:MM
+        #[arg(long, value_name = "TIME")]
+        at: Option<String>,
+    },
+    /// Hide timer details in status output
+    #[command(
+        long_about = "Turn privacy mode on or off. While it is on, status output shows a \
+        generic \"focusing\" text instead of the countdown and hides session details and \
+        progress, which is useful when sharing your screen. The daemon keeps tracking \
+        the timer as usual."
+    )]
+    Privacy {
+        /// Whether privacy mode is on
+        #[arg(value_enum)]
+        state: Switch,
+    },
+    /// Set or change the label of the current session
+    #[command(
+        long_about = "Change the task label of the session in progress, e.g. after starting \
+        without one or when switching to something else. The new label is shown in the \
+        tooltip, passed to hooks, and recorded in the history when the work session ends. \
+        An empty label clears it. With --tag, the tags are replaced as well."
+    )]
+    #[command(after_help = "\
+EXAMPLES:
+
+    tomat annotate \"code review\"
+    tomat annotate writing --project client-x
+    tomat annotate \"\"")]
+    Annotate {
+        /// New task label
+        label: String,
+        /// Tag of the session, replacing the current tags (repeatable)
+        #[arg(long = "tag", value_name = "TAG", visible_alias = "project")]
+        tags: Vec<String>,
+    },
+    /// Write down what you did in the current or last work session
+    #[command(
+        long_about = "Save notes on the work session in progress, or on the one that just \
+        ended, e.g. during the review after it (see [timer] review). Notes on the running \
+        session are recorded when it ends; after it, they can be written until the next \
+        work session starts. Notes replace earlier notes on the same session, and an empty \
+        text clears them. They are shown by 'tomat history list'."
+    )]
+    #[command(after_help = "\
+EXAMPLES:
+
+    tomat note \"Fixed the parser, tests still missing\"
+    tomat note \"\"")]
+    Note {
+        /// What you did
+        text: String,
+    },
+    /// Fast-forward the daemon's clock, for demos and debugging
+    #[command(
+        hide = true,
+        long_about = "Move the daemon's clock forward, so that hooks, sounds and \
+        notifications of phase transitions can be tried without waiting. Without a \
+        duration, the clock skips to the end of the running phase. Only available when \
+        the daemon runs with TOMAT_DEV=1. The clock stays ahead until the daemon exits."
+    )]
+    Simulate {
+        /// How far to move the clock, e.g. 10m or 90s (plain numbers are minutes)
+        duration: Option<String>,
+    },
+    /// Send a raw protocol message to the daemon
+    #[command(
+        long_about = "Send a protocol message to the daemon as-is and print its JSON \
+        response, e.g. for debugging or when writing bindings for another language. The \
+        message is a JSON object with a \"command\" and its \"args\"; it may span several \
+        lines and is sent on a single line."
+    )]
+    #[command(after_help = "\
+EXAMPLES:
+
+    tomat raw '{\"command\":\"status\",\"args\":null}'
+    tomat raw '{\"command\":\"start\",\"args\":{\"work\":50}}'
+    echo '{\"command\":\"ping\",\"args\":null}' | tomat raw -")]
+    Raw {
+        /// Message as JSON, or - to read it from stdin
+        message: String,
+    },
+    /// Show or set the daily goal
+    #[command(
+        long_about = "Show the work sessions completed today and the daily goal, or set the \
+        goal, overriding 'daily_pomodoros' in the [goal] section of the config. A goal of 0 \
+        turns it off, and --reset goes back to the config. The goal is kept by the daemon \
+        until it is changed again."
+    )]
+    #[command(after_help = "\
+EXAMPLES:
+
+    tomat goal
+    tomat goal 6
+    tomat goal --reset")]
+    Goal {
+        /// Work sessions to complete each day, 0 for no goal
+        #[arg(conflicts_with = "reset")]
+        pomodoros: Option<u32>,
+        /// Use the goal from the config again
+        #[arg(long)]
+        reset: bool,
+    },
+    /// Plan the tasks of the day
+    #[command(
+        long_about = "Build a queue of tasks with the number of pomodoros to spend on each. \
+        'tomat start' without --task labels the session with the first unfinished task, and \
+        once its pomodoros are done, the following work sessions move on to the next task."
+    )]
+    #[command(after_help = "\
+EXAMPLES:
+
+    tomat plan add \"write spec\" 3
+    tomat plan add emails
+    tomat plan show")]
+    Plan {
+        #[command(subcommand)]
+        action: PlanAction,
+    },
+    /// Inspect and correct the session history
+    #[command(
+        long_about = "List, edit, and delete sessions in the history database, for example \
+        to correct mis-logged or test sessions."
+    )]
+    History {
+        #[command(subcommand)]
+        action: HistoryAction,
+    },
+    /// Show focus statistics from the history
+    #[command(
+        long_about = "Show the pomodoros and focus time of today, this week and this month, and \
+        summarize the completed work sessions in the history over the last \
+        calendar weeks, this week included: pomodoros, focused time, active days, the best day and the current streak. \
+        With --by, the focused time is broken down by task or tag instead; sessions with \
+        several tags count towards each of them. With --efficiency, a table shows the \
+        focus efficiency of each day instead: by default the minutes worked out of the \
+        minutes planned, counting work sessions that were skipped or stopped early, and \
+        the pauses per session. The definition is set in the [stats] section of the \
+        config. With --heatmap, a calendar of daily focus time is shown above the summary, with a \
+        column per week and a row per weekday. Colors are used when printing to a terminal \
+        and NO_COLOR is not set. With --export-plot, a bar chart of the daily focus time \
+        of the last 30 days is saved as an SVG or PNG image instead."
+    )]
+    #[command(after_help = "\
+EXAMPLES:
+
+    # Totals of the last 26 weeks
+    tomat stats
+
+    # Heatmap of the last year
+    tomat stats --heatmap --weeks 52
+
+    # Time spent per project tag over the last 4 weeks
+    tomat stats --by tag --weeks 4
+
+    # Focus efficiency of each day this week
+    tomat stats --efficiency --weeks 1
+
+    # Chart of the daily focus time of the last 30 days
+    tomat stats --export-plot png --plot-file focus.png")]
+    Stats {
+        /// Show a heatmap of daily focus time
+        #[arg(long)]
+        heatmap: bool,
+        /// Show the focused time per task or tag
+        #[arg(long, value_enum)]
+        by: Option<StatsGroup>,
+        /// Show the focus efficiency of each day
+        #[arg(long, conflicts_with = "by")]
+        efficiency: bool,
+        /// Number of weeks to cover, up to today
+        #[arg(long, default_value_t = 26, value_parser = clap::value_parser!(u32).range(1..))]
+        weeks: u32,
+        /// Save a bar chart of the daily focus time of the last 30 days as an image
+        #[arg(
+            long,
+            value_enum,
+            value_name = "FORMAT",
+            conflicts_with_all = ["heatmap", "by", "efficiency", "weeks"]
+        )]
+        export_plot: Option<PlotFormat>,
+        /// File to save the chart to (default: tomat-focus.svg or tomat-focus.png)
+        #[arg(long, value_name = "FILE", requires = "export_plot")]
+        plot_file: Option<std::path::PathBuf>,
+    },
+}
+
+impl Commands {
+    /// Whether the command has a `--porcelain` output
+    pub fn supports_porcelain(&self) -> bool {
+        matches!(
+            self,
+            Commands::Start { .. }
+                | Commands::Stop
+                | Commands::Away { .. }
+                | Commands::Pause
+                | Commands::Resume
+                | Commands::Toggle
+                | Commands::Skip { .. }
+                | Commands::PostponeLongBreak
+                | Commands::Break { .. }
+                | Commands::Privacy { .. }
+                | Commands::Annotate { .. }
+                | Commands::Note { .. }
+                | Commands::Status { .. }
+                | Commands::Watch { .. }
+                | Commands::Plan { .. }
+                | Commands::Log { .. }
+        )
+    }
+}
+
+/// Parse an update interval in seconds, which has to be positive
+fn parse_interval(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(seconds) if seconds > 0.0 && seconds.is_finite() => Ok(seconds),
+        _ => Err(format!("'{}' is not a positive number of seconds", s)),
+    }
 }
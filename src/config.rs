@@ -45,6 +45,8 @@ pub enum SoundMode {
     Embedded,
     /// Use system beep
     SystemBeep,
+    /// Generate chimes procedurally instead of playing sound files
+    Synth,
     /// No sound
     None,
 }
@@ -56,9 +58,10 @@ impl std::str::FromStr for SoundMode {
         match s.to_lowercase().as_str() {
             "embedded" => Ok(Self::Embedded),
             "system-beep" => Ok(Self::SystemBeep),
+            "synth" => Ok(Self::Synth),
             "none" => Ok(Self::None),
             _ => Err(format!(
-                "Unknown sound mode: '{}'. Supported: embedded, system-beep, none",
+                "Unknown sound mode: '{}'. Supported: embedded, system-beep, synth, none",
                 s
             )),
         }
@@ -75,6 +78,46 @@ pub enum AutoAdvanceMode {
     ToWork,
 }
 
+/// What happens when the last task of the day plan is done
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum PlanCompleteAction {
+    /// Take an extended long break (default)
+    #[default]
+    LongBreak,
+    /// Stop the timer
+    Stop,
+}
+
+/// How much the waybar tooltip tells about the running phase
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TooltipDetail {
+    /// Only the phase and whether it is paused
+    Minimal,
+    /// Phase, session progress, duration and task (default)
+    #[default]
+    Normal,
+    /// Everything in normal, plus the remaining time and when the phase ends
+    Full,
+}
+
+impl std::str::FromStr for TooltipDetail {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "minimal" => Ok(Self::Minimal),
+            "normal" => Ok(Self::Normal),
+            "full" => Ok(Self::Full),
+            _ => Err(format!(
+                "Unknown tooltip detail: '{}'. Supported: minimal, normal, full",
+                s
+            )),
+        }
+    }
+}
+
 impl AutoAdvanceMode {
     pub fn should_advance(&self, from_work: bool) -> bool {
         match self {
@@ -86,6 +129,17 @@ impl AutoAdvanceMode {
     }
 }
 
+impl std::fmt::Display for AutoAdvanceMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::None => write!(f, "none"),
+            Self::All => write!(f, "all"),
+            Self::ToBreak => write!(f, "to-break"),
+            Self::ToWork => write!(f, "to-work"),
+        }
+    }
+}
+
 impl std::str::FromStr for AutoAdvanceMode {
     type Err = String;
 
@@ -121,6 +175,15 @@ where
     }
 }
 
+fn deserialize_optional_auto_advance<'de, D>(
+    deserializer: D,
+) -> Result<Option<AutoAdvanceMode>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserialize_auto_advance(deserializer).map(Some)
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct Config {
     #[serde(default)]
@@ -133,6 +196,79 @@ pub struct Config {
     pub display: DisplayConfig,
     #[serde(default)]
     pub hooks: HooksConfig,
+    #[serde(default)]
+    pub report: ReportConfig,
+    #[serde(default)]
+    pub focus: FocusConfig,
+    #[serde(default)]
+    pub daemon: DaemonConfig,
+    #[serde(default)]
+    pub schedule: ScheduleConfig,
+    #[serde(default)]
+    pub goal: GoalConfig,
+    #[serde(default)]
+    pub metrics: MetricsConfig,
+    #[serde(default)]
+    pub stats: StatsConfig,
+    #[serde(default)]
+    pub indicator: IndicatorConfig,
+    /// Timer settings that replace `[timer]` on some days of the week
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub profiles: std::collections::BTreeMap<String, TimerProfile>,
+}
+
+/// Timer settings of a profile for some weekdays; unset settings are taken
+/// from `[timer]`
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct TimerProfile {
+    /// Days of the week the profile applies on, e.g. ["sat", "sun"]
+    /// (default: Saturday and Sunday for a profile named "weekend", else none)
+    #[serde(default)]
+    pub days: Option<Vec<chrono::Weekday>>,
+    #[serde(default)]
+    pub work: Option<f32>,
+    #[serde(default, rename = "break")]
+    pub break_time: Option<f32>,
+    #[serde(default)]
+    pub long_break: Option<f32>,
+    #[serde(default)]
+    pub sessions: Option<u32>,
+    #[serde(default, deserialize_with = "deserialize_optional_auto_advance")]
+    pub auto_advance: Option<AutoAdvanceMode>,
+    #[serde(default)]
+    pub prepare: Option<f32>,
+    #[serde(default)]
+    pub review: Option<f32>,
+    #[serde(default)]
+    pub jitter: Option<f32>,
+}
+
+impl TimerProfile {
+    /// Whether the profile called `name` applies on `weekday`
+    fn applies_on(&self, name: &str, weekday: chrono::Weekday) -> bool {
+        use chrono::Weekday;
+        match &self.days {
+            Some(days) => days.contains(&weekday),
+            None => name == "weekend" && matches!(weekday, Weekday::Sat | Weekday::Sun),
+        }
+    }
+
+    /// The timer settings with this profile applied on top of `base`
+    fn apply(&self, base: &TimerConfig) -> TimerConfig {
+        let profile = self.clone();
+        let base = base.clone();
+        TimerConfig {
+            work: profile.work.unwrap_or(base.work),
+            break_time: profile.break_time.unwrap_or(base.break_time),
+            long_break: profile.long_break.unwrap_or(base.long_break),
+            sessions: profile.sessions.unwrap_or(base.sessions),
+            auto_advance: profile.auto_advance.unwrap_or(base.auto_advance),
+            prepare: profile.prepare.unwrap_or(base.prepare),
+            review: profile.review.unwrap_or(base.review),
+            jitter: profile.jitter.unwrap_or(base.jitter),
+            ..base
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -149,9 +285,37 @@ pub struct TimerConfig {
     /// Sessions until long break (default: 4)
     #[serde(default = "default_sessions")]
     pub sessions: u32,
+    /// Also take a long break after this many focused minutes, whichever comes
+    /// first (default: disabled)
+    #[serde(default)]
+    pub long_break_after_minutes: Option<f32>,
+    /// Pauses allowed per work session; pausing once more voids the session
+    /// (default: unlimited)
+    #[serde(default)]
+    pub max_pauses: Option<u32>,
+    /// Minutes of preparation before each work session, e.g. to review the
+    /// task (default: 0, none)
+    #[serde(default)]
+    pub prepare: f32,
+    /// Minutes of review after each work session, before its break
+    /// (default: 0, none)
+    #[serde(default)]
+    pub review: f32,
+    /// Randomize the length of work sessions and breaks by up to this
+    /// fraction, e.g. 0.1 for ±10%, at most 0.5 (default: 0, none)
+    #[serde(default)]
+    pub jitter: f32,
     /// Automatically advance between timer states (default: none)
     #[serde(default, deserialize_with = "deserialize_auto_advance")]
     pub auto_advance: AutoAdvanceMode,
+    /// What to do when the day plan is done: "long-break" or "stop"
+    /// (default: "long-break")
+    #[serde(default)]
+    pub plan_complete: PlanCompleteAction,
+    /// Length of the long break after the day plan is done, in minutes
+    /// (default: twice the long break)
+    #[serde(default)]
+    pub plan_complete_break: Option<f32>,
 }
 
 fn default_work() -> f32 {
@@ -194,6 +358,54 @@ pub struct NotificationConfig {
     /// Custom message for work->long break transition
     #[serde(default = "default_long_break_message")]
     pub long_break_message: String,
+    /// Message when the last task of the day plan is done
+    #[serde(default = "default_plan_complete_message")]
+    pub plan_complete_message: String,
+    /// Message when the timer stops because the workday is over, empty to
+    /// stop without a notification
+    #[serde(default = "default_wrap_up_message")]
+    pub wrap_up_message: String,
+    /// Message when the daily goal is reached, empty for no notification
+    #[serde(default = "default_goal_message")]
+    pub goal_message: String,
+    /// Message when the preparation is over and work starts, empty for no
+    /// notification
+    #[serde(default = "default_prepare_message")]
+    pub prepare_message: String,
+    /// Message when a work session ends with a review, empty for no
+    /// notification
+    #[serde(default = "default_review_message")]
+    pub review_message: String,
+    /// Command that asks for a note on the work session that just ended,
+    /// e.g. `["zenity", "--entry"]`, run from an "Add note" action on the
+    /// notification. Its output is saved with `tomat note` (default: none)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note_prompt: Option<Vec<String>>,
+    /// Show an i3-nagbar/swaynag prompt when a phase ends without auto-advancing (default: false)
+    #[serde(default)]
+    pub nagbar: bool,
+    /// Urgency of the notification when a break ends (default: same as `urgency`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub break_end_urgency: Option<NotificationUrgency>,
+    /// Repeat the break-over notification every N minutes until work is started (default: off)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub break_end_nag: Option<u32>,
+    /// Skip the sound and notification of a phase change within this many
+    /// seconds of the previous one, 0 to never skip (default: 2)
+    #[serde(default = "default_throttle")]
+    pub throttle: u32,
+    /// Phase changes mirrored to a phone paired with KDE Connect
+    #[serde(default)]
+    pub kdeconnect: KdeConnectConfig,
+}
+
+impl NotificationConfig {
+    /// Urgency to use for the notification that a break is over
+    pub fn effective_break_end_urgency(&self) -> NotificationUrgency {
+        self.break_end_urgency
+            .clone()
+            .unwrap_or_else(|| self.urgency.clone())
+    }
 }
 
 fn default_notification_enabled() -> bool {
@@ -208,6 +420,10 @@ fn default_timeout() -> u32 {
     5000
 }
 
+fn default_throttle() -> u32 {
+    2
+}
+
 fn default_work_message() -> String {
     "Break time! Take a short rest ☕".to_string()
 }
@@ -220,22 +436,51 @@ fn default_long_break_message() -> String {
     "Long break time! Take a well-deserved rest 🏖️".to_string()
 }
 
+fn default_plan_complete_message() -> String {
+    "Plan complete! All planned tasks are done 🎉".to_string()
+}
+
+fn default_wrap_up_message() -> String {
+    "Workday is over. Time to wrap up 🌇".to_string()
+}
+
+fn default_goal_message() -> String {
+    "Daily goal reached! Well done 🎯".to_string()
+}
+
+fn default_prepare_message() -> String {
+    "Ready? Focus starts now 🍅".to_string()
+}
+
+fn default_review_message() -> String {
+    "Session done! Note what you got done 📝".to_string()
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct DisplayIcons {
     /// Icon for work phase (default: "🍅")
     #[serde(default = "default_work_icon")]
     pub work: String,
+    /// Icon for the preparation before work (default: "📋")
+    #[serde(default = "default_prepare_icon")]
+    pub prepare: String,
+    /// Icon for the review after work (default: "📝")
+    #[serde(default = "default_review_icon")]
+    pub review: String,
     /// Icon for break phase (default: "☕")
     #[serde(default = "default_break_icon", rename = "break")]
     pub break_icon: String,
     /// Icon for long break phase (default: "🏖️")
     #[serde(default = "default_long_break_icon")]
     pub long_break: String,
+    /// Icon while idle (default: the work icon)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idle: Option<String>,
     /// Symbol for playing state (default: "▶")
-    #[serde(default = "default_play_symbol")]
+    #[serde(default = "default_play_symbol", alias = "running")]
     pub play: String,
     /// Symbol for paused state (default: "⏸")
-    #[serde(default = "default_pause_symbol")]
+    #[serde(default = "default_pause_symbol", alias = "paused")]
     pub pause: String,
     /// Symbol for stopped/idle state (default: "⏹")
     #[serde(default = "default_stop_symbol")]
@@ -246,6 +491,14 @@ fn default_work_icon() -> String {
     "🍅".to_string()
 }
 
+fn default_prepare_icon() -> String {
+    "📋".to_string()
+}
+
+fn default_review_icon() -> String {
+    "📝".to_string()
+}
+
 fn default_break_icon() -> String {
     "☕".to_string()
 }
@@ -270,8 +523,11 @@ impl Default for DisplayIcons {
     fn default() -> Self {
         Self {
             work: default_work_icon(),
+            prepare: default_prepare_icon(),
+            review: default_review_icon(),
             break_icon: default_break_icon(),
             long_break: default_long_break_icon(),
+            idle: None,
             play: default_play_symbol(),
             pause: default_pause_symbol(),
             stop: default_stop_symbol(),
@@ -279,26 +535,252 @@ impl Default for DisplayIcons {
     }
 }
 
+/// Colors of the phases in colored output (zellij and `tomat prompt --json`)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DisplayColors {
+    /// Color while working (default: "#f38ba8")
+    #[serde(default = "default_work_color")]
+    pub work: String,
+    /// Color during short breaks (default: "#a6e3a1")
+    #[serde(default = "default_break_color", rename = "break")]
+    pub break_color: String,
+    /// Color during long breaks (default: "#89b4fa")
+    #[serde(default = "default_long_break_color")]
+    pub long_break: String,
+    /// Color while paused, in any phase but idle (default: "#f9e2af")
+    #[serde(default = "default_paused_color")]
+    pub paused: String,
+    /// Color while idle (default: "#cdd6f4")
+    #[serde(default = "default_idle_color")]
+    pub idle: String,
+}
+
+impl DisplayColors {
+    /// Pick the color for a phase
+    pub fn color_for(&self, phase: &crate::timer::Phase, is_paused: bool) -> &str {
+        use crate::timer::Phase;
+
+        match phase {
+            Phase::Idle => &self.idle,
+            _ if is_paused => &self.paused,
+            Phase::Prepare | Phase::Work | Phase::Review => &self.work,
+            Phase::Break => &self.break_color,
+            Phase::LongBreak => &self.long_break,
+        }
+    }
+}
+
+fn default_work_color() -> String {
+    "#f38ba8".to_string()
+}
+
+fn default_break_color() -> String {
+    "#a6e3a1".to_string()
+}
+
+fn default_long_break_color() -> String {
+    "#89b4fa".to_string()
+}
+
+fn default_paused_color() -> String {
+    "#f9e2af".to_string()
+}
+
+fn default_idle_color() -> String {
+    "#cdd6f4".to_string()
+}
+
+impl Default for DisplayColors {
+    fn default() -> Self {
+        Self {
+            work: default_work_color(),
+            break_color: default_break_color(),
+            long_break: default_long_break_color(),
+            paused: default_paused_color(),
+            idle: default_idle_color(),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct DisplayConfig {
     /// Text format template for active phases (default: "{icon} {time} {state}")
-    /// Available placeholders: {icon}, {time}, {state}, {phase}, {session}
+    /// Available placeholders: {icon}, {time}, {state}, {phase}, {session},
+    /// {sessions_total}, {percentage}, {task}, {completed}, {goal}
     #[serde(default = "default_text_format")]
     pub text_format: String,
     /// Text format template for idle phase (default: same as text_format)
     /// Set to empty string "" to hide widget when idle
-    /// Available placeholders: {icon}, {time}, {state}, {phase}, {session}
+    /// Available placeholders: {icon}, {time}, {state}, {phase}, {session},
+    /// {sessions_total}, {percentage}, {task}, {completed}, {goal}
     #[serde(default)]
     pub text_format_idle: Option<String>,
     /// Icon configuration for phases and states
     #[serde(default)]
     pub icons: DisplayIcons,
+    /// Phase colors for colored output
+    #[serde(default)]
+    pub colors: DisplayColors,
+    /// Templates for individual phases, overriding text_format
+    #[serde(default)]
+    pub formats: DisplayFormats,
+    /// Alternative text template, emitted as `alt` in the waybar output
+    /// (default: "{session}")
+    #[serde(default = "default_alt_format")]
+    pub alt_format: String,
+    /// Swap the text and alternative templates every this many seconds
+    /// while a session is running (default: 0, never)
+    #[serde(default)]
+    pub rotate_seconds: u64,
+    /// How much detail the waybar tooltip shows (default: "normal")
+    #[serde(default)]
+    pub tooltip: TooltipDetail,
+    /// Tooltip template, in place of the built-in tooltip (default: none)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tooltip_format: Option<String>,
+    /// Named pipe the daemon writes each status update to (default: none)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fifo: Option<PathBuf>,
+    /// Named variants of these settings, picked with `status --profile`
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub profiles: std::collections::BTreeMap<String, DisplayProfile>,
+}
+
+/// Display settings of a profile; unset settings are taken from `[display]`
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DisplayProfile {
+    #[serde(default)]
+    pub text_format: Option<String>,
+    #[serde(default)]
+    pub text_format_idle: Option<String>,
+    #[serde(default)]
+    pub formats: Option<DisplayFormats>,
+    #[serde(default)]
+    pub alt_format: Option<String>,
+    #[serde(default)]
+    pub rotate_seconds: Option<u64>,
+    #[serde(default)]
+    pub tooltip: Option<TooltipDetail>,
+    #[serde(default)]
+    pub tooltip_format: Option<String>,
+}
+
+/// Per-phase text templates; unset phases use `text_format`
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DisplayFormats {
+    /// Template while working
+    #[serde(default)]
+    pub work: Option<String>,
+    /// Template during the review after work
+    #[serde(default)]
+    pub review: Option<String>,
+    /// Template during short breaks (and long breaks, unless long_break is set)
+    #[serde(default, rename = "break")]
+    pub break_time: Option<String>,
+    /// Template during long breaks
+    #[serde(default)]
+    pub long_break: Option<String>,
+    /// Template while paused, in any phase but idle
+    #[serde(default)]
+    pub paused: Option<String>,
+}
+
+impl DisplayConfig {
+    /// The display settings with a profile from `[display.profiles]`
+    /// applied. A profile that is not configured but named after a tooltip
+    /// detail level (e.g. "minimal") only sets the tooltip detail.
+    pub fn with_profile(&self, name: &str) -> Result<Self, crate::error::TomatError> {
+        let profile = match self.profiles.get(name) {
+            Some(profile) => profile.clone(),
+            None => DisplayProfile {
+                tooltip: Some(name.parse().map_err(|_| {
+                    crate::error::TomatError::InvalidArgument(format!(
+                        "Unknown display profile '{}'. Configure it under [display.profiles.{}]",
+                        name, name
+                    ))
+                })?),
+                ..Default::default()
+            },
+        };
+
+        let base = self.clone();
+        Ok(Self {
+            text_format: profile.text_format.unwrap_or(base.text_format),
+            text_format_idle: profile.text_format_idle.or(base.text_format_idle),
+            formats: profile.formats.unwrap_or(base.formats),
+            alt_format: profile.alt_format.unwrap_or(base.alt_format),
+            rotate_seconds: profile.rotate_seconds.unwrap_or(base.rotate_seconds),
+            tooltip: profile.tooltip.unwrap_or(base.tooltip),
+            tooltip_format: profile.tooltip_format.or(base.tooltip_format),
+            ..base
+        })
+    }
+
+    /// Pick the text template for a phase. An explicit template (from
+    /// `--format`) wins over the configuration, except when idle.
+    pub fn template_for<'a>(
+        &'a self,
+        phase: &crate::timer::Phase,
+        is_paused: bool,
+        explicit: Option<&'a str>,
+    ) -> &'a str {
+        use crate::timer::Phase;
+
+        if matches!(phase, Phase::Idle) {
+            return self
+                .text_format_idle
+                .as_deref()
+                .unwrap_or(&self.text_format);
+        }
+        if let Some(template) = explicit {
+            return template;
+        }
+
+        let phase_format = match phase {
+            Phase::Work => self.formats.work.as_deref(),
+            Phase::Review => self.formats.review.as_deref(),
+            Phase::Break => self.formats.break_time.as_deref(),
+            Phase::LongBreak => self
+                .formats
+                .long_break
+                .as_deref()
+                .or(self.formats.break_time.as_deref()),
+            Phase::Idle | Phase::Prepare => None,
+        };
+        self.formats
+            .paused
+            .as_deref()
+            .filter(|_| is_paused)
+            .or(phase_format)
+            .unwrap_or(&self.text_format)
+    }
+
+    /// Pick the text and alternative templates at `now`, swapping them
+    /// every other `rotate_seconds` outside of the idle phase
+    pub fn templates_at<'a>(
+        &'a self,
+        phase: &crate::timer::Phase,
+        is_paused: bool,
+        explicit: Option<&'a str>,
+        now: u64,
+    ) -> (&'a str, &'a str) {
+        let text = self.template_for(phase, is_paused, explicit);
+        let alt = self.alt_format.as_str();
+        let rotated = self.rotate_seconds > 0
+            && !matches!(phase, crate::timer::Phase::Idle)
+            && (now / self.rotate_seconds) % 2 == 1;
+        if rotated { (alt, text) } else { (text, alt) }
+    }
 }
 
 fn default_text_format() -> String {
     "{icon} {time} {state}".to_string()
 }
 
+fn default_alt_format() -> String {
+    "{session}".to_string()
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SoundConfig {
     /// Sound mode: "embedded", "system-beep", or "none" (default: "embedded")
@@ -323,12 +805,86 @@ pub struct SoundConfig {
     /// Volume level 0.0-1.0 (default: 0.5)
     #[serde(default = "default_volume")]
     pub volume: f32,
-    /// Custom sound file for work->break transition (overrides embedded)
+    /// Custom sound file for work->break transition (overrides embedded), or "none"
     pub work_to_break: Option<String>,
-    /// Custom sound file for break->work transition (overrides embedded)
+    /// Custom sound file for prepare->work transition (overrides embedded), or "none"
+    pub prepare_to_work: Option<String>,
+    /// Custom sound file for break->work transition (overrides embedded), or "none"
     pub break_to_work: Option<String>,
-    /// Custom sound file for work->long_break transition (overrides embedded)
+    /// Custom sound file for work->long_break transition (overrides embedded), or "none"
     pub work_to_long_break: Option<String>,
+    /// Play sounds from the freedesktop sound theme instead of the embedded
+    /// ones (default: false)
+    #[serde(default)]
+    pub theme_sounds: bool,
+    /// Sound theme to use with theme_sounds (default: "freedesktop")
+    #[serde(default = "default_sound_theme")]
+    pub theme: String,
+    /// Play a soft chime every N minutes during work (default: disabled)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval_chime: Option<u32>,
+    /// Tone settings for mode = "synth"
+    #[serde(default)]
+    pub synth: SynthConfig,
+    /// Audio backend to play through, e.g. "alsa" or "jack" (default: system default)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend: Option<String>,
+    /// Output device name, e.g. "pipewire" or "hw:0,0" (default: system default)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device: Option<String>,
+    /// Output buffer size in frames (default: about 50ms of audio)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub buffer_size: Option<u32>,
+}
+
+/// Whether a per-transition sound setting silences that transition
+pub fn is_sound_off(setting: &str) -> bool {
+    setting.eq_ignore_ascii_case("none")
+}
+
+/// Tones used by the synthesized sound mode
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SynthConfig {
+    /// Note frequencies in Hz, played in order at the end of work and
+    /// reversed at the end of a break (default: A5, E5, C5)
+    #[serde(default = "default_synth_frequencies")]
+    pub frequencies: Vec<f32>,
+    /// Length of each note in milliseconds (default: 180)
+    #[serde(default = "default_synth_note_ms")]
+    pub note_ms: u64,
+    /// Fade-in at the start of each note in milliseconds (default: 10)
+    #[serde(default = "default_synth_attack_ms")]
+    pub attack_ms: u64,
+    /// Fade-out at the end of each note in milliseconds (default: 120)
+    #[serde(default = "default_synth_release_ms")]
+    pub release_ms: u64,
+}
+
+fn default_synth_frequencies() -> Vec<f32> {
+    vec![880.0, 659.25, 523.25]
+}
+
+fn default_synth_note_ms() -> u64 {
+    180
+}
+
+fn default_synth_attack_ms() -> u64 {
+    10
+}
+
+fn default_synth_release_ms() -> u64 {
+    120
+}
+
+impl Default for SynthConfig {
+    fn default() -> Self {
+        Self {
+            frequencies: default_synth_frequencies(),
+            note_ms: default_synth_note_ms(),
+            attack_ms: default_synth_attack_ms(),
+            release_ms: default_synth_release_ms(),
+        }
+    }
 }
 
 fn default_use_embedded() -> bool {
@@ -339,6 +895,10 @@ fn default_volume() -> f32 {
     0.5
 }
 
+fn default_sound_theme() -> String {
+    "freedesktop".to_string()
+}
+
 fn default_sound_enabled() -> bool {
     true
 }
@@ -355,8 +915,16 @@ impl Default for SoundConfig {
             use_embedded: true,
             volume: 0.5,
             work_to_break: None,
+            prepare_to_work: None,
             break_to_work: None,
             work_to_long_break: None,
+            theme_sounds: false,
+            theme: default_sound_theme(),
+            interval_chime: None,
+            synth: SynthConfig::default(),
+            backend: None,
+            device: None,
+            buffer_size: None,
         }
     }
 }
@@ -396,7 +964,14 @@ impl Default for TimerConfig {
             break_time: default_break(),
             long_break: default_long_break(),
             sessions: default_sessions(),
+            long_break_after_minutes: None,
+            max_pauses: None,
+            prepare: 0.0,
+            review: 0.0,
+            jitter: 0.0,
             auto_advance: AutoAdvanceMode::None,
+            plan_complete: PlanCompleteAction::default(),
+            plan_complete_break: None,
         }
     }
 }
@@ -411,6 +986,17 @@ impl Default for NotificationConfig {
             work_message: default_work_message(),
             break_message: default_break_message(),
             long_break_message: default_long_break_message(),
+            plan_complete_message: default_plan_complete_message(),
+            wrap_up_message: default_wrap_up_message(),
+            goal_message: default_goal_message(),
+            prepare_message: default_prepare_message(),
+            review_message: default_review_message(),
+            note_prompt: None,
+            nagbar: false,
+            break_end_urgency: None,
+            break_end_nag: None,
+            throttle: default_throttle(),
+            kdeconnect: KdeConnectConfig::default(),
         }
     }
 }
@@ -421,17 +1007,33 @@ impl Default for DisplayConfig {
             text_format: default_text_format(),
             text_format_idle: None,
             icons: DisplayIcons::default(),
+            colors: DisplayColors::default(),
+            formats: DisplayFormats::default(),
+            alt_format: default_alt_format(),
+            rotate_seconds: 0,
+            tooltip: TooltipDetail::default(),
+            tooltip_format: None,
+            fifo: None,
+            profiles: std::collections::BTreeMap::new(),
         }
     }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct HooksConfig {
+    #[serde(default)]
+    pub on_prepare_start: Option<HookCommand>,
+    #[serde(default)]
+    pub on_prepare_end: Option<HookCommand>,
     #[serde(default)]
     pub on_work_start: Option<HookCommand>,
     #[serde(default)]
     pub on_work_end: Option<HookCommand>,
     #[serde(default)]
+    pub on_review_start: Option<HookCommand>,
+    #[serde(default)]
+    pub on_review_end: Option<HookCommand>,
+    #[serde(default)]
     pub on_break_start: Option<HookCommand>,
     #[serde(default)]
     pub on_break_end: Option<HookCommand>,
@@ -439,6 +1041,16 @@ pub struct HooksConfig {
     pub on_long_break_start: Option<HookCommand>,
     #[serde(default)]
     pub on_long_break_end: Option<HookCommand>,
+    /// Runs when any phase starts, after the hook of that phase
+    #[serde(default)]
+    pub on_phase_start: Option<HookCommand>,
+    /// Runs when any phase ends, after the hook of that phase
+    #[serde(default)]
+    pub on_phase_end: Option<HookCommand>,
+    /// Runs on every phase change, pause, resume and stop, with the old and
+    /// new status as JSON on stdin
+    #[serde(default)]
+    pub on_state_change: Option<HookCommand>,
     #[serde(default)]
     pub on_pause: Option<HookCommand>,
     #[serde(default)]
@@ -447,6 +1059,10 @@ pub struct HooksConfig {
     pub on_stop: Option<HookCommand>,
     #[serde(default)]
     pub on_skip: Option<HookCommand>,
+    #[serde(default)]
+    pub on_plan_complete: Option<HookCommand>,
+    #[serde(default)]
+    pub on_goal_reached: Option<HookCommand>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -456,7 +1072,7 @@ pub struct HookCommand {
     /// Command arguments (default: empty)
     #[serde(default)]
     pub args: Vec<String>,
-    /// Timeout in seconds (default: 5)
+    /// Timeout in seconds for the hook and anything it started (default: 5)
     #[serde(default = "default_hook_timeout")]
     pub timeout: u64,
     /// Working directory (default: user's home directory)
@@ -465,22 +1081,101 @@ pub struct HookCommand {
     /// Capture output for debugging (default: false, redirects to /dev/null)
     #[serde(default)]
     pub capture_output: bool,
+    /// Phases the hook runs in, e.g. ["work"] (default: all)
+    #[serde(default)]
+    pub phases: Vec<HookPhase>,
+    /// Run the hook only in long breaks (default: false)
+    #[serde(default)]
+    pub only_long_break: bool,
+}
+
+/// Phase that a hook can be limited to
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum HookPhase {
+    Prepare,
+    Work,
+    Review,
+    Break,
+    LongBreak,
+}
+
+impl HookPhase {
+    /// Name of the phase as in `TOMAT_PHASE`
+    fn name(self) -> &'static str {
+        match self {
+            HookPhase::Prepare => "prepare",
+            HookPhase::Work => "work",
+            HookPhase::Review => "review",
+            HookPhase::Break => "break",
+            HookPhase::LongBreak => "long_break",
+        }
+    }
 }
 
 fn default_hook_timeout() -> u64 {
     5
 }
 
+/// Process groups of hooks that may still have processes running
+static HOOK_GROUPS: std::sync::Mutex<Vec<i32>> = std::sync::Mutex::new(Vec::new());
+
+/// How long a timed out hook gets to exit after SIGTERM before it is killed
+const HOOK_KILL_GRACE: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// How often the process group of a hook without a timeout is checked for
+/// processes it left running
+const HOOK_GROUP_POLL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Whether any process in the group is still alive
+fn group_alive(pgid: i32) -> bool {
+    unsafe { libc::killpg(pgid, 0) == 0 }
+}
+
+fn signal_group(pgid: i32, signal: i32) {
+    unsafe {
+        libc::killpg(pgid, signal);
+    }
+}
+
+fn untrack_group(pgid: i32) {
+    HOOK_GROUPS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .retain(|&group| group != pgid);
+}
+
+/// Terminate every hook process that is still running, e.g. background jobs
+/// a hook left behind, when the daemon stops
+pub fn terminate_hooks() {
+    let groups = std::mem::take(&mut *HOOK_GROUPS.lock().unwrap_or_else(|e| e.into_inner()));
+    for pgid in groups {
+        if group_alive(pgid) {
+            signal_group(pgid, libc::SIGTERM);
+        }
+    }
+}
+
+/// What hooks are told about the timer, through `TOMAT_*` environment variables
+#[derive(Debug, Clone, Default)]
+pub struct HookEnv {
+    pub phase: String,
+    pub remaining_seconds: u64,
+    pub session_count: u32,
+    pub auto_advance: String,
+    pub task: Option<String>,
+    pub tags: Vec<String>,
+}
+
 impl HookCommand {
-    /// Execute the hook command asynchronously
-    pub async fn execute(
-        &self,
-        event: &str,
-        phase: &str,
-        remaining_seconds: u64,
-        session_count: u32,
-        auto_advance: &str,
-    ) {
+    /// Whether the hook's phase filters let it run in `phase`
+    pub fn runs_in(&self, phase: &str) -> bool {
+        (!self.only_long_break || phase == HookPhase::LongBreak.name())
+            && (self.phases.is_empty() || self.phases.iter().any(|p| p.name() == phase))
+    }
+
+    /// Execute the hook command asynchronously, writing `input` to its stdin
+    pub async fn execute(&self, event: &str, env: &HookEnv, input: Option<&str>) {
         use std::process::Stdio;
         use tokio::process::Command;
 
@@ -489,10 +1184,12 @@ impl HookCommand {
 
         // Set environment variables
         cmd.env("TOMAT_EVENT", event);
-        cmd.env("TOMAT_PHASE", phase);
-        cmd.env("TOMAT_REMAINING_SECONDS", remaining_seconds.to_string());
-        cmd.env("TOMAT_SESSION_COUNT", session_count.to_string());
-        cmd.env("TOMAT_AUTO_ADVANCE", auto_advance);
+        cmd.env("TOMAT_PHASE", &env.phase);
+        cmd.env("TOMAT_REMAINING_SECONDS", env.remaining_seconds.to_string());
+        cmd.env("TOMAT_SESSION_COUNT", env.session_count.to_string());
+        cmd.env("TOMAT_AUTO_ADVANCE", &env.auto_advance);
+        cmd.env("TOMAT_TASK", env.task.as_deref().unwrap_or_default());
+        cmd.env("TOMAT_TAGS", env.tags.join(","));
 
         // Set working directory
         if let Some(cwd) = &self.cwd {
@@ -501,6 +1198,10 @@ impl HookCommand {
             cmd.current_dir(home);
         }
 
+        // Run in a process group of its own, so that anything the hook starts
+        // can be terminated along with it
+        cmd.process_group(0);
+
         // Configure output handling
         if self.capture_output {
             cmd.stdout(Stdio::piped());
@@ -510,6 +1211,10 @@ impl HookCommand {
             cmd.stderr(Stdio::null());
         }
 
+        if input.is_some() {
+            cmd.stdin(Stdio::piped());
+        }
+
         // Spawn the command
         let mut child = match cmd.spawn() {
             Ok(child) => child,
@@ -519,6 +1224,22 @@ impl HookCommand {
             }
         };
 
+        // Closing stdin after the input lets the hook read it to the end
+        if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+            use tokio::io::AsyncWriteExt;
+            if let Err(e) = stdin.write_all(input.as_bytes()).await {
+                eprintln!("Failed to write to hook command '{}': {}", self.cmd, e);
+            }
+        }
+
+        let pgid = child.id().map(|pid| pid as i32);
+        if let Some(pgid) = pgid {
+            let mut groups = HOOK_GROUPS.lock().unwrap_or_else(|e| e.into_inner());
+            // The ID of an empty group may be reused by an unrelated one
+            groups.retain(|&group| group_alive(group));
+            groups.push(pgid);
+        }
+
         // Wait for command to complete with optional timeout
         // timeout = 0 means no timeout (wait indefinitely)
         if self.timeout == 0 {
@@ -536,7 +1257,8 @@ impl HookCommand {
         } else {
             // Apply timeout
             let timeout_duration = std::time::Duration::from_secs(self.timeout);
-            match tokio::time::timeout(timeout_duration, child.wait()).await {
+            let deadline = tokio::time::Instant::now() + timeout_duration;
+            match tokio::time::timeout_at(deadline, child.wait()).await {
                 Ok(Ok(status)) => {
                     if !status.success() {
                         eprintln!("Hook command '{}' exited with status: {}", self.cmd, status);
@@ -553,43 +1275,530 @@ impl HookCommand {
                     let _ = child.kill().await;
                 }
             }
+
+            // Processes the hook started in the background share its timeout,
+            // rather than outliving it
+            if let Some(pgid) = pgid {
+                while group_alive(pgid) && tokio::time::Instant::now() < deadline {
+                    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+                }
+                if group_alive(pgid) {
+                    eprintln!(
+                        "Terminating processes left running by hook command '{}'",
+                        self.cmd
+                    );
+                    signal_group(pgid, libc::SIGTERM);
+                    tokio::time::sleep(HOOK_KILL_GRACE).await;
+                    signal_group(pgid, libc::SIGKILL);
+                }
+            }
+        }
+
+        // Without a timeout, leftover processes may run until the daemon stops.
+        // The group is only tracked while it has processes, as its ID may be
+        // reused by an unrelated process group once it is empty.
+        match pgid {
+            Some(pgid) if self.timeout == 0 => {
+                tokio::spawn(async move {
+                    while group_alive(pgid) {
+                        tokio::time::sleep(HOOK_GROUP_POLL).await;
+                    }
+                    untrack_group(pgid);
+                });
+            }
+            Some(pgid) => untrack_group(pgid),
+            None => {}
+        }
+    }
+}
+
+impl HooksConfig {
+    /// Execute a hook by event name
+    pub async fn execute_hook(&self, event: &str, env: &HookEnv) {
+        let hook = match event {
+            "prepare_start" => &self.on_prepare_start,
+            "prepare_end" => &self.on_prepare_end,
+            "work_start" => &self.on_work_start,
+            "work_end" => &self.on_work_end,
+            "review_start" => &self.on_review_start,
+            "review_end" => &self.on_review_end,
+            "break_start" => &self.on_break_start,
+            "break_end" => &self.on_break_end,
+            "long_break_start" => &self.on_long_break_start,
+            "long_break_end" => &self.on_long_break_end,
+            "pause" => &self.on_pause,
+            "resume" => &self.on_resume,
+            "stop" => &self.on_stop,
+            "skip" => &self.on_skip,
+            "plan_complete" => &self.on_plan_complete,
+            "goal_reached" => &self.on_goal_reached,
+            _ => return,
+        };
+        let generic = match event {
+            "prepare_start" | "work_start" | "review_start" | "break_start"
+            | "long_break_start" => &self.on_phase_start,
+            "prepare_end" | "work_end" | "review_end" | "break_end" | "long_break_end" => {
+                &self.on_phase_end
+            }
+            _ => &None,
+        };
+
+        for hook_cmd in [hook, generic].into_iter().flatten() {
+            if hook_cmd.runs_in(&env.phase) {
+                hook_cmd.execute(event, env, None).await;
+            }
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportMethod {
+    /// Pipe the message to a sendmail-compatible command (default)
+    #[default]
+    Sendmail,
+    /// Deliver via SMTP using curl
+    Smtp,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct GoalConfig {
+    /// Work sessions to complete each day (default: none)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily_pomodoros: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ScheduleConfig {
+    /// Local time of day after which no new work sessions start, "HH:MM"
+    /// (default: none)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workday_end: Option<String>,
+    /// Profiles from `[profiles]` that cycles started at some times of day
+    /// use, by "HH:MM-HH:MM" range, e.g. "06:00-12:00" = "morning"
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub presets: std::collections::BTreeMap<String, String>,
+}
+
+impl ScheduleConfig {
+    /// The end of the workday, if one is set and valid
+    pub fn workday_end_time(&self) -> Option<chrono::NaiveTime> {
+        let end = self.workday_end.as_deref()?;
+        match chrono::NaiveTime::parse_from_str(end, "%H:%M") {
+            Ok(time) => Some(time),
+            Err(_) => {
+                eprintln!(
+                    "Invalid workday_end '{}', expected HH:MM. Ignoring it.",
+                    end
+                );
+                None
+            }
+        }
+    }
+
+    /// The preset whose time range contains `time`. Ranges include their
+    /// start but not their end, and may wrap around midnight.
+    pub fn preset_at(&self, time: chrono::NaiveTime) -> Option<&str> {
+        self.presets.iter().find_map(|(range, preset)| {
+            let Some((start, end)) = parse_time_range(range) else {
+                eprintln!(
+                    "Invalid preset time range '{}', expected HH:MM-HH:MM. Ignoring it.",
+                    range
+                );
+                return None;
+            };
+            let contains = if start <= end {
+                (start..end).contains(&time)
+            } else {
+                time >= start || time < end
+            };
+            contains.then_some(preset.as_str())
+        })
+    }
+}
+
+/// Parse a "HH:MM-HH:MM" time range
+fn parse_time_range(range: &str) -> Option<(chrono::NaiveTime, chrono::NaiveTime)> {
+    let (start, end) = range.split_once('-')?;
+    let parse = |time: &str| chrono::NaiveTime::parse_from_str(time.trim(), "%H:%M").ok();
+    Some((parse(start)?, parse(end)?))
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReportConfig {
+    /// Email a summary of the day's sessions (default: false)
+    #[serde(default)]
+    pub enabled: bool,
+    /// Local time of day at which the report is sent, "HH:MM" (default: "18:00")
+    #[serde(default = "default_report_time")]
+    pub time: String,
+    /// Recipient address
+    #[serde(default)]
+    pub to: String,
+    /// Sender address (default: "tomat@localhost")
+    #[serde(default = "default_report_from")]
+    pub from: String,
+    /// Email subject (default: "Tomat daily summary")
+    #[serde(default = "default_report_subject")]
+    pub subject: String,
+    /// Delivery method: "sendmail" or "smtp" (default: "sendmail")
+    #[serde(default)]
+    pub method: ReportMethod,
+    /// Sendmail-compatible command, invoked with `-t` (default: "sendmail")
+    #[serde(default = "default_sendmail")]
+    pub sendmail: String,
+    /// SMTP server URL, e.g. "smtps://smtp.example.com:465"
+    #[serde(default)]
+    pub smtp_url: Option<String>,
+    /// SMTP user name
+    #[serde(default)]
+    pub smtp_user: Option<String>,
+    /// Command printing the SMTP password on stdout (e.g. ["pass", "show", "smtp"])
+    #[serde(default)]
+    pub smtp_password_cmd: Vec<String>,
+}
+
+fn default_report_time() -> String {
+    "18:00".to_string()
+}
+
+fn default_report_from() -> String {
+    "tomat@localhost".to_string()
+}
+
+fn default_report_subject() -> String {
+    "Tomat daily summary".to_string()
+}
+
+fn default_sendmail() -> String {
+    "sendmail".to_string()
+}
+
+impl Default for ReportConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            time: default_report_time(),
+            to: String::new(),
+            from: default_report_from(),
+            subject: default_report_subject(),
+            method: ReportMethod::default(),
+            sendmail: default_sendmail(),
+            smtp_url: None,
+            smtp_user: None,
+            smtp_password_cmd: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FocusConfig {
+    /// Applications to keep away during work, matched case-insensitively
+    /// against the window's app id or class (default: none)
+    #[serde(default)]
+    pub block: Vec<String>,
+    /// What to do with blocked applications when work starts (default: "warn")
+    #[serde(default)]
+    pub action: FocusAction,
+    /// Hyprland special workspace to move the focused window to during work
+    /// (default: disabled)
+    #[serde(default)]
+    pub hyprland_workspace: Option<String>,
+    /// Lock the screen when a long break starts (default: false)
+    #[serde(default)]
+    pub lock_on_long_break: bool,
+    /// Command that locks the screen (default: ["loginctl", "lock-session"])
+    #[serde(default = "default_lock_command")]
+    pub lock_command: Vec<String>,
+    /// Break message and wallpaper shown during long breaks
+    #[serde(default)]
+    pub lockscreen: LockscreenConfig,
+}
+
+fn default_lock_command() -> Vec<String> {
+    vec!["loginctl".to_string(), "lock-session".to_string()]
+}
+
+impl Default for FocusConfig {
+    fn default() -> Self {
+        Self {
+            block: Vec::new(),
+            action: FocusAction::default(),
+            hyprland_workspace: None,
+            lock_on_long_break: false,
+            lock_command: default_lock_command(),
+            lockscreen: LockscreenConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LockscreenConfig {
+    /// File that holds "Break until HH:MM" during long breaks and is emptied
+    /// afterwards, for a lock screen label to show (default: none)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message_file: Option<PathBuf>,
+    /// Image to set as the wallpaper during long breaks (default: none)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wallpaper: Option<PathBuf>,
+    /// Image to set as the wallpaper again after long breaks (default: none)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restore_wallpaper: Option<PathBuf>,
+    /// Command that sets the wallpaper, with {file} replaced by the image
+    /// (default: ["swww", "img", "{file}"])
+    #[serde(default = "default_wallpaper_command")]
+    pub wallpaper_command: Vec<String>,
+}
+
+fn default_wallpaper_command() -> Vec<String> {
+    vec!["swww".to_string(), "img".to_string(), "{file}".to_string()]
+}
+
+impl Default for LockscreenConfig {
+    fn default() -> Self {
+        Self {
+            message_file: None,
+            wallpaper: None,
+            restore_wallpaper: None,
+            wallpaper_command: default_wallpaper_command(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KdeConnectConfig {
+    /// ID of the paired device to notify, as listed by `kdeconnect-cli -l`
+    /// (default: none)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device: Option<String>,
+    /// Notify when a break starts (default: true)
+    #[serde(default = "default_kdeconnect_event")]
+    pub break_start: bool,
+    /// Notify when a long break starts (default: true)
+    #[serde(default = "default_kdeconnect_event")]
+    pub long_break_start: bool,
+    /// Notify when a break is over (default: true)
+    #[serde(default = "default_kdeconnect_event")]
+    pub work_start: bool,
+    /// Notify when the timer stops after the day plan or workday is done
+    /// (default: true)
+    #[serde(default = "default_kdeconnect_event")]
+    pub stop: bool,
+}
+
+fn default_kdeconnect_event() -> bool {
+    true
+}
+
+impl KdeConnectConfig {
+    /// Whether a phase change starting `event` (a hook event name such as
+    /// "break_start") is sent to the phone
+    pub fn sends(&self, event: &str) -> bool {
+        self.device.is_some()
+            && match event {
+                "break_start" => self.break_start,
+                "long_break_start" => self.long_break_start,
+                "work_start" => self.work_start,
+                "stop" => self.stop,
+                _ => false,
+            }
+    }
+}
+
+impl Default for KdeConnectConfig {
+    fn default() -> Self {
+        Self {
+            device: None,
+            break_start: default_kdeconnect_event(),
+            long_break_start: default_kdeconnect_event(),
+            work_start: default_kdeconnect_event(),
+            stop: default_kdeconnect_event(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct MetricsConfig {
+    /// File to keep Prometheus metrics in for the node_exporter textfile
+    /// collector (default: none)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub textfile: Option<PathBuf>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IndicatorConfig {
+    /// LED in /sys/class/leds to show the phase on (default: none)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sysfs: Option<PathBuf>,
+    /// Address of an OpenRGB SDK server whose devices show the phase,
+    /// "host:port" (default: none)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub openrgb: Option<String>,
+    /// Color during work (default: "#ff0000")
+    #[serde(default = "default_indicator_work_color")]
+    pub work_color: String,
+    /// Color during breaks (default: "#00ff00")
+    #[serde(default = "default_indicator_break_color")]
+    pub break_color: String,
+    /// Color while idle, black for off (default: "#000000")
+    #[serde(default = "default_indicator_idle_color")]
+    pub idle_color: String,
+}
+
+fn default_indicator_work_color() -> String {
+    "#ff0000".to_string()
+}
+
+fn default_indicator_break_color() -> String {
+    "#00ff00".to_string()
+}
+
+fn default_indicator_idle_color() -> String {
+    "#000000".to_string()
+}
+
+impl Default for IndicatorConfig {
+    fn default() -> Self {
+        Self {
+            sysfs: None,
+            openrgb: None,
+            work_color: default_indicator_work_color(),
+            break_color: default_indicator_break_color(),
+            idle_color: default_indicator_idle_color(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct StatsConfig {
+    /// What the focus efficiency compares (default: "minutes")
+    #[serde(default)]
+    pub efficiency: EfficiencyBasis,
+    /// Percentage points taken off the efficiency for each pause per work
+    /// session (default: 0)
+    #[serde(default)]
+    pub pause_penalty: f64,
+    /// Local time of day at which a new day starts in the statistics,
+    /// "HH:MM" (default: midnight)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub day_start: Option<String>,
+}
+
+impl StatsConfig {
+    /// The start of a day, midnight unless a valid time is set
+    pub fn day_start_time(&self) -> chrono::NaiveTime {
+        let Some(start) = self.day_start.as_deref() else {
+            return chrono::NaiveTime::MIN;
+        };
+        match chrono::NaiveTime::parse_from_str(start, "%H:%M") {
+            Ok(time) => time,
+            Err(_) => {
+                eprintln!(
+                    "Invalid day_start '{}', expected HH:MM. Using midnight.",
+                    start
+                );
+                chrono::NaiveTime::MIN
+            }
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum EfficiencyBasis {
+    /// Minutes worked out of the minutes planned for the work sessions (default)
+    #[default]
+    Minutes,
+    /// Work sessions completed out of the work sessions started
+    Sessions,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum FocusAction {
+    /// Only send a notification listing the open applications (default)
+    #[default]
+    Warn,
+    /// Minimize the applications and send a notification listing them
+    Minimize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DaemonConfig {
+    /// Where the daemon keeps its timer state between restarts (default: "file")
+    #[serde(default)]
+    pub state_backend: StateBackend,
+    /// Register the end of each phase as a systemd user timer (default: false)
+    #[serde(default)]
+    pub register_alarm: bool,
+    /// Reload the config file when it changes (default: false)
+    #[serde(default)]
+    pub watch_config: bool,
+    /// Send a notification when the config is reloaded (default: false)
+    #[serde(default)]
+    pub reload_notification: bool,
+    /// Start the daemon when a client command finds it not running (default: false)
+    #[serde(default)]
+    pub auto_spawn: bool,
+    /// TCP address to listen on in addition to the socket, e.g.
+    /// "127.0.0.1:7878" (default: none)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tcp_listen: Option<String>,
+    /// Token that clients connecting over TCP have to send (default: none)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tcp_token: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum StateBackend {
+    /// JSON file in the runtime directory (default)
+    #[default]
+    File,
+    /// The history database
+    Sqlite,
+    /// Keep nothing, start fresh every time
+    #[serde(alias = "none")]
+    Memory,
+}
+
+impl Config {
+    /// The timer settings on `weekday`, with the first profile from
+    /// `[profiles]` that applies on it, and the name of that profile
+    pub fn timer_on(&self, weekday: chrono::Weekday) -> (TimerConfig, Option<&str>) {
+        match self
+            .profiles
+            .iter()
+            .find(|(name, profile)| profile.applies_on(name, weekday))
+        {
+            Some((name, profile)) => (profile.apply(&self.timer), Some(name.as_str())),
+            None => (self.timer.clone(), None),
         }
     }
-}
 
-impl HooksConfig {
-    /// Execute a hook by event name
-    pub async fn execute_hook(
+    /// The timer settings on `weekday` at `time`. The profile that
+    /// `[schedule.presets]` picks for the time of day wins over the one for
+    /// the weekday.
+    pub fn timer_at(
         &self,
-        event: &str,
-        phase: &str,
-        remaining_seconds: u64,
-        session_count: u32,
-        auto_advance: &str,
-    ) {
-        let hook = match event {
-            "work_start" => &self.on_work_start,
-            "work_end" => &self.on_work_end,
-            "break_start" => &self.on_break_start,
-            "break_end" => &self.on_break_end,
-            "long_break_start" => &self.on_long_break_start,
-            "long_break_end" => &self.on_long_break_end,
-            "pause" => &self.on_pause,
-            "resume" => &self.on_resume,
-            "stop" => &self.on_stop,
-            "skip" => &self.on_skip,
-            _ => return,
+        weekday: chrono::Weekday,
+        time: chrono::NaiveTime,
+    ) -> (TimerConfig, Option<&str>) {
+        let Some(name) = self.schedule.preset_at(time) else {
+            return self.timer_on(weekday);
         };
-
-        if let Some(hook_cmd) = hook {
-            hook_cmd
-                .execute(event, phase, remaining_seconds, session_count, auto_advance)
-                .await;
+        match self.profiles.get_key_value(name) {
+            Some((name, profile)) => (profile.apply(&self.timer), Some(name.as_str())),
+            None => {
+                eprintln!(
+                    "Unknown preset '{}' in [schedule.presets]. Configure it under [profiles.{}]",
+                    name, name
+                );
+                self.timer_on(weekday)
+            }
         }
     }
-}
 
-impl Config {
     /// Get the config file path
     pub fn config_path() -> Option<PathBuf> {
         // Check for TOMAT_CONFIG environment variable first
@@ -605,6 +1814,23 @@ impl Config {
         Self::load_with_logging(false)
     }
 
+    /// Load config from `path` when it changed while the daemon is running
+    ///
+    /// Unlike `load`, errors are returned rather than replaced by defaults, so
+    /// that a broken edit keeps the settings in use.
+    pub fn reload_from(path: &std::path::Path) -> Result<Self, crate::error::TomatError> {
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+        let contents = fs::read_to_string(path)?;
+        toml::from_str(&contents).map_err(|e| {
+            crate::error::TomatError::InvalidArgument(format!(
+                "Failed to parse config file {:?}: {}",
+                path, e
+            ))
+        })
+    }
+
     /// Load config with optional logging (for daemon mode)
     pub fn load_with_logging(log: bool) -> Self {
         let config_path = Self::config_path();
@@ -666,6 +1892,30 @@ impl Config {
 mod tests {
     use super::*;
 
+    #[tokio::test]
+    async fn test_hook_group_untracked_once_empty() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let pgid_file = temp_dir.path().join("pgid");
+        // The shell leads the hook's process group, and leaves a job behind
+        let hook: HookCommand = toml::from_str(&format!(
+            "cmd = \"sh\"\nargs = [\"-c\", \"echo $$ > {}; sleep 0.5 &\"]\ntimeout = 0",
+            pgid_file.display()
+        ))
+        .unwrap();
+        hook.execute("work_start", &HookEnv::default(), None).await;
+
+        let pgid: i32 = std::fs::read_to_string(&pgid_file)
+            .unwrap()
+            .trim()
+            .parse()
+            .unwrap();
+        let tracked = || HOOK_GROUPS.lock().unwrap().contains(&pgid);
+        assert!(tracked(), "the group has a job left running");
+
+        tokio::time::sleep(std::time::Duration::from_millis(500) + HOOK_GROUP_POLL * 2).await;
+        assert!(!tracked(), "the group should be untracked once it is empty");
+    }
+
     #[test]
     fn test_default_config() {
         let config = Config::default();
@@ -684,6 +1934,15 @@ mod tests {
         assert_eq!(config.display.text_format, "{icon} {time} {state}");
     }
 
+    #[test]
+    fn test_long_break_after_minutes() {
+        let config: Config = toml::from_str("[timer]\nlong_break_after_minutes = 120\n").unwrap();
+        assert_eq!(config.timer.long_break_after_minutes, Some(120.0));
+        assert_eq!(config.timer.sessions, 4);
+
+        assert_eq!(Config::default().timer.long_break_after_minutes, None);
+    }
+
     #[test]
     fn test_config_serialization() {
         let config = Config::default();
@@ -950,6 +2209,38 @@ mod tests {
         assert_eq!(hook.timeout, 5); // Default
         assert!(hook.cwd.is_none()); // Default
         assert!(!hook.capture_output); // Default
+        assert!(hook.runs_in("work") && hook.runs_in("idle")); // Default
+    }
+
+    #[test]
+    fn test_hook_phase_filters() {
+        let toml_str = r#"
+            [hooks.on_phase_start]
+            cmd = "echo"
+            phases = ["work", "long_break"]
+
+            [hooks.on_break_start]
+            cmd = "echo"
+            only_long_break = true
+        "#;
+
+        let config: Config = toml::from_str(toml_str).unwrap();
+        let hook = config.hooks.on_phase_start.as_ref().unwrap();
+        assert!(hook.runs_in("work"));
+        assert!(hook.runs_in("long_break"));
+        assert!(!hook.runs_in("break"));
+
+        let hook = config.hooks.on_break_start.as_ref().unwrap();
+        assert!(hook.runs_in("long_break"));
+        assert!(!hook.runs_in("break"));
+
+        // Unknown phases are rejected rather than never matching
+        let toml_str = r#"
+            [hooks.on_phase_start]
+            cmd = "echo"
+            phases = ["lunch"]
+        "#;
+        assert!(toml::from_str::<Config>(toml_str).is_err());
     }
 
     #[test]
@@ -999,6 +2290,19 @@ mod tests {
         assert_eq!(config.timer.auto_advance, AutoAdvanceMode::ToWork);
     }
 
+    #[test]
+    fn test_auto_advance_mode_display() {
+        for mode in [
+            AutoAdvanceMode::None,
+            AutoAdvanceMode::All,
+            AutoAdvanceMode::ToBreak,
+            AutoAdvanceMode::ToWork,
+        ] {
+            assert_eq!(mode.to_string().parse::<AutoAdvanceMode>(), Ok(mode));
+        }
+        assert_eq!(AutoAdvanceMode::ToBreak.to_string(), "to-break");
+    }
+
     #[test]
     fn test_auto_advance_mode_logic() {
         // None - never advances
@@ -1042,6 +2346,18 @@ mod tests {
         let config: Config = toml::from_str(toml_str).unwrap();
         assert_eq!(config.sound.effective_mode(), SoundMode::SystemBeep);
 
+        let toml_str = r#"
+            [sound]
+            mode = "synth"
+
+            [sound.synth]
+            frequencies = [440.0, 660.0]
+        "#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.sound.effective_mode(), SoundMode::Synth);
+        assert_eq!(config.sound.synth.frequencies, vec![440.0, 660.0]);
+        assert_eq!(config.sound.synth.note_ms, 180);
+
         let toml_str = r#"
             [sound]
             mode = "none"
@@ -1050,6 +2366,60 @@ mod tests {
         assert_eq!(config.sound.effective_mode(), SoundMode::None);
     }
 
+    #[test]
+    fn test_sound_output_settings() {
+        let toml_str = r#"
+            [sound]
+            backend = "alsa"
+            device = "pipewire"
+            buffer_size = 2048
+        "#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.sound.backend.as_deref(), Some("alsa"));
+        assert_eq!(config.sound.device.as_deref(), Some("pipewire"));
+        assert_eq!(config.sound.buffer_size, Some(2048));
+
+        let config = Config::default();
+        assert_eq!(config.sound.device, None);
+        assert_eq!(config.sound.buffer_size, None);
+    }
+
+    #[test]
+    fn test_per_transition_sound_off() {
+        let toml_str = r#"
+            [sound]
+            work_to_break = "none"
+            break_to_work = "/home/user/sounds/break-over.ogg"
+        "#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert!(is_sound_off(config.sound.work_to_break.as_deref().unwrap()));
+        assert!(!is_sound_off(
+            config.sound.break_to_work.as_deref().unwrap()
+        ));
+        assert!(is_sound_off("None"));
+    }
+
+    #[test]
+    fn test_theme_sounds() {
+        let config = Config::default();
+        assert!(!config.sound.theme_sounds);
+        assert_eq!(config.sound.theme, "freedesktop");
+
+        let toml_str = r#"
+            [sound]
+            theme_sounds = true
+            theme = "ocean"
+            break_to_work = "message-new-instant"
+        "#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert!(config.sound.theme_sounds);
+        assert_eq!(config.sound.theme, "ocean");
+        assert_eq!(
+            config.sound.break_to_work.as_deref(),
+            Some("message-new-instant")
+        );
+    }
+
     #[test]
     fn test_sound_mode_backwards_compatibility() {
         // Test deprecated enabled = false -> None
@@ -1221,6 +2591,24 @@ mod tests {
         assert_eq!(config.display.icons.stop, "X");
     }
 
+    #[test]
+    fn test_display_icons_idle_and_aliases() {
+        let toml_str = r#"
+            [display.icons]
+            work = "\ue001"
+            idle = "-"
+            running = ""
+            paused = "P"
+        "#;
+
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.display.icons.work, "\u{e001}");
+        assert_eq!(config.display.icons.idle.as_deref(), Some("-"));
+        assert_eq!(config.display.icons.play, "");
+        assert_eq!(config.display.icons.pause, "P");
+        assert_eq!(Config::default().display.icons.idle, None);
+    }
+
     #[test]
     fn test_display_icons_partial() {
         let toml_str = r#"
@@ -1237,4 +2625,356 @@ mod tests {
         assert_eq!(config.display.icons.pause, "⏸");
         assert_eq!(config.display.icons.stop, "⏹");
     }
+
+    #[test]
+    fn test_report_config_defaults() {
+        let config = Config::default();
+        assert!(!config.report.enabled);
+        assert_eq!(config.report.time, "18:00");
+        assert_eq!(config.report.method, ReportMethod::Sendmail);
+        assert_eq!(config.report.sendmail, "sendmail");
+        assert!(config.report.smtp_url.is_none());
+    }
+
+    #[test]
+    fn test_report_config_parsing() {
+        let toml_str = r#"
+            [report]
+            enabled = true
+            time = "17:30"
+            to = "me@example.com"
+            method = "smtp"
+            smtp_url = "smtps://smtp.example.com:465"
+            smtp_user = "me"
+            smtp_password_cmd = ["pass", "show", "smtp"]
+        "#;
+
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert!(config.report.enabled);
+        assert_eq!(config.report.time, "17:30");
+        assert_eq!(config.report.to, "me@example.com");
+        assert_eq!(config.report.method, ReportMethod::Smtp);
+        assert_eq!(
+            config.report.smtp_url.as_deref(),
+            Some("smtps://smtp.example.com:465")
+        );
+        assert_eq!(
+            config.report.smtp_password_cmd,
+            vec!["pass", "show", "smtp"]
+        );
+        // Unset fields use defaults
+        assert_eq!(config.report.from, "tomat@localhost");
+    }
+
+    #[test]
+    fn test_display_per_phase_formats() {
+        use crate::timer::Phase;
+
+        let toml_str = r#"
+            [display]
+            text_format = "{icon} {time}"
+            text_format_idle = "{icon}"
+
+            [display.formats]
+            work = "{time}"
+            break = "☕ BREAK {time}"
+            paused = "{icon} ⏸"
+        "#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        let display = &config.display;
+
+        assert_eq!(display.template_for(&Phase::Work, false, None), "{time}");
+        assert_eq!(
+            display.template_for(&Phase::Break, false, None),
+            "☕ BREAK {time}"
+        );
+        // Long breaks fall back to the break format
+        assert_eq!(
+            display.template_for(&Phase::LongBreak, false, None),
+            "☕ BREAK {time}"
+        );
+        assert_eq!(display.template_for(&Phase::Break, true, None), "{icon} ⏸");
+        assert_eq!(display.template_for(&Phase::Idle, false, None), "{icon}");
+
+        // An explicit --format wins, except when idle
+        assert_eq!(
+            display.template_for(&Phase::Work, true, Some("{phase}")),
+            "{phase}"
+        );
+        assert_eq!(
+            display.template_for(&Phase::Idle, false, Some("{phase}")),
+            "{icon}"
+        );
+
+        // Without per-phase formats, text_format is used
+        let display = DisplayConfig::default();
+        assert_eq!(
+            display.template_for(&Phase::LongBreak, true, None),
+            "{icon} {time} {state}"
+        );
+    }
+
+    #[test]
+    fn test_display_rotation() {
+        use crate::timer::Phase;
+
+        let display = DisplayConfig::default();
+        assert_eq!(
+            display.templates_at(&Phase::Work, false, None, 7),
+            ("{icon} {time} {state}", "{session}")
+        );
+
+        let toml_str = r#"
+            [display]
+            text_format = "{time}"
+            alt_format = "{phase} {session}"
+            rotate_seconds = 5
+        "#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        let display = &config.display;
+
+        assert_eq!(
+            display.templates_at(&Phase::Work, false, None, 4),
+            ("{time}", "{phase} {session}")
+        );
+        assert_eq!(
+            display.templates_at(&Phase::Work, false, None, 5),
+            ("{phase} {session}", "{time}")
+        );
+        assert_eq!(
+            display.templates_at(&Phase::Break, true, None, 10),
+            ("{time}", "{phase} {session}")
+        );
+        // The idle display does not rotate
+        assert_eq!(
+            display.templates_at(&Phase::Idle, false, None, 5),
+            ("{time}", "{phase} {session}")
+        );
+    }
+
+    #[test]
+    fn test_timer_profiles() {
+        use chrono::Weekday;
+
+        let config: Config = toml::from_str(
+            r#"
+            [timer]
+            work = 50
+            auto_advance = "to-break"
+
+            [profiles.weekend]
+            work = 25
+            auto_advance = true
+
+            [profiles.friday]
+            days = ["fri"]
+            sessions = 2
+            "#,
+        )
+        .unwrap();
+
+        let (timer, profile) = config.timer_on(Weekday::Mon);
+        assert_eq!(profile, None);
+        assert_eq!(timer.work, 50.0);
+
+        // A profile named "weekend" applies on Saturday and Sunday by default
+        let (timer, profile) = config.timer_on(Weekday::Sun);
+        assert_eq!(profile, Some("weekend"));
+        assert_eq!(timer.work, 25.0);
+        assert_eq!(timer.auto_advance, AutoAdvanceMode::All);
+        assert_eq!(timer.sessions, 4);
+
+        let (timer, profile) = config.timer_on(Weekday::Fri);
+        assert_eq!(profile, Some("friday"));
+        assert_eq!(timer.work, 50.0);
+        assert_eq!(timer.sessions, 2);
+        assert_eq!(timer.auto_advance, AutoAdvanceMode::ToBreak);
+    }
+
+    #[test]
+    fn test_schedule_presets() {
+        use chrono::{NaiveTime, Weekday};
+        let at = |h, m| NaiveTime::from_hms_opt(h, m, 0).unwrap();
+
+        let config: Config = toml::from_str(
+            r#"
+            [schedule.presets]
+            "06:00-12:00" = "morning"
+            "22:00-02:00" = "night"
+            "13:00-14:00" = "missing"
+
+            [profiles.morning]
+            work = 50
+
+            [profiles.night]
+            work = 15
+
+            [profiles.weekend]
+            work = 25
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.schedule.preset_at(at(6, 0)), Some("morning"));
+        assert_eq!(config.schedule.preset_at(at(12, 0)), None);
+        assert_eq!(config.schedule.preset_at(at(23, 30)), Some("night"));
+        assert_eq!(config.schedule.preset_at(at(1, 59)), Some("night"));
+
+        // The preset for the time of day wins over the weekday
+        let (timer, profile) = config.timer_at(Weekday::Sat, at(9, 30));
+        assert_eq!(profile, Some("morning"));
+        assert_eq!(timer.work, 50.0);
+        let (timer, profile) = config.timer_at(Weekday::Sat, at(15, 0));
+        assert_eq!(profile, Some("weekend"));
+        assert_eq!(timer.work, 25.0);
+
+        // Unknown presets are ignored
+        let (timer, profile) = config.timer_at(Weekday::Mon, at(13, 30));
+        assert_eq!(profile, None);
+        assert_eq!(timer.work, 25.0);
+    }
+
+    #[test]
+    fn test_display_profiles() {
+        use crate::timer::Phase;
+
+        let toml_str = r#"
+            [display]
+            text_format = "{icon} {time}"
+
+            [display.profiles.laptop]
+            text_format = "{icon}"
+            tooltip = "minimal"
+
+            [display.profiles.desk]
+            tooltip = "full"
+        "#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.display.tooltip, TooltipDetail::Normal);
+
+        let laptop = config.display.with_profile("laptop").unwrap();
+        assert_eq!(laptop.template_for(&Phase::Work, false, None), "{icon}");
+        assert_eq!(laptop.tooltip, TooltipDetail::Minimal);
+
+        // Unset settings are taken from [display]
+        let desk = config.display.with_profile("desk").unwrap();
+        assert_eq!(
+            desk.template_for(&Phase::Work, false, None),
+            "{icon} {time}"
+        );
+        assert_eq!(desk.tooltip, TooltipDetail::Full);
+
+        // Detail levels work as profiles without configuration
+        let full = config.display.with_profile("full").unwrap();
+        assert_eq!(full.tooltip, TooltipDetail::Full);
+        assert_eq!(full.text_format, "{icon} {time}");
+
+        assert!(config.display.with_profile("phone").is_err());
+    }
+
+    #[test]
+    fn test_focus_config_parsing() {
+        let config = Config::default();
+        assert!(config.focus.block.is_empty());
+        assert_eq!(config.focus.action, FocusAction::Warn);
+
+        let toml_str = r#"
+            [focus]
+            block = ["discord", "steam"]
+            action = "minimize"
+            hyprland_workspace = "focus"
+        "#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.focus.block, vec!["discord", "steam"]);
+        assert_eq!(config.focus.action, FocusAction::Minimize);
+        assert_eq!(config.focus.hyprland_workspace.as_deref(), Some("focus"));
+    }
+
+    #[test]
+    fn test_kdeconnect_events() {
+        let config = Config::default();
+        assert!(!config.notification.kdeconnect.sends("break_start"));
+
+        let toml_str = r#"
+            [notification.kdeconnect]
+            device = "a1b2c3d4"
+            work_start = false
+        "#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        let kdeconnect = &config.notification.kdeconnect;
+        assert!(kdeconnect.sends("break_start"));
+        assert!(kdeconnect.sends("long_break_start"));
+        assert!(kdeconnect.sends("stop"));
+        assert!(!kdeconnect.sends("work_start"));
+        assert!(!kdeconnect.sends("pause"));
+    }
+
+    #[test]
+    fn test_daemon_state_backend() {
+        assert_eq!(Config::default().daemon.state_backend, StateBackend::File);
+
+        let toml_str = r#"
+            [daemon]
+            state_backend = "sqlite"
+        "#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.daemon.state_backend, StateBackend::Sqlite);
+
+        let toml_str = r#"
+            [daemon]
+            state_backend = "none"
+        "#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.daemon.state_backend, StateBackend::Memory);
+        assert!(!config.daemon.register_alarm);
+    }
+
+    #[test]
+    fn test_notification_break_end_urgency() {
+        let config = Config::default();
+        assert_eq!(
+            config.notification.effective_break_end_urgency(),
+            NotificationUrgency::Normal
+        );
+        assert_eq!(config.notification.break_end_nag, None);
+
+        let toml_str = r#"
+            [notification]
+            urgency = "low"
+            break_end_urgency = "critical"
+            break_end_nag = 2
+        "#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.notification.urgency, NotificationUrgency::Low);
+        assert_eq!(
+            config.notification.effective_break_end_urgency(),
+            NotificationUrgency::Critical
+        );
+        assert_eq!(config.notification.break_end_nag, Some(2));
+    }
+
+    #[test]
+    fn test_notification_throttle() {
+        assert_eq!(Config::default().notification.throttle, 2);
+
+        let toml_str = r#"
+            [notification]
+            throttle = 0
+        "#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.notification.throttle, 0);
+    }
+
+    #[test]
+    fn test_notification_nagbar() {
+        assert!(!Config::default().notification.nagbar);
+
+        let toml_str = r#"
+            [notification]
+            nagbar = true
+        "#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert!(config.notification.nagbar);
+        assert!(config.notification.enabled);
+    }
 }
@@ -0,0 +1,131 @@
+use serde_json::json;
+use tokio::process::Command;
+
+use crate::ServerResponse;
+use crate::config::DisplayConfig;
+use crate::error::TomatError;
+use crate::server::send_command;
+use crate::timer::{Format, StatusOutput, TimerState, TimerStatus};
+
+/// Settings of `tomat dwm`
+pub struct DwmOptions {
+    pub format: Option<String>,
+    /// Shell commands whose output is shown after the timer
+    pub with: Vec<String>,
+    pub separator: String,
+    /// Print the names instead of setting them
+    pub print: bool,
+}
+
+/// Join the non-empty parts of the status with `separator`
+fn root_name(parts: &[String], separator: &str) -> String {
+    parts
+        .iter()
+        .map(|part| part.trim())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// The timer status as plain text, or nothing if the daemon is not running
+pub async fn timer_text(format: Option<&str>, display: &DisplayConfig) -> String {
+    let status = send_command("status", json!({ "output": "plain" }))
+        .await
+        .and_then(ServerResponse::into_result)
+        .and_then(|response| Ok(serde_json::from_value::<TimerStatus>(response.data)?));
+    let Ok(status) = status else {
+        return String::new();
+    };
+
+    let (template, alt_template) = display.templates_at(
+        &status.phase,
+        status.is_paused,
+        format,
+        crate::timer::current_timestamp(),
+    );
+    match TimerState::format_status(&status, &Format::Plain, template, alt_template, display) {
+        StatusOutput::Plain(text) => text,
+        _ => unreachable!("plain format always produces plain output"),
+    }
+}
+
+/// First line of a shell command's output, or nothing if it fails
+async fn command_text(command: &str) -> String {
+    match Command::new("sh").args(["-c", command]).output().await {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .to_string(),
+        _ => String::new(),
+    }
+}
+
+/// Set the name of the X root window, which dwm shows as its status
+async fn set_root_name(name: &str) -> Result<(), TomatError> {
+    let output = Command::new("xsetroot")
+        .args(["-name", name])
+        .output()
+        .await?;
+    if !output.status.success() {
+        return Err(TomatError::Other(format!(
+            "xsetroot failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
+
+/// Set the root window name every `interval` seconds, when it changed. The
+/// daemon may come and go; while it is not running only the commands are shown.
+pub async fn run(
+    options: &DwmOptions,
+    interval: f64,
+    display: &DisplayConfig,
+) -> Result<(), TomatError> {
+    let interval = std::time::Duration::from_secs_f64(interval);
+    let mut last_name = None;
+
+    loop {
+        let mut parts = vec![timer_text(options.format.as_deref(), display).await];
+        for command in &options.with {
+            parts.push(command_text(command).await);
+        }
+
+        let name = root_name(&parts, &options.separator);
+        if last_name.as_ref() != Some(&name) {
+            if options.print {
+                println!("{}", name);
+            } else {
+                set_root_name(&name).await?;
+            }
+            last_name = Some(name);
+        }
+
+        tokio::time::sleep(interval).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_root_name() {
+        let parts =
+            |parts: &[&str]| -> Vec<String> { parts.iter().map(|part| part.to_string()).collect() };
+        assert_eq!(
+            root_name(&parts(&["🍅 24:59 ▶", "Mon 12:00\n"]), " | "),
+            "🍅 24:59 ▶ | Mon 12:00"
+        );
+        // Empty parts, e.g. a hidden idle timer, leave no separator behind
+        assert_eq!(root_name(&parts(&["", "Mon 12:00"]), " | "), "Mon 12:00");
+        assert_eq!(root_name(&parts(&["", ""]), " | "), "");
+    }
+
+    #[tokio::test]
+    async fn test_command_text() {
+        assert_eq!(command_text("printf 'a\\nb'").await, "a");
+        assert_eq!(command_text("exit 1").await, "");
+    }
+}
@@ -0,0 +1,152 @@
+use serde::{Deserialize, Serialize};
+
+/// Machine-readable error codes sent to clients in `ServerResponse`
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    DaemonNotRunning,
+    DaemonAlreadyRunning,
+    InvalidArgument,
+    InvalidState,
+    UnknownCommand,
+    Protocol,
+    Io,
+    History,
+    Unauthorized,
+    Internal,
+}
+
+/// Errors produced by tomat, both in the daemon and in the client
+#[derive(Debug, thiserror::Error)]
+pub enum TomatError {
+    #[error("Failed to connect to daemon: it is not running")]
+    DaemonNotRunning,
+    #[error("Another daemon instance is already running")]
+    DaemonAlreadyRunning,
+    #[error("{0}")]
+    InvalidArgument(String),
+    #[error("{0}")]
+    InvalidState(String),
+    #[error("Unknown command: {0}")]
+    UnknownCommand(String),
+    #[error("Invalid message: {0}")]
+    Protocol(#[from] serde_json::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("History database error: {0}")]
+    History(#[from] rusqlite::Error),
+    #[error("Missing or wrong token")]
+    Unauthorized,
+    /// An error reported by the daemon that has no more specific variant on this side
+    #[error("{message}")]
+    Remote { code: ErrorCode, message: String },
+    #[error("{0}")]
+    Other(String),
+}
+
+impl TomatError {
+    /// Rebuild an error from the code and message of a failed `ServerResponse`
+    pub fn from_response(code: Option<ErrorCode>, message: String) -> Self {
+        match code {
+            Some(ErrorCode::InvalidArgument) => TomatError::InvalidArgument(message),
+            Some(ErrorCode::InvalidState) => TomatError::InvalidState(message),
+            Some(code) => TomatError::Remote { code, message },
+            None => TomatError::Other(message),
+        }
+    }
+
+    /// The machine-readable code for this error
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            TomatError::DaemonNotRunning => ErrorCode::DaemonNotRunning,
+            TomatError::DaemonAlreadyRunning => ErrorCode::DaemonAlreadyRunning,
+            TomatError::InvalidArgument(_) => ErrorCode::InvalidArgument,
+            TomatError::InvalidState(_) => ErrorCode::InvalidState,
+            TomatError::UnknownCommand(_) => ErrorCode::UnknownCommand,
+            TomatError::Protocol(_) => ErrorCode::Protocol,
+            TomatError::Io(_) => ErrorCode::Io,
+            TomatError::History(_) => ErrorCode::History,
+            TomatError::Unauthorized => ErrorCode::Unauthorized,
+            TomatError::Remote { code, .. } => *code,
+            TomatError::Other(_) => ErrorCode::Internal,
+        }
+    }
+
+    /// A remediation hint to show along with the error, if there is one
+    pub fn hint(&self) -> Option<&'static str> {
+        match self.code() {
+            ErrorCode::DaemonNotRunning => Some(
+                "Start the daemon with `tomat daemon start`, or run `tomat daemon install` \
+                 to start it automatically on login",
+            ),
+            ErrorCode::DaemonAlreadyRunning => {
+                Some("Stop the running daemon first with `tomat daemon stop`")
+            }
+            ErrorCode::UnknownCommand | ErrorCode::Protocol => Some(
+                "The daemon may be from a different tomat version; restart it with \
+                 `tomat daemon stop && tomat daemon start`",
+            ),
+            ErrorCode::History => {
+                Some("Check the history database (set TOMAT_HISTORY to use another location)")
+            }
+            ErrorCode::Unauthorized => {
+                Some("Set TOMAT_TOKEN to the `tcp_token` in the daemon's [daemon] config")
+            }
+            _ => None,
+        }
+    }
+}
+
+impl From<String> for TomatError {
+    fn from(message: String) -> Self {
+        TomatError::Other(message)
+    }
+}
+
+impl From<&str> for TomatError {
+    fn from(message: &str) -> Self {
+        TomatError::Other(message.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_codes_and_hints() {
+        let error = TomatError::DaemonNotRunning;
+        assert_eq!(error.code(), ErrorCode::DaemonNotRunning);
+        assert!(error.to_string().contains("Failed to connect"));
+        assert!(error.hint().unwrap().contains("tomat daemon start"));
+
+        let error = TomatError::InvalidState("Cannot skip when timer is idle.".to_string());
+        assert_eq!(error.code(), ErrorCode::InvalidState);
+        assert!(error.hint().is_none());
+
+        let error: TomatError = "something went wrong".into();
+        assert_eq!(error.code(), ErrorCode::Internal);
+    }
+
+    #[test]
+    fn test_from_response_round_trip() {
+        let error = TomatError::UnknownCommand("frobnicate".to_string());
+        let rebuilt = TomatError::from_response(Some(error.code()), error.to_string());
+        assert_eq!(rebuilt.code(), ErrorCode::UnknownCommand);
+        assert_eq!(rebuilt.to_string(), "Unknown command: frobnicate");
+        assert!(rebuilt.hint().is_some());
+
+        let rebuilt = TomatError::from_response(Some(ErrorCode::InvalidArgument), "bad".into());
+        assert!(matches!(rebuilt, TomatError::InvalidArgument(_)));
+    }
+
+    #[test]
+    fn test_error_code_serialization() {
+        assert_eq!(
+            serde_json::to_string(&ErrorCode::DaemonNotRunning).unwrap(),
+            "\"daemon_not_running\""
+        );
+        let code: ErrorCode = serde_json::from_str("\"invalid_state\"").unwrap();
+        assert_eq!(code, ErrorCode::InvalidState);
+    }
+}
@@ -0,0 +1,204 @@
+use std::ffi::CString;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::{FileTypeExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+
+use crate::config::DisplayConfig;
+use crate::timer::{Format, StatusOutput, TimerState};
+
+/// Render the status as a line of plain text with the display templates
+pub fn status_line(state: &TimerState, display: &DisplayConfig) -> String {
+    let status = state.get_timer_status();
+    let (template, alt_template) = display.templates_at(
+        &status.phase,
+        status.is_paused,
+        None,
+        crate::timer::current_timestamp(),
+    );
+    match TimerState::format_status(&status, &Format::Plain, template, alt_template, display) {
+        StatusOutput::Plain(text) => text,
+        _ => unreachable!("plain format always produces plain output"),
+    }
+}
+
+/// Create a named pipe that only the user can read and write
+fn mkfifo(path: &Path) -> std::io::Result<()> {
+    let path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|_| std::io::Error::from(std::io::ErrorKind::InvalidInput))?;
+    // SAFETY: the path is a valid NUL-terminated string
+    if unsafe { libc::mkfifo(path.as_ptr(), 0o600) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Writes status lines to a named pipe, for bars that read from a pipe
+/// instead of running a command. The pipe is kept open while a reader is
+/// connected, so that readers like `cat` do not see the end of the file
+/// after each line.
+pub struct FifoSink {
+    path: PathBuf,
+    pipe: Option<File>,
+    /// Last line written to the connected reader
+    last_line: Option<String>,
+    /// Last error reported, to log each problem only once
+    last_error: Option<String>,
+}
+
+impl FifoSink {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            pipe: None,
+            last_line: None,
+            last_error: None,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Open the pipe for writing, creating it if needed. Fails with ENXIO
+    /// while no reader is connected.
+    fn open(&self) -> std::io::Result<File> {
+        match std::fs::metadata(&self.path) {
+            Ok(metadata) if !metadata.file_type().is_fifo() => {
+                return Err(std::io::Error::other("not a named pipe"));
+            }
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => mkfifo(&self.path)?,
+            Err(e) => return Err(e),
+        }
+        OpenOptions::new()
+            .write(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(&self.path)
+    }
+
+    /// Write `line` if it changed since the last write, or a reader has
+    /// connected since then. Without a reader, nothing is written.
+    pub fn update(&mut self, line: &str) {
+        if self.pipe.is_none() {
+            match self.open() {
+                Ok(pipe) => {
+                    self.pipe = Some(pipe);
+                    self.last_line = None;
+                    self.last_error = None;
+                }
+                Err(e) if e.raw_os_error() == Some(libc::ENXIO) => return,
+                Err(e) => {
+                    let error = e.to_string();
+                    if self.last_error.as_ref() != Some(&error) {
+                        eprintln!("Failed to open {}: {}", self.path.display(), error);
+                        self.last_error = Some(error);
+                    }
+                    return;
+                }
+            }
+        }
+
+        let Some(pipe) = self.pipe.as_mut() else {
+            return;
+        };
+        if self.last_line.as_deref() == Some(line) {
+            return;
+        }
+        // Write the line in one go, so a reader never gets half a line
+        // followed by the next one
+        let mut buffer = String::with_capacity(line.len() + 1);
+        buffer.push_str(line);
+        buffer.push('\n');
+        match pipe.write(buffer.as_bytes()) {
+            Ok(written) if written == buffer.len() => self.last_line = Some(line.to_string()),
+            // The reader went away or fell behind, leaving a partial line at
+            // best: close the pipe so that it sees the end of the file, and
+            // wait for the next reader
+            _ => self.pipe = None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+
+    #[test]
+    fn test_status_line() {
+        let mut state = TimerState::new(25.0, 5.0, 15.0, 4);
+        state.start_work();
+        let display = DisplayConfig {
+            text_format: "{phase} {session}".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(status_line(&state, &display), "Work 1/4");
+    }
+
+    #[test]
+    fn test_fifo_sink() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tomat.fifo");
+        let mut sink = FifoSink::new(path.clone());
+
+        // Without a reader the pipe is created, but nothing is written
+        sink.update("first");
+        assert!(std::fs::metadata(&path).unwrap().file_type().is_fifo());
+
+        let reader = OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(&path)
+            .unwrap();
+        let mut reader = BufReader::new(reader);
+
+        // A new reader gets the current line, and then each change
+        sink.update("first");
+        sink.update("first");
+        sink.update("second");
+        let mut lines = String::new();
+        reader.read_line(&mut lines).unwrap();
+        reader.read_line(&mut lines).unwrap();
+        assert_eq!(lines, "first\nsecond\n");
+    }
+
+    #[test]
+    fn test_fifo_sink_drops_pipe_on_short_write() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tomat.fifo");
+        let mut sink = FifoSink::new(path.clone());
+        sink.update("first");
+
+        let mut reader = OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(&path)
+            .unwrap();
+
+        // A line that does not fit in the pipe buffer is not written in one go
+        sink.update(&"x".repeat(1 << 20));
+        assert!(sink.pipe.is_none());
+        assert!(sink.last_line.is_none());
+
+        // Once the reader caught up, the next update opens the pipe again
+        let mut partial = Vec::new();
+        let _ = std::io::Read::read_to_end(&mut reader, &mut partial);
+        assert!(!partial.is_empty() && !partial.ends_with(b"\n"));
+        sink.update("second");
+        assert!(sink.pipe.is_some());
+        assert_eq!(sink.last_line.as_deref(), Some("second"));
+    }
+
+    #[test]
+    fn test_fifo_sink_refuses_regular_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("status.txt");
+        std::fs::write(&path, "keep").unwrap();
+
+        let mut sink = FifoSink::new(path.clone());
+        sink.update("line");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "keep");
+    }
+}
@@ -0,0 +1,287 @@
+use tokio::process::Command;
+
+use crate::config::{FocusAction, FocusConfig, NotificationConfig};
+use crate::error::TomatError;
+
+/// Window management interface used to find and minimize applications
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Backend {
+    Sway,
+    Hyprland,
+    X11,
+}
+
+impl Backend {
+    /// Pick the backend for the current session from the environment
+    fn detect() -> Option<Self> {
+        let has = |var: &str| std::env::var_os(var).is_some_and(|v| !v.is_empty());
+        if has("SWAYSOCK") {
+            Some(Backend::Sway)
+        } else if crate::hyprland::is_running() {
+            Some(Backend::Hyprland)
+        } else if has("DISPLAY") {
+            Some(Backend::X11)
+        } else {
+            None
+        }
+    }
+}
+
+/// An open window and the application it belongs to
+#[derive(Debug, Clone, PartialEq)]
+struct Window {
+    id: String,
+    app: String,
+}
+
+/// Warn about (or minimize) blocked applications at the start of a work phase
+pub async fn enforce(config: FocusConfig, notification: NotificationConfig) {
+    if config.block.is_empty() || crate::timer::is_testing() {
+        return;
+    }
+
+    let Some(backend) = Backend::detect() else {
+        eprintln!("Focus mode: no supported window manager found (Sway, Hyprland, or X11)");
+        return;
+    };
+
+    let windows = match list_windows(backend).await {
+        Ok(windows) => windows,
+        Err(e) => {
+            eprintln!("Focus mode: failed to list windows: {}", e);
+            return;
+        }
+    };
+
+    let offenders = find_offenders(&windows, &config.block);
+    if offenders.is_empty() {
+        return;
+    }
+
+    if config.action == FocusAction::Minimize {
+        for window in &offenders {
+            if let Err(e) = minimize(backend, window).await {
+                eprintln!("Focus mode: failed to minimize {}: {}", window.app, e);
+            }
+        }
+    }
+
+    let mut apps: Vec<&str> = offenders.iter().map(|w| w.app.as_str()).collect();
+    apps.sort_unstable();
+    apps.dedup();
+    let body = match config.action {
+        FocusAction::Warn => format!("Distracting applications are open: {}", apps.join(", ")),
+        FocusAction::Minimize => format!("Minimized: {}", apps.join(", ")),
+    };
+    if notification.enabled {
+        show_notification(&body, &notification);
+    } else {
+        println!("Focus mode: {}", body);
+    }
+}
+
+/// Windows whose application matches one of the blocked names
+fn find_offenders(windows: &[Window], block: &[String]) -> Vec<Window> {
+    windows
+        .iter()
+        .filter(|window| {
+            let app = window.app.to_lowercase();
+            block
+                .iter()
+                .any(|blocked| !blocked.is_empty() && app.contains(&blocked.to_lowercase()))
+        })
+        .cloned()
+        .collect()
+}
+
+async fn list_windows(backend: Backend) -> Result<Vec<Window>, TomatError> {
+    match backend {
+        Backend::Sway => {
+            let output = run(Command::new("swaymsg").args(["-r", "-t", "get_tree"])).await?;
+            Ok(parse_sway_tree(&serde_json::from_str(&output)?))
+        }
+        Backend::Hyprland => {
+            let reply = crate::hyprland::request("j/clients").await?;
+            Ok(parse_hyprland_clients(&serde_json::from_str(&reply)?))
+        }
+        Backend::X11 => {
+            let output = run(Command::new("wmctrl").arg("-lx")).await?;
+            Ok(parse_wmctrl(&output))
+        }
+    }
+}
+
+async fn minimize(backend: Backend, window: &Window) -> Result<(), TomatError> {
+    match backend {
+        Backend::Sway => {
+            let criteria = format!("[con_id={}] move scratchpad", window.id);
+            run(Command::new("swaymsg").arg(criteria)).await?;
+        }
+        Backend::Hyprland => {
+            crate::hyprland::dispatch(&format!(
+                "movetoworkspacesilent special:minimized,address:{}",
+                window.id
+            ))
+            .await?;
+        }
+        Backend::X11 => {
+            run(Command::new("wmctrl").args(["-ir", &window.id, "-b", "add,hidden"])).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Run a command and return its stdout, failing on a non-zero exit status
+async fn run(cmd: &mut Command) -> Result<String, TomatError> {
+    let output = cmd.output().await?;
+    if !output.status.success() {
+        return Err(TomatError::Other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Collect the windows of a `swaymsg -t get_tree` tree
+fn parse_sway_tree(node: &serde_json::Value) -> Vec<Window> {
+    let mut windows = Vec::new();
+
+    let app = node
+        .get("app_id")
+        .and_then(|v| v.as_str())
+        .or_else(|| node.pointer("/window_properties/class")?.as_str());
+    if let (Some(app), Some(id)) = (app, node.get("id").and_then(|v| v.as_i64())) {
+        windows.push(Window {
+            id: id.to_string(),
+            app: app.to_string(),
+        });
+    }
+
+    for key in ["nodes", "floating_nodes"] {
+        if let Some(children) = node.get(key).and_then(|v| v.as_array()) {
+            windows.extend(children.iter().flat_map(parse_sway_tree));
+        }
+    }
+    windows
+}
+
+/// Collect the windows of the Hyprland `j/clients` request
+fn parse_hyprland_clients(clients: &serde_json::Value) -> Vec<Window> {
+    clients
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|client| {
+            Some(Window {
+                id: client.get("address")?.as_str()?.to_string(),
+                app: client.get("class")?.as_str()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Collect the windows of `wmctrl -lx` ("<id> <desktop> <instance.class> <host> <title>")
+fn parse_wmctrl(output: &str) -> Vec<Window> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let id = fields.next()?;
+            let class = fields.nth(1)?;
+            Some(Window {
+                id: id.to_string(),
+                app: class.split_once('.').map_or(class, |(_, c)| c).to_string(),
+            })
+        })
+        .collect()
+}
+
+fn show_notification(body: &str, config: &NotificationConfig) {
+    let mut notification = crate::timer::new_notification(config, "x-tomat.focus");
+    notification.summary("Tomat focus mode").body(body);
+
+    if let Err(e) = notification.show() {
+        eprintln!("Failed to send notification: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(id: &str, app: &str) -> Window {
+        Window {
+            id: id.to_string(),
+            app: app.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_find_offenders() {
+        let windows = vec![
+            window("1", "firefox"),
+            window("2", "discord"),
+            window("3", "steam_app_123"),
+            window("4", "com.discordapp.Discord"),
+        ];
+        let block = vec!["Discord".to_string(), "steam".to_string()];
+
+        let ids: Vec<String> = find_offenders(&windows, &block)
+            .into_iter()
+            .map(|w| w.id)
+            .collect();
+        assert_eq!(ids, vec!["2", "3", "4"]);
+        assert!(find_offenders(&windows, &[String::new()]).is_empty());
+    }
+
+    #[test]
+    fn test_parse_sway_tree() {
+        let tree = serde_json::json!({
+            "id": 1,
+            "nodes": [{
+                "id": 2,
+                "nodes": [
+                    { "id": 10, "app_id": "foot", "nodes": [] },
+                    { "id": 11, "app_id": null, "window_properties": { "class": "discord" } }
+                ],
+                "floating_nodes": [{ "id": 12, "app_id": "steam" }]
+            }]
+        });
+
+        assert_eq!(
+            parse_sway_tree(&tree),
+            vec![
+                window("10", "foot"),
+                window("11", "discord"),
+                window("12", "steam")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_hyprland_clients() {
+        let clients = serde_json::json!([
+            { "address": "0x5c1a", "class": "discord", "title": "Discord" },
+            { "address": "0x5c1b", "class": "kitty" }
+        ]);
+
+        assert_eq!(
+            parse_hyprland_clients(&clients),
+            vec![window("0x5c1a", "discord"), window("0x5c1b", "kitty")]
+        );
+    }
+
+    #[test]
+    fn test_parse_wmctrl() {
+        let output = "0x01e00003  0 discord.discord       host Friends - Discord\n\
+                      0x02400007  1 Navigator.firefox     host Mozilla Firefox\n";
+
+        assert_eq!(
+            parse_wmctrl(output),
+            vec![
+                window("0x01e00003", "discord"),
+                window("0x02400007", "firefox")
+            ]
+        );
+    }
+}
@@ -0,0 +1,1046 @@
+use chrono::{
+    DateTime, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone,
+};
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::error::TomatError;
+use crate::timer::CycleProgress;
+
+/// A single finished phase stored in the history database
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionRecord {
+    pub id: i64,
+    pub phase: String,
+    pub started_at: u64,
+    pub ended_at: u64,
+    pub duration_seconds: u64,
+    pub outcome: String,
+    pub task: Option<String>,
+    pub tags: Vec<String>,
+    /// Length the phase was started with, longer than the duration when it
+    /// was ended early
+    pub planned_seconds: u64,
+    /// Number of times the phase was paused
+    pub interruptions: u32,
+    /// Offset from UTC in seconds of the local time where the phase started,
+    /// None for sessions recorded before it was stored
+    pub utc_offset: Option<i32>,
+    /// What was done in the session, written down with `tomat note`
+    pub notes: Option<String>,
+}
+
+/// A finished phase to add to the history
+pub struct NewSession<'a> {
+    pub phase: &'a str,
+    pub started_at: u64,
+    pub ended_at: u64,
+    pub outcome: &'a str,
+    pub task: Option<&'a str>,
+    pub tags: &'a [String],
+    pub planned_seconds: u64,
+    pub interruptions: u32,
+    pub notes: Option<&'a str>,
+}
+
+impl<'a> NewSession<'a> {
+    /// A phase that ran as planned, without task, tags, or pauses
+    pub fn new(phase: &'a str, started_at: u64, ended_at: u64, outcome: &'a str) -> Self {
+        Self {
+            phase,
+            started_at,
+            ended_at,
+            outcome,
+            task: None,
+            tags: &[],
+            planned_seconds: ended_at.saturating_sub(started_at),
+            interruptions: 0,
+            notes: None,
+        }
+    }
+}
+
+/// Aggregated history for a single local calendar day
+#[derive(Debug, Clone, Serialize)]
+pub struct DaySummary {
+    pub date: NaiveDate,
+    pub work_sessions: u32,
+    pub work_seconds: u64,
+    pub break_seconds: u64,
+    pub first_start: Option<u64>,
+    pub last_end: Option<u64>,
+}
+
+/// Session history backed by an SQLite database
+pub struct HistoryStore {
+    conn: Connection,
+}
+
+impl HistoryStore {
+    /// Get the history database path
+    pub fn default_path() -> Option<PathBuf> {
+        // Check for TOMAT_HISTORY environment variable first
+        if let Ok(path) = std::env::var("TOMAT_HISTORY") {
+            return Some(PathBuf::from(path));
+        }
+
+        dirs::data_dir().map(|dir| dir.join("tomat").join("history.db"))
+    }
+
+    /// Open the history database at the default location, creating it if necessary
+    pub fn open_default() -> Result<Self, TomatError> {
+        let path = Self::default_path().ok_or("Could not determine data directory")?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        Ok(Self::open(&path)?)
+    }
+
+    /// Open (and migrate) the history database at the given path
+    pub fn open(path: &Path) -> rusqlite::Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS sessions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                phase TEXT NOT NULL,
+                started_at INTEGER NOT NULL,
+                ended_at INTEGER NOT NULL,
+                duration_seconds INTEGER NOT NULL,
+                outcome TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS sessions_started_at ON sessions (started_at);
+            CREATE TABLE IF NOT EXISTS meta (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            );",
+        )?;
+
+        // Databases created before manual logging have no task column
+        let has_task: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('sessions') WHERE name = 'task'",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_task {
+            conn.execute("ALTER TABLE sessions ADD COLUMN task TEXT", [])?;
+        }
+
+        // Databases created before tagging have no tags column
+        let has_tags: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('sessions') WHERE name = 'tags'",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_tags {
+            conn.execute("ALTER TABLE sessions ADD COLUMN tags TEXT", [])?;
+        }
+
+        // Databases created before the efficiency statistics have no planned
+        // length or pause count, those sessions count as run as planned
+        let has_planned: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('sessions') WHERE name = 'planned_seconds'",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_planned {
+            conn.execute_batch(
+                "ALTER TABLE sessions ADD COLUMN planned_seconds INTEGER;
+                 ALTER TABLE sessions ADD COLUMN interruptions INTEGER NOT NULL DEFAULT 0;",
+            )?;
+        }
+
+        // Databases created before the timezone-aware statistics have no UTC
+        // offset, those sessions are put on days in the current timezone
+        let has_offset: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('sessions') WHERE name = 'utc_offset'",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_offset {
+            conn.execute("ALTER TABLE sessions ADD COLUMN utc_offset INTEGER", [])?;
+        }
+
+        // Databases created before the review phase have no notes column
+        let has_notes: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('sessions') WHERE name = 'notes'",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_notes {
+            conn.execute("ALTER TABLE sessions ADD COLUMN notes TEXT", [])?;
+        }
+
+        Ok(Self { conn })
+    }
+
+    /// Record a finished phase and return its id. Tags are stored as a
+    /// comma-separated list, see `parse_tags`. The UTC offset of the current
+    /// timezone at the start of the phase is stored with it, so that it stays
+    /// on the day it was done on after traveling.
+    pub fn record(&self, session: &NewSession) -> rusqlite::Result<i64> {
+        let tags = (!session.tags.is_empty()).then(|| session.tags.join(","));
+        let utc_offset = Local
+            .timestamp_opt(session.started_at as i64, 0)
+            .single()
+            .map(|dt| dt.offset().local_minus_utc());
+        self.conn.execute(
+            "INSERT INTO sessions (phase, started_at, ended_at, duration_seconds, outcome, task,
+                 tags, planned_seconds, interruptions, utc_offset, notes)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                session.phase,
+                session.started_at as i64,
+                session.ended_at as i64,
+                session.ended_at.saturating_sub(session.started_at) as i64,
+                session.outcome,
+                session.task,
+                tags,
+                session.planned_seconds as i64,
+                session.interruptions,
+                utc_offset,
+                session.notes
+            ],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Get all sessions that started within the given Unix timestamp range
+    pub fn sessions_between(&self, from: u64, to: u64) -> rusqlite::Result<Vec<SessionRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, phase, started_at, ended_at, duration_seconds, outcome, task, tags,
+                 planned_seconds, interruptions, utc_offset, notes
+             FROM sessions WHERE started_at >= ?1 AND started_at < ?2
+             ORDER BY started_at",
+        )?;
+        let rows = stmt.query_map(params![from as i64, to as i64], session_from_row)?;
+        rows.collect()
+    }
+
+    /// Get a single session by id
+    pub fn session(&self, id: i64) -> rusqlite::Result<Option<SessionRecord>> {
+        self.conn
+            .query_row(
+                "SELECT id, phase, started_at, ended_at, duration_seconds, outcome, task, tags,
+                     planned_seconds, interruptions, utc_offset, notes
+                 FROM sessions WHERE id = ?1",
+                params![id],
+                session_from_row,
+            )
+            .optional()
+    }
+
+    /// Delete a session, returning whether it existed
+    pub fn delete(&self, id: i64) -> rusqlite::Result<bool> {
+        Ok(self
+            .conn
+            .execute("DELETE FROM sessions WHERE id = ?1", params![id])?
+            > 0)
+    }
+
+    /// Change the duration (keeping the start time) and/or the task of a session.
+    /// An empty task clears it. Returns whether the session existed.
+    pub fn edit(
+        &self,
+        id: i64,
+        duration_seconds: Option<u64>,
+        task: Option<&str>,
+    ) -> rusqlite::Result<bool> {
+        let mut changed = 0;
+        if let Some(duration) = duration_seconds {
+            changed = self.conn.execute(
+                "UPDATE sessions SET duration_seconds = ?2, ended_at = started_at + ?2
+                 WHERE id = ?1",
+                params![id, duration as i64],
+            )?;
+        }
+        if let Some(task) = task {
+            let task = (!task.is_empty()).then_some(task);
+            changed = self.conn.execute(
+                "UPDATE sessions SET task = ?2 WHERE id = ?1",
+                params![id, task],
+            )?;
+        }
+        Ok(changed > 0)
+    }
+
+    /// Mark the work sessions that ran on after `since`, when the user went
+    /// away, as abandoned: sessions that started before it end at it, and
+    /// the others keep their times but count no time worked. Returns the
+    /// number of them that had been recorded as completed.
+    pub fn abandon_since(&self, since: u64) -> rusqlite::Result<u32> {
+        let since = since as i64;
+        let tx = self.conn.unchecked_transaction()?;
+        let completed: u32 = tx.query_row(
+            "SELECT COUNT(*) FROM sessions
+             WHERE phase = 'work' AND ended_at > ?1 AND outcome = 'completed'",
+            params![since],
+            |row| row.get(0),
+        )?;
+        tx.execute(
+            "UPDATE sessions SET outcome = 'abandoned', duration_seconds = 0
+             WHERE phase = 'work' AND started_at >= ?1",
+            params![since],
+        )?;
+        tx.execute(
+            "UPDATE sessions SET outcome = 'abandoned', ended_at = ?1,
+                 duration_seconds = ?1 - started_at
+             WHERE phase = 'work' AND started_at < ?1 AND ended_at > ?1",
+            params![since],
+        )?;
+        tx.commit()?;
+        Ok(completed)
+    }
+
+    /// Set the notes of a session, an empty text clears them. Returns whether
+    /// the session existed.
+    pub fn set_notes(&self, id: i64, notes: &str) -> rusqlite::Result<bool> {
+        let notes = (!notes.is_empty()).then_some(notes);
+        Ok(self.conn.execute(
+            "UPDATE sessions SET notes = ?2 WHERE id = ?1",
+            params![id, notes],
+        )? > 0)
+    }
+
+    /// Summarize all completed phases of a local calendar day
+    pub fn day_summary(&self, date: NaiveDate) -> rusqlite::Result<DaySummary> {
+        let (from, to) = local_day_bounds(date);
+        let sessions = self.sessions_between(from, to)?;
+
+        let mut summary = DaySummary {
+            date,
+            work_sessions: 0,
+            work_seconds: 0,
+            break_seconds: 0,
+            first_start: None,
+            last_end: None,
+        };
+
+        for session in sessions.iter().filter(|s| s.outcome == "completed") {
+            if session.phase == "work" {
+                summary.work_sessions += 1;
+                summary.work_seconds += session.duration_seconds;
+            } else {
+                summary.break_seconds += session.duration_seconds;
+            }
+            summary.first_start = Some(
+                summary
+                    .first_start
+                    .map_or(session.started_at, |t| t.min(session.started_at)),
+            );
+            summary.last_end = Some(
+                summary
+                    .last_end
+                    .map_or(session.ended_at, |t| t.max(session.ended_at)),
+            );
+        }
+
+        Ok(summary)
+    }
+
+    /// The cycle in progress on a local calendar day: the work sessions
+    /// completed since the last long break, leaving out full cycles of
+    /// `sessions_until_long_break` whose long break was not taken. None if
+    /// no cycle is in progress.
+    pub fn cycle_progress(
+        &self,
+        date: NaiveDate,
+        sessions_until_long_break: u32,
+    ) -> rusqlite::Result<Option<CycleProgress>> {
+        let (from, to) = local_day_bounds(date);
+        let mut cycle: Vec<SessionRecord> = Vec::new();
+        for session in self.sessions_between(from, to)? {
+            if session.outcome != "completed" {
+                continue;
+            }
+            match session.phase.as_str() {
+                "work" => cycle.push(session),
+                "long_break" => cycle.clear(),
+                _ => {}
+            }
+        }
+
+        let done = cycle.len() % sessions_until_long_break.max(1) as usize;
+        let cycle = &cycle[cycle.len() - done..];
+        let Some(first) = cycle.first() else {
+            return Ok(None);
+        };
+        Ok(Some(CycleProgress {
+            sessions: cycle.len() as u32,
+            focused_seconds: cycle.iter().map(|session| session.duration_seconds).sum(),
+            started_at: first.started_at,
+            valid_until: to,
+        }))
+    }
+
+    /// Read a value from the metadata table
+    pub fn get_meta(&self, key: &str) -> rusqlite::Result<Option<String>> {
+        self.conn
+            .query_row(
+                "SELECT value FROM meta WHERE key = ?1",
+                params![key],
+                |row| row.get(0),
+            )
+            .optional()
+    }
+
+    /// Write a value to the metadata table
+    pub fn set_meta(&self, key: &str, value: &str) -> rusqlite::Result<()> {
+        self.conn.execute(
+            "INSERT INTO meta (key, value) VALUES (?1, ?2)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            params![key, value],
+        )?;
+        Ok(())
+    }
+
+    /// Remove a value from the metadata table
+    pub fn delete_meta(&self, key: &str) -> rusqlite::Result<()> {
+        self.conn
+            .execute("DELETE FROM meta WHERE key = ?1", params![key])?;
+        Ok(())
+    }
+}
+
+/// Build a `SessionRecord` from a row selecting all session columns in order
+fn session_from_row(row: &rusqlite::Row) -> rusqlite::Result<SessionRecord> {
+    let duration_seconds = row.get::<_, i64>(4)? as u64;
+    Ok(SessionRecord {
+        id: row.get(0)?,
+        phase: row.get(1)?,
+        started_at: row.get::<_, i64>(2)? as u64,
+        ended_at: row.get::<_, i64>(3)? as u64,
+        duration_seconds,
+        outcome: row.get(5)?,
+        task: row.get(6)?,
+        tags: row
+            .get::<_, Option<String>>(7)?
+            .map(|tags| tags.split(',').map(str::to_string).collect())
+            .unwrap_or_default(),
+        planned_seconds: row
+            .get::<_, Option<i64>>(8)?
+            .map_or(duration_seconds, |planned| planned as u64),
+        interruptions: row.get(9)?,
+        utc_offset: row.get(10)?,
+        notes: row.get(11)?,
+    })
+}
+
+/// Get the Unix timestamp range [start, end) covering a local calendar day
+pub fn local_day_bounds(date: NaiveDate) -> (u64, u64) {
+    day_bounds(date, NaiveTime::MIN)
+}
+
+/// Get the Unix timestamp range [start, end) of a local day that starts at
+/// `day_start` instead of midnight. A start skipped by a daylight saving
+/// change moves to the first local time after the gap.
+pub fn day_bounds(date: NaiveDate, day_start: NaiveTime) -> (u64, u64) {
+    let to_timestamp = |date: NaiveDate| {
+        let mut start = date.and_time(day_start);
+        // Gaps are at most a few hours, step through them in 15 minutes
+        for _ in 0..16 {
+            if let Some(dt) = Local.from_local_datetime(&start).earliest() {
+                return dt.timestamp().max(0) as u64;
+            }
+            start += Duration::minutes(15);
+        }
+        0
+    };
+    let next_day = date.succ_opt().unwrap_or(date);
+    (to_timestamp(date), to_timestamp(next_day))
+}
+
+/// The day a session counts towards for days starting at `day_start`: the
+/// local date where the session started, or the previous one when it started
+/// before `day_start`. Sessions without a stored UTC offset use the current
+/// timezone.
+pub fn session_date(session: &SessionRecord, day_start: NaiveTime) -> Option<NaiveDate> {
+    let started_at = session.started_at as i64;
+    let local = match session.utc_offset.and_then(FixedOffset::east_opt) {
+        Some(offset) => offset.timestamp_opt(started_at, 0).single()?.naive_local(),
+        None => Local.timestamp_opt(started_at, 0).single()?.naive_local(),
+    };
+    Some((local - day_start.signed_duration_since(NaiveTime::MIN)).date())
+}
+
+/// Clean up the tags given for a session: trim them, drop empty ones and
+/// duplicates. Commas are refused, since they separate the stored tags.
+pub fn parse_tags<S: AsRef<str>>(tags: &[S]) -> Result<Vec<String>, TomatError> {
+    let mut parsed: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.as_ref().trim();
+        if tag.contains(',') {
+            return Err(TomatError::InvalidArgument(format!(
+                "Invalid tag '{}' (tags cannot contain commas, repeat --tag instead)",
+                tag
+            )));
+        }
+        if !tag.is_empty() && !parsed.iter().any(|t| t == tag) {
+            parsed.push(tag.to_string());
+        }
+    }
+    Ok(parsed)
+}
+
+/// Task and tags of a session for display, e.g. "writing #thesis"
+pub fn format_labels(task: Option<&str>, tags: &[String]) -> String {
+    task.into_iter()
+        .map(str::to_string)
+        .chain(tags.iter().map(|tag| format!("#{}", tag)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Record a work session done away from the timer, returning its id and a
+/// confirmation message
+pub fn log_session(
+    duration: &str,
+    task: Option<&str>,
+    tags: &[String],
+    at: Option<&str>,
+) -> Result<(i64, String), TomatError> {
+    let tags = parse_tags(tags)?;
+    let duration_seconds = parse_duration(duration).ok_or_else(|| {
+        TomatError::InvalidArgument(format!(
+            "Invalid duration '{}' (expected e.g. 50m, 1h30m or 90s)",
+            duration
+        ))
+    })?;
+
+    let now = Local::now();
+    let started_at = match at {
+        Some(at) => parse_start_time(at, now).ok_or_else(|| {
+            TomatError::InvalidArgument(format!(
+                "Invalid start time '{}' (expected HH:MM or YYYY-MM-DD HH:MM)",
+                at
+            ))
+        })?,
+        None => (now.timestamp().max(0) as u64).saturating_sub(duration_seconds),
+    };
+    let ended_at = started_at + duration_seconds;
+    if ended_at > now.timestamp().max(0) as u64 {
+        return Err(TomatError::InvalidArgument(
+            "Cannot log a session that ends in the future".to_string(),
+        ));
+    }
+
+    let store = HistoryStore::open_default()?;
+    let id = store.record(&NewSession {
+        task,
+        tags: &tags,
+        ..NewSession::new("work", started_at, ended_at, "completed")
+    })?;
+
+    let start = Local
+        .timestamp_opt(started_at as i64, 0)
+        .single()
+        .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default();
+    let labels = format_labels(task, &tags);
+    let task = if labels.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", labels)
+    };
+    Ok((
+        id,
+        format!(
+            "Logged {} work session{} starting {}",
+            format_duration(duration_seconds),
+            task,
+            start
+        ),
+    ))
+}
+
+/// Render the sessions of a local calendar day (today by default) as a table
+pub fn list_sessions(date: Option<&str>) -> Result<String, TomatError> {
+    let date = match date {
+        Some(date) => NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| {
+            TomatError::InvalidArgument(format!("Invalid date '{}' (expected YYYY-MM-DD)", date))
+        })?,
+        None => Local::now().date_naive(),
+    };
+
+    let (from, to) = local_day_bounds(date);
+    let sessions = HistoryStore::open_default()?.sessions_between(from, to)?;
+    if sessions.is_empty() {
+        return Ok(format!("No sessions recorded on {}", date));
+    }
+
+    let mut text = format!(
+        "{:>6}  {:<5}  {:<10}  {:>8}  {:<9}  {}\n",
+        "ID", "START", "PHASE", "DURATION", "OUTCOME", "TASK"
+    );
+    for session in sessions {
+        let start = Local
+            .timestamp_opt(session.started_at as i64, 0)
+            .single()
+            .map(|dt| dt.format("%H:%M").to_string())
+            .unwrap_or_default();
+        text.push_str(&format!(
+            "{:>6}  {:<5}  {:<10}  {:>8}  {:<9}  {}\n",
+            session.id,
+            start,
+            session.phase,
+            format_duration(session.duration_seconds),
+            session.outcome,
+            format_labels(session.task.as_deref(), &session.tags)
+        ));
+        if let Some(notes) = &session.notes {
+            text.push_str(&format!("{:>6}  {}\n", "", notes));
+        }
+    }
+    Ok(text.trim_end().to_string())
+}
+
+/// Delete a session from the history
+pub fn delete_session(id: i64) -> Result<String, TomatError> {
+    if HistoryStore::open_default()?.delete(id)? {
+        Ok(format!("Deleted session {}", id))
+    } else {
+        Err(no_such_session(id))
+    }
+}
+
+/// Change the duration and/or task of a session in the history
+pub fn edit_session(
+    id: i64,
+    duration: Option<&str>,
+    task: Option<&str>,
+) -> Result<String, TomatError> {
+    if duration.is_none() && task.is_none() {
+        return Err(TomatError::InvalidArgument(
+            "Nothing to change: pass --duration and/or --task".to_string(),
+        ));
+    }
+    let duration_seconds = duration
+        .map(|duration| {
+            parse_duration(duration).ok_or_else(|| {
+                TomatError::InvalidArgument(format!(
+                    "Invalid duration '{}' (expected e.g. 50m, 1h30m or 90s)",
+                    duration
+                ))
+            })
+        })
+        .transpose()?;
+
+    let store = HistoryStore::open_default()?;
+    if !store.edit(id, duration_seconds, task)? {
+        return Err(no_such_session(id));
+    }
+
+    let session = store.session(id)?.ok_or_else(|| no_such_session(id))?;
+    let labels = format_labels(session.task.as_deref(), &session.tags);
+    let task = if labels.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", labels)
+    };
+    Ok(format!(
+        "Updated session {}: {} {} session{}",
+        id,
+        format_duration(session.duration_seconds),
+        session.phase,
+        task
+    ))
+}
+
+/// Set the notes of a session in the history, an empty text clears them
+pub fn note_session(store: &HistoryStore, id: i64, notes: &str) -> Result<String, TomatError> {
+    if !store.set_notes(id, notes)? {
+        return Err(no_such_session(id));
+    }
+    Ok(if notes.is_empty() {
+        format!("Notes of session {} cleared", id)
+    } else {
+        format!("Notes saved to session {}", id)
+    })
+}
+
+fn no_such_session(id: i64) -> TomatError {
+    TomatError::InvalidArgument(format!(
+        "No session with id {} (see `tomat history list`)",
+        id
+    ))
+}
+
+/// Parse a duration such as "50m", "1h30m", "90s" or "25" (minutes) into seconds
+pub fn parse_duration(input: &str) -> Option<u64> {
+    let input: String = input.chars().filter(|c| !c.is_whitespace()).collect();
+    if input.is_empty() {
+        return None;
+    }
+    if let Ok(minutes) = input.parse::<u64>() {
+        return (minutes > 0).then_some(minutes * 60);
+    }
+
+    let mut total = 0u64;
+    let mut number = String::new();
+    for c in input.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let value: u64 = number.parse().ok()?;
+        number.clear();
+        total += match c {
+            'h' => value * 3600,
+            'm' => value * 60,
+            's' => value,
+            _ => return None,
+        };
+    }
+
+    (number.is_empty() && total > 0).then_some(total)
+}
+
+/// Parse "HH:MM" (today) or "YYYY-MM-DD HH:MM" in local time into a Unix timestamp
+fn parse_start_time(input: &str, now: DateTime<Local>) -> Option<u64> {
+    let input = input.trim();
+    let datetime = match NaiveTime::parse_from_str(input, "%H:%M") {
+        Ok(time) => now.date_naive().and_time(time),
+        Err(_) => NaiveDateTime::parse_from_str(input, "%Y-%m-%d %H:%M").ok()?,
+    };
+    Local
+        .from_local_datetime(&datetime)
+        .earliest()
+        .map(|dt| dt.timestamp().max(0) as u64)
+}
+
+/// Format a number of seconds as a compact duration (e.g. "2h 05m")
+pub fn format_duration(seconds: u64) -> String {
+    let hours = seconds / 3600;
+    let minutes = (seconds % 3600) / 60;
+    if hours > 0 {
+        format!("{}h {:02}m", hours, minutes)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+impl DaySummary {
+    /// Render the summary as human-readable text
+    pub fn to_text(&self) -> String {
+        let format_time = |timestamp: Option<u64>| {
+            timestamp
+                .and_then(|t| Local.timestamp_opt(t as i64, 0).single())
+                .map(|dt| dt.format("%H:%M").to_string())
+                .unwrap_or_else(|| "-".to_string())
+        };
+
+        format!(
+            "Tomat summary for {}\n\n\
+             Pomodoros completed: {}\n\
+             Focused time:        {}\n\
+             Break time:          {}\n\
+             First session:       {}\n\
+             Last session ended:  {}\n",
+            self.date.format("%A, %Y-%m-%d"),
+            self.work_sessions,
+            format_duration(self.work_seconds),
+            format_duration(self.break_seconds),
+            format_time(self.first_start),
+            format_time(self.last_end),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_store() -> (tempfile::TempDir, HistoryStore) {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = HistoryStore::open(&temp_dir.path().join("history.db")).unwrap();
+        (temp_dir, store)
+    }
+
+    #[test]
+    fn test_record_and_query_sessions() {
+        let (_dir, store) = temp_store();
+
+        let id = store
+            .record(&NewSession {
+                task: Some("reading"),
+                tags: &["thesis".to_string(), "ch2".to_string()],
+                ..NewSession::new("work", 1000, 2500, "completed")
+            })
+            .unwrap();
+        store
+            .record(&NewSession::new("break", 2500, 2800, "completed"))
+            .unwrap();
+
+        let sessions = store.sessions_between(0, 5000).unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].id, id);
+        assert_eq!(sessions[0].phase, "work");
+        assert_eq!(sessions[0].duration_seconds, 1500);
+        assert_eq!(sessions[0].task.as_deref(), Some("reading"));
+        assert_eq!(sessions[0].tags, ["thesis", "ch2"]);
+        assert_eq!(sessions[1].phase, "break");
+        assert!(sessions[1].tags.is_empty());
+
+        // Range is half-open on the start time
+        assert_eq!(store.sessions_between(1000, 2500).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_day_summary_counts_completed_sessions() {
+        let (_dir, store) = temp_store();
+        let today = Local::now().date_naive();
+        let (start, _) = local_day_bounds(today);
+
+        store
+            .record(&NewSession::new(
+                "work",
+                start + 100,
+                start + 1600,
+                "completed",
+            ))
+            .unwrap();
+        store
+            .record(&NewSession::new(
+                "break",
+                start + 1600,
+                start + 1900,
+                "completed",
+            ))
+            .unwrap();
+        store
+            .record(&NewSession::new(
+                "work",
+                start + 1900,
+                start + 2000,
+                "skipped",
+            ))
+            .unwrap();
+
+        let summary = store.day_summary(today).unwrap();
+        assert_eq!(summary.work_sessions, 1);
+        assert_eq!(summary.work_seconds, 1500);
+        assert_eq!(summary.break_seconds, 300);
+        assert_eq!(summary.first_start, Some(start + 100));
+        assert_eq!(summary.last_end, Some(start + 1900));
+
+        let text = summary.to_text();
+        assert!(text.contains("Pomodoros completed: 1"));
+        assert!(text.contains("25m"));
+    }
+
+    #[test]
+    fn test_cycle_progress() {
+        let (_dir, store) = temp_store();
+        let today = Local::now().date_naive();
+        let (start, end) = local_day_bounds(today);
+        let work = |offset: u64, outcome: &str| {
+            store
+                .record(&NewSession::new(
+                    "work",
+                    start + offset,
+                    start + offset + 1500,
+                    outcome,
+                ))
+                .unwrap();
+        };
+
+        assert_eq!(store.cycle_progress(today, 4).unwrap(), None);
+
+        // Sessions before the last long break belong to an earlier cycle
+        work(0, "completed");
+        store
+            .record(&NewSession::new(
+                "long_break",
+                start + 1500,
+                start + 2400,
+                "completed",
+            ))
+            .unwrap();
+        work(3000, "completed");
+        work(5000, "skipped");
+        work(6000, "completed");
+
+        let progress = store.cycle_progress(today, 4).unwrap().unwrap();
+        assert_eq!(progress.sessions, 2);
+        assert_eq!(progress.focused_seconds, 3000);
+        assert_eq!(progress.started_at, start + 3000);
+        assert_eq!(progress.valid_until, end);
+
+        // A full cycle without its long break is left out
+        assert_eq!(store.cycle_progress(today, 2).unwrap(), None);
+        work(8000, "completed");
+        let progress = store.cycle_progress(today, 2).unwrap().unwrap();
+        assert_eq!(progress.sessions, 1);
+        assert_eq!(progress.started_at, start + 8000);
+    }
+
+    #[test]
+    fn test_meta_round_trip() {
+        let (_dir, store) = temp_store();
+
+        assert_eq!(store.get_meta("last_report").unwrap(), None);
+        store.set_meta("last_report", "2026-01-01").unwrap();
+        store.set_meta("last_report", "2026-01-02").unwrap();
+        assert_eq!(
+            store.get_meta("last_report").unwrap().as_deref(),
+            Some("2026-01-02")
+        );
+    }
+
+    #[test]
+    fn test_edit_and_delete_sessions() {
+        let (_dir, store) = temp_store();
+        let id = store
+            .record(&NewSession {
+                task: Some("testing"),
+                ..NewSession::new("work", 1000, 2500, "completed")
+            })
+            .unwrap();
+
+        assert!(store.edit(id, Some(20 * 60), None).unwrap());
+        let session = store.session(id).unwrap().unwrap();
+        assert_eq!(session.started_at, 1000);
+        assert_eq!(session.ended_at, 1000 + 20 * 60);
+        assert_eq!(session.duration_seconds, 20 * 60);
+        assert_eq!(session.task.as_deref(), Some("testing"));
+
+        assert!(store.edit(id, None, Some("writing")).unwrap());
+        let session = store.session(id).unwrap().unwrap();
+        assert_eq!(session.task.as_deref(), Some("writing"));
+        assert_eq!(session.duration_seconds, 20 * 60);
+
+        assert!(store.edit(id, None, Some("")).unwrap());
+        assert_eq!(store.session(id).unwrap().unwrap().task, None);
+
+        assert!(store.set_notes(id, "fixed the parser").unwrap());
+        let session = store.session(id).unwrap().unwrap();
+        assert_eq!(session.notes.as_deref(), Some("fixed the parser"));
+        assert!(store.set_notes(id, "").unwrap());
+        assert_eq!(store.session(id).unwrap().unwrap().notes, None);
+
+        assert!(store.delete(id).unwrap());
+        assert!(store.session(id).unwrap().is_none());
+        assert!(!store.delete(id).unwrap());
+        assert!(!store.edit(id, Some(60), None).unwrap());
+    }
+
+    #[test]
+    fn test_abandon_since() {
+        let (_dir, store) = temp_store();
+        let before = store
+            .record(&NewSession::new("work", 1000, 2500, "completed"))
+            .unwrap();
+        let spanning = store
+            .record(&NewSession::new("work", 3000, 4500, "completed"))
+            .unwrap();
+        let after = store
+            .record(&NewSession::new("work", 5000, 6500, "completed"))
+            .unwrap();
+        let break_after = store
+            .record(&NewSession::new("break", 6500, 6800, "completed"))
+            .unwrap();
+
+        // Away from 4000 on
+        assert_eq!(store.abandon_since(4000).unwrap(), 2);
+
+        let session = store.session(before).unwrap().unwrap();
+        assert_eq!(session.outcome, "completed");
+        let session = store.session(spanning).unwrap().unwrap();
+        assert_eq!(session.outcome, "abandoned");
+        assert_eq!(session.ended_at, 4000);
+        assert_eq!(session.duration_seconds, 1000);
+        // Sessions that started while away are kept, without any time worked
+        let session = store.session(after).unwrap().unwrap();
+        assert_eq!(session.outcome, "abandoned");
+        assert_eq!((session.started_at, session.ended_at), (5000, 6500));
+        assert_eq!(session.duration_seconds, 0);
+        assert!(store.session(break_after).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_migrates_database_without_task_column() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("history.db");
+        Connection::open(&path)
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE sessions (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    phase TEXT NOT NULL,
+                    started_at INTEGER NOT NULL,
+                    ended_at INTEGER NOT NULL,
+                    duration_seconds INTEGER NOT NULL,
+                    outcome TEXT NOT NULL
+                );
+                INSERT INTO sessions (phase, started_at, ended_at, duration_seconds, outcome)
+                VALUES ('work', 0, 1500, 1500, 'completed');",
+            )
+            .unwrap();
+
+        let store = HistoryStore::open(&path).unwrap();
+        let sessions = store.sessions_between(0, 10).unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].task, None);
+        assert!(sessions[0].tags.is_empty());
+        // Old sessions count as run as planned
+        assert_eq!(sessions[0].planned_seconds, 1500);
+        assert_eq!(sessions[0].interruptions, 0);
+    }
+
+    #[test]
+    fn test_parse_tags() {
+        assert_eq!(
+            parse_tags(&[" thesis ", "", "ch2", "thesis"]).unwrap(),
+            ["thesis", "ch2"]
+        );
+        assert!(parse_tags(&["a,b"]).is_err());
+    }
+
+    #[test]
+    fn test_format_labels() {
+        let tags = ["thesis".to_string(), "ch2".to_string()];
+        assert_eq!(
+            format_labels(Some("writing"), &tags),
+            "writing #thesis #ch2"
+        );
+        assert_eq!(format_labels(None, &tags), "#thesis #ch2");
+        assert_eq!(format_labels(None, &[]), "");
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("50m"), Some(50 * 60));
+        assert_eq!(parse_duration("1h30m"), Some(90 * 60));
+        assert_eq!(parse_duration("1h 30m"), Some(90 * 60));
+        assert_eq!(parse_duration("90s"), Some(90));
+        assert_eq!(parse_duration("25"), Some(25 * 60));
+        assert_eq!(parse_duration("0m"), None);
+        assert_eq!(parse_duration("m"), None);
+        assert_eq!(parse_duration("5x"), None);
+        assert_eq!(parse_duration("1h30"), None);
+    }
+
+    #[test]
+    fn test_parse_start_time() {
+        let now = Local.with_ymd_and_hms(2026, 3, 10, 18, 0, 0).unwrap();
+        let expected = Local.with_ymd_and_hms(2026, 3, 10, 14, 0, 0).unwrap();
+        assert_eq!(
+            parse_start_time("14:00", now),
+            Some(expected.timestamp() as u64)
+        );
+
+        let expected = Local.with_ymd_and_hms(2026, 3, 9, 9, 15, 0).unwrap();
+        assert_eq!(
+            parse_start_time("2026-03-09 09:15", now),
+            Some(expected.timestamp() as u64)
+        );
+        assert_eq!(parse_start_time("25:00", now), None);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(0), "0m");
+        assert_eq!(format_duration(25 * 60), "25m");
+        assert_eq!(format_duration(2 * 3600 + 5 * 60), "2h 05m");
+    }
+}
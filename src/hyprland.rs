@@ -0,0 +1,268 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::UnixStream;
+
+use crate::error::TomatError;
+
+/// A window moved into the focus room, and the workspace to return it to
+#[derive(Debug, Clone, PartialEq)]
+struct MovedWindow {
+    address: String,
+    workspace: i64,
+}
+
+/// The window currently in the focus room, restored when the work phase ends
+static MOVED_WINDOW: Mutex<Option<MovedWindow>> = Mutex::new(None);
+
+/// Whether the daemon runs inside a Hyprland session
+pub fn is_running() -> bool {
+    std::env::var_os("HYPRLAND_INSTANCE_SIGNATURE").is_some_and(|s| !s.is_empty())
+}
+
+/// Path of the Hyprland command socket for an instance
+fn socket_path(runtime_dir: Option<&Path>, signature: &str) -> PathBuf {
+    // Hyprland 0.40+ keeps its sockets in the runtime directory, older versions in /tmp
+    let socket = Path::new("hypr").join(signature).join(".socket.sock");
+    runtime_dir
+        .map(|dir| dir.join(&socket))
+        .filter(|path| path.exists())
+        .unwrap_or_else(|| Path::new("/tmp").join(socket))
+}
+
+/// Send a request to the Hyprland command socket and return the reply
+pub async fn request(command: &str) -> Result<String, TomatError> {
+    let signature = std::env::var("HYPRLAND_INSTANCE_SIGNATURE")
+        .map_err(|_| TomatError::Other("Hyprland is not running".to_string()))?;
+    let runtime_dir = std::env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from);
+    request_at(&socket_path(runtime_dir.as_deref(), &signature), command).await
+}
+
+async fn request_at(socket: &Path, command: &str) -> Result<String, TomatError> {
+    let mut stream = UnixStream::connect(socket).await?;
+    stream.write_all(command.as_bytes()).await?;
+    stream.shutdown().await?;
+
+    let mut reply = String::new();
+    stream.read_to_string(&mut reply).await?;
+    Ok(reply)
+}
+
+/// Run a dispatcher, failing unless Hyprland replies "ok"
+pub async fn dispatch(args: &str) -> Result<(), TomatError> {
+    dispatch_at(None, args).await
+}
+
+async fn dispatch_at(socket: Option<&Path>, args: &str) -> Result<(), TomatError> {
+    let command = format!("dispatch {}", args);
+    let reply = match socket {
+        Some(socket) => request_at(socket, &command).await?,
+        None => request(&command).await?,
+    };
+    if reply.trim() == "ok" {
+        Ok(())
+    } else {
+        Err(TomatError::Other(format!(
+            "Hyprland rejected '{}': {}",
+            args,
+            reply.trim()
+        )))
+    }
+}
+
+/// Parse the reply to `j/activewindow`; special workspaces have negative ids
+fn parse_active_window(reply: &str) -> Option<MovedWindow> {
+    let window: serde_json::Value = serde_json::from_str(reply).ok()?;
+    let workspace = window.pointer("/workspace/id")?.as_i64()?;
+    Some(MovedWindow {
+        address: window.get("address")?.as_str()?.to_string(),
+        workspace,
+    })
+    .filter(|window| window.workspace > 0)
+}
+
+/// Move the focused window to the special workspace (or just show it if no
+/// window is focused) at the start of a work phase
+pub async fn enter_focus_room(workspace: &str) {
+    if let Err(e) = enter_focus_room_at(None, workspace).await {
+        eprintln!("Focus room: {}", e);
+    }
+}
+
+async fn enter_focus_room_at(socket: Option<&Path>, workspace: &str) -> Result<(), TomatError> {
+    let reply = match socket {
+        Some(socket) => request_at(socket, "j/activewindow").await?,
+        None => request("j/activewindow").await?,
+    };
+
+    match parse_active_window(&reply) {
+        Some(window) => {
+            dispatch_at(
+                socket,
+                &format!(
+                    "movetoworkspace special:{},address:{}",
+                    workspace, window.address
+                ),
+            )
+            .await?;
+            if let Ok(mut moved) = MOVED_WINDOW.lock() {
+                *moved = Some(window);
+            }
+        }
+        None => {
+            dispatch_at(socket, &format!("togglespecialworkspace {}", workspace)).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Return the window moved by `enter_focus_room` to where it came from and
+/// hide the special workspace when the work phase ends
+pub async fn leave_focus_room(workspace: &str) {
+    if let Err(e) = leave_focus_room_at(None, workspace).await {
+        eprintln!("Focus room: {}", e);
+    }
+}
+
+async fn leave_focus_room_at(socket: Option<&Path>, workspace: &str) -> Result<(), TomatError> {
+    let moved = MOVED_WINDOW.lock().ok().and_then(|mut moved| moved.take());
+    match moved {
+        Some(window) => {
+            // Moving the window back also leaves the special workspace
+            dispatch_at(
+                socket,
+                &format!(
+                    "movetoworkspace {},address:{}",
+                    window.workspace, window.address
+                ),
+            )
+            .await
+        }
+        None => {
+            let monitors = match socket {
+                Some(socket) => request_at(socket, "j/monitors").await?,
+                None => request("j/monitors").await?,
+            };
+            if special_workspace_shown(&monitors, workspace) {
+                dispatch_at(socket, &format!("togglespecialworkspace {}", workspace)).await?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Whether the special workspace is shown on the focused monitor, from `j/monitors`
+fn special_workspace_shown(monitors: &str, workspace: &str) -> bool {
+    let Ok(serde_json::Value::Array(monitors)) = serde_json::from_str(monitors) else {
+        return false;
+    };
+    let name = format!("special:{}", workspace);
+    monitors.iter().any(|monitor| {
+        monitor.get("focused").and_then(|v| v.as_bool()) == Some(true)
+            && monitor
+                .pointer("/specialWorkspace/name")
+                .and_then(|v| v.as_str())
+                == Some(name.as_str())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use tokio::net::UnixListener;
+
+    /// Serve canned Hyprland replies and record the requests received
+    fn fake_hyprland(
+        socket: &Path,
+        active_window: &'static str,
+    ) -> Arc<std::sync::Mutex<Vec<String>>> {
+        let listener = UnixListener::bind(socket).unwrap();
+        let requests = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = String::new();
+                stream.read_to_string(&mut request).await.unwrap();
+                let reply = match request.as_str() {
+                    "j/activewindow" => active_window,
+                    "j/monitors" => r#"[{"focused": true, "specialWorkspace": {"name": ""}}]"#,
+                    _ => "ok",
+                };
+                recorded.lock().unwrap().push(request);
+                stream.write_all(reply.as_bytes()).await.unwrap();
+            }
+        });
+
+        requests
+    }
+
+    #[test]
+    fn test_socket_path() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        assert_eq!(
+            socket_path(Some(temp_dir.path()), "abc"),
+            PathBuf::from("/tmp/hypr/abc/.socket.sock")
+        );
+
+        let socket_dir = temp_dir.path().join("hypr").join("abc");
+        std::fs::create_dir_all(&socket_dir).unwrap();
+        std::fs::write(socket_dir.join(".socket.sock"), "").unwrap();
+        assert_eq!(
+            socket_path(Some(temp_dir.path()), "abc"),
+            socket_dir.join(".socket.sock")
+        );
+    }
+
+    #[test]
+    fn test_parse_active_window() {
+        let reply = r#"{"address": "0x55d1", "workspace": {"id": 3, "name": "3"}}"#;
+        assert_eq!(
+            parse_active_window(reply),
+            Some(MovedWindow {
+                address: "0x55d1".to_string(),
+                workspace: 3
+            })
+        );
+
+        // No focused window, or one already on a special workspace
+        assert_eq!(parse_active_window("{}"), None);
+        let reply = r#"{"address": "0x55d1", "workspace": {"id": -98, "name": "special:focus"}}"#;
+        assert_eq!(parse_active_window(reply), None);
+    }
+
+    #[test]
+    fn test_special_workspace_shown() {
+        let monitors = r#"[
+            {"focused": false, "specialWorkspace": {"name": "special:focus"}},
+            {"focused": true, "specialWorkspace": {"name": ""}}
+        ]"#;
+        assert!(!special_workspace_shown(monitors, "focus"));
+
+        let monitors = r#"[{"focused": true, "specialWorkspace": {"name": "special:focus"}}]"#;
+        assert!(special_workspace_shown(monitors, "focus"));
+    }
+
+    #[tokio::test]
+    async fn test_focus_room_moves_and_restores_window() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let socket = temp_dir.path().join(".socket.sock");
+        let requests = fake_hyprland(
+            &socket,
+            r#"{"address": "0x55d1", "workspace": {"id": 2, "name": "2"}}"#,
+        );
+
+        enter_focus_room_at(Some(&socket), "focus").await.unwrap();
+        leave_focus_room_at(Some(&socket), "focus").await.unwrap();
+
+        assert_eq!(
+            *requests.lock().unwrap(),
+            vec![
+                "j/activewindow",
+                "dispatch movetoworkspace special:focus,address:0x55d1",
+                "dispatch movetoworkspace 2,address:0x55d1",
+            ]
+        );
+    }
+}
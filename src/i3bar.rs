@@ -0,0 +1,202 @@
+use serde_json::{Value, json};
+use std::io::{BufRead, Write};
+use tokio::sync::mpsc;
+
+use crate::ServerResponse;
+use crate::config::DisplayConfig;
+use crate::error::TomatError;
+use crate::server::send_command;
+use crate::timer::{Format, StatusOutput, TimerState, TimerStatus};
+
+/// Name of our block, which i3bar sends back with click events
+const BLOCK_NAME: &str = "tomat";
+
+/// The i3bar block for a timer status, or a block with empty text, which
+/// i3bar hides, without a status
+fn block(status: Option<&TimerStatus>, format: Option<&str>, display: &DisplayConfig) -> Value {
+    let Some(status) = status else {
+        return json!({ "name": BLOCK_NAME, "full_text": "" });
+    };
+    let (template, alt_template) = display.templates_at(
+        &status.phase,
+        status.is_paused,
+        format,
+        crate::timer::current_timestamp(),
+    );
+    // The i3blocks output already has the text, short text and color on
+    // one line each
+    let StatusOutput::Plain(output) =
+        TimerState::format_status(status, &Format::I3blocks, template, alt_template, display)
+    else {
+        unreachable!("i3blocks format always produces plain output")
+    };
+    let mut lines = output.splitn(3, '\n');
+    let (full_text, short_text, color) = (
+        lines.next().unwrap_or_default(),
+        lines.next().unwrap_or_default(),
+        lines.next().unwrap_or_default(),
+    );
+
+    json!({
+        "name": BLOCK_NAME,
+        "full_text": full_text,
+        "short_text": short_text,
+        "color": color,
+    })
+}
+
+/// The daemon command for an i3bar click event: left click toggles, middle
+/// click stops and right click skips
+pub fn click_command(event: &str) -> Option<&'static str> {
+    // Events are elements of an endless JSON array, one per line
+    let event = event.trim().trim_start_matches(['[', ',']).trim_start();
+    let event: Value = serde_json::from_str(event).ok()?;
+    if event["name"] != BLOCK_NAME {
+        return None;
+    }
+    match event["button"].as_u64()? {
+        1 => Some("toggle"),
+        2 => Some("stop"),
+        3 => Some("skip"),
+        _ => None,
+    }
+}
+
+/// Read i3bar click events from stdin on a background thread. The channel
+/// closes when i3bar closes stdin.
+fn spawn_click_reader() -> mpsc::UnboundedReceiver<&'static str> {
+    let (sender, receiver) = mpsc::unbounded_channel();
+
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lock().lines() {
+            let Ok(line) = line else { break };
+            if let Some(command) = click_command(&line)
+                && sender.send(command).is_err()
+            {
+                break;
+            }
+        }
+    });
+
+    receiver
+}
+
+/// Speak the i3bar protocol: print the header and a status line whenever
+/// the status changes, checking every `interval` seconds and right after a
+/// click. Returns when i3bar closes the pipe.
+pub async fn run(
+    format: Option<&str>,
+    interval: f64,
+    display: &DisplayConfig,
+) -> Result<(), TomatError> {
+    let interval = std::time::Duration::from_secs_f64(interval);
+    let mut clicks = spawn_click_reader();
+    let mut last_line = None;
+
+    {
+        let mut stdout = std::io::stdout().lock();
+        let header = json!({ "version": 1, "click_events": true });
+        if writeln!(stdout, "{}\n[", header)
+            .and_then(|_| stdout.flush())
+            .is_err()
+        {
+            return Ok(());
+        }
+    }
+
+    loop {
+        let status = send_command("status", json!({ "output": "waybar" }))
+            .await
+            .and_then(ServerResponse::into_result)
+            .and_then(|response| Ok(serde_json::from_value::<TimerStatus>(response.data)?))
+            .ok();
+        let line = json!([block(status.as_ref(), format, display)]).to_string();
+        if last_line.as_ref() != Some(&line) {
+            // Every status line but the first continues the endless array
+            let separator = if last_line.is_some() { "," } else { "" };
+            let mut stdout = std::io::stdout().lock();
+            if writeln!(stdout, "{}{}", separator, line)
+                .and_then(|_| stdout.flush())
+                .is_err()
+            {
+                return Ok(());
+            }
+            last_line = Some(line);
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {}
+            command = clicks.recv() => {
+                // Without clicks, keep updating until i3bar closes stdout
+                let Some(command) = command else {
+                    tokio::time::sleep(interval).await;
+                    continue;
+                };
+                if let Err(e) = send_command(command, Value::Null)
+                    .await
+                    .and_then(ServerResponse::into_result)
+                {
+                    eprintln!("Failed to {} the timer: {}", command, e);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timer::Phase;
+
+    fn status(phase: Phase, is_paused: bool) -> TimerStatus {
+        TimerStatus {
+            phase,
+            is_paused,
+            remaining_seconds: 1500,
+            duration_minutes: 25.0,
+            current_session: 1,
+            sessions_until_long_break: 4,
+            privacy: false,
+            task: None,
+            pauses_left: None,
+            banked_break_seconds: 0,
+            completed_today: 0,
+            daily_goal: None,
+        }
+    }
+
+    #[test]
+    fn test_block() {
+        let display = DisplayConfig::default();
+
+        let block = block(Some(&status(Phase::Work, false)), None, &display);
+        assert_eq!(block["name"], "tomat");
+        assert_eq!(block["full_text"], "🍅 25:00 ▶");
+        assert_eq!(block["short_text"], "25:00");
+        assert_eq!(block["color"], "#f38ba8");
+
+        // Without a daemon the block is hidden
+        let hidden = super::block(None, None, &display);
+        assert_eq!(hidden["full_text"], "");
+    }
+
+    #[test]
+    fn test_click_command() {
+        assert_eq!(click_command("["), None);
+        assert_eq!(
+            click_command(r#"{"name":"tomat","button":1,"x":10}"#),
+            Some("toggle")
+        );
+        assert_eq!(
+            click_command(r#",{"name":"tomat","button":2}"#),
+            Some("stop")
+        );
+        assert_eq!(
+            click_command(r#"[{"name":"tomat","button":3}"#),
+            Some("skip")
+        );
+        // Scrolling and clicks on other blocks are ignored
+        assert_eq!(click_command(r#",{"name":"tomat","button":4}"#), None);
+        assert_eq!(click_command(r#",{"name":"clock","button":1}"#), None);
+    }
+}
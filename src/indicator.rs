@@ -0,0 +1,400 @@
+use std::path::Path;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use crate::config::IndicatorConfig;
+use crate::error::TomatError;
+use crate::timer::{Phase, TimerState};
+
+/// Magic bytes starting every OpenRGB SDK packet
+const OPENRGB_MAGIC: &[u8; 4] = b"ORGB";
+/// OpenRGB SDK packet ids
+const REQUEST_CONTROLLER_COUNT: u32 = 0;
+const REQUEST_CONTROLLER_DATA: u32 = 1;
+const SET_CLIENT_NAME: u32 = 50;
+const UPDATE_LEDS: u32 = 1050;
+const SET_CUSTOM_MODE: u32 = 1100;
+/// How long to wait for the OpenRGB server
+const OPENRGB_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// What the indicator shows: a color, and whether it is for work, which is
+/// all a single-color LED can show
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Light {
+    pub color: [u8; 3],
+    pub work: bool,
+}
+
+/// Parse a "#rrggbb" color
+fn parse_color(color: &str) -> Option<[u8; 3]> {
+    let hex = color
+        .strip_prefix('#')
+        .filter(|hex| hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()))?;
+    let rgb = u32::from_str_radix(hex, 16).ok()?;
+    Some([(rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8])
+}
+
+/// The light for the current phase, None if no indicator is set up or its
+/// color is invalid
+pub fn light(config: &IndicatorConfig, state: &TimerState) -> Option<Light> {
+    if config.sysfs.is_none() && config.openrgb.is_none() {
+        return None;
+    }
+    let color = match state.phase {
+        Phase::Idle => &config.idle_color,
+        Phase::Prepare | Phase::Work | Phase::Review => &config.work_color,
+        Phase::Break | Phase::LongBreak => &config.break_color,
+    };
+    Some(Light {
+        color: parse_color(color)?,
+        work: matches!(state.phase, Phase::Work),
+    })
+}
+
+/// Show a light on the configured LED and OpenRGB devices
+pub async fn show(config: IndicatorConfig, light: Light) {
+    if let Some(led) = &config.sysfs
+        && let Err(e) = set_sysfs_led(led, light)
+    {
+        eprintln!("Failed to set LED {}: {}", led.display(), e);
+    }
+    if let Some(address) = &config.openrgb {
+        match tokio::time::timeout(OPENRGB_TIMEOUT, set_openrgb(address, light.color)).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => eprintln!("Failed to set OpenRGB colors at {}: {}", address, e),
+            Err(_) => eprintln!("OpenRGB server at {} did not respond", address),
+        }
+    }
+}
+
+/// Set an LED in /sys/class/leds. Multicolor LEDs take the color, other
+/// LEDs are on during work and off otherwise.
+fn set_sysfs_led(led: &Path, light: Light) -> std::io::Result<()> {
+    let multicolor = led.join("multi_intensity");
+    let on = if multicolor.exists() {
+        let [r, g, b] = light.color;
+        std::fs::write(&multicolor, format!("{} {} {}", r, g, b))?;
+        light.color != [0, 0, 0]
+    } else {
+        light.work
+    };
+    let max = std::fs::read_to_string(led.join("max_brightness"))
+        .map(|max| max.trim().to_string())
+        .unwrap_or_else(|_| "1".to_string());
+    std::fs::write(led.join("brightness"), if on { max.as_str() } else { "0" })
+}
+
+/// An OpenRGB SDK packet for a device
+fn openrgb_packet(device: u32, id: u32, data: &[u8]) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(16 + data.len());
+    packet.extend_from_slice(OPENRGB_MAGIC);
+    packet.extend_from_slice(&device.to_le_bytes());
+    packet.extend_from_slice(&id.to_le_bytes());
+    packet.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    packet.extend_from_slice(data);
+    packet
+}
+
+/// The data of an UPDATE_LEDS packet setting `leds` LEDs to one color
+fn update_leds_data(leds: u16, [r, g, b]: [u8; 3]) -> Vec<u8> {
+    let size = 4 + 2 + 4 * usize::from(leds);
+    let mut data = Vec::with_capacity(size);
+    data.extend_from_slice(&(size as u32).to_le_bytes());
+    data.extend_from_slice(&leds.to_le_bytes());
+    for _ in 0..leds {
+        data.extend_from_slice(&[r, g, b, 0]);
+    }
+    data
+}
+
+/// Reads the little-endian fields of an OpenRGB controller description
+struct Fields<'a>(&'a [u8]);
+
+impl Fields<'_> {
+    fn skip(&mut self, bytes: usize) -> Option<()> {
+        self.0 = self.0.get(bytes..)?;
+        Some(())
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        let value = u16::from_le_bytes(self.0.get(..2)?.try_into().ok()?);
+        self.skip(2)?;
+        Some(value)
+    }
+
+    /// Skip a string, stored with its length in front
+    fn skip_string(&mut self) -> Option<()> {
+        let len = self.u16()?;
+        self.skip(usize::from(len))
+    }
+}
+
+/// The number of LEDs in a controller description of SDK protocol version
+/// 0, which is what the server sends to clients that do not ask for another
+fn led_count(data: &[u8]) -> Option<u16> {
+    let mut fields = Fields(data);
+    // Data size and device type
+    fields.skip(8)?;
+    // Name, description, version, serial and location
+    for _ in 0..5 {
+        fields.skip_string()?;
+    }
+
+    let modes = fields.u16()?;
+    // Active mode
+    fields.skip(4)?;
+    for _ in 0..modes {
+        fields.skip_string()?;
+        // Value, flags, speed range, color range, speed, direction, color mode
+        fields.skip(9 * 4)?;
+        let colors = fields.u16()?;
+        fields.skip(usize::from(colors) * 4)?;
+    }
+
+    let zones = fields.u16()?;
+    for _ in 0..zones {
+        fields.skip_string()?;
+        // Type, LED range and LED count
+        fields.skip(4 * 4)?;
+        let matrix = fields.u16()?;
+        fields.skip(usize::from(matrix))?;
+    }
+
+    fields.u16()
+}
+
+/// Send a packet to the OpenRGB server
+async fn send(stream: &mut TcpStream, device: u32, id: u32, data: &[u8]) -> std::io::Result<()> {
+    stream.write_all(&openrgb_packet(device, id, data)).await
+}
+
+/// Read the data of the next packet with the given id, skipping
+/// notifications the server sends in between
+async fn receive(stream: &mut TcpStream, id: u32) -> Result<Vec<u8>, TomatError> {
+    loop {
+        let mut header = [0u8; 16];
+        stream.read_exact(&mut header).await?;
+        if &header[..4] != OPENRGB_MAGIC {
+            return Err(TomatError::Other("Not an OpenRGB SDK server".to_string()));
+        }
+        let packet_id = u32::from_le_bytes(header[8..12].try_into().unwrap());
+        let size = u32::from_le_bytes(header[12..16].try_into().unwrap());
+        let mut data = vec![0u8; size as usize];
+        stream.read_exact(&mut data).await?;
+        if packet_id == id {
+            return Ok(data);
+        }
+    }
+}
+
+/// Set all LEDs of all devices on an OpenRGB SDK server to one color
+async fn set_openrgb(address: &str, color: [u8; 3]) -> Result<(), TomatError> {
+    let mut stream = TcpStream::connect(address).await?;
+    send(&mut stream, 0, SET_CLIENT_NAME, b"tomat\0").await?;
+
+    send(&mut stream, 0, REQUEST_CONTROLLER_COUNT, &[]).await?;
+    let data = receive(&mut stream, REQUEST_CONTROLLER_COUNT).await?;
+    let count = data
+        .get(..4)
+        .map_or(0, |count| u32::from_le_bytes(count.try_into().unwrap()));
+
+    for device in 0..count {
+        send(&mut stream, device, REQUEST_CONTROLLER_DATA, &[]).await?;
+        let data = receive(&mut stream, REQUEST_CONTROLLER_DATA).await?;
+        let leds = led_count(&data).ok_or_else(|| {
+            TomatError::Other(format!("Invalid description of device {}", device))
+        })?;
+        send(&mut stream, device, SET_CUSTOM_MODE, &[]).await?;
+        send(
+            &mut stream,
+            device,
+            UPDATE_LEDS,
+            &update_leds_data(leds, color),
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tokio::net::TcpListener;
+
+    fn string(data: &mut Vec<u8>, text: &str) {
+        data.extend_from_slice(&(text.len() as u16 + 1).to_le_bytes());
+        data.extend_from_slice(text.as_bytes());
+        data.push(0);
+    }
+
+    /// A controller description with one mode with a color, one zone with a
+    /// 1x2 matrix, and `leds` LEDs
+    fn controller(leds: u16) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&[0; 8]);
+        for text in ["Keyboard", "RGB keyboard", "1.0", "", "HID"] {
+            string(&mut data, text);
+        }
+        data.extend_from_slice(&1u16.to_le_bytes());
+        data.extend_from_slice(&[0; 4]);
+        string(&mut data, "Direct");
+        data.extend_from_slice(&[0; 36]);
+        data.extend_from_slice(&1u16.to_le_bytes());
+        data.extend_from_slice(&[255, 0, 0, 0]);
+        data.extend_from_slice(&1u16.to_le_bytes());
+        string(&mut data, "Keys");
+        data.extend_from_slice(&[0; 16]);
+        data.extend_from_slice(&16u16.to_le_bytes());
+        data.extend_from_slice(&[0; 16]);
+        data.extend_from_slice(&leds.to_le_bytes());
+        // LED names and colors follow, which are not read
+        string(&mut data, "Key: A");
+        data
+    }
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("#ff8000"), Some([255, 128, 0]));
+        assert_eq!(parse_color("ff8000"), None);
+        assert_eq!(parse_color("#ff80"), None);
+    }
+
+    #[test]
+    fn test_light() {
+        let mut config = IndicatorConfig::default();
+        let mut state = TimerState::new(25.0, 5.0, 15.0, 4);
+        assert_eq!(light(&config, &state), None);
+
+        config.openrgb = Some("127.0.0.1:6742".to_string());
+        assert_eq!(
+            light(&config, &state),
+            Some(Light {
+                color: [0, 0, 0],
+                work: false
+            })
+        );
+        state.start_work();
+        assert_eq!(
+            light(&config, &state),
+            Some(Light {
+                color: [255, 0, 0],
+                work: true
+            })
+        );
+        state.phase = Phase::LongBreak;
+        assert_eq!(light(&config, &state).unwrap().color, [0, 255, 0]);
+
+        config.break_color = "green".to_string();
+        assert_eq!(light(&config, &state), None);
+    }
+
+    #[test]
+    fn test_set_sysfs_led() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("max_brightness"), "3\n").unwrap();
+        let brightness = || std::fs::read_to_string(dir.path().join("brightness")).unwrap();
+        let red = Light {
+            color: [255, 0, 0],
+            work: true,
+        };
+        let green = Light {
+            color: [0, 255, 0],
+            work: false,
+        };
+
+        // A single-color LED is only on during work
+        set_sysfs_led(dir.path(), red).unwrap();
+        assert_eq!(brightness(), "3");
+        set_sysfs_led(dir.path(), green).unwrap();
+        assert_eq!(brightness(), "0");
+
+        std::fs::write(dir.path().join("multi_intensity"), "0 0 0").unwrap();
+        set_sysfs_led(dir.path(), green).unwrap();
+        assert_eq!(brightness(), "3");
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("multi_intensity")).unwrap(),
+            "0 255 0"
+        );
+    }
+
+    #[test]
+    fn test_led_count() {
+        assert_eq!(led_count(&controller(104)), Some(104));
+        assert_eq!(led_count(&controller(104)[..40]), None);
+    }
+
+    #[test]
+    fn test_update_leds_data() {
+        assert_eq!(
+            update_leds_data(2, [1, 2, 3]),
+            [14, 0, 0, 0, 2, 0, 1, 2, 3, 0, 1, 2, 3, 0]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_set_openrgb() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+
+        // A server with two devices, which announces a device list update
+        // before answering
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            loop {
+                let mut header = [0u8; 16];
+                if stream.read_exact(&mut header).await.is_err() {
+                    break;
+                }
+                let device = u32::from_le_bytes(header[4..8].try_into().unwrap());
+                let id = u32::from_le_bytes(header[8..12].try_into().unwrap());
+                let size = u32::from_le_bytes(header[12..16].try_into().unwrap());
+                let mut data = vec![0u8; size as usize];
+                stream.read_exact(&mut data).await.unwrap();
+                let reply = match id {
+                    REQUEST_CONTROLLER_COUNT => Some(2u32.to_le_bytes().to_vec()),
+                    REQUEST_CONTROLLER_DATA => Some(controller(device as u16 + 1)),
+                    _ => None,
+                };
+                if let Some(reply) = reply {
+                    stream
+                        .write_all(&openrgb_packet(0, 100, &[]))
+                        .await
+                        .unwrap();
+                    stream
+                        .write_all(&openrgb_packet(device, id, &reply))
+                        .await
+                        .unwrap();
+                }
+                recorded.lock().unwrap().push((device, id, data));
+            }
+        });
+
+        set_openrgb(&address, [255, 0, 0]).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let requests = requests.lock().unwrap();
+        let ids: Vec<(u32, u32)> = requests
+            .iter()
+            .map(|(device, id, _)| (*device, *id))
+            .collect();
+        assert_eq!(
+            ids,
+            [
+                (0, SET_CLIENT_NAME),
+                (0, REQUEST_CONTROLLER_COUNT),
+                (0, REQUEST_CONTROLLER_DATA),
+                (0, SET_CUSTOM_MODE),
+                (0, UPDATE_LEDS),
+                (1, REQUEST_CONTROLLER_DATA),
+                (1, SET_CUSTOM_MODE),
+                (1, UPDATE_LEDS),
+            ]
+        );
+        // The second device has two LEDs
+        assert_eq!(requests[7].2, update_leds_data(2, [255, 0, 0]));
+    }
+}
@@ -0,0 +1,92 @@
+use std::io::Read;
+use tokio::sync::mpsc;
+
+/// Commands available as single keys in `watch --interactive`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeyAction {
+    Toggle,
+    Skip,
+    Quit,
+}
+
+impl KeyAction {
+    pub fn from_key(key: u8) -> Option<Self> {
+        match key {
+            b' ' => Some(KeyAction::Toggle),
+            b's' => Some(KeyAction::Skip),
+            b'q' => Some(KeyAction::Quit),
+            _ => None,
+        }
+    }
+}
+
+/// Puts the terminal on stdin into non-canonical mode without echo, so keys
+/// are read as they are pressed. The previous settings are restored on drop.
+pub struct RawMode {
+    original: libc::termios,
+}
+
+impl RawMode {
+    /// Enable raw key input, or return `None` if stdin is not a terminal
+    pub fn enable() -> Option<Self> {
+        // SAFETY: tcgetattr/tcsetattr only read and write the termios struct we own
+        unsafe {
+            let mut original: libc::termios = std::mem::zeroed();
+            if libc::tcgetattr(libc::STDIN_FILENO, &mut original) != 0 {
+                return None;
+            }
+
+            let mut raw = original;
+            raw.c_lflag &= !(libc::ICANON | libc::ECHO);
+            raw.c_cc[libc::VMIN] = 1;
+            raw.c_cc[libc::VTIME] = 0;
+            if libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw) != 0 {
+                return None;
+            }
+
+            Some(Self { original })
+        }
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        // SAFETY: restores the settings read in `enable`
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.original);
+        }
+    }
+}
+
+/// Read key actions from stdin on a background thread. The channel closes
+/// when stdin reaches end of file.
+pub fn spawn_key_reader() -> mpsc::UnboundedReceiver<KeyAction> {
+    let (sender, receiver) = mpsc::unbounded_channel();
+
+    std::thread::spawn(move || {
+        for byte in std::io::stdin().lock().bytes() {
+            let Ok(byte) = byte else { break };
+            if let Some(action) = KeyAction::from_key(byte)
+                && sender.send(action).is_err()
+            {
+                break;
+            }
+        }
+    });
+
+    receiver
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_actions() {
+        assert_eq!(KeyAction::from_key(b' '), Some(KeyAction::Toggle));
+        assert_eq!(KeyAction::from_key(b's'), Some(KeyAction::Skip));
+        assert_eq!(KeyAction::from_key(b'q'), Some(KeyAction::Quit));
+        assert_eq!(KeyAction::from_key(b'\n'), None);
+        assert_eq!(KeyAction::from_key(b'x'), None);
+    }
+}
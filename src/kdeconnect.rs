@@ -0,0 +1,49 @@
+use tokio::process::Command;
+
+/// Arguments for `dbus-send` to ping a KDE Connect device with a message,
+/// which the phone shows as a notification
+fn ping_args(device: &str, message: &str) -> Vec<String> {
+    vec![
+        "--session".to_string(),
+        "--type=method_call".to_string(),
+        "--dest=org.kde.kdeconnect".to_string(),
+        format!("/modules/kdeconnect/devices/{}/ping", device),
+        "org.kde.kdeconnect.device.ping.sendPing".to_string(),
+        format!("string:Tomat: {}", message),
+    ]
+}
+
+/// Send a phase change message to the phone through the KDE Connect daemon
+///
+/// Failures, e.g. because the phone is out of reach, are only logged.
+pub async fn send(device: String, message: String) {
+    match Command::new("dbus-send")
+        .args(ping_args(&device, &message))
+        .output()
+        .await
+    {
+        Ok(output) if !output.status.success() => eprintln!(
+            "Failed to notify KDE Connect device {}: {}",
+            device,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+        Ok(_) => {}
+        Err(e) => eprintln!("Failed to run dbus-send: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ping_args() {
+        let args = ping_args("a1b2c3d4", "Break time!\n4 pomodoros done");
+        assert!(args.contains(&"--dest=org.kde.kdeconnect".to_string()));
+        assert!(args.contains(&"/modules/kdeconnect/devices/a1b2c3d4/ping".to_string()));
+        assert_eq!(
+            args.last().unwrap(),
+            "string:Tomat: Break time!\n4 pomodoros done"
+        );
+    }
+}
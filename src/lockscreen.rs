@@ -0,0 +1,175 @@
+use chrono::{Local, TimeZone};
+use std::path::Path;
+use tokio::process::Command;
+
+use crate::config::FocusConfig;
+use crate::timer::{Phase, TimerState};
+
+/// Lock screen message and wallpaper shown during long breaks, set when a
+/// long break starts and undone when it ends, and the screen lock at the
+/// start of long breaks
+#[derive(Default)]
+pub struct Lockscreen {
+    /// Message last written to the message file, None until the first
+    /// write, which clears a message left behind by an earlier daemon
+    message: Option<Option<String>>,
+    /// Whether the break wallpaper is set
+    wallpaper: bool,
+    /// Whether the screen was locked for the current long break
+    locked: bool,
+}
+
+/// The message shown on the lock screen, None outside of long breaks
+pub fn break_message(state: &TimerState) -> Option<String> {
+    if !matches!(state.phase, Phase::LongBreak) {
+        return None;
+    }
+    let end = state
+        .get_finish_time()
+        .and_then(|finish| Local.timestamp_opt(finish as i64, 0).single());
+    Some(match end {
+        Some(end) => format!("Break until {}", end.format("%H:%M")),
+        None => "On a break".to_string(),
+    })
+}
+
+impl Lockscreen {
+    /// Bring the message file and the wallpaper in line with the timer, and
+    /// lock the screen once a long break is running
+    pub fn update(&mut self, focus: &FocusConfig, state: &TimerState) {
+        let on_long_break = matches!(state.phase, Phase::LongBreak);
+        if !on_long_break {
+            self.locked = false;
+        } else if focus.lock_on_long_break && !state.is_paused && !self.locked {
+            tokio::spawn(run(focus.lock_command.clone()));
+            self.locked = true;
+        }
+
+        let config = &focus.lockscreen;
+        if let Some(path) = &config.message_file {
+            let message = break_message(state);
+            if self.message.as_ref() != Some(&message) {
+                if let Err(e) = write_message(path, message.as_deref().unwrap_or_default()) {
+                    eprintln!(
+                        "Failed to write lock screen message to {}: {}",
+                        path.display(),
+                        e
+                    );
+                }
+                self.message = Some(message);
+            }
+        }
+
+        if config.wallpaper.is_some() && on_long_break != self.wallpaper {
+            let file = if on_long_break {
+                &config.wallpaper
+            } else {
+                &config.restore_wallpaper
+            };
+            if let Some(file) = file.clone() {
+                tokio::spawn(run(wallpaper_args(&config.wallpaper_command, &file)));
+            }
+            self.wallpaper = on_long_break;
+        }
+    }
+}
+
+/// Replace the contents of the message file at once, so that a lock screen
+/// reading it never sees half a message
+pub fn write_message(path: &Path, message: &str) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    std::fs::write(&temp, message)?;
+    std::fs::rename(&temp, path)
+}
+
+/// The wallpaper command with `{file}` replaced by the image to show
+fn wallpaper_args(command: &[String], file: &Path) -> Vec<String> {
+    let file = file.to_string_lossy();
+    command
+        .iter()
+        .map(|arg| arg.replace("{file}", &file))
+        .collect()
+}
+
+/// Run the screen lock or wallpaper command
+async fn run(command: Vec<String>) {
+    if crate::timer::is_testing() {
+        return;
+    }
+    let Some((program, args)) = command.split_first() else {
+        return;
+    };
+    match Command::new(program).args(args).output().await {
+        Ok(output) if !output.status.success() => eprintln!(
+            "{} failed: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+        Ok(_) => {}
+        Err(e) => eprintln!("Failed to run {}: {}", program, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_break_message() {
+        let mut state = TimerState::new(25.0, 5.0, 15.0, 4);
+        assert_eq!(break_message(&state), None);
+        state.start_work();
+        assert_eq!(break_message(&state), None);
+
+        state.phase = Phase::LongBreak;
+        state.duration_minutes = 15.0;
+        let end = Local
+            .timestamp_opt(state.start_time as i64 + 900, 0)
+            .unwrap()
+            .format("%H:%M");
+        assert_eq!(break_message(&state), Some(format!("Break until {}", end)));
+
+        state.is_paused = true;
+        assert_eq!(break_message(&state), Some("On a break".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_locks_once_per_long_break() {
+        let focus = FocusConfig {
+            lock_on_long_break: true,
+            lock_command: vec!["true".to_string()],
+            ..FocusConfig::default()
+        };
+        let mut lockscreen = Lockscreen::default();
+        let mut state = TimerState::new(25.0, 5.0, 15.0, 4);
+        state.start_work();
+        lockscreen.update(&focus, &state);
+        assert!(!lockscreen.locked);
+
+        // A long break waiting to be started does not lock yet
+        state.phase = Phase::LongBreak;
+        state.is_paused = true;
+        lockscreen.update(&focus, &state);
+        assert!(!lockscreen.locked);
+        state.is_paused = false;
+        lockscreen.update(&focus, &state);
+        assert!(lockscreen.locked);
+
+        state.start_work();
+        lockscreen.update(&focus, &state);
+        assert!(!lockscreen.locked);
+    }
+
+    #[test]
+    fn test_wallpaper_args() {
+        let command = ["swww", "img", "{file}"].map(String::from);
+        assert_eq!(
+            wallpaper_args(&command, Path::new("/tmp/break.png")),
+            ["swww", "img", "/tmp/break.png"]
+        );
+    }
+}
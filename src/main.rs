@@ -1,39 +1,163 @@
+mod alarm;
 mod audio;
 mod cli;
 mod config;
+mod dwm;
+mod error;
+mod fifo;
+mod focus;
+mod history;
+mod hyprland;
+mod i3bar;
+mod indicator;
+mod interactive;
+mod kdeconnect;
+mod lockscreen;
+mod metrics;
+mod nagbar;
+mod oneshot;
+mod plot;
+mod porcelain;
+mod prometheus;
+mod report;
+mod rofi;
 mod server;
+mod service;
+mod sound_theme;
+mod state;
+mod stats;
+mod streamdeck;
+mod template;
 mod timer;
+mod trace;
+mod watch;
+mod waybar;
 
 use clap::Parser;
 use serde::{Deserialize, Serialize};
+use std::io::IsTerminal;
 
-use crate::cli::{Cli, Commands, DaemonAction};
+use crate::cli::{Cli, Commands, DaemonAction, HistoryAction, InitSystem, PlanAction, Switch};
 use crate::config::Config;
+use crate::error::{ErrorCode, TomatError};
 use crate::server::{run_daemon, send_command};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Debug)]
 struct ServerResponse {
     success: bool,
     data: serde_json::Value,
     message: String,
+    /// Machine-readable error code, set when `success` is false
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<ErrorCode>,
 }
 
-/// Fetch and format timer status from daemon
-async fn fetch_and_format_status(
+impl ServerResponse {
+    /// A successful response without data
+    fn ok(message: impl Into<String>) -> Self {
+        Self::with_data(serde_json::Value::Null, message)
+    }
+
+    /// A successful response carrying data
+    fn with_data(data: serde_json::Value, message: impl Into<String>) -> Self {
+        Self {
+            success: true,
+            data,
+            message: message.into(),
+            error: None,
+        }
+    }
+
+    /// A failed response carrying the error's code and message
+    fn error(error: &TomatError) -> Self {
+        Self {
+            success: false,
+            data: serde_json::Value::Null,
+            message: error.to_string(),
+            error: Some(error.code()),
+        }
+    }
+
+    /// Turn a failed response back into an error
+    fn into_result(self) -> Result<Self, TomatError> {
+        if self.success {
+            Ok(self)
+        } else {
+            Err(TomatError::from_response(self.error, self.message))
+        }
+    }
+}
+
+/// Print an error to stderr, followed by a remediation hint if there is one
+fn print_error(error: &TomatError) {
+    eprintln!("Error: {}", error);
+    if let Some(hint) = error.hint() {
+        eprintln!("Hint: {}", hint);
+    }
+}
+
+/// Fetch the raw timer status from the daemon
+async fn fetch_timer_status() -> Result<timer::TimerStatus, TomatError> {
+    let response = send_command("status", serde_json::json!({ "output": "plain" }))
+        .await?
+        .into_result()?;
+    Ok(serde_json::from_value(response.data)?)
+}
+
+/// Print the outcome of a timer command: its message (or `message` in its
+/// place), or in porcelain mode the message followed by the new status
+async fn print_outcome(
+    result: Result<ServerResponse, TomatError>,
+    message: Option<&str>,
+    porcelain: bool,
+) -> Result<(), TomatError> {
+    match result {
+        Ok(response) if porcelain => print!(
+            "{}{}",
+            porcelain::ok(Some(&response.message)),
+            porcelain::status(&fetch_timer_status().await?)
+        ),
+        Ok(response) => println!("{}", message.unwrap_or(&response.message)),
+        // Porcelain errors are printed by main, which exits with status 1
+        Err(e) if porcelain => return Err(e),
+        Err(e) => print_error(&e),
+    }
+    Ok(())
+}
+
+/// Display settings from the config, with a display profile applied if given
+fn display_config(profile: Option<&str>) -> Result<config::DisplayConfig, TomatError> {
+    let display = Config::load().display;
+    match profile {
+        Some(name) => display.with_profile(name),
+        None => Ok(display),
+    }
+}
+
+/// Display settings for an output format: without a profile given, the
+/// profile named after the output format is used if one is configured
+fn output_display_config(
+    profile: Option<&str>,
+    output: &str,
+) -> Result<config::DisplayConfig, TomatError> {
+    let display = Config::load().display;
+    match profile.or_else(|| display.profiles.contains_key(output).then_some(output)) {
+        Some(name) => display.with_profile(name),
+        None => Ok(display),
+    }
+}
+
+/// Fetch timer status from daemon and build the output structure
+async fn fetch_status_output(
     output_format: &str,
-    text_template: &str,
-    text_template_idle: &str,
-    icons: &config::DisplayIcons,
-) -> Result<String, Box<dyn std::error::Error>> {
+    format_override: Option<&str>,
+    display: &config::DisplayConfig,
+) -> Result<timer::StatusOutput, TomatError> {
     let args = serde_json::json!({
         "output": output_format,
     });
 
-    let response = send_command("status", args).await?;
-
-    if !response.success {
-        return Err(response.message.into());
-    }
+    let response = send_command("status", args).await?.into_result()?;
 
     // Parse TimerStatus from response
     let timer_status: timer::TimerStatus = serde_json::from_value(response.data)?;
@@ -43,16 +167,31 @@ async fn fetch_and_format_status(
         .parse::<timer::Format>()
         .unwrap_or(timer::Format::Waybar);
 
-    // Choose template based on phase
-    let template = if matches!(timer_status.phase, timer::Phase::Idle) {
-        text_template_idle
-    } else {
-        text_template
-    };
+    // Choose templates based on phase and display rotation
+    let (template, alt_template) = display.templates_at(
+        &timer_status.phase,
+        timer_status.is_paused,
+        format_override,
+        timer::current_timestamp(),
+    );
 
     // Format with client-side template
-    let status_output =
-        timer::TimerState::format_status(&timer_status, &format_enum, template, icons);
+    Ok(timer::TimerState::format_status(
+        &timer_status,
+        &format_enum,
+        template,
+        alt_template,
+        display,
+    ))
+}
+
+/// Fetch and format timer status from daemon
+async fn fetch_and_format_status(
+    output_format: &str,
+    format_override: Option<&str>,
+    display: &config::DisplayConfig,
+) -> Result<String, TomatError> {
+    let status_output = fetch_status_output(output_format, format_override, display).await?;
 
     // Convert to string based on format type
     let output = match status_output {
@@ -63,9 +202,166 @@ async fn fetch_and_format_status(
     Ok(output)
 }
 
+/// The status for shell prompts as text, CSS class and color. The text is
+/// empty while idle, and everything is empty without a daemon.
+async fn prompt_status(
+    format_override: Option<&str>,
+    display: &config::DisplayConfig,
+) -> serde_json::Value {
+    let status = send_command("status", serde_json::json!({ "output": "waybar" }))
+        .await
+        .and_then(ServerResponse::into_result)
+        .and_then(|response| Ok(serde_json::from_value::<timer::TimerStatus>(response.data)?));
+    let Ok(status) = status else {
+        return serde_json::json!({ "text": "", "class": "", "color": "" });
+    };
+
+    let (template, alt_template) = display.templates_at(
+        &status.phase,
+        status.is_paused,
+        format_override,
+        timer::current_timestamp(),
+    );
+    let timer::StatusOutput::Waybar { text, class, .. } = timer::TimerState::format_status(
+        &status,
+        &timer::Format::Waybar,
+        template,
+        alt_template,
+        display,
+    ) else {
+        unreachable!("waybar format always produces waybar output")
+    };
+    let text = if matches!(status.phase, timer::Phase::Idle) {
+        String::new()
+    } else {
+        text
+    };
+
+    serde_json::json!({
+        "text": text,
+        "class": class,
+        "color": display.colors.color_for(&status.phase, status.is_paused),
+    })
+}
+
+/// Part of the status printed instead of the full output
+#[derive(Clone, Copy)]
+enum StatusField {
+    Tooltip,
+    Class,
+}
+
+/// Fetch the status and format it for printing: the full output, or only
+/// the tooltip or CSS class
+async fn fetch_status_text(
+    output_format: &str,
+    format_override: Option<&str>,
+    field: Option<StatusField>,
+    display: &config::DisplayConfig,
+) -> Result<String, TomatError> {
+    let Some(field) = field else {
+        return fetch_and_format_status(output_format, format_override, display).await;
+    };
+    match fetch_status_output("waybar", format_override, display).await? {
+        timer::StatusOutput::Waybar { tooltip, class, .. } => Ok(match field {
+            StatusField::Tooltip => tooltip,
+            StatusField::Class => class,
+        }),
+        _ => unreachable!("waybar format always produces waybar output"),
+    }
+}
+
+/// Print the status every `interval` seconds until the daemon stops,
+/// optionally reading single-key commands from stdin
+async fn follow_status(
+    output_format: &str,
+    format_override: Option<&str>,
+    field: Option<StatusField>,
+    interval: f64,
+    interactive: bool,
+    porcelain: bool,
+    display: &config::DisplayConfig,
+) {
+    let interval_duration = std::time::Duration::from_secs_f64(interval);
+
+    // Restores the terminal settings when the loop ends
+    let _raw_mode = interactive.then(interactive::RawMode::enable).flatten();
+    let mut keys = interactive.then(interactive::spawn_key_reader);
+
+    loop {
+        let output = if porcelain {
+            // Each status is followed by an empty line
+            fetch_timer_status()
+                .await
+                .map(|status| porcelain::ok(None) + &porcelain::status(&status))
+        } else {
+            fetch_status_text(output_format, format_override, field, display).await
+        };
+        match output {
+            Ok(output) => println!("{}", output),
+            Err(e) => {
+                if porcelain {
+                    print!("{}", porcelain::error(&e));
+                } else {
+                    print_error(&e);
+                }
+                // Exit on error (daemon might be stopped)
+                break;
+            }
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(interval_duration) => {}
+            Some(action) = async {
+                match keys.as_mut() {
+                    Some(keys) => keys.recv().await,
+                    None => std::future::pending().await,
+                }
+            } => {
+                let command = match action {
+                    interactive::KeyAction::Toggle => "toggle",
+                    interactive::KeyAction::Skip => "skip",
+                    interactive::KeyAction::Quit => break,
+                };
+                if let Err(e) = send_command(command, serde_json::Value::Null)
+                    .await
+                    .and_then(ServerResponse::into_result)
+                {
+                    print_error(&e);
+                }
+            }
+            // Leave through the loop so the terminal settings are restored
+            _ = tokio::signal::ctrl_c(), if interactive => break,
+        }
+    }
+}
+
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() {
     let cli = Cli::parse();
+    let porcelain = cli.porcelain;
+    if let Err(e) = run(cli).await {
+        if porcelain {
+            print!("{}", porcelain::error(&e));
+        } else {
+            print_error(&e);
+        }
+        std::process::exit(1);
+    }
+}
+
+async fn run(cli: Cli) -> Result<(), TomatError> {
+    let porcelain = cli.porcelain;
+    if porcelain && !cli.command.supports_porcelain() {
+        return Err(TomatError::InvalidArgument(
+            "This command has no --porcelain output".to_string(),
+        ));
+    }
+
+    // Managing the daemon itself never starts it behind the user's back
+    if !matches!(cli.command, Commands::Daemon { .. }) {
+        crate::server::set_spawn_daemon(cli.spawn_daemon || Config::load().daemon.auto_spawn);
+    }
 
     match cli.command {
         Commands::Daemon { action } => match action {
@@ -75,21 +371,43 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             DaemonAction::Stop => {
                 crate::server::stop_daemon().await?;
             }
-            DaemonAction::Status => {
-                crate::server::daemon_status().await?;
+            DaemonAction::Restart => {
+                crate::server::restart_daemon().await?;
             }
-            DaemonAction::Install { force } => {
-                install_systemd_service(force)?;
+            DaemonAction::Status { verbose, output } => {
+                crate::server::daemon_status(verbose, output == "json").await?;
+            }
+            DaemonAction::Install {
+                force,
+                autostart,
+                print,
+                template,
+                init,
+            } => {
+                let template = template.as_deref();
+                if autostart {
+                    crate::service::install_autostart_entry(force, print, template)?;
+                } else if init == InitSystem::Systemd {
+                    crate::service::install_systemd_service(force, print, template)?;
+                } else {
+                    crate::service::install_init_service(init, force, print, template)?;
+                }
             }
-            DaemonAction::Uninstall => {
-                uninstall_systemd_service()?;
+            DaemonAction::Uninstall { autostart, init } => {
+                if autostart {
+                    crate::service::uninstall_autostart_entry()?;
+                } else if init == InitSystem::Systemd {
+                    crate::service::uninstall_systemd_service()?;
+                } else {
+                    crate::service::uninstall_init_service(init)?;
+                }
             }
-            DaemonAction::Run => {
-                run_daemon().await?;
+            DaemonAction::Run { trace_transitions } => {
+                run_daemon(trace_transitions).await?;
             }
         },
 
-        Commands::Start { timer } => {
+        Commands::Start { timer, force } => {
             // Only send values that were explicitly provided
             // Daemon will use config defaults for missing values
             let mut args = serde_json::json!({});
@@ -109,6 +427,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             if let Some(auto_advance) = &timer.auto_advance {
                 args["auto_advance"] = serde_json::json!(auto_advance);
             }
+            if let Some(task) = &timer.task {
+                args["task"] = serde_json::json!(task);
+            }
+            if !timer.tags.is_empty() {
+                args["tags"] = serde_json::json!(timer.tags);
+            }
+            if force {
+                args["force"] = serde_json::json!(true);
+            }
 
             // Handle sound_mode with deprecated flag support
             let sound_mode = if let Some(ref mode) = timer.sound_mode {
@@ -128,324 +455,395 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 args["volume"] = serde_json::json!(volume);
             }
 
-            match send_command("start", args).await {
-                Ok(response) => {
-                    if response.success {
-                        println!("{}", response.message);
-                    } else {
-                        eprintln!("Error: {}", response.message);
-                    }
-                }
-                Err(e) => eprintln!("Failed to connect to daemon: {}", e),
-            }
+            let result = send_command("start", args)
+                .await
+                .and_then(ServerResponse::into_result);
+            print_outcome(result, None, porcelain).await?;
         }
 
-        Commands::Stop => match send_command("stop", serde_json::Value::Null).await {
-            Ok(response) => {
-                if response.success {
-                    println!("Timer stopped");
-                } else {
-                    eprintln!("Error: {}", response.message);
-                }
-            }
-            Err(e) => eprintln!("Failed to connect to daemon: {}", e),
-        },
+        Commands::Stop => {
+            let result = send_command("stop", serde_json::Value::Null)
+                .await
+                .and_then(ServerResponse::into_result);
+            print_outcome(result, Some("Timer stopped"), porcelain).await?;
+        }
 
-        Commands::Status { output, format } => {
-            // Load config for display format defaults
-            let config = Config::load();
-            let text_template = format.unwrap_or_else(|| config.display.text_format.clone());
-            let text_template_idle = config
-                .display
-                .text_format_idle
-                .unwrap_or_else(|| config.display.text_format.clone());
-
-            match fetch_and_format_status(
-                &output,
-                &text_template,
-                &text_template_idle,
-                &config.display.icons,
-            )
-            .await
-            {
-                Ok(output) => println!("{}", output),
-                Err(e) => eprintln!("Failed to connect to daemon: {}", e),
-            }
+        Commands::Away { idle } => {
+            let idle_seconds = match idle.as_deref() {
+                Some(idle) => crate::history::parse_duration(idle).ok_or_else(|| {
+                    TomatError::InvalidArgument(format!(
+                        "Invalid duration '{}' (expected e.g. 30m, 1h or 90s)",
+                        idle
+                    ))
+                })?,
+                None => 0,
+            };
+            let result = send_command("away", serde_json::json!({ "idle_seconds": idle_seconds }))
+                .await
+                .and_then(ServerResponse::into_result);
+            print_outcome(result, None, porcelain).await?;
         }
 
-        Commands::Watch {
+        Commands::Status {
             output,
             format,
+            profile,
+            schema,
+            tooltip_only,
+            class_only,
+            follow,
             interval,
+            interactive,
         } => {
+            if schema && porcelain {
+                return Err(TomatError::InvalidArgument(
+                    "--schema describes the JSON output and cannot be used with --porcelain"
+                        .to_string(),
+                ));
+            }
+            if schema {
+                let format_enum = output
+                    .parse::<timer::Format>()
+                    .unwrap_or(timer::Format::Waybar);
+                let schema = timer::StatusOutput::schema(&format_enum);
+                println!("{}", serde_json::to_string_pretty(&schema)?);
+                return Ok(());
+            }
+
             // Load config for display format defaults
-            let config = Config::load();
-            let text_template = format.unwrap_or_else(|| config.display.text_format.clone());
-            let text_template_idle = config
-                .display
-                .text_format_idle
-                .unwrap_or_else(|| config.display.text_format.clone());
-            let interval_duration = std::time::Duration::from_secs_f64(interval);
-
-            loop {
-                match fetch_and_format_status(
-                    &output,
-                    &text_template,
-                    &text_template_idle,
-                    &config.display.icons,
-                )
-                .await
-                {
-                    Ok(output) => println!("{}", output),
-                    Err(e) => {
-                        eprintln!("Failed to connect to daemon: {}", e);
-                        // Exit on error (daemon might be stopped)
-                        break;
-                    }
+            let display = match output_display_config(profile.as_deref(), &output) {
+                Ok(display) => display,
+                Err(e) => {
+                    print_error(&e);
+                    return Ok(());
                 }
+            };
 
-                tokio::time::sleep(interval_duration).await;
-            }
-        }
+            let field = if tooltip_only {
+                Some(StatusField::Tooltip)
+            } else if class_only {
+                Some(StatusField::Class)
+            } else {
+                None
+            };
 
-        Commands::Skip => match send_command("skip", serde_json::Value::Null).await {
-            Ok(response) => {
-                if response.success {
-                    println!("Skipped to next phase");
-                } else {
-                    eprintln!("Error: {}", response.message);
-                }
+            if follow {
+                follow_status(
+                    &output,
+                    format.as_deref(),
+                    field,
+                    interval,
+                    interactive,
+                    porcelain,
+                    &display,
+                )
+                .await;
+                return Ok(());
             }
-            Err(e) => eprintln!("Failed to connect to daemon: {}", e),
-        },
 
-        Commands::Pause => match send_command("pause", serde_json::Value::Null).await {
-            Ok(response) => {
-                if response.success {
-                    println!("{}", response.message);
-                } else {
-                    eprintln!("Error: {}", response.message);
-                }
+            if porcelain {
+                let status = fetch_timer_status().await?;
+                print!("{}{}", porcelain::ok(None), porcelain::status(&status));
+                return Ok(());
             }
-            Err(e) => eprintln!("Failed to connect to daemon: {}", e),
-        },
 
-        Commands::Resume => match send_command("resume", serde_json::Value::Null).await {
-            Ok(response) => {
-                if response.success {
-                    println!("{}", response.message);
-                } else {
-                    eprintln!("Error: {}", response.message);
-                }
+            match fetch_status_text(&output, format.as_deref(), field, &display).await {
+                Ok(output) => println!("{}", output),
+                Err(e) => print_error(&e),
             }
-            Err(e) => eprintln!("Failed to connect to daemon: {}", e),
-        },
+        }
 
-        Commands::Toggle => match send_command("toggle", serde_json::Value::Null).await {
-            Ok(response) => {
-                if response.success {
-                    println!("{}", response.message);
-                } else {
-                    eprintln!("Error: {}", response.message);
+        Commands::Watch {
+            output,
+            format,
+            profile,
+            interval,
+            interactive,
+        } => {
+            // Load config for display format defaults
+            match output_display_config(profile.as_deref(), &output) {
+                Ok(display) => {
+                    follow_status(
+                        &output,
+                        format.as_deref(),
+                        None,
+                        interval,
+                        interactive,
+                        porcelain,
+                        &display,
+                    )
+                    .await
                 }
+                Err(e) => print_error(&e),
             }
-            Err(e) => eprintln!("Failed to connect to daemon: {}", e),
-        },
-    }
-
-    Ok(())
-}
+        }
 
-/// Install systemd user service for tomat daemon
-fn install_systemd_service(force: bool) -> Result<(), Box<dyn std::error::Error>> {
-    use std::fs;
+        Commands::Subscribe => crate::server::print_events().await?,
 
-    // Get the current executable path
-    let exe_path = std::env::current_exe()?;
-    let exe_path_str = exe_path.to_string_lossy();
+        Commands::Prompt { format, json } => {
+            let status = prompt_status(format.as_deref(), &Config::load().display).await;
+            if json {
+                println!("{}", status);
+            } else if let Some(text) = status["text"].as_str().filter(|text| !text.is_empty()) {
+                println!("{}", text);
+            }
+        }
 
-    // Create systemd user directory using XDG config directory
-    let systemd_dir = if let Some(config_dir) = dirs::config_dir() {
-        config_dir.join("systemd").join("user")
-    } else {
-        // Fallback to HOME/.config if XDG config dir is not available
-        let home = std::env::var("HOME")?;
-        std::path::PathBuf::from(home)
-            .join(".config")
-            .join("systemd")
-            .join("user")
-    };
+        Commands::RofiBlocks { profile, interval } => {
+            let display = display_config(profile.as_deref())?;
+            rofi::run(interval, &display).await?;
+        }
 
-    fs::create_dir_all(&systemd_dir)?;
-
-    // Generate service file content
-    let service_content = format!(
-        r#"[Install]
-WantedBy=graphical-session.target
-
-[Service]
-Environment="PATH=%h/.local/bin:%h/.cargo/bin:/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin:/sbin:/bin"
-ExecStart={} daemon run
-Restart=always
-RestartSec=5
-
-[Unit]
-After=graphical-session.target
-Description=Tomat Pomodoro server
-PartOf=graphical-session.target
-"#,
-        exe_path_str
-    );
+        Commands::Waybar {
+            format,
+            profile,
+            interval,
+        } => {
+            let display = display_config(profile.as_deref())?;
+            waybar::run(format.as_deref(), interval, &display).await?;
+        }
 
-    // Write service file
-    let service_path = systemd_dir.join("tomat.service");
+        Commands::I3bar {
+            format,
+            profile,
+            interval,
+        } => {
+            let display = display_config(profile.as_deref())?;
+            i3bar::run(format.as_deref(), interval, &display).await?;
+        }
 
-    // Check if service file already exists (unless --force is used)
-    if service_path.exists() && !force {
-        use std::io::{self, Write};
+        Commands::Dwm {
+            format,
+            profile,
+            with,
+            separator,
+            interval,
+            print,
+        } => {
+            let display = display_config(profile.as_deref())?;
+            let options = dwm::DwmOptions {
+                format,
+                with,
+                separator,
+                print,
+            };
+            dwm::run(&options, interval, &display).await?;
+        }
 
-        print!(
-            "⚠ Service file already exists at: {}\nOverwrite? [y/N]: ",
-            service_path.display()
-        );
-        io::stdout().flush()?;
+        Commands::StreamdeckBridge {
+            format,
+            profile,
+            launch,
+        } => {
+            let display = display_config(profile.as_deref())?;
+            let launch = streamdeck::parse_launch_args(&launch)?;
+            streamdeck::run(&launch, format.as_deref(), &display).await?;
+        }
 
-        let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
+        Commands::Skip { merge } => {
+            let args = if merge {
+                serde_json::json!({ "merge": true })
+            } else {
+                serde_json::Value::Null
+            };
 
-        let response = input.trim().to_lowercase();
-        if response != "y" && response != "yes" {
-            println!("Installation cancelled.");
-            return Ok(());
+            let result = send_command("skip", args)
+                .await
+                .and_then(ServerResponse::into_result);
+            let message = (!merge).then_some("Skipped to next phase");
+            print_outcome(result, message, porcelain).await?;
         }
-    }
 
-    fs::write(&service_path, service_content)?;
+        Commands::PostponeLongBreak => {
+            let result = send_command("postpone_long_break", serde_json::Value::Null)
+                .await
+                .and_then(ServerResponse::into_result);
+            print_outcome(result, None, porcelain).await?;
+        }
 
-    println!(
-        "✓ Systemd service file installed to: {}",
-        service_path.display()
-    );
+        Commands::Break { banked } => {
+            let result = send_command("break", serde_json::json!({ "banked": banked }))
+                .await
+                .and_then(ServerResponse::into_result);
+            print_outcome(result, None, porcelain).await?;
+        }
 
-    // Reload systemd and enable service
-    let reload_result = std::process::Command::new("systemctl")
-        .args(["--user", "daemon-reload"])
-        .status();
-
-    match reload_result {
-        Ok(status) if status.success() => {
-            println!("✓ Systemd daemon reloaded");
-
-            let enable_result = std::process::Command::new("systemctl")
-                .args(["--user", "enable", "tomat.service"])
-                .status();
-
-            match enable_result {
-                Ok(status) if status.success() => {
-                    println!("✓ Tomat service enabled");
-                    println!("\nService installed successfully!");
-                    println!("\nTo start the daemon:");
-                    println!("  systemctl --user start tomat.service");
-                    println!("\nTo check status:");
-                    println!("  systemctl --user status tomat.service");
-                    println!("\nTo enable auto-start on login:");
-                    println!("  loginctl enable-linger $USER");
-                }
-                Ok(_) => {
-                    eprintln!("⚠ Warning: Failed to enable tomat.service");
-                    eprintln!(
-                        "You can enable it manually with: systemctl --user enable tomat.service"
-                    );
-                }
-                Err(e) => {
-                    eprintln!("⚠ Warning: Failed to run systemctl enable: {}", e);
-                    eprintln!(
-                        "You can enable it manually with: systemctl --user enable tomat.service"
-                    );
-                }
-            }
+        Commands::Pause => {
+            let result = send_command("pause", serde_json::Value::Null)
+                .await
+                .and_then(ServerResponse::into_result);
+            print_outcome(result, None, porcelain).await?;
         }
-        Ok(_) => {
-            eprintln!("⚠ Warning: Failed to reload systemd daemon");
-            eprintln!("You can reload manually with: systemctl --user daemon-reload");
+
+        Commands::Resume => {
+            let result = send_command("resume", serde_json::Value::Null)
+                .await
+                .and_then(ServerResponse::into_result);
+            print_outcome(result, None, porcelain).await?;
         }
-        Err(e) => {
-            eprintln!("⚠ Warning: Failed to run systemctl daemon-reload: {}", e);
-            eprintln!("Systemctl might not be available or you might not be using systemd");
+
+        Commands::Toggle => {
+            let result = send_command("toggle", serde_json::Value::Null)
+                .await
+                .and_then(ServerResponse::into_result);
+            print_outcome(result, None, porcelain).await?;
         }
-    }
 
-    Ok(())
-}
+        Commands::Oneshot {
+            minutes,
+            work,
+            focus,
+            block: _,
+        } => {
+            oneshot::run(minutes.or(work), focus).await?;
+        }
 
-/// Uninstall systemd user service for tomat daemon
-fn uninstall_systemd_service() -> Result<(), Box<dyn std::error::Error>> {
-    use std::fs;
+        Commands::Log {
+            duration,
+            task,
+            tags,
+            at,
+        } => {
+            let (id, message) =
+                history::log_session(&duration, task.as_deref(), &tags, at.as_deref())?;
+            if porcelain {
+                print!(
+                    "{}{}",
+                    porcelain::ok(Some(&message)),
+                    porcelain::line("id", id)
+                );
+            } else {
+                println!("{} (id {})", message, id);
+            }
+        }
 
-    // Use XDG config directory consistently
-    let service_path = if let Some(config_dir) = dirs::config_dir() {
-        config_dir
-            .join("systemd")
-            .join("user")
-            .join("tomat.service")
-    } else {
-        // Fallback to HOME/.config if XDG config dir is not available
-        let home = std::env::var("HOME")?;
-        std::path::PathBuf::from(home)
-            .join(".config")
-            .join("systemd")
-            .join("user")
-            .join("tomat.service")
-    };
+        Commands::Privacy { state } => {
+            let result = send_command(
+                "privacy",
+                serde_json::json!({ "enabled": state == Switch::On }),
+            )
+            .await
+            .and_then(ServerResponse::into_result);
+            print_outcome(result, None, porcelain).await?;
+        }
 
-    // Check if service file exists
-    if !service_path.exists() {
-        println!("Tomat service is not installed (service file not found)");
-        return Ok(());
-    }
+        Commands::Annotate { label, tags } => {
+            let mut args = serde_json::json!({ "task": label });
+            if !tags.is_empty() {
+                args["tags"] = serde_json::json!(tags);
+            }
+            let result = send_command("annotate", args)
+                .await
+                .and_then(ServerResponse::into_result);
+            print_outcome(result, None, porcelain).await?;
+        }
 
-    // Try to stop and disable the service first
-    let stop_result = std::process::Command::new("systemctl")
-        .args(["--user", "stop", "tomat.service"])
-        .status();
+        Commands::Note { text } => {
+            let result = send_command("note", serde_json::json!({ "text": text }))
+                .await
+                .and_then(ServerResponse::into_result);
+            print_outcome(result, None, porcelain).await?;
+        }
 
-    match stop_result {
-        Ok(status) if status.success() => println!("✓ Tomat service stopped"),
-        Ok(_) => eprintln!("⚠ Warning: Failed to stop tomat.service (might not be running)"),
-        Err(e) => eprintln!("⚠ Warning: Failed to run systemctl stop: {}", e),
-    }
+        Commands::Goal { pomodoros, reset } => {
+            let args = serde_json::json!({ "pomodoros": pomodoros, "reset": reset });
+            let response = send_command("goal", args).await?.into_result()?;
+            println!("{}", response.message);
+        }
 
-    let disable_result = std::process::Command::new("systemctl")
-        .args(["--user", "disable", "tomat.service"])
-        .status();
+        Commands::Plan { action } => {
+            let args = match &action {
+                PlanAction::Add { task, pomodoros } => serde_json::json!({
+                    "action": "add",
+                    "task": task,
+                    "pomodoros": pomodoros,
+                }),
+                PlanAction::Show => serde_json::json!({ "action": "show" }),
+                PlanAction::Clear => serde_json::json!({ "action": "clear" }),
+            };
 
-    match disable_result {
-        Ok(status) if status.success() => println!("✓ Tomat service disabled"),
-        Ok(_) => eprintln!("⚠ Warning: Failed to disable tomat.service"),
-        Err(e) => eprintln!("⚠ Warning: Failed to run systemctl disable: {}", e),
-    }
+            match send_command("plan", args)
+                .await
+                .and_then(ServerResponse::into_result)
+            {
+                Ok(response) if matches!(action, PlanAction::Show) => {
+                    let plan: Vec<timer::PlannedTask> = serde_json::from_value(response.data)?;
+                    if porcelain {
+                        print!("{}{}", porcelain::ok(None), porcelain::plan(&plan));
+                    } else {
+                        println!("{}", timer::format_plan(&plan));
+                    }
+                }
+                Ok(response) if porcelain => print!("{}", porcelain::ok(Some(&response.message))),
+                Ok(response) => println!("{}", response.message),
+                Err(e) if porcelain => return Err(e),
+                Err(e) => print_error(&e),
+            }
+        }
 
-    // Remove service file
-    match fs::remove_file(&service_path) {
-        Ok(()) => {
-            println!("✓ Service file removed: {}", service_path.display());
+        Commands::Simulate { duration } => {
+            let args = match duration {
+                Some(duration) => match history::parse_duration(&duration) {
+                    Some(seconds) => serde_json::json!({ "seconds": seconds }),
+                    None => {
+                        return Err(TomatError::InvalidArgument(format!(
+                            "Invalid duration '{}' (expected e.g. 10m, 1h30m or 90s)",
+                            duration
+                        )));
+                    }
+                },
+                None => serde_json::Value::Null,
+            };
 
-            // Reload systemd
-            let reload_result = std::process::Command::new("systemctl")
-                .args(["--user", "daemon-reload"])
-                .status();
+            let response = send_command("simulate", args).await?.into_result()?;
+            println!("{}", response.message);
+        }
 
-            match reload_result {
-                Ok(status) if status.success() => println!("✓ Systemd daemon reloaded"),
-                Ok(_) => eprintln!("⚠ Warning: Failed to reload systemd daemon"),
-                Err(e) => eprintln!("⚠ Warning: Failed to run systemctl daemon-reload: {}", e),
-            }
+        Commands::Raw { message } => {
+            let message = if message == "-" {
+                std::io::read_to_string(std::io::stdin())?
+            } else {
+                message
+            };
+            // The protocol is line-delimited, so the message is sent compacted
+            let message: serde_json::Value = serde_json::from_str(&message).map_err(|e| {
+                TomatError::InvalidArgument(format!("Message is not valid JSON: {}", e))
+            })?;
+            println!("{}", crate::server::send_raw(&message.to_string()).await?);
+        }
 
-            println!("\nTomat service uninstalled successfully!");
+        Commands::History { action } => {
+            let message = match action {
+                HistoryAction::List { date } => history::list_sessions(date.as_deref())?,
+                HistoryAction::Delete { id } => history::delete_session(id)?,
+                HistoryAction::Edit { id, duration, task } => {
+                    history::edit_session(id, duration.as_deref(), task.as_deref())?
+                }
+            };
+            println!("{}", message);
         }
-        Err(e) => {
-            eprintln!("Failed to remove service file: {}", e);
-            return Err(e.into());
+
+        Commands::Stats {
+            heatmap,
+            by,
+            efficiency,
+            weeks,
+            export_plot,
+            plot_file,
+        } => {
+            let color = std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+            let config = Config::load().stats;
+            if let Some(format) = export_plot {
+                println!(
+                    "{}",
+                    plot::export_plot(format, plot_file.as_deref(), &config)?
+                );
+                return Ok(());
+            }
+            println!(
+                "{}",
+                stats::show_stats(weeks, heatmap, by, efficiency, &config, color)?
+            );
         }
     }
 
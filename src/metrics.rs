@@ -0,0 +1,229 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Maximum number of distinct command names tracked, to bound memory use
+const MAX_TRACKED_COMMANDS: usize = 64;
+
+/// Timing statistics for a single protocol command
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct CommandTiming {
+    pub count: u64,
+    pub total_micros: u64,
+    pub max_micros: u64,
+}
+
+impl CommandTiming {
+    /// Average handling time in microseconds
+    pub fn average_micros(&self) -> u64 {
+        self.total_micros.checked_div(self.count).unwrap_or(0)
+    }
+}
+
+/// Runtime statistics collected by the daemon
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct DaemonMetrics {
+    pub started_at: u64,
+    pub status_cache_hits: u64,
+    pub status_cache_misses: u64,
+    pub commands: BTreeMap<String, CommandTiming>,
+    /// When the timer last changed phase
+    #[serde(default)]
+    pub last_transition_at: Option<u64>,
+    /// Config file the daemon took its settings from, if any
+    #[serde(default)]
+    pub config_file: Option<PathBuf>,
+    /// How sounds are played, filled in when the metrics are reported
+    #[serde(default)]
+    pub audio: String,
+    /// Whether notifications can be shown, filled in when the metrics are reported
+    #[serde(default)]
+    pub notifications: String,
+}
+
+impl DaemonMetrics {
+    pub fn new(started_at: u64) -> Self {
+        Self {
+            started_at,
+            ..Default::default()
+        }
+    }
+
+    /// Record how long handling a command took
+    pub fn record(&mut self, command: &str, elapsed: Duration) {
+        let name =
+            if self.commands.contains_key(command) || self.commands.len() < MAX_TRACKED_COMMANDS {
+                command
+            } else {
+                "other"
+            };
+
+        let micros = elapsed.as_micros().min(u64::MAX as u128) as u64;
+        let timing = self.commands.entry(name.to_string()).or_default();
+        timing.count += 1;
+        timing.total_micros = timing.total_micros.saturating_add(micros);
+        timing.max_micros = timing.max_micros.max(micros);
+    }
+
+    /// Number of requests handled
+    pub fn requests(&self) -> u64 {
+        self.commands.values().map(|timing| timing.count).sum()
+    }
+
+    /// Render uptime, requests and backends as human-readable text
+    pub fn summary_text(&self, now: u64) -> String {
+        let last_transition = match self.last_transition_at {
+            Some(at) => format!(
+                "{} ago",
+                crate::history::format_duration(now.saturating_sub(at))
+            ),
+            None => "none yet".to_string(),
+        };
+        let config_file = match &self.config_file {
+            Some(path) => path.display().to_string(),
+            None => "none, using defaults".to_string(),
+        };
+        format!(
+            "Uptime:          {}\n\
+             Requests:        {}\n\
+             Last transition: {}\n\
+             Config file:     {}\n\
+             Audio:           {}\n\
+             Notifications:   {}\n",
+            crate::history::format_duration(now.saturating_sub(self.started_at)),
+            self.requests(),
+            last_transition,
+            config_file,
+            self.audio,
+            self.notifications
+        )
+    }
+
+    /// Render the status cache statistics and per-command timings as a
+    /// human-readable table
+    pub fn to_text(&self) -> String {
+        let mut text = format!(
+            "Status cache: {} hits, {} misses\n\n{:<12} {:>8} {:>10} {:>10}\n",
+            self.status_cache_hits,
+            self.status_cache_misses,
+            "COMMAND",
+            "COUNT",
+            "AVG (µs)",
+            "MAX (µs)"
+        );
+        for (command, timing) in &self.commands {
+            text.push_str(&format!(
+                "{:<12} {:>8} {:>10} {:>10}\n",
+                command,
+                timing.count,
+                timing.average_micros(),
+                timing.max_micros
+            ));
+        }
+        text
+    }
+}
+
+/// Serialized `status` response, reused until the state changes or the second ticks over
+#[derive(Default)]
+pub struct StatusCache {
+    generation: u64,
+    entry: Option<(u64, u64, String)>,
+}
+
+impl StatusCache {
+    /// Drop the cached response after a state change
+    pub fn invalidate(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+        self.entry = None;
+    }
+
+    /// Get the cached response if it is still valid at the given second
+    pub fn get(&self, now: u64) -> Option<&str> {
+        match &self.entry {
+            Some((generation, second, response))
+                if *generation == self.generation && *second == now =>
+            {
+                Some(response)
+            }
+            _ => None,
+        }
+    }
+
+    /// Store the serialized response computed at the given second
+    pub fn store(&mut self, now: u64, response: String) {
+        self.entry = Some((self.generation, now, response));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_command_timings() {
+        let mut metrics = DaemonMetrics::new(0);
+        metrics.record("status", Duration::from_micros(100));
+        metrics.record("status", Duration::from_micros(300));
+        metrics.record("skip", Duration::from_micros(50));
+
+        let status = &metrics.commands["status"];
+        assert_eq!(status.count, 2);
+        assert_eq!(status.average_micros(), 200);
+        assert_eq!(status.max_micros, 300);
+        assert_eq!(metrics.commands["skip"].count, 1);
+
+        assert_eq!(metrics.requests(), 3);
+
+        let text = metrics.to_text();
+        assert!(text.contains("Status cache: 0 hits"));
+        assert!(text.contains("status"));
+    }
+
+    #[test]
+    fn test_summary_text() {
+        let mut metrics = DaemonMetrics::new(0);
+        metrics.record("status", Duration::from_micros(100));
+        metrics.audio = "embedded sounds".to_string();
+
+        let text = metrics.summary_text(90);
+        assert!(text.contains("Uptime:          1m\n"), "{}", text);
+        assert!(text.contains("Requests:        1\n"), "{}", text);
+        assert!(text.contains("Last transition: none yet\n"), "{}", text);
+        assert!(text.contains("Config file:     none, using defaults\n"));
+        assert!(text.contains("Audio:           embedded sounds\n"));
+
+        metrics.last_transition_at = Some(30);
+        metrics.config_file = Some(PathBuf::from("/etc/tomat.toml"));
+        let text = metrics.summary_text(150);
+        assert!(text.contains("Last transition: 2m ago\n"), "{}", text);
+        assert!(text.contains("Config file:     /etc/tomat.toml\n"));
+    }
+
+    #[test]
+    fn test_record_limits_tracked_commands() {
+        let mut metrics = DaemonMetrics::new(0);
+        for i in 0..MAX_TRACKED_COMMANDS + 10 {
+            metrics.record(&format!("cmd{}", i), Duration::from_micros(1));
+        }
+        assert_eq!(metrics.commands.len(), MAX_TRACKED_COMMANDS + 1);
+        assert_eq!(metrics.commands["other"].count, 10);
+    }
+
+    #[test]
+    fn test_status_cache_invalidation() {
+        let mut cache = StatusCache::default();
+        assert!(cache.get(100).is_none());
+
+        cache.store(100, "cached".to_string());
+        assert_eq!(cache.get(100), Some("cached"));
+
+        // A new second invalidates the entry
+        assert!(cache.get(101).is_none());
+
+        // So does a state change
+        cache.invalidate();
+        assert!(cache.get(100).is_none());
+    }
+}
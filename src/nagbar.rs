@@ -0,0 +1,179 @@
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+
+use crate::timer::Phase;
+
+/// The nagbar currently shown, so it can be dismissed when the timer moves on
+static ACTIVE_NAGBAR: Mutex<Option<Child>> = Mutex::new(None);
+
+/// Which nagbar the session can show: Some(true) for swaynag, which needs
+/// Sway, Some(false) for i3-nagbar under X, and None on other Wayland
+/// compositors, which have neither
+fn session_nagbar(sway: bool, wayland: bool, x11: bool) -> Option<bool> {
+    if sway {
+        Some(true)
+    } else if x11 && !wayland {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Whether an environment variable is set to something
+fn env_set(name: &str) -> bool {
+    std::env::var_os(name).is_some_and(|value| !value.is_empty())
+}
+
+/// Quote a string for use inside a `sh -c` command line
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+/// Get the (label, tomat arguments) buttons offered for a paused phase
+fn buttons(phase: &Phase) -> Vec<(&'static str, &'static str)> {
+    match phase {
+        Phase::Break | Phase::LongBreak => vec![
+            ("Start break", "resume"),
+            ("Keep working", "skip && {tomat} resume"),
+        ],
+        Phase::Prepare => vec![("Start preparing", "resume"), ("Skip to work", "skip")],
+        Phase::Work => vec![("Start working", "resume")],
+        Phase::Review => vec![("Start review", "resume"), ("Skip to break", "skip")],
+        Phase::Idle => Vec::new(),
+    }
+}
+
+/// Build the nagbar command line for a phase that is waiting to be resumed
+fn build_command(phase: &Phase, message: &str, tomat: &str, sway: bool) -> Command {
+    let mut cmd = Command::new(if sway { "swaynag" } else { "i3-nagbar" });
+    cmd.args(["-t", "warning", "-m", message]);
+
+    let tomat = shell_quote(tomat);
+    for (label, action) in buttons(phase) {
+        let action = format!("{} {}", tomat, action.replace("{tomat}", &tomat));
+        if sway {
+            // -Z runs the action without a terminal and dismisses the bar
+            cmd.args(["-Z", label, &action]);
+        } else {
+            // i3-nagbar runs actions via `sh -c`, so the bar is our parent
+            cmd.args(["-B", label, &format!("{}; kill $PPID", action)]);
+        }
+    }
+
+    cmd
+}
+
+/// Show a nagbar asking the user to continue into the given (paused) phase
+pub fn show(phase: &Phase, message: &str) {
+    dismiss();
+
+    let tomat = match std::env::current_exe() {
+        Ok(path) => path.to_string_lossy().to_string(),
+        Err(_) => "tomat".to_string(),
+    };
+
+    let Some(sway) = session_nagbar(
+        env_set("SWAYSOCK"),
+        env_set("WAYLAND_DISPLAY"),
+        env_set("DISPLAY"),
+    ) else {
+        eprintln!("Not showing a nagbar: swaynag needs Sway, and i3-nagbar needs X11");
+        return;
+    };
+
+    let spawned = build_command(phase, message, &tomat, sway)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+
+    match spawned {
+        Ok(child) => {
+            if let Ok(mut active) = ACTIVE_NAGBAR.lock() {
+                *active = Some(child);
+            }
+        }
+        Err(e) => eprintln!("Failed to show nagbar: {}", e),
+    }
+}
+
+/// Dismiss the nagbar if one is still shown
+pub fn dismiss() {
+    if let Ok(mut active) = ACTIVE_NAGBAR.lock()
+        && let Some(mut child) = active.take()
+    {
+        // Only kill the bar if it is still open; otherwise just reap it
+        if let Ok(None) = child.try_wait() {
+            let _ = child.kill();
+        }
+        let _ = child.wait();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(cmd: &Command) -> Vec<String> {
+        cmd.get_args()
+            .map(|a| a.to_string_lossy().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_swaynag_break_buttons() {
+        let cmd = build_command(&Phase::Break, "Break time!", "/usr/bin/tomat", true);
+        assert_eq!(cmd.get_program(), "swaynag");
+
+        let args = args(&cmd);
+        assert_eq!(
+            args,
+            vec![
+                "-t",
+                "warning",
+                "-m",
+                "Break time!",
+                "-Z",
+                "Start break",
+                "'/usr/bin/tomat' resume",
+                "-Z",
+                "Keep working",
+                "'/usr/bin/tomat' skip && '/usr/bin/tomat' resume",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_i3_nagbar_work_buttons() {
+        let cmd = build_command(&Phase::Work, "Back to work!", "tomat", false);
+        assert_eq!(cmd.get_program(), "i3-nagbar");
+
+        let args = args(&cmd);
+        assert!(args.contains(&"-B".to_string()));
+        assert!(args.contains(&"Start working".to_string()));
+        assert!(args.contains(&"'tomat' resume; kill $PPID".to_string()));
+        assert!(!args.contains(&"Keep working".to_string()));
+    }
+
+    #[test]
+    fn test_session_nagbar() {
+        assert_eq!(session_nagbar(true, true, true), Some(true));
+        assert_eq!(session_nagbar(false, false, true), Some(false));
+        // Hyprland, river and other compositors have no nagbar, even with XWayland
+        assert_eq!(session_nagbar(false, true, true), None);
+        assert_eq!(session_nagbar(false, true, false), None);
+        assert_eq!(session_nagbar(false, false, false), None);
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("/usr/bin/tomat"), "'/usr/bin/tomat'");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+    }
+
+    #[test]
+    fn test_dismiss_without_nagbar() {
+        // Dismissing when nothing is shown is a no-op
+        dismiss();
+    }
+}
@@ -0,0 +1,131 @@
+use std::io::{IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+use crate::config::Config;
+use crate::error::TomatError;
+use crate::timer::{Format, Phase, StatusOutput, TimerState, TimerStatus};
+
+/// Run a single work timer in the foreground, without the daemon.
+///
+/// Progress goes to stderr so that stdout stays clean for pipelines. Returns
+/// once the timer has finished, or with an error if it was interrupted. With
+/// `focus`, the applications in `[focus] block` are dealt with at the start.
+pub async fn run(minutes: Option<f32>, focus: bool) -> Result<(), TomatError> {
+    let config = Config::load();
+    let minutes = minutes.unwrap_or(config.timer.work);
+    crate::server::validate_duration("Work", minutes).map_err(TomatError::InvalidArgument)?;
+
+    if focus {
+        if config.focus.block.is_empty() {
+            eprintln!("Warning: --focus has no effect, [focus] block is empty");
+        }
+        crate::focus::enforce(config.focus.clone(), config.notification.clone()).await;
+    }
+
+    let total = Duration::from_secs_f32(minutes * 60.0);
+    let started = Instant::now();
+    let started_at = crate::timer::current_timestamp();
+    let interactive = std::io::stderr().is_terminal();
+    let mut last_printed = None;
+
+    let interrupted = tokio::select! {
+        _ = async {
+            loop {
+                let remaining = total.saturating_sub(started.elapsed());
+                let remaining_seconds = remaining.as_secs_f32().ceil() as u64;
+                if remaining.is_zero() {
+                    break;
+                }
+
+                // Redraw every second on a terminal, otherwise print a line per minute
+                let due = if interactive {
+                    last_printed != Some(remaining_seconds)
+                } else {
+                    last_printed.is_none_or(|last| last / 60 != remaining_seconds / 60)
+                };
+                if due {
+                    print_progress(&config, minutes, remaining_seconds, interactive);
+                    last_printed = Some(remaining_seconds);
+                }
+
+                tokio::time::sleep(remaining.min(Duration::from_millis(250))).await;
+            }
+        } => false,
+        _ = tokio::signal::ctrl_c() => true,
+    };
+
+    if interactive {
+        eprintln!();
+    }
+    if interrupted {
+        return Err(TomatError::Other("Timer interrupted".to_string()));
+    }
+
+    eprintln!("Done: {}min work session completed", minutes);
+    finish(&config, started_at);
+    Ok(())
+}
+
+fn print_progress(config: &Config, minutes: f32, remaining_seconds: u64, interactive: bool) {
+    let status = TimerStatus {
+        phase: Phase::Work,
+        is_paused: false,
+        remaining_seconds,
+        duration_minutes: minutes,
+        current_session: 1,
+        sessions_until_long_break: 1,
+        privacy: false,
+        task: None,
+        pauses_left: None,
+        banked_break_seconds: 0,
+        completed_today: 0,
+        daily_goal: None,
+    };
+    let StatusOutput::Plain(text) = TimerState::format_status(
+        &status,
+        &Format::Plain,
+        config.display.template_for(&Phase::Work, false, None),
+        &config.display.alt_format,
+        &config.display,
+    ) else {
+        return;
+    };
+
+    let mut stderr = std::io::stderr();
+    if interactive {
+        // Clear the rest of the line in case the text got shorter
+        let _ = write!(stderr, "\r{}\x1b[K", text);
+    } else {
+        let _ = writeln!(stderr, "{}", text);
+    }
+    let _ = stderr.flush();
+}
+
+/// Record the finished session and let the user know
+fn finish(config: &Config, started_at: u64) {
+    let ended_at = crate::timer::current_timestamp();
+    match crate::history::HistoryStore::open_default() {
+        Ok(store) => {
+            if let Err(e) = store.record(&crate::history::NewSession::new(
+                "work",
+                started_at,
+                ended_at,
+                "completed",
+            )) {
+                eprintln!("Failed to record session history: {}", e);
+            }
+        }
+        Err(e) => eprintln!("Failed to open history database: {}", e),
+    }
+
+    if config.notification.enabled && !crate::timer::is_testing() {
+        let mut notification =
+            crate::timer::new_notification(&config.notification, crate::timer::PHASE_CATEGORY);
+        notification
+            .summary("Tomat")
+            .body(&config.notification.work_message);
+        if let Err(e) = notification.show() {
+            eprintln!("Failed to send notification: {}", e);
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use chrono::{Duration, NaiveDate};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use crate::cli::PlotFormat;
+use crate::config::StatsConfig;
+use crate::error::TomatError;
+use crate::history::{HistoryStore, format_duration};
+use crate::stats::{Focus, daily_focus, today, work_sessions};
+
+/// Number of days shown in the chart, up to today
+pub const PLOT_DAYS: i64 = 30;
+
+const WIDTH: u32 = 760;
+const HEIGHT: u32 = 300;
+/// Space around the bars for the title and the axis labels
+const LEFT: u32 = 60;
+const RIGHT: u32 = 20;
+const TOP: u32 = 40;
+const BOTTOM: u32 = 40;
+/// Colors of the bars, the grid lines and the text
+const BAR_COLOR: &str = "#30a14e";
+const GRID_COLOR: &str = "#d0d7de";
+const TEXT_COLOR: &str = "#24292f";
+
+/// Highest value of the y axis in hours, a whole number of hours above the
+/// best day
+fn axis_hours(max_seconds: u64) -> u64 {
+    max_seconds.div_ceil(3600).max(1)
+}
+
+/// Render the focus time of each of the `PLOT_DAYS` days up to `today` as an
+/// SVG bar chart
+pub fn render_svg(days: &BTreeMap<NaiveDate, Focus>, today: NaiveDate) -> String {
+    let from = today - Duration::days(PLOT_DAYS - 1);
+    let seconds: Vec<u64> = (0..PLOT_DAYS)
+        .map(|day| {
+            let date = from + Duration::days(day);
+            days.get(&date).map_or(0, |focus| focus.seconds)
+        })
+        .collect();
+    let total: u64 = seconds.iter().sum();
+    let hours = axis_hours(seconds.iter().copied().max().unwrap_or(0));
+
+    let plot_width = f64::from(WIDTH - LEFT - RIGHT);
+    let plot_height = f64::from(HEIGHT - TOP - BOTTOM);
+    let bottom = f64::from(HEIGHT - BOTTOM);
+    let slot = plot_width / PLOT_DAYS as f64;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{WIDTH}\" height=\"{HEIGHT}\" \
+         viewBox=\"0 0 {WIDTH} {HEIGHT}\" font-family=\"sans-serif\" font-size=\"12\" \
+         fill=\"{TEXT_COLOR}\">\n\
+         <rect width=\"{WIDTH}\" height=\"{HEIGHT}\" fill=\"#ffffff\"/>\n"
+    );
+    let _ = writeln!(
+        svg,
+        "<text x=\"{LEFT}\" y=\"24\" font-size=\"15\">Focus per day from {} to {} ({})</text>",
+        from,
+        today,
+        format_duration(total)
+    );
+
+    // A grid line and label for each hour, or every other hour on busy days
+    let step = if hours > 8 { 2 } else { 1 };
+    for hour in (0..=hours).step_by(step) {
+        let y = bottom - plot_height * hour as f64 / hours as f64;
+        let _ = writeln!(
+            svg,
+            "<line x1=\"{LEFT}\" y1=\"{y:.1}\" x2=\"{}\" y2=\"{y:.1}\" stroke=\"{GRID_COLOR}\"/>",
+            WIDTH - RIGHT
+        );
+        let _ = writeln!(
+            svg,
+            "<text x=\"{}\" y=\"{:.1}\" text-anchor=\"end\">{}h</text>",
+            LEFT - 8,
+            y + 4.0,
+            hour
+        );
+    }
+
+    for (day, &seconds) in seconds.iter().enumerate() {
+        let date = from + Duration::days(day as i64);
+        let x = f64::from(LEFT) + slot * day as f64;
+        if seconds > 0 {
+            let height = plot_height * seconds as f64 / (hours * 3600) as f64;
+            let _ = writeln!(
+                svg,
+                "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{BAR_COLOR}\">\
+                 <title>{}: {}</title></rect>",
+                x + slot * 0.15,
+                bottom - height,
+                slot * 0.7,
+                height,
+                date,
+                format_duration(seconds)
+            );
+        }
+        // Dates below every week, counting back from today
+        if (PLOT_DAYS - 1 - day as i64) % 7 == 0 {
+            let _ = writeln!(
+                svg,
+                "<text x=\"{:.1}\" y=\"{}\" text-anchor=\"middle\">{}</text>",
+                x + slot / 2.0,
+                HEIGHT - BOTTOM + 18,
+                date.format("%b %-d")
+            );
+        }
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Render an SVG image as a PNG image of the same size
+fn svg_to_png(svg: &str) -> Result<Vec<u8>, TomatError> {
+    let mut options = resvg::usvg::Options::default();
+    options.fontdb_mut().load_system_fonts();
+    let tree = resvg::usvg::Tree::from_str(svg, &options)
+        .map_err(|e| TomatError::Other(format!("Failed to render chart: {}", e)))?;
+    let mut pixmap = resvg::tiny_skia::Pixmap::new(WIDTH, HEIGHT)
+        .ok_or_else(|| TomatError::Other("Failed to create image".to_string()))?;
+    resvg::render(
+        &tree,
+        resvg::tiny_skia::Transform::default(),
+        &mut pixmap.as_mut(),
+    );
+    pixmap
+        .encode_png()
+        .map_err(|e| TomatError::Other(format!("Failed to encode PNG: {}", e)))
+}
+
+/// Save a chart of the daily focus time of the last `PLOT_DAYS` days to
+/// `path`, or to tomat-focus.svg or .png in the current directory
+pub fn export_plot(
+    format: PlotFormat,
+    path: Option<&Path>,
+    config: &StatsConfig,
+) -> Result<String, TomatError> {
+    let day_start = config.day_start_time();
+    let today = today(day_start);
+    let from = today - Duration::days(PLOT_DAYS - 1);
+    let sessions = work_sessions(&HistoryStore::open_default()?, from, today, day_start)?;
+    let svg = render_svg(&daily_focus(&sessions, day_start), today);
+
+    let path = path.map_or_else(
+        || PathBuf::from(format!("tomat-focus.{}", format.extension())),
+        Path::to_path_buf,
+    );
+    match format {
+        PlotFormat::Svg => std::fs::write(&path, svg)?,
+        PlotFormat::Png => std::fs::write(&path, svg_to_png(&svg)?)?,
+    }
+    Ok(format!(
+        "Saved the focus time of the last {} days to {}",
+        PLOT_DAYS,
+        path.display()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn focus(minutes: u64) -> Focus {
+        Focus {
+            sessions: (minutes / 25) as u32,
+            seconds: minutes * 60,
+        }
+    }
+
+    #[test]
+    fn test_render_svg() {
+        let today = date("2026-03-30");
+        let days = BTreeMap::from([
+            // Before the chart
+            (date("2026-02-28"), focus(600)),
+            (date("2026-03-01"), focus(150)),
+            (date("2026-03-30"), focus(50)),
+        ]);
+
+        let svg = render_svg(&days, today);
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.ends_with("</svg>\n"));
+        assert!(svg.contains("Focus per day from 2026-03-01 to 2026-03-30 (3h 20m)"));
+        // A bar per day with focus time, on an axis up to 3 hours
+        assert_eq!(svg.matches(BAR_COLOR).count(), 2);
+        assert!(svg.contains("<title>2026-03-01: 2h 30m</title>"));
+        assert!(svg.contains(">3h</text>"));
+        assert!(!svg.contains(">4h</text>"));
+        // Weekly dates ending today
+        assert!(svg.contains(">Mar 30</text>"));
+        assert!(svg.contains(">Mar 23</text>"));
+        assert!(!svg.contains(">Mar 29</text>"));
+    }
+
+    #[test]
+    fn test_svg_to_png() {
+        let svg = render_svg(&BTreeMap::new(), date("2026-03-30"));
+        assert!(svg.contains(">1h</text>"));
+        let png = svg_to_png(&svg).unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+    }
+}
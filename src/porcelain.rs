@@ -0,0 +1,175 @@
+use crate::error::TomatError;
+use crate::timer::{Phase, PlannedTask, TimerStatus};
+
+/// Whether a character is an emoji or pictographic symbol, which automation
+/// frontends often cannot handle
+fn is_emoji(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x200D // zero width joiner
+            | 0x2300..=0x23FF // miscellaneous technical, e.g. ⏸
+            | 0x25A0..=0x25FF // geometric shapes, e.g. ▶
+            | 0x2600..=0x27BF // miscellaneous symbols and dingbats, e.g. ☕ ✓
+            | 0x2B00..=0x2BFF
+            | 0xFE00..=0xFE0F // variation selectors
+            | 0x1F000..=0x1FAFF
+    )
+}
+
+/// A value that fits on one porcelain line: emoji are dropped and control
+/// characters such as newlines become spaces
+fn clean(value: &str) -> String {
+    value
+        .chars()
+        .filter(|&c| !is_emoji(c))
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// One "key=value" line
+pub fn line(key: &str, value: impl std::fmt::Display) -> String {
+    format!("{}={}\n", key, clean(&value.to_string()))
+}
+
+/// A command that succeeded, with its message if it has one
+pub fn ok(message: Option<&str>) -> String {
+    let mut lines = line("result", "ok");
+    if let Some(message) = message {
+        lines += &line("message", message);
+    }
+    lines
+}
+
+/// A command that failed, with the machine-readable error code
+pub fn error(error: &TomatError) -> String {
+    let code = serde_json::to_value(error.code())
+        .ok()
+        .and_then(|code| code.as_str().map(str::to_string))
+        .unwrap_or_default();
+    line("result", "error") + &line("error", code) + &line("message", error)
+}
+
+/// The timer status, one field per line in a fixed order
+pub fn status(status: &TimerStatus) -> String {
+    // The task is hidden in privacy mode, as in the other outputs
+    let private = status.privacy && !matches!(status.phase, Phase::Idle);
+    let task = status
+        .task
+        .as_deref()
+        .filter(|_| !private)
+        .unwrap_or_default();
+
+    [
+        line("phase", &status.phase),
+        line("paused", status.is_paused),
+        line("remaining", status.remaining_seconds),
+        line(
+            "time",
+            format!(
+                "{:02}:{:02}",
+                status.remaining_seconds / 60,
+                status.remaining_seconds % 60
+            ),
+        ),
+        line("duration", (status.duration_minutes * 60.0).round() as u64),
+        line("session", status.current_session),
+        line("sessions", status.sessions_until_long_break),
+        line("privacy", status.privacy),
+        line("task", task),
+    ]
+    .concat()
+}
+
+/// The day plan, one task per line as "completed/pomodoros name"
+pub fn plan(plan: &[PlannedTask]) -> String {
+    plan.iter()
+        .map(|planned| {
+            line(
+                "task",
+                format!(
+                    "{}/{} {}",
+                    planned.completed, planned.pomodoros, planned.task
+                ),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean() {
+        assert_eq!(
+            clean("Break time! Take a short rest ☕"),
+            "Break time! Take a short rest"
+        );
+        assert_eq!(
+            clean("Long break 🏖️\n4 pomodoros done"),
+            "Long break 4 pomodoros done"
+        );
+        assert_eq!(clean("Café ⏸ ▶ ✓"), "Café");
+    }
+
+    #[test]
+    fn test_status() {
+        let status = TimerStatus {
+            phase: Phase::Work,
+            is_paused: false,
+            remaining_seconds: 1499,
+            duration_minutes: 25.0,
+            current_session: 1,
+            sessions_until_long_break: 4,
+            privacy: false,
+            task: Some("Write 📝 report".to_string()),
+            pauses_left: None,
+            banked_break_seconds: 0,
+            completed_today: 0,
+            daily_goal: None,
+        };
+        assert_eq!(
+            super::status(&status),
+            "phase=work\npaused=false\nremaining=1499\ntime=24:59\nduration=1500\n\
+             session=1\nsessions=4\nprivacy=false\ntask=Write report\n"
+        );
+
+        let status = TimerStatus {
+            privacy: true,
+            ..status
+        };
+        assert!(super::status(&status).ends_with("privacy=true\ntask=\n"));
+    }
+
+    #[test]
+    fn test_error() {
+        assert_eq!(
+            error(&TomatError::DaemonNotRunning),
+            "result=error\nerror=daemon_not_running\n\
+             message=Failed to connect to daemon: it is not running\n"
+        );
+    }
+
+    #[test]
+    fn test_plan() {
+        let plan = vec![
+            PlannedTask {
+                task: "Write report".to_string(),
+                pomodoros: 2,
+                completed: 2,
+            },
+            PlannedTask {
+                task: "Review".to_string(),
+                pomodoros: 1,
+                completed: 0,
+            },
+        ];
+        assert_eq!(
+            super::plan(&plan),
+            "task=2/2 Write report\ntask=0/1 Review\n"
+        );
+    }
+}
@@ -0,0 +1,136 @@
+use std::fmt::Write as _;
+use std::path::Path;
+
+use crate::timer::{Phase, TimerState};
+
+/// Phases reported by `tomat_phase`, as their label values
+const PHASES: [Phase; 6] = [
+    Phase::Idle,
+    Phase::Prepare,
+    Phase::Work,
+    Phase::Review,
+    Phase::Break,
+    Phase::LongBreak,
+];
+
+/// Append the help and type lines of a gauge
+fn header(out: &mut String, name: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} gauge", name);
+}
+
+/// Append a gauge without labels
+fn gauge(out: &mut String, name: &str, help: &str, value: u64) {
+    header(out, name, help);
+    let _ = writeln!(out, "{} {}", name, value);
+}
+
+/// Render the timer state in the Prometheus text exposition format
+pub fn render(state: &TimerState) -> String {
+    let mut out = String::new();
+
+    header(
+        &mut out,
+        "tomat_phase",
+        "Current timer phase, 1 for the current phase and 0 for the others.",
+    );
+    for phase in &PHASES {
+        let current = std::mem::discriminant(phase) == std::mem::discriminant(&state.phase);
+        let _ = writeln!(
+            out,
+            "tomat_phase{{phase=\"{}\"}} {}",
+            phase,
+            u8::from(current)
+        );
+    }
+    gauge(
+        &mut out,
+        "tomat_paused",
+        "Whether the timer is paused.",
+        u64::from(state.is_paused),
+    );
+    gauge(
+        &mut out,
+        "tomat_phase_duration_seconds",
+        "Length of the current phase.",
+        (state.duration_minutes * 60.0) as u64,
+    );
+    gauge(
+        &mut out,
+        "tomat_phase_end_timestamp_seconds",
+        "When the running phase ends, as a Unix timestamp, or 0 when no phase is running.",
+        state.get_finish_time().unwrap_or_default(),
+    );
+    gauge(
+        &mut out,
+        "tomat_sessions_completed",
+        "Work sessions completed since the last long break.",
+        u64::from(state.current_session_count),
+    );
+    gauge(
+        &mut out,
+        "tomat_sessions_until_long_break",
+        "Work sessions before a long break.",
+        u64::from(state.sessions_until_long_break),
+    );
+    gauge(
+        &mut out,
+        "tomat_focused_seconds",
+        "Seconds worked since the last long break.",
+        state.focused_seconds,
+    );
+
+    out
+}
+
+/// Write `contents` to `path` atomically, so that the textfile collector
+/// never reads a partly written file
+pub fn write_textfile(path: &Path, contents: &str) -> std::io::Result<()> {
+    // The collector only reads *.prom files, so it skips the temporary file
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    std::fs::write(&temp, contents)?;
+    std::fs::rename(&temp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let mut state = TimerState::new(25.0, 5.0, 15.0, 4);
+        state.start_work();
+        state.start_time = 1_000;
+        state.current_session_count = 2;
+
+        let text = render(&state);
+        assert!(text.contains("# TYPE tomat_phase gauge\n"));
+        assert!(text.contains("tomat_phase{phase=\"idle\"} 0\n"));
+        assert!(text.contains("tomat_phase{phase=\"work\"} 1\n"));
+        assert!(text.contains("tomat_phase{phase=\"long_break\"} 0\n"));
+        assert!(text.contains("tomat_paused 0\n"));
+        assert!(text.contains("tomat_phase_duration_seconds 1500\n"));
+        assert!(text.contains("tomat_phase_end_timestamp_seconds 2500\n"));
+        assert!(text.contains("tomat_sessions_completed 2\n"));
+        assert!(text.contains("tomat_sessions_until_long_break 4\n"));
+
+        // A paused phase has no end
+        state.is_paused = true;
+        let text = render(&state);
+        assert!(text.contains("tomat_paused 1\n"));
+        assert!(text.contains("tomat_phase_end_timestamp_seconds 0\n"));
+    }
+
+    #[test]
+    fn test_write_textfile() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tomat.prom");
+
+        write_textfile(&path, "tomat_paused 0\n").unwrap();
+        write_textfile(&path, "tomat_paused 1\n").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "tomat_paused 1\n");
+        // Only the final file is left behind
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
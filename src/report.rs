@@ -0,0 +1,283 @@
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, TimeZone};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::config::{ReportConfig, ReportMethod};
+use crate::error::TomatError;
+use crate::history::DaySummary;
+
+/// Metadata key storing the date of the last report that was sent
+pub const LAST_REPORT_KEY: &str = "last_report_date";
+
+/// Compute when the next end-of-day report is due, or None if reports are disabled
+pub fn next_report_time(
+    config: &ReportConfig,
+    now: DateTime<Local>,
+    last_sent: Option<NaiveDate>,
+) -> Option<DateTime<Local>> {
+    if !config.enabled {
+        return None;
+    }
+
+    let time = match NaiveTime::parse_from_str(&config.time, "%H:%M") {
+        Ok(time) => time,
+        Err(_) => {
+            eprintln!(
+                "Invalid report time '{}', expected HH:MM. Daily report disabled.",
+                config.time
+            );
+            return None;
+        }
+    };
+
+    let today = now.date_naive();
+    let date = if last_sent == Some(today) {
+        today.succ_opt()?
+    } else {
+        today
+    };
+
+    Local.from_local_datetime(&date.and_time(time)).earliest()
+}
+
+/// Build the full email message (headers and body) for a day summary
+pub fn build_message(config: &ReportConfig, summary: &DaySummary) -> String {
+    let now = Local::now();
+    format!(
+        "From: {}\r\nTo: {}\r\nSubject: {} - {}\r\nDate: {}\r\nMessage-ID: {}\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n{}",
+        config.from,
+        config.to,
+        config.subject,
+        summary.date.format("%Y-%m-%d"),
+        now.to_rfc2822(),
+        message_id(&config.from, now),
+        summary.to_text().replace('\n', "\r\n")
+    )
+}
+
+/// Generate a unique Message-ID using the domain of the sender address
+fn message_id(from: &str, now: DateTime<Local>) -> String {
+    let domain = from
+        .rsplit_once('@')
+        .map(|(_, domain)| domain.trim_end_matches('>').trim())
+        .filter(|domain| !domain.is_empty())
+        .unwrap_or("localhost");
+
+    format!(
+        "<{}.{}@{}>",
+        now.timestamp_nanos_opt().unwrap_or_default(),
+        std::process::id(),
+        domain
+    )
+}
+
+/// Send the daily summary using the configured delivery method
+pub async fn send_report(config: &ReportConfig, summary: &DaySummary) -> Result<(), TomatError> {
+    if config.to.is_empty() {
+        return Err("No recipient configured (report.to)".into());
+    }
+
+    let message = build_message(config, summary);
+
+    // Holds the curl config with SMTP credentials until the command finishes
+    let mut _credentials = None;
+
+    let mut cmd = match config.method {
+        ReportMethod::Sendmail => {
+            let mut cmd = Command::new(&config.sendmail);
+            cmd.arg("-t");
+            cmd
+        }
+        ReportMethod::Smtp => {
+            let url = config
+                .smtp_url
+                .as_ref()
+                .ok_or("SMTP delivery requires report.smtp_url")?;
+
+            let mut cmd = Command::new("curl");
+            cmd.args(["--silent", "--show-error", "--ssl", "--url", url])
+                .args(["--mail-from", &config.from])
+                .args(["--mail-rcpt", &config.to])
+                .args(["--upload-file", "-"]);
+
+            // Credentials go in a private curl config file rather than on
+            // the command line, where other users could read them
+            if let Some(user) = &config.smtp_user {
+                let password = smtp_password(config).await?;
+                let file = CredentialsFile::create(user, &password)?;
+                cmd.arg("--config").arg(file.path());
+                _credentials = Some(file);
+            }
+            cmd
+        }
+    };
+
+    let mut child = cmd
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(message.as_bytes()).await?;
+    }
+
+    let output = child.wait_with_output().await?;
+    if !output.status.success() {
+        return Err(format!(
+            "Mail command exited with status {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+/// A curl config file holding SMTP credentials, removed when dropped
+struct CredentialsFile(PathBuf);
+
+impl CredentialsFile {
+    fn create(user: &str, password: &str) -> std::io::Result<Self> {
+        use std::os::unix::fs::OpenOptionsExt;
+
+        let path = crate::server::get_runtime_dir()
+            .join(format!("tomat-smtp-{}.curlrc", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut file = std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(&path)?;
+        let credentials = Self(path);
+        writeln!(
+            file,
+            "user = \"{}\"",
+            curl_quote(&format!("{}:{}", user, password))
+        )?;
+
+        Ok(credentials)
+    }
+
+    fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for CredentialsFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Escape a value for use inside a double-quoted curl config parameter
+fn curl_quote(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Run the configured password command and return its first line of output
+async fn smtp_password(config: &ReportConfig) -> Result<String, TomatError> {
+    let Some((program, args)) = config.smtp_password_cmd.split_first() else {
+        return Ok(String::new());
+    };
+
+    let output = Command::new(program).args(args).output().await?;
+    if !output.status.success() {
+        return Err(format!("Password command exited with status {}", output.status).into());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Timelike;
+
+    fn enabled_config() -> ReportConfig {
+        ReportConfig {
+            enabled: true,
+            to: "me@example.com".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_next_report_time_disabled() {
+        let config = ReportConfig::default();
+        assert!(next_report_time(&config, Local::now(), None).is_none());
+    }
+
+    #[test]
+    fn test_next_report_time_today_and_tomorrow() {
+        let config = enabled_config();
+        let now = Local::now();
+        let today = now.date_naive();
+
+        // Not sent yet today: due today at the configured time
+        let next = next_report_time(&config, now, None).unwrap();
+        assert_eq!(next.date_naive(), today);
+        assert_eq!((next.hour(), next.minute()), (18, 0));
+
+        // Already sent today: due tomorrow
+        let next = next_report_time(&config, now, Some(today)).unwrap();
+        assert_eq!(next.date_naive(), today.succ_opt().unwrap());
+    }
+
+    #[test]
+    fn test_next_report_time_invalid_time() {
+        let config = ReportConfig {
+            time: "6pm".to_string(),
+            ..enabled_config()
+        };
+        assert!(next_report_time(&config, Local::now(), None).is_none());
+    }
+
+    #[test]
+    fn test_message_id_uses_sender_domain() {
+        let now = Local::now();
+        assert!(message_id("Tomat <tomat@example.org>", now).ends_with("@example.org>"));
+        assert!(message_id("tomat", now).ends_with("@localhost>"));
+        assert_ne!(
+            message_id("a@b", now),
+            message_id("a@b", now + chrono::Duration::nanoseconds(1))
+        );
+    }
+
+    #[test]
+    fn test_curl_quote() {
+        assert_eq!(curl_quote(r#"me:p"a\ss"#), r#"me:p\"a\\ss"#);
+    }
+
+    #[test]
+    fn test_build_message_headers() {
+        let config = enabled_config();
+        let summary = DaySummary {
+            date: NaiveDate::from_ymd_opt(2026, 3, 14).unwrap(),
+            work_sessions: 6,
+            work_seconds: 150 * 60,
+            break_seconds: 30 * 60,
+            first_start: None,
+            last_end: None,
+        };
+
+        let message = build_message(&config, &summary);
+        assert!(message.starts_with("From: tomat@localhost\r\n"));
+        assert!(message.contains("To: me@example.com\r\n"));
+        assert!(message.contains("Subject: Tomat daily summary - 2026-03-14\r\n"));
+        assert!(message.contains("\r\nDate: "));
+        assert!(message.contains("\r\nMessage-ID: <"));
+        assert!(message.contains("@localhost>\r\n"));
+        assert!(message.contains("Pomodoros completed: 6"));
+        assert!(message.contains("2h 30m"));
+    }
+}
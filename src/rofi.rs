@@ -0,0 +1,276 @@
+use serde_json::{Value, json};
+use std::io::BufRead;
+use tokio::sync::mpsc;
+
+use crate::ServerResponse;
+use crate::config::DisplayConfig;
+use crate::error::TomatError;
+use crate::server::send_command;
+use crate::timer::{Format, Phase, StatusOutput, TimerState, TimerStatus};
+
+/// Format of the events rofi-blocks writes to our stdin, set explicitly
+/// since the default differs between rofi-blocks versions
+const EVENT_FORMAT: &str = r#"{"name":"{{name_enum}}","data":"{{data_escaped}}"}"#;
+
+/// Daemon commands that menu entries may run
+const COMMANDS: [&str; 5] = ["start", "pause", "resume", "skip", "stop"];
+
+/// Menu entries for a timer status, as label and daemon command
+fn entries(status: &TimerStatus) -> Vec<(&'static str, &'static str)> {
+    match (&status.phase, status.is_paused) {
+        (Phase::Idle, _) => vec![("Start", "start")],
+        (_, true) => vec![("Resume", "resume"), ("Skip", "skip"), ("Stop", "stop")],
+        (_, false) => vec![("Pause", "pause"), ("Skip", "skip"), ("Stop", "stop")],
+    }
+}
+
+/// Whether `tomat note` has a session to take notes on: the work session in
+/// progress, or the one that just ended
+fn takes_notes(status: &TimerStatus) -> bool {
+    match status.phase {
+        // A paused work session that has not run yet is still to be started
+        Phase::Work => {
+            !status.is_paused || status.remaining_seconds < (status.duration_minutes * 60.0) as u64
+        }
+        Phase::Review | Phase::Break | Phase::LongBreak => true,
+        Phase::Idle | Phase::Prepare => false,
+    }
+}
+
+/// Escape text for the Pango markup that rofi renders messages with
+fn escape_markup(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// A rofi-blocks update showing the status, and `notice` below it if given
+pub fn update(status: &TimerStatus, display: &DisplayConfig, notice: Option<&str>) -> Value {
+    let (template, alt_template) = display.templates_at(
+        &status.phase,
+        status.is_paused,
+        None,
+        crate::timer::current_timestamp(),
+    );
+    let StatusOutput::Waybar { text, tooltip, .. } =
+        TimerState::format_status(status, &Format::Waybar, template, alt_template, display)
+    else {
+        unreachable!("waybar format always produces waybar output")
+    };
+
+    let mut message = format!("{}\n{}", text, tooltip);
+    if takes_notes(status) {
+        message.push_str("\nType a note and press Shift+Enter to add it to the session");
+    }
+    if let Some(notice) = notice {
+        message.push_str(&format!("\n{}", notice));
+    }
+
+    let lines: Vec<Value> = entries(status)
+        .into_iter()
+        .map(|(label, command)| json!({ "text": label, "data": command }))
+        .collect();
+
+    json!({
+        "prompt": "tomat",
+        "message": escape_markup(&message),
+        "event format": EVENT_FORMAT,
+        "lines": lines,
+    })
+}
+
+/// The daemon command to run for a rofi-blocks event, if it selected an entry
+pub fn selected_command(event: &str) -> Option<&'static str> {
+    let event: Value = serde_json::from_str(event).ok()?;
+    if event["name"] != "SELECT_ENTRY" {
+        return None;
+    }
+    let data = event["data"].as_str()?;
+    COMMANDS.into_iter().find(|&command| command == data)
+}
+
+/// The note typed into rofi for a rofi-blocks event, if custom input was run
+pub fn typed_note(event: &str) -> Option<String> {
+    let event: Value = serde_json::from_str(event).ok()?;
+    if event["name"] != "EXEC_CUSTOM_INPUT" {
+        return None;
+    }
+    let note = event["data"].as_str()?.trim();
+    (!note.is_empty()).then(|| note.to_string())
+}
+
+/// The daemon command and arguments to run for a rofi-blocks event
+fn event_command(event: &str) -> Option<(&'static str, Value)> {
+    if let Some(command) = selected_command(event) {
+        return Some((command, Value::Null));
+    }
+    typed_note(event).map(|text| ("note", json!({ "text": text })))
+}
+
+/// Read rofi-blocks events from stdin on a background thread. The channel
+/// closes when rofi closes stdin.
+fn spawn_event_reader() -> mpsc::UnboundedReceiver<(&'static str, Value)> {
+    let (sender, receiver) = mpsc::unbounded_channel();
+
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lock().lines() {
+            let Ok(line) = line else { break };
+            if let Some(command) = event_command(&line)
+                && sender.send(command).is_err()
+            {
+                break;
+            }
+        }
+    });
+
+    receiver
+}
+
+/// Print rofi-blocks updates every `interval` seconds and run the entries
+/// selected in rofi, until rofi closes or the daemon stops
+pub async fn run(interval: f64, display: &DisplayConfig) -> Result<(), TomatError> {
+    let interval = std::time::Duration::from_secs_f64(interval);
+    let mut events = spawn_event_reader();
+    let mut notice = None;
+
+    loop {
+        let status = match send_command("status", json!({ "output": "waybar" }))
+            .await
+            .and_then(ServerResponse::into_result)
+        {
+            Ok(response) => serde_json::from_value::<TimerStatus>(response.data)?,
+            Err(e) => {
+                println!(
+                    "{}",
+                    json!({ "message": escape_markup(&e.to_string()), "lines": [] })
+                );
+                return Err(e);
+            }
+        };
+        println!("{}", update(&status, display, notice.take().as_deref()));
+
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {}
+            command = events.recv() => {
+                let Some((command, args)) = command else { break };
+                match send_command(command, args)
+                    .await
+                    .and_then(ServerResponse::into_result)
+                {
+                    // Confirm notes, which do not change the status
+                    Ok(response) if command == "note" => notice = Some(response.message),
+                    Ok(_) => {}
+                    Err(e) => notice = Some(e.to_string()),
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(phase: Phase, is_paused: bool) -> TimerStatus {
+        TimerStatus {
+            phase,
+            is_paused,
+            remaining_seconds: 1500,
+            duration_minutes: 25.0,
+            current_session: 1,
+            sessions_until_long_break: 4,
+            privacy: false,
+            task: Some("R&D <notes>".to_string()),
+            pauses_left: None,
+            banked_break_seconds: 0,
+            completed_today: 0,
+            daily_goal: None,
+        }
+    }
+
+    #[test]
+    fn test_update() {
+        let display = DisplayConfig::default();
+
+        let update = update(&status(Phase::Work, false), &display, None);
+        assert_eq!(update["prompt"], "tomat");
+        assert_eq!(
+            update["message"],
+            "🍅 25:00 ▶\nWork (1/4) - 25.0min\nTask: R&amp;D &lt;notes&gt;\n\
+             Type a note and press Shift+Enter to add it to the session"
+        );
+        assert_eq!(update["event format"], EVENT_FORMAT);
+        assert_eq!(update["lines"][0]["text"], "Pause");
+        assert_eq!(update["lines"][0]["data"], "pause");
+        assert_eq!(update["lines"].as_array().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_entries_follow_the_state() {
+        let commands = |status: &TimerStatus| -> Vec<&str> {
+            entries(status)
+                .into_iter()
+                .map(|(_, command)| command)
+                .collect()
+        };
+        assert_eq!(commands(&status(Phase::Idle, false)), ["start"]);
+        assert_eq!(
+            commands(&status(Phase::Break, true)),
+            ["resume", "skip", "stop"]
+        );
+        assert_eq!(
+            commands(&status(Phase::LongBreak, false)),
+            ["pause", "skip", "stop"]
+        );
+    }
+
+    #[test]
+    fn test_selected_command() {
+        assert_eq!(
+            selected_command(r#"{"name":"SELECT_ENTRY","data":"skip"}"#),
+            Some("skip")
+        );
+        // Other events and unknown commands are ignored
+        assert_eq!(
+            selected_command(r#"{"name":"INPUT_CHANGE","data":"skip"}"#),
+            None
+        );
+        assert_eq!(
+            selected_command(r#"{"name":"SELECT_ENTRY","data":"shutdown"}"#),
+            None
+        );
+        assert_eq!(selected_command("not json"), None);
+    }
+
+    #[test]
+    fn test_typed_note() {
+        assert_eq!(
+            typed_note(r#"{"name":"EXEC_CUSTOM_INPUT","data":" finished draft "}"#).as_deref(),
+            Some("finished draft")
+        );
+        assert_eq!(
+            event_command(r#"{"name":"EXEC_CUSTOM_INPUT","data":"finished draft"}"#),
+            Some(("note", json!({ "text": "finished draft" })))
+        );
+        assert_eq!(
+            typed_note(r#"{"name":"EXEC_CUSTOM_INPUT","data":"  "}"#),
+            None
+        );
+        assert_eq!(
+            typed_note(r#"{"name":"INPUT_CHANGE","data":"finished"}"#),
+            None
+        );
+    }
+
+    #[test]
+    fn test_note_hint() {
+        let display = DisplayConfig::default();
+        let hint = "Type a note and press Shift+Enter";
+
+        let message = |phase| update(&status(phase, false), &display, None)["message"].clone();
+        assert!(message(Phase::Break).as_str().unwrap().contains(hint));
+        assert!(!message(Phase::Idle).as_str().unwrap().contains(hint));
+    }
+}
@@ -4,121 +4,212 @@ use std::fs::File;
 use std::io::Write;
 use std::path::PathBuf;
 use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::net::{UnixListener, UnixStream};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream, UnixListener, UnixStream};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore, broadcast, mpsc, oneshot};
 
 use crate::ServerResponse;
-use crate::timer::TimerState;
+use crate::error::TomatError;
+use crate::metrics::{DaemonMetrics, StatusCache};
+use crate::state::StateStore;
+use crate::timer::{Phase, TimerState, TimerStatus};
 
 #[derive(Serialize, Deserialize)]
 struct ClientMessage {
     command: String,
     args: serde_json::Value,
+    /// Authenticates clients connecting over TCP
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    token: Option<String>,
+}
+
+/// Get the directory holding the socket, PID file, and state file
+///
+/// Uses `$XDG_RUNTIME_DIR` or `/run/user/<uid>`. On systems that have neither
+/// (e.g. Alpine, WSL, BSDs) this falls back to a private per-user directory at
+/// `$XDG_CACHE_HOME/tomat/run`, or `/tmp/tomat-<uid>` without a home directory.
+pub(crate) fn get_runtime_dir() -> PathBuf {
+    if let Some(dir) = dirs::runtime_dir() {
+        return dir;
+    }
+
+    let uid = unsafe { libc::getuid() };
+    let system_dir = PathBuf::from(format!("/run/user/{}", uid));
+    if system_dir.is_dir() {
+        return system_dir;
+    }
+
+    let fallbacks = dirs::cache_dir()
+        .map(|dir| dir.join("tomat").join("run"))
+        .into_iter()
+        .chain(std::iter::once(
+            std::env::temp_dir().join(format!("tomat-{}", uid)),
+        ));
+
+    for dir in fallbacks {
+        match ensure_private_dir(&dir) {
+            Ok(()) => return dir,
+            Err(e) => eprintln!(
+                "Warning: cannot use runtime directory {}: {}",
+                dir.display(),
+                e
+            ),
+        }
+    }
+
+    system_dir
+}
+
+/// Create a directory only accessible by the current user, or verify an existing one
+fn ensure_private_dir(dir: &std::path::Path) -> std::io::Result<()> {
+    use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+
+    if let Some(parent) = dir.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    match std::fs::DirBuilder::new().mode(0o700).create(dir) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+        Err(e) => return Err(e),
+    }
+
+    // Refuse symlinks and directories owned by someone else (e.g. in /tmp)
+    let metadata = std::fs::symlink_metadata(dir)?;
+    if !metadata.is_dir() || metadata.uid() != unsafe { libc::getuid() } {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            "not a directory owned by the current user",
+        ));
+    }
+    if metadata.mode() & 0o077 != 0 {
+        std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))?;
+    }
+
+    Ok(())
 }
 
 fn get_socket_path() -> PathBuf {
-    dirs::runtime_dir()
-        .unwrap_or_else(|| PathBuf::from(format!("/run/user/{}", unsafe { libc::getuid() })))
-        .join("tomat.sock")
+    get_runtime_dir().join("tomat.sock")
 }
 
 fn get_pid_file_path() -> PathBuf {
-    dirs::runtime_dir()
-        .unwrap_or_else(|| PathBuf::from(format!("/run/user/{}", unsafe { libc::getuid() })))
-        .join("tomat.pid")
+    get_runtime_dir().join("tomat.pid")
 }
 
-fn get_state_file_path() -> PathBuf {
-    dirs::runtime_dir()
-        .unwrap_or_else(|| PathBuf::from(format!("/run/user/{}", unsafe { libc::getuid() })))
-        .join("tomat.state")
+/// The file daemons lock while they run. Unlike the socket and the PID file
+/// it is never removed, so daemons launched at once always lock the same file.
+fn get_lock_file_path() -> PathBuf {
+    get_runtime_dir().join("tomat.lock")
 }
 
-/// Save timer state to disk
-fn save_state(state: &TimerState) {
-    let state_path = get_state_file_path();
-    match serde_json::to_string_pretty(state) {
-        Ok(json) => {
-            if let Err(e) = std::fs::write(&state_path, json) {
-                eprintln!("Failed to save timer state: {}", e);
-            }
-        }
-        Err(e) => {
-            eprintln!("Failed to serialize timer state: {}", e);
-        }
+/// Where the file state backend keeps the timer state
+///
+/// Uses `$XDG_STATE_HOME/tomat` (`~/.local/state/tomat`), which unlike the
+/// runtime directory survives a reboot, so the day's session count is kept.
+fn get_state_file_path() -> PathBuf {
+    match dirs::state_dir() {
+        Some(dir) => dir.join("tomat").join("tomat.state"),
+        None => get_legacy_state_file_path(),
     }
 }
 
-/// Load timer state from disk
-fn load_state() -> Option<TimerState> {
-    let state_path = get_state_file_path();
+/// Where versions before the move to the state directory kept the state file
+fn get_legacy_state_file_path() -> PathBuf {
+    get_runtime_dir().join("tomat.state")
+}
 
-    if !state_path.exists() {
-        return None;
+/// Move a state file left in the runtime directory by an earlier version to
+/// `path`, unless there already is one. Returns whether a file was moved.
+fn migrate_state_file(legacy: &std::path::Path, path: &std::path::Path) -> std::io::Result<bool> {
+    if legacy == path || path.exists() || !legacy.exists() {
+        return Ok(false);
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    // The runtime directory is usually a tmpfs, so renaming may not work
+    if std::fs::rename(legacy, path).is_err() {
+        std::fs::copy(legacy, path)?;
+        std::fs::remove_file(legacy)?;
     }
+    Ok(true)
+}
 
-    match std::fs::read_to_string(&state_path) {
-        Ok(contents) => match serde_json::from_str::<TimerState>(&contents) {
-            Ok(state) => {
-                println!("Restored timer state from {:?}", state_path);
-                println!(
-                    "  State: phase={:?}, paused={}, work={}min, break={}min, long_break={}min",
-                    state.phase,
-                    state.is_paused,
-                    state.work_duration,
-                    state.break_duration,
-                    state.long_break_duration
-                );
-                Some(state)
-            }
-            Err(e) => {
-                eprintln!(
-                    "Failed to parse state file (corrupted?): {}. Starting with fresh state.",
-                    e
-                );
-                // Remove corrupted state file
-                let _ = std::fs::remove_file(&state_path);
-                None
-            }
-        },
+/// Where timer state is saved, chosen from `[daemon] state_backend` at startup
+static STATE_STORE: Mutex<Option<Box<dyn StateStore>>> = Mutex::new(None);
+
+/// Run `f` with the state store, defaulting to the state file
+fn with_state_store<T>(f: impl FnOnce(&mut dyn StateStore) -> T) -> T {
+    let mut store = STATE_STORE.lock().unwrap_or_else(|e| e.into_inner());
+    let store =
+        store.get_or_insert_with(|| Box::new(crate::state::FileStore::new(get_state_file_path())));
+    f(store.as_mut())
+}
+
+/// Save timer state
+fn save_state(state: &TimerState) {
+    with_state_store(|store| {
+        if let Err(e) = store.save(state) {
+            eprintln!("Failed to save timer state: {}", e);
+        }
+    });
+}
+
+/// Load saved timer state
+fn load_state() -> Option<TimerState> {
+    with_state_store(|store| match store.load() {
+        Ok(Some(state)) => {
+            println!("Restored timer state from {}", store.describe());
+            println!(
+                "  State: phase={:?}, paused={}, work={}min, break={}min, long_break={}min",
+                state.phase,
+                state.is_paused,
+                state.work_duration,
+                state.break_duration,
+                state.long_break_duration
+            );
+            Some(state)
+        }
+        Ok(None) => None,
         Err(e) => {
-            eprintln!("Failed to read state file: {}", e);
+            eprintln!(
+                "Failed to load timer state (corrupted?): {}. Starting with fresh state.",
+                e
+            );
+            // Remove corrupted state
+            let _ = store.clear();
             None
         }
-    }
+    })
 }
 
 /// Validate timer parameters
+/// Validate a phase duration in minutes, named `what` in the error
+pub(crate) fn validate_duration(what: &str, minutes: f32) -> Result<(), String> {
+    if minutes.is_nan() || minutes <= 0.0 {
+        return Err(format!("{} duration must be greater than 0", what));
+    }
+    if minutes > 600.0 {
+        return Err(format!(
+            "{} duration must be 600 minutes (10 hours) or less",
+            what
+        ));
+    }
+    Ok(())
+}
+
 fn validate_timer_params(
     work: f32,
     break_time: f32,
     long_break: f32,
     sessions: u32,
 ) -> Result<(), String> {
-    // Validate work duration
-    if work <= 0.0 {
-        return Err("Work duration must be greater than 0".to_string());
-    }
-    if work > 600.0 {
-        return Err("Work duration must be 600 minutes (10 hours) or less".to_string());
-    }
-
-    // Validate break duration
-    if break_time <= 0.0 {
-        return Err("Break duration must be greater than 0".to_string());
-    }
-    if break_time > 600.0 {
-        return Err("Break duration must be 600 minutes (10 hours) or less".to_string());
-    }
-
-    // Validate long break duration
-    if long_break <= 0.0 {
-        return Err("Long break duration must be greater than 0".to_string());
-    }
-    if long_break > 600.0 {
-        return Err("Long break duration must be 600 minutes (10 hours) or less".to_string());
-    }
+    validate_duration("Work", work)?;
+    validate_duration("Break", break_time)?;
+    validate_duration("Long break", long_break)?;
 
     // Validate sessions
     if sessions == 0 {
@@ -131,19 +222,103 @@ fn validate_timer_params(
     Ok(())
 }
 
+/// Commands that never start the daemon: a ping asks whether it runs, and
+/// the others end the timer or the daemon, which leaves nothing to do for a
+/// daemon that is not running
+const NO_SPAWN_COMMANDS: [&str; 4] = ["ping", "shutdown", "stop", "away"];
+
+/// Whether `send_command` starts the daemon when it is not running
+static SPAWN_DAEMON: AtomicBool = AtomicBool::new(false);
+
+/// Let `send_command` start the daemon and retry when it is not running, for
+/// clients such as bar modules that may run before the daemon is up
+pub fn set_spawn_daemon(enabled: bool) {
+    SPAWN_DAEMON.store(enabled, Ordering::Relaxed);
+}
+
 pub async fn send_command(
     command: &str,
     args: serde_json::Value,
-) -> Result<ServerResponse, Box<dyn std::error::Error>> {
-    let socket_path = get_socket_path();
-    let mut stream = UnixStream::connect(&socket_path).await?;
-
+) -> Result<ServerResponse, TomatError> {
+    let remote = remote_address();
     let message = ClientMessage {
         command: command.to_string(),
         args,
+        token: remote
+            .as_ref()
+            .and_then(|_| std::env::var("TOMAT_TOKEN").ok()),
     };
-
     let request = serde_json::to_string(&message)?;
+
+    let response = match send_raw(&request).await {
+        Err(TomatError::DaemonNotRunning)
+            if SPAWN_DAEMON.load(Ordering::Relaxed)
+                && !NO_SPAWN_COMMANDS.contains(&command)
+                && remote.is_none() =>
+        {
+            if let Err(e) = spawn_daemon(false).await {
+                // Another client may be starting the daemon at the same time
+                wait_for_daemon().await.map_err(|_| e)?;
+            }
+            send_raw(&request).await?
+        }
+        response => response?,
+    };
+    Ok(serde_json::from_str(&response)?)
+}
+
+/// Wait up to two seconds for a daemon to answer on the socket
+async fn wait_for_daemon() -> Result<(), TomatError> {
+    let start = std::time::Instant::now();
+    loop {
+        match send_raw(PING_REQUEST).await {
+            Ok(_) => return Ok(()),
+            Err(e) if start.elapsed() >= Duration::from_secs(2) => return Err(e),
+            Err(_) => tokio::time::sleep(Duration::from_millis(50)).await,
+        }
+    }
+}
+
+/// Address of a daemon on another machine to talk to over TCP instead of
+/// the local socket, from `TOMAT_ADDRESS`
+fn remote_address() -> Option<String> {
+    std::env::var("TOMAT_ADDRESS")
+        .ok()
+        .filter(|address| !address.is_empty())
+}
+
+/// A failed connection to the daemon, which is not running if nothing
+/// listens at its address
+fn not_running(e: std::io::Error) -> TomatError {
+    match e.kind() {
+        std::io::ErrorKind::NotFound | std::io::ErrorKind::ConnectionRefused => {
+            TomatError::DaemonNotRunning
+        }
+        _ => e.into(),
+    }
+}
+
+/// Send a single protocol line to the daemon and return its response line
+pub async fn send_raw(request: &str) -> Result<String, TomatError> {
+    match remote_address() {
+        Some(address) => {
+            let stream = TcpStream::connect(&address).await.map_err(not_running)?;
+            exchange(stream, request).await
+        }
+        None => {
+            let stream = UnixStream::connect(get_socket_path())
+                .await
+                .map_err(not_running)?;
+            exchange(stream, request).await
+        }
+    }
+}
+
+/// Write a request line to a connected daemon and read its response line
+async fn exchange<S: AsyncRead + AsyncWrite + Unpin>(
+    mut stream: S,
+    request: &str,
+) -> Result<String, TomatError> {
     stream.write_all(request.as_bytes()).await?;
     stream.write_all(b"\n").await?;
 
@@ -151,43 +326,526 @@ pub async fn send_command(
     let mut response = String::new();
     reader.read_line(&mut response).await?;
 
-    Ok(serde_json::from_str(&response)?)
+    Ok(response.trim_end().to_string())
+}
+
+/// Subscribe to the daemon's events and print each event line until the
+/// daemon stops
+pub async fn print_events() -> Result<(), TomatError> {
+    let remote = remote_address();
+    let message = ClientMessage {
+        command: "subscribe".to_string(),
+        args: serde_json::Value::Null,
+        token: remote
+            .as_ref()
+            .and_then(|_| std::env::var("TOMAT_TOKEN").ok()),
+    };
+    let request = serde_json::to_string(&message)?;
+
+    match remote {
+        Some(address) => {
+            let stream = TcpStream::connect(&address).await.map_err(not_running)?;
+            follow_events(stream, &request).await
+        }
+        None => {
+            let stream = UnixStream::connect(get_socket_path())
+                .await
+                .map_err(not_running)?;
+            follow_events(stream, &request).await
+        }
+    }
+}
+
+/// Send the subscribe request and print the event lines that follow its
+/// response until the connection closes
+async fn follow_events<S: AsyncRead + AsyncWrite + Unpin>(
+    mut stream: S,
+    request: &str,
+) -> Result<(), TomatError> {
+    stream.write_all(request.as_bytes()).await?;
+    stream.write_all(b"\n").await?;
+
+    let mut lines = BufReader::new(stream).lines();
+    let response = lines.next_line().await?.unwrap_or_default();
+    serde_json::from_str::<ServerResponse>(&response)?.into_result()?;
+
+    while let Some(event) = lines.next_line().await? {
+        println!("{}", event);
+    }
+    Ok(())
+}
+
+/// How long to wait for the daemon to answer a ping
+const PING_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// A ping as a protocol line, for checks that must not start the daemon
+const PING_REQUEST: &str = r#"{"command":"ping","args":null}"#;
+
+/// Ask the daemon listening on the socket for its PID
+///
+/// This is the authoritative check whether a daemon is running: the PID file
+/// may be left over from a crash, and its PID may since belong to another
+/// process.
+pub async fn ping_daemon() -> Option<u32> {
+    let response =
+        tokio::time::timeout(PING_TIMEOUT, send_command("ping", serde_json::Value::Null))
+            .await
+            .ok()?
+            .ok()?;
+
+    // Daemons from before `ping` answer with an error, but they do answer
+    response
+        .data
+        .get("pid")
+        .and_then(|pid| pid.as_u64())
+        .map(|pid| pid as u32)
+        .or_else(read_pid_file)
+        .or(Some(0))
+}
+
+/// Read the PID from the PID file, if there is a valid one
+fn read_pid_file() -> Option<u32> {
+    std::fs::read_to_string(get_pid_file_path())
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Whether a process holds the lock on the PID file, as a running daemon does
+fn is_pid_file_locked() -> bool {
+    File::open(get_pid_file_path()).is_ok_and(|file| file.try_lock_exclusive().is_err())
+}
+
+/// The history database, opened when the daemon starts and kept open for as
+/// long as it runs
+static HISTORY_STORE: Mutex<Option<crate::history::HistoryStore>> = Mutex::new(None);
+
+/// Open the history database for the daemon
+fn open_history() {
+    match crate::history::HistoryStore::open_default() {
+        Ok(store) => *HISTORY_STORE.lock().unwrap_or_else(|e| e.into_inner()) = Some(store),
+        Err(e) => eprintln!("Failed to open history database: {}", e),
+    }
+}
+
+/// Run `f` with the history database on a blocking thread, so a slow disk
+/// does not hold up the daemon. A database that could not be opened when
+/// the daemon started is opened again.
+async fn with_history<T: Send + 'static>(
+    f: impl FnOnce(&crate::history::HistoryStore) -> Result<T, TomatError> + Send + 'static,
+) -> Result<T, TomatError> {
+    tokio::task::spawn_blocking(move || {
+        let mut store = HISTORY_STORE.lock().unwrap_or_else(|e| e.into_inner());
+        let store = match store.take() {
+            Some(opened) => store.insert(opened),
+            None => store.insert(crate::history::HistoryStore::open_default()?),
+        };
+        f(store)
+    })
+    .await
+    .map_err(|e| TomatError::from(format!("History task failed: {}", e)))?
+}
+
+/// Record a finished phase in the history database, returning its id
+async fn record_history(session: &crate::history::NewSession<'_>) -> Option<i64> {
+    let phase = session.phase.to_string();
+    let outcome = session.outcome.to_string();
+    let task = session.task.map(str::to_string);
+    let tags = session.tags.to_vec();
+    let notes = session.notes.map(str::to_string);
+    let (started_at, ended_at) = (session.started_at, session.ended_at);
+    let (planned_seconds, interruptions) = (session.planned_seconds, session.interruptions);
+
+    let recorded = with_history(move |store| {
+        Ok(store.record(&crate::history::NewSession {
+            task: task.as_deref(),
+            tags: &tags,
+            planned_seconds,
+            interruptions,
+            notes: notes.as_deref(),
+            ..crate::history::NewSession::new(&phase, started_at, ended_at, &outcome)
+        })?)
+    })
+    .await;
+    match recorded {
+        Ok(id) => Some(id),
+        Err(e) => {
+            eprintln!("Failed to record session history: {}", e);
+            None
+        }
+    }
+}
+
+/// The cycle in progress today according to the history, for a daemon that
+/// starts without saved state
+fn backfill_cycle(sessions_until_long_break: u32) -> Option<crate::timer::CycleProgress> {
+    let store = HISTORY_STORE.lock().unwrap_or_else(|e| e.into_inner());
+    let store = store.as_ref()?;
+    let today = chrono::Local::now().date_naive();
+    match store.cycle_progress(today, sessions_until_long_break) {
+        Ok(Some(progress)) => {
+            println!(
+                "  Found {} pomodoro(s) completed today since the last long break, the next cycle continues from there",
+                progress.sessions
+            );
+            Some(progress)
+        }
+        Ok(None) => None,
+        Err(e) => {
+            eprintln!("Failed to read session history: {}", e);
+            None
+        }
+    }
+}
+
+/// Record the work session or break in progress as ended early with
+/// `outcome`, with the time it ran so far, and return its id. Nothing is
+/// recorded for other phases, or for a work session that has not been
+/// started yet.
+async fn record_unfinished_phase(state: &TimerState, outcome: &str) -> Option<i64> {
+    record_phase_until(state, outcome, crate::timer::current_timestamp()).await
+}
+
+/// Record the phase in progress like [`record_unfinished_phase`], as ended
+/// at `ended_at` in the past. A running phase does not count the time since.
+async fn record_phase_until(state: &TimerState, outcome: &str, ended_at: u64) -> Option<i64> {
+    use crate::timer::Phase;
+
+    let is_work = matches!(state.phase, Phase::Work);
+    if !matches!(state.phase, Phase::Work | Phase::Break | Phase::LongBreak)
+        || state.is_awaiting_work()
+    {
+        return None;
+    }
+    let mut ran = state.get_elapsed_seconds();
+    if !state.is_paused {
+        let since = crate::timer::current_timestamp().saturating_sub(ended_at);
+        ran = ran.saturating_sub(since);
+    }
+    if ran == 0 {
+        return None;
+    }
+    let phase = state.phase.to_string();
+    record_history(&crate::history::NewSession {
+        task: state.task.as_deref().filter(|_| is_work),
+        tags: if is_work { &state.tags } else { &[] },
+        planned_seconds: (state.duration_minutes * 60.0) as u64,
+        interruptions: state.pauses,
+        notes: state.notes.as_deref().filter(|_| is_work),
+        ..crate::history::NewSession::new(&phase, ended_at.saturating_sub(ran), ended_at, outcome)
+    })
+    .await
+}
+
+/// Remind the user that the break is over and work has not been started
+fn send_break_over_nag(config: &crate::config::NotificationConfig) {
+    if crate::timer::is_testing() {
+        return;
+    }
+
+    let mut notification = crate::timer::new_notification(config, crate::timer::PHASE_CATEGORY);
+    notification
+        .summary("Tomat")
+        .body(&config.break_message)
+        .urgency(config.effective_break_end_urgency().into());
+    if let Err(e) = notification.show() {
+        eprintln!("Failed to send notification: {}", e);
+    }
+}
+
+/// Send the end-of-day report for today and remember that it was sent
+async fn send_daily_report(config: &crate::config::ReportConfig) {
+    let today = chrono::Local::now().date_naive();
+
+    let summary = with_history(move |store| {
+        if let Err(e) = store.set_meta(crate::report::LAST_REPORT_KEY, &today.to_string()) {
+            eprintln!("Failed to store report date: {}", e);
+        }
+        Ok(store.day_summary(today)?)
+    })
+    .await;
+    let summary = match summary {
+        Ok(summary) => summary,
+        Err(e) => {
+            eprintln!("Failed to summarize history: {}", e);
+            return;
+        }
+    };
+
+    let config = config.clone();
+    tokio::spawn(async move {
+        match crate::report::send_report(&config, &summary).await {
+            Ok(()) => println!("Sent daily report to {}", config.to),
+            Err(e) => eprintln!("Failed to send daily report: {}", e),
+        }
+    });
 }
 
 /// Execute a hook asynchronously (fire-and-forget)
 fn execute_hook(hooks: &crate::config::HooksConfig, event: &str, state: &TimerState) {
     let hooks = hooks.clone();
-    let phase_str = state.phase.to_string();
-    let remaining = state.get_remaining_seconds();
-    let session_count = state.current_session_count;
-    let auto_advance = format!("{:?}", state.auto_advance).to_lowercase();
+    let env = state.hook_env();
     let event = event.to_string();
 
     tokio::spawn(async move {
-        hooks
-            .execute_hook(&event, &phase_str, remaining, session_count, &auto_advance)
-            .await;
+        hooks.execute_hook(&event, &env).await;
     });
 }
 
-async fn handle_client(
-    stream: UnixStream,
-    state: &mut TimerState,
-    config: &crate::config::Config,
-) -> Result<bool, Box<dyn std::error::Error>> {
+/// Run the state change hook (fire-and-forget) with the `old` and `new`
+/// status as JSON on its stdin
+fn execute_state_change_hook(
+    hooks: &crate::config::HooksConfig,
+    event: &str,
+    old: &TimerStatus,
+    new: &TimerStatus,
+    state: &TimerState,
+) {
+    let env = state.hook_env();
+    let Some(hook) = hooks
+        .on_state_change
+        .clone()
+        .filter(|hook| hook.runs_in(&env.phase))
+    else {
+        return;
+    };
+    let input = serde_json::json!({ "event": event, "old": old, "new": new }).to_string();
+    let event = event.to_string();
+
+    tokio::spawn(async move {
+        hook.execute(&event, &env, Some(&input)).await;
+    });
+}
+
+/// Write a serialized response line back to the client
+async fn write_response<S: AsyncWrite + Unpin>(
+    writer: &mut S,
+    response_json: &str,
+) -> Result<(), TomatError> {
+    writer.write_all(response_json.as_bytes()).await?;
+    writer.write_all(b"\n").await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+/// How many events a slow subscriber may fall behind before it misses some
+const EVENT_CAPACITY: usize = 64;
+
+/// The event that turns the `previous` status into the `current` one, if any:
+/// a phase change, pause, resume, stop, or a tick of the running countdown
+fn timer_event(previous: &TimerStatus, current: &TimerStatus) -> Option<&'static str> {
+    if previous.phase != current.phase {
+        Some(if current.phase == Phase::Idle {
+            "stop"
+        } else {
+            "phase_change"
+        })
+    } else if previous.is_paused != current.is_paused {
+        Some(if current.is_paused { "pause" } else { "resume" })
+    } else if previous.remaining_seconds != current.remaining_seconds
+        && !current.is_paused
+        && current.phase != Phase::Idle
+    {
+        Some("tick")
+    } else {
+        None
+    }
+}
+
+/// Write each event to a subscriber until it disconnects or the daemon stops
+async fn forward_events<S: AsyncWrite + Unpin>(
+    mut writer: S,
+    mut events: broadcast::Receiver<String>,
+) {
+    loop {
+        let event = match events.recv().await {
+            Ok(event) => event,
+            // A subscriber that fell behind skips the events it missed
+            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+        let written = async {
+            writer.write_all(event.as_bytes()).await?;
+            writer.write_all(b"\n").await?;
+            writer.flush().await
+        };
+        if written.await.is_err() {
+            break;
+        }
+    }
+}
+
+/// Compare tokens in constant time, so that response times tell nothing
+/// about how much of a guessed token was right
+fn token_matches(expected: &str, given: &str) -> bool {
+    !expected.is_empty()
+        && expected.len() == given.len()
+        && expected
+            .bytes()
+            .zip(given.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// A client's request, passed to the daemon loop, which owns the timer state
+struct Request {
+    message: ClientMessage,
+    received: std::time::Instant,
+    reply: oneshot::Sender<Reply>,
+}
+
+/// The daemon loop's answer to a request
+struct Reply {
+    response: String,
+    /// Told once the response is written, so that a shutdown can wait for it
+    written: Option<oneshot::Sender<()>>,
+}
+
+/// Serve one client in a task of its own, so that a slow client holds up
+/// neither the others nor the timer. Clients connecting over TCP have to
+/// send `token` within `timeout`; the token is None for the local socket.
+/// The `permit` is held until the request is answered.
+fn spawn_client<S: AsyncRead + AsyncWrite + Unpin + Send + 'static>(
+    stream: S,
+    token: Option<String>,
+    timeout: Option<Duration>,
+    permit: Option<OwnedSemaphorePermit>,
+    requests: &mpsc::Sender<Request>,
+    events: &broadcast::Sender<String>,
+) {
+    let requests = requests.clone();
+    let events = events.clone();
+    tokio::spawn(async move {
+        let _permit = permit;
+        let client = handle_client(stream, token.as_deref(), &requests, &events);
+        let result = match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, client)
+                .await
+                .unwrap_or_else(|_| Err("Client did not send a request in time".into())),
+            None => client.await,
+        };
+        if let Err(e) = result {
+            eprintln!("Error handling client: {}", e);
+        }
+    });
+}
+
+/// Longest request line the daemon reads, so that a client cannot make it
+/// buffer without bounds before it is authenticated
+const MAX_REQUEST_BYTES: u64 = 64 * 1024;
+
+/// The read-only commands that clients connecting over TCP may send
+const TCP_COMMANDS: [&str; 3] = ["ping", "status", "subscribe"];
+
+/// Read one request, have the daemon loop answer it, and write the response
+/// back. Subscribers keep their connection, and get the `events` on it.
+async fn handle_client<S: AsyncRead + AsyncWrite + Unpin + Send + 'static>(
+    stream: S,
+    token: Option<&str>,
+    requests: &mpsc::Sender<Request>,
+    events: &broadcast::Sender<String>,
+) -> Result<(), TomatError> {
     let mut reader = BufReader::new(stream);
     let mut line = String::new();
 
-    if reader.read_line(&mut line).await? == 0 {
-        return Ok(false);
+    let read = (&mut reader)
+        .take(MAX_REQUEST_BYTES)
+        .read_line(&mut line)
+        .await?;
+    if read == 0 {
+        return Ok(());
+    }
+
+    let received = std::time::Instant::now();
+    let mut writer = reader.into_inner();
+    if read as u64 == MAX_REQUEST_BYTES && !line.ends_with('\n') {
+        let response = ServerResponse::error(&TomatError::InvalidArgument(format!(
+            "Request is longer than {} bytes",
+            MAX_REQUEST_BYTES
+        )));
+        return write_response(&mut writer, &serde_json::to_string(&response)?).await;
+    }
+    let message: ClientMessage = match serde_json::from_str(&line) {
+        Ok(message) => message,
+        Err(e) => {
+            let response = ServerResponse::error(&TomatError::Protocol(e));
+            return write_response(&mut writer, &serde_json::to_string(&response)?).await;
+        }
+    };
+    if let Some(token) = token
+        && !token_matches(token, message.token.as_deref().unwrap_or_default())
+    {
+        let response = ServerResponse::error(&TomatError::Unauthorized);
+        return write_response(&mut writer, &serde_json::to_string(&response)?).await;
+    }
+    // Remote clients may only look at the timer, not control the daemon
+    if token.is_some() && !TCP_COMMANDS.contains(&message.command.as_str()) {
+        let response = ServerResponse::error(&TomatError::InvalidArgument(format!(
+            "'{}' is not allowed over TCP; only ping, status and subscribe are",
+            message.command
+        )));
+        return write_response(&mut writer, &serde_json::to_string(&response)?).await;
+    }
+
+    // Subscribe before the request is answered, so that no event is missed
+    let subscription = (message.command == "subscribe").then(|| events.subscribe());
+
+    let (reply, answer) = oneshot::channel();
+    let request = Request {
+        message,
+        received,
+        reply,
+    };
+    let Reply { response, written } = match requests.send(request).await {
+        Ok(()) => answer.await.ok(),
+        Err(_) => None,
+    }
+    .ok_or("The daemon is shutting down")?;
+
+    write_response(&mut writer, &response).await?;
+    if let Some(written) = written {
+        let _ = written.send(());
+    }
+    if let Some(subscription) = subscription {
+        tokio::spawn(forward_events(writer, subscription));
     }
+    Ok(())
+}
 
-    let message: ClientMessage = serde_json::from_str(&line)?;
+/// Answer a request with a serialized response, and whether the daemon
+/// should shut down
+async fn answer_request(
+    message: &ClientMessage,
+    received: std::time::Instant,
+    state: &mut TimerState,
+    config: &crate::config::Config,
+    cache: &mut StatusCache,
+    metrics: &mut DaemonMetrics,
+) -> Result<(String, bool), TomatError> {
+    let now = crate::timer::current_timestamp();
+    let is_status = message.command == "status";
+
+    // Fast path: status polled again within the same second with no state change
+    if is_status
+        && let Some(response_json) = cache.get(now)
+        && message
+            .args
+            .get("output")
+            .and_then(|v| v.as_str())
+            .unwrap_or("waybar")
+            .parse::<crate::timer::Format>()
+            .is_ok()
+    {
+        let response_json = response_json.to_string();
+        metrics.status_cache_hits += 1;
+        metrics.record(&message.command, received.elapsed());
+        return Ok((response_json, false));
+    }
 
     let response = match message.command.as_str() {
         "start" => {
-            // Load config fresh for each start command
-            let fresh_config = crate::config::Config::load();
+            let fresh_config = start_config(config);
 
             let work = message
                 .args
@@ -211,24 +869,16 @@ async fn handle_client(
                 .get("sessions")
                 .and_then(|v| v.as_u64())
                 .unwrap_or(fresh_config.timer.sessions as u64) as u32;
-            let auto_advance = message
-                .args
-                .get("auto_advance")
-                .and_then(|v| {
-                    // Try as string first (new format)
-                    if let Some(s) = v.as_str() {
-                        s.parse::<crate::config::AutoAdvanceMode>().ok()
-                    } else {
-                        v.as_bool().map(|b| {
-                            if b {
-                                crate::config::AutoAdvanceMode::All
-                            } else {
-                                crate::config::AutoAdvanceMode::None
-                            }
-                        })
-                    }
-                })
-                .unwrap_or_else(|| fresh_config.timer.auto_advance.clone());
+            let auto_advance = match message.args.get("auto_advance") {
+                None | Some(serde_json::Value::Null) => Ok(fresh_config.timer.auto_advance.clone()),
+                Some(serde_json::Value::String(mode)) => {
+                    mode.parse::<crate::config::AutoAdvanceMode>()
+                }
+                // Older clients send a boolean
+                Some(serde_json::Value::Bool(true)) => Ok(crate::config::AutoAdvanceMode::All),
+                Some(serde_json::Value::Bool(false)) => Ok(crate::config::AutoAdvanceMode::None),
+                Some(_) => Err("'auto_advance' must be a mode name or a boolean".to_string()),
+            };
 
             // Parse sound_mode (ignore for now, not stored in state)
             let _sound_mode = message
@@ -244,42 +894,233 @@ async fn handle_client(
                 .and_then(|v| v.as_f64())
                 .unwrap_or(0.5) as f32;
 
+            let force = message
+                .args
+                .get("force")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
+
             // Validate parameters
             if let Err(err_msg) = validate_timer_params(work, break_time, long_break, sessions) {
-                ServerResponse {
-                    success: false,
-                    data: serde_json::Value::Null,
-                    message: err_msg,
-                }
+                ServerResponse::error(&TomatError::InvalidArgument(err_msg))
+            } else if let Err(err_msg) = &auto_advance {
+                ServerResponse::error(&TomatError::InvalidArgument(err_msg.clone()))
+            } else if let Some(conflict) = start_conflict(state).filter(|_| !force) {
+                ServerResponse::error(&TomatError::InvalidState(conflict))
+            } else if let Some(end) = workday_over(&fresh_config).filter(|_| !force) {
+                ServerResponse::error(&TomatError::InvalidState(format!(
+                    "The workday ended at {}. Work anyway with 'tomat start --force'",
+                    end.format("%H:%M")
+                )))
             } else {
-                state.work_duration = work;
-                state.break_duration = break_time;
-                state.long_break_duration = long_break;
-                state.sessions_until_long_break = sessions;
-                state.auto_advance = auto_advance;
-                state.current_session_count = 0;
-
-                // Always start a fresh work session
-                state.start_work();
+                let Ok(auto_advance) = auto_advance else {
+                    unreachable!("invalid auto-advance modes are rejected above")
+                };
+                let mut fresh_config = fresh_config;
+                fresh_config.timer = crate::config::TimerConfig {
+                    work,
+                    break_time,
+                    long_break,
+                    sessions,
+                    auto_advance,
+                    ..fresh_config.timer
+                };
+                let task = message
+                    .args
+                    .get("task")
+                    .and_then(|v| v.as_str())
+                    .filter(|task| !task.is_empty())
+                    .map(str::to_string);
+                let tags: Vec<&str> = message
+                    .args
+                    .get("tags")
+                    .and_then(|v| v.as_array())
+                    .map(|tags| tags.iter().filter_map(|tag| tag.as_str()).collect())
+                    .unwrap_or_default();
+                match crate::history::parse_tags(&tags) {
+                    Ok(tags) => {
+                        start_cycle(state, &fresh_config, task, tags, &config.hooks);
+                        ServerResponse::ok(format!(
+                            "Pomodoro started: {:.1}min work, {:.1}min break, {:.1}min long break every {} sessions",
+                            work, break_time, long_break, sessions
+                        ))
+                    }
+                    Err(e) => ServerResponse::error(&e),
+                }
+            }
+        }
+        "privacy" => match message.args.get("enabled").and_then(|v| v.as_bool()) {
+            Some(enabled) => {
+                state.privacy = enabled;
+                save_state(state);
 
-                // Execute work_start hook
-                execute_hook(&config.hooks, "work_start", state);
+                ServerResponse::ok(if enabled {
+                    "Privacy mode on"
+                } else {
+                    "Privacy mode off"
+                })
+            }
+            None => ServerResponse::error(&TomatError::InvalidArgument(
+                "Missing boolean 'enabled' argument".to_string(),
+            )),
+        },
+        "annotate" => {
+            let task = message.args.get("task").and_then(|v| v.as_str());
+            let tags: Option<Vec<&str>> = message
+                .args
+                .get("tags")
+                .and_then(|v| v.as_array())
+                .map(|tags| tags.iter().filter_map(|tag| tag.as_str()).collect());
+            match (task, tags.as_deref().map(crate::history::parse_tags)) {
+                (None, _) => ServerResponse::error(&TomatError::InvalidArgument(
+                    "Missing string 'task' argument".to_string(),
+                )),
+                (Some(_), Some(Err(e))) => ServerResponse::error(&e),
+                _ if matches!(state.phase, crate::timer::Phase::Idle) => {
+                    ServerResponse::error(&TomatError::InvalidState(
+                        "No session in progress. Label the next one with 'tomat start --label'."
+                            .to_string(),
+                    ))
+                }
+                (Some(task), tags) => {
+                    state.task = (!task.is_empty()).then(|| task.to_string());
+                    if let Some(Ok(tags)) = tags {
+                        state.tags = tags;
+                    }
+                    save_state(state);
 
-                // Save state after starting
+                    let labels = crate::history::format_labels(state.task.as_deref(), &state.tags);
+                    ServerResponse::ok(if labels.is_empty() {
+                        "Session label cleared".to_string()
+                    } else {
+                        format!("Session labeled {}", labels)
+                    })
+                }
+            }
+        }
+        "note" => match message.args.get("text").and_then(|v| v.as_str()) {
+            None => ServerResponse::error(&TomatError::InvalidArgument(
+                "Missing string 'text' argument".to_string(),
+            )),
+            // A work session in progress keeps its notes until it is recorded
+            Some(text)
+                if matches!(state.phase, crate::timer::Phase::Work)
+                    && !state.is_awaiting_work() =>
+            {
+                state.notes = (!text.is_empty()).then(|| text.to_string());
                 save_state(state);
-
-                ServerResponse {
-                    success: true,
-                    data: serde_json::Value::Null,
-                    message: format!(
-                        "Pomodoro started: {:.1}min work, {:.1}min break, {:.1}min long break every {} sessions",
-                        work, break_time, long_break, sessions
-                    ),
+                ServerResponse::ok(if text.is_empty() {
+                    "Notes of the current session cleared"
+                } else {
+                    "Notes saved to the current session"
+                })
+            }
+            Some(text) => match state.last_work_session {
+                None => ServerResponse::error(&TomatError::InvalidState(
+                    "No work session to take notes on. Write notes during a work session or \
+                     in the review or break after it."
+                        .to_string(),
+                )),
+                Some(id) => match with_history({
+                    let text = text.to_string();
+                    move |store| crate::history::note_session(store, id, &text)
+                })
+                .await
+                {
+                    Ok(message) => ServerResponse::ok(message),
+                    Err(e) => ServerResponse::error(&e),
+                },
+            },
+        },
+        "goal" => {
+            let reset = message
+                .args
+                .get("reset")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
+            let goal = message.args.get("pomodoros").filter(|v| !v.is_null());
+            let update = match goal {
+                _ if reset => Ok(Some(None)),
+                Some(goal) => match goal.as_u64().and_then(|goal| u32::try_from(goal).ok()) {
+                    Some(goal) => Ok(Some(Some(goal))),
+                    None => Err(TomatError::InvalidArgument(
+                        "'pomodoros' must be a non-negative integer".to_string(),
+                    )),
+                },
+                None => Ok(None),
+            };
+            match update {
+                Ok(update) => {
+                    if let Some(goal_override) = update {
+                        state.goal_override = goal_override;
+                        save_state(state);
+                    }
+                    let completed = state.completed_today();
+                    let goal = state.daily_goal();
+                    let progress = match goal {
+                        Some(goal) => format!("{}/{} pomodoros today", completed, goal),
+                        None => format!("{} pomodoros today, no daily goal set", completed),
+                    };
+                    ServerResponse::with_data(
+                        serde_json::json!({ "completed": completed, "goal": goal }),
+                        progress,
+                    )
                 }
+                Err(e) => ServerResponse::error(&e),
             }
         }
+        "plan" => match message.args.get("action").and_then(|v| v.as_str()) {
+            Some("add") => {
+                let task = message
+                    .args
+                    .get("task")
+                    .and_then(|v| v.as_str())
+                    .map(str::trim)
+                    .unwrap_or_default();
+                let pomodoros = message
+                    .args
+                    .get("pomodoros")
+                    .map_or(Some(1), |v| v.as_u64().and_then(|n| u32::try_from(n).ok()));
+
+                match pomodoros {
+                    _ if task.is_empty() => ServerResponse::error(&TomatError::InvalidArgument(
+                        "Missing task name".to_string(),
+                    )),
+                    Some(pomodoros) if pomodoros > 0 => {
+                        state.plan.push(crate::timer::PlannedTask {
+                            task: task.to_string(),
+                            pomodoros,
+                            completed: 0,
+                        });
+                        save_state(state);
+
+                        ServerResponse::ok(format!(
+                            "Planned {} pomodoro{} for '{}'",
+                            pomodoros,
+                            if pomodoros == 1 { "" } else { "s" },
+                            task
+                        ))
+                    }
+                    _ => ServerResponse::error(&TomatError::InvalidArgument(
+                        "The number of pomodoros must be a positive integer".to_string(),
+                    )),
+                }
+            }
+            Some("show") => ServerResponse::with_data(serde_json::to_value(&state.plan)?, "Plan"),
+            Some("clear") => {
+                state.plan.clear();
+                save_state(state);
+
+                ServerResponse::ok("Plan cleared")
+            }
+            _ => ServerResponse::error(&TomatError::InvalidArgument(
+                "Expected plan action 'add', 'show' or 'clear'".to_string(),
+            )),
+        },
         "stop" => {
+            record_unfinished_phase(state, "stopped").await;
             state.stop();
+            crate::nagbar::dismiss();
 
             // Execute hook
             execute_hook(&config.hooks, "stop", state);
@@ -287,10 +1128,38 @@ async fn handle_client(
             // Save state after stopping
             save_state(state);
 
-            ServerResponse {
-                success: true,
-                data: serde_json::Value::Null,
-                message: "Timer stopped".to_string(),
+            ServerResponse::ok("Timer stopped")
+        }
+        "away" => {
+            let idle_seconds = message
+                .args
+                .get("idle_seconds")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(0);
+            if matches!(state.phase, crate::timer::Phase::Idle) {
+                ServerResponse::error(&TomatError::InvalidState(
+                    "Timer is not running".to_string(),
+                ))
+            } else {
+                // Nothing that ran while the user was away counts as work
+                let away_since = crate::timer::current_timestamp().saturating_sub(idle_seconds);
+                match with_history(move |store| Ok(store.abandon_since(away_since)?)).await {
+                    Ok(completed) => {
+                        state.completed_today = state.completed_today().saturating_sub(completed);
+                    }
+                    Err(e) => eprintln!("Failed to update session history: {}", e),
+                }
+                record_phase_until(state, "abandoned", away_since).await;
+                state.stop();
+                crate::nagbar::dismiss();
+
+                execute_hook(&config.hooks, "stop", state);
+                save_state(state);
+
+                ServerResponse::ok(format!(
+                    "Timer stopped after {} away",
+                    crate::history::format_duration(idle_seconds)
+                ))
             }
         }
         "status" => {
@@ -306,95 +1175,98 @@ async fn handle_client(
                     let timer_status = state.get_timer_status();
                     let data = serde_json::to_value(timer_status)?;
 
-                    ServerResponse {
-                        success: true,
-                        data,
-                        message: "Status retrieved".to_string(),
-                    }
+                    ServerResponse::with_data(data, "Status retrieved")
                 }
-                Err(e) => ServerResponse {
-                    success: false,
-                    data: serde_json::Value::Null,
-                    message: e,
-                },
+                Err(e) => ServerResponse::error(&TomatError::InvalidArgument(e)),
             }
         }
         "skip" => {
             // Cannot skip when in Idle phase
             if matches!(state.phase, crate::timer::Phase::Idle) {
-                ServerResponse {
-                    success: false,
-                    data: serde_json::Value::Null,
-                    message: "Cannot skip when timer is idle. Use 'tomat start' first.".to_string(),
+                ServerResponse::error(&TomatError::InvalidState(
+                    "Cannot skip when timer is idle. Use 'tomat start' first.".to_string(),
+                ))
+            } else if message
+                .args
+                .get("merge")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false)
+            {
+                if matches!(
+                    state.phase,
+                    crate::timer::Phase::Prepare
+                        | crate::timer::Phase::Work
+                        | crate::timer::Phase::Review
+                ) {
+                    ServerResponse::error(&TomatError::InvalidState(
+                        "Can only merge a break. Use 'tomat skip' to end a work session."
+                            .to_string(),
+                    ))
+                } else {
+                    execute_hook(&config.hooks, "skip", state);
+                    record_unfinished_phase(state, "skipped").await;
+
+                    let banked = state.merge_break();
+                    crate::nagbar::dismiss();
+
+                    execute_hook(&config.hooks, "work_start", state);
+                    save_state(state);
+
+                    ServerResponse::ok(format!(
+                        "Break skipped, {} added to the next long break",
+                        crate::history::format_duration(banked)
+                    ))
                 }
             } else {
                 // Execute skip hook BEFORE phase transition
                 execute_hook(&config.hooks, "skip", state);
+                let in_work = matches!(state.phase, crate::timer::Phase::Work);
+                let skipped = record_unfinished_phase(state, "skipped").await;
 
                 if let Err(e) = state.next_phase(&config.sound, &config.notification, &config.hooks)
                 {
                     eprintln!("Error during phase transition: {}", e);
                 }
+                if in_work && skipped.is_some() {
+                    state.last_work_session = skipped;
+                }
 
                 // Save state after phase transition
                 save_state(state);
 
-                ServerResponse {
-                    success: true,
-                    data: serde_json::Value::Null,
-                    message: "Skipped to next phase".to_string(),
-                }
+                ServerResponse::ok("Skipped to next phase")
             }
         }
         "toggle" => {
-            // Handle Idle phase - start timer with config defaults
+            // Toggle is a smart start/pause/resume: from idle it starts a
+            // cycle exactly like `start` without arguments
             if matches!(state.phase, crate::timer::Phase::Idle) {
-                // Load fresh config to get user's configured defaults
-                let fresh_config = crate::config::Config::load();
-
-                // Initialize timer state with config defaults
-                state.work_duration = fresh_config.timer.work;
-                state.break_duration = fresh_config.timer.break_time;
-                state.long_break_duration = fresh_config.timer.long_break;
-                state.sessions_until_long_break = fresh_config.timer.sessions;
-                state.auto_advance = fresh_config.timer.auto_advance;
-                state.current_session_count = 0;
-
-                // Start work phase
-                state.start_work();
-
-                // Execute work_start hook
-                execute_hook(&config.hooks, "work_start", state);
-
-                // Save state after starting
-                save_state(state);
+                let fresh_config = start_config(config);
+                if let Some(end) = workday_over(&fresh_config) {
+                    ServerResponse::error(&TomatError::InvalidState(format!(
+                        "The workday ended at {}. Work anyway with 'tomat start --force'",
+                        end.format("%H:%M")
+                    )))
+                } else {
+                    start_cycle(state, &fresh_config, None, Vec::new(), &config.hooks);
 
-                ServerResponse {
-                    success: true,
-                    data: serde_json::Value::Null,
-                    message: format!(
+                    ServerResponse::ok(format!(
                         "Timer started: {:.1}min work, {:.1}min break",
                         state.work_duration, state.break_duration
-                    ),
+                    ))
                 }
             } else if state.is_paused {
                 // Check if this is the first toggle on an uninitialized timer
                 // (start_time == 0 means timer has never been started)
                 if state.start_time == 0 {
-                    // Load fresh config to get user's configured defaults
-                    let fresh_config = crate::config::Config::load();
-
-                    // Initialize timer state with config defaults if not already set via CLI
-                    state.work_duration = fresh_config.timer.work;
-                    state.break_duration = fresh_config.timer.break_time;
-                    state.long_break_duration = fresh_config.timer.long_break;
-                    state.sessions_until_long_break = fresh_config.timer.sessions;
-                    state.auto_advance = fresh_config.timer.auto_advance;
+                    // Initialize timer state with config defaults, as `start` would
+                    apply_timer_config(state, &start_config(config));
                     state.duration_minutes = state.work_duration;
                 }
 
                 // Resume if paused
                 let pending_hook = state.resume();
+                crate::nagbar::dismiss();
 
                 // Execute resume hook
                 execute_hook(&config.hooks, "resume", state);
@@ -407,76 +1279,88 @@ async fn handle_client(
                 // Save state after resuming
                 save_state(state);
 
-                ServerResponse {
-                    success: true,
-                    data: serde_json::Value::Null,
-                    message: "Timer resumed".to_string(),
-                }
+                ServerResponse::ok("Timer resumed")
             } else {
                 // Pause timer if running (preserves progress)
-                state.pause();
-
-                // Execute hook
-                execute_hook(&config.hooks, "pause", state);
-
-                // Save state after pausing
-                save_state(state);
+                pause_timer(state, config).await
+            }
+        }
+        "postpone_long_break" => {
+            let in_long_break = matches!(state.phase, crate::timer::Phase::LongBreak);
+            match state.postpone_long_break() {
+                Ok(()) => {
+                    save_state(state);
+                    ServerResponse::ok(if in_long_break {
+                        format!(
+                            "Long break postponed: taking a {:.1}min break, \
+                             long break after the next work session",
+                            state.break_duration
+                        )
+                    } else {
+                        "Long break postponed until after the next work session".to_string()
+                    })
+                }
+                Err(e) => ServerResponse::error(&e),
+            }
+        }
+        "break" => {
+            if !message
+                .args
+                .get("banked")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false)
+            {
+                ServerResponse::error(&TomatError::InvalidArgument(
+                    "Only banked breaks can be taken on demand: pass 'banked'".to_string(),
+                ))
+            } else {
+                let in_work = matches!(state.phase, crate::timer::Phase::Work);
+                // The work session ends early if there is a break to take
+                if in_work && state.banked_break_seconds > 0 {
+                    record_unfinished_phase(state, "skipped").await;
+                }
+                match state.take_banked_break() {
+                    Ok(banked) => {
+                        crate::nagbar::dismiss();
+                        if in_work {
+                            execute_hook(&config.hooks, "break_start", state);
+                        }
+                        save_state(state);
 
-                ServerResponse {
-                    success: true,
-                    data: serde_json::Value::Null,
-                    message: "Timer paused".to_string(),
+                        let banked = crate::history::format_duration(banked);
+                        ServerResponse::ok(if in_work {
+                            format!("Taking a {} banked break", banked)
+                        } else {
+                            format!("Break extended by {} of banked time", banked)
+                        })
+                    }
+                    Err(e) => ServerResponse::error(&e),
                 }
             }
         }
         "pause" => {
             // Cannot pause when in Idle phase
             if matches!(state.phase, crate::timer::Phase::Idle) {
-                ServerResponse {
-                    success: false,
-                    data: serde_json::Value::Null,
-                    message: "Cannot pause when timer is idle. Use 'tomat start' first."
-                        .to_string(),
-                }
+                ServerResponse::error(&TomatError::InvalidState(
+                    "Cannot pause when timer is idle. Use 'tomat start' first.".to_string(),
+                ))
             } else if state.is_paused {
-                ServerResponse {
-                    success: true,
-                    data: serde_json::Value::Null,
-                    message: "Timer is already paused".to_string(),
-                }
+                ServerResponse::ok("Timer is already paused")
             } else {
-                state.pause();
-
-                // Execute hook
-                execute_hook(&config.hooks, "pause", state);
-
-                // Save state after pausing
-                save_state(state);
-
-                ServerResponse {
-                    success: true,
-                    data: serde_json::Value::Null,
-                    message: "Timer paused".to_string(),
-                }
+                pause_timer(state, config).await
             }
         }
         "resume" => {
             // Cannot resume when in Idle phase
             if matches!(state.phase, crate::timer::Phase::Idle) {
-                ServerResponse {
-                    success: false,
-                    data: serde_json::Value::Null,
-                    message: "Cannot resume when timer is idle. Use 'tomat start' first."
-                        .to_string(),
-                }
+                ServerResponse::error(&TomatError::InvalidState(
+                    "Cannot resume when timer is idle. Use 'tomat start' first.".to_string(),
+                ))
             } else if !state.is_paused {
-                ServerResponse {
-                    success: true,
-                    data: serde_json::Value::Null,
-                    message: "Timer is already running".to_string(),
-                }
+                ServerResponse::ok("Timer is already running")
             } else {
                 let pending_hook = state.resume();
+                crate::nagbar::dismiss();
 
                 // Execute resume hook
                 execute_hook(&config.hooks, "resume", state);
@@ -489,59 +1373,171 @@ async fn handle_client(
                 // Save state after resuming
                 save_state(state);
 
-                ServerResponse {
-                    success: true,
-                    data: serde_json::Value::Null,
-                    message: "Timer resumed".to_string(),
+                ServerResponse::ok("Timer resumed")
+            }
+        }
+        "simulate" | "set_time_offset" if !crate::timer::is_dev_mode() => {
+            ServerResponse::error(&TomatError::InvalidState(
+                "Clock simulation is disabled; start the daemon with TOMAT_DEV=1 to enable it"
+                    .to_string(),
+            ))
+        }
+        "simulate" => {
+            let seconds = match message.args.get("seconds") {
+                Some(seconds) => seconds.as_u64(),
+                // Without a duration, skip to the end of the running phase
+                None => state
+                    .get_finish_time()
+                    .map(|finish| finish.saturating_sub(crate::timer::current_timestamp())),
+            };
+
+            match seconds {
+                Some(seconds) => {
+                    let offset = crate::timer::advance_clock(seconds);
+                    ServerResponse::with_data(
+                        serde_json::json!({ "offset": offset }),
+                        format!("Clock advanced by {}s, now {}s ahead", seconds, offset),
+                    )
                 }
+                None if message.args.get("seconds").is_some() => {
+                    ServerResponse::error(&TomatError::InvalidArgument(
+                        "'seconds' must be a non-negative integer".to_string(),
+                    ))
+                }
+                None => ServerResponse::error(&TomatError::InvalidState(
+                    "The timer is not running; give a duration to advance the clock by".to_string(),
+                )),
+            }
+        }
+        "set_time_offset" => match message.args.get("seconds").and_then(|s| s.as_u64()) {
+            Some(seconds) => {
+                crate::timer::set_clock_offset(seconds);
+                ServerResponse::with_data(
+                    serde_json::json!({ "offset": seconds }),
+                    format!("Clock set to {}s ahead", seconds),
+                )
             }
+            None => ServerResponse::error(&TomatError::InvalidArgument(
+                "'seconds' must be a non-negative integer".to_string(),
+            )),
+        },
+        "stats" => {
+            let report = DaemonMetrics {
+                last_transition_at: state.last_transition_at,
+                config_file: crate::config::Config::config_path().filter(|path| path.exists()),
+                audio: crate::audio::describe(&config.sound),
+                notifications: crate::timer::describe_notifications(&config.notification),
+                ..metrics.clone()
+            };
+            ServerResponse::with_data(serde_json::to_value(&report)?, "Daemon statistics")
+        }
+        "ping" => {
+            ServerResponse::with_data(serde_json::json!({ "pid": std::process::id() }), "pong")
         }
+        "subscribe" => ServerResponse::ok("Subscribed to events"),
         "shutdown" => {
             save_state(state);
-            ServerResponse {
-                success: true,
-                data: serde_json::Value::Null,
-                message: "Daemon shutting down".to_string(),
-            }
+            ServerResponse::ok("Daemon shutting down")
         }
-        _ => ServerResponse {
-            success: false,
-            data: serde_json::Value::Null,
-            message: "Unknown command".to_string(),
-        },
+        _ => ServerResponse::error(&TomatError::UnknownCommand(message.command.clone())),
     };
 
     let should_shutdown = message.command == "shutdown";
 
     let response_json = serde_json::to_string(&response)?;
-    let mut writer = reader.into_inner();
-    writer.write_all(response_json.as_bytes()).await?;
-    writer.write_all(b"\n").await?;
-    writer.flush().await?;
+    if is_status {
+        metrics.status_cache_misses += 1;
+        if response.success {
+            cache.store(now, response_json.clone());
+        }
+    } else if !matches!(message.command.as_str(), "stats" | "ping" | "subscribe") {
+        cache.invalidate();
+    }
+
+    metrics.record(&message.command, received.elapsed());
 
-    Ok(should_shutdown)
+    Ok((response_json, should_shutdown))
 }
 
-pub async fn run_daemon() -> Result<(), Box<dyn std::error::Error>> {
+/// Exit status of a daemon that adopted one already running, which service
+/// managers should neither restart nor report as failed
+pub const ADOPTED_EXIT_STATUS: i32 = 75;
+
+/// How long a daemon waits for another one that is starting up to answer
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Outcome of the startup negotiation between daemons
+enum Startup {
+    /// We hold the daemon lock, so we are the daemon
+    Lead(File),
+    /// Another daemon, with this PID, is running
+    Adopt(u32),
+}
+
+/// Decide which of several daemons launched at once, e.g. by systemd and by
+/// the compositor, gets to run
+///
+/// The daemon holds the lock file for as long as it runs. A daemon that finds
+/// it locked waits for the holder to answer on the socket and adopts it, or
+/// takes over if the holder dies while starting up.
+async fn negotiate_startup() -> Result<Startup, TomatError> {
+    let lock_file = File::options()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(get_lock_file_path())?;
+    let start = std::time::Instant::now();
+
+    loop {
+        // Daemons from before the lock file only lock the PID file
+        if lock_file.try_lock_exclusive().is_ok() && !is_pid_file_locked() {
+            return Ok(Startup::Lead(lock_file));
+        }
+        if let Some(pid) = ping_daemon().await {
+            return Ok(Startup::Adopt(pid));
+        }
+        if start.elapsed() >= STARTUP_TIMEOUT {
+            return Err(TomatError::DaemonAlreadyRunning);
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+}
+
+/// Run the daemon in the foreground. With `trace`, every change of the timer
+/// state is logged with its cause.
+pub async fn run_daemon(trace: bool) -> Result<(), TomatError> {
     let socket_path = get_socket_path();
     let pid_file_path = get_pid_file_path();
 
-    // Create and lock PID file to prevent multiple daemon instances
-    let mut pid_file = File::create(&pid_file_path)?;
-    pid_file.try_lock_exclusive().map_err(|_| {
-        format!(
-            "Another daemon instance is already running. PID file locked: {:?}",
-            pid_file_path
-        )
-    })?;
+    let lock_file = match negotiate_startup().await? {
+        Startup::Lead(lock_file) => lock_file,
+        Startup::Adopt(pid) => {
+            println!(
+                "Tomat daemon is already running (PID: {}), adopting it instead of starting another",
+                pid
+            );
+            std::process::exit(ADOPTED_EXIT_STATUS);
+        }
+    };
+
+    // Lock the PID file as well, which clients check to tell a hung daemon from
+    // a crashed one. The file is only truncated once locked.
+    let mut pid_file = File::options()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&pid_file_path)?;
+    pid_file
+        .try_lock_exclusive()
+        .map_err(|_| TomatError::DaemonAlreadyRunning)?;
 
     // Write current PID to the locked file
+    pid_file.set_len(0)?;
     let pid = std::process::id();
     write!(pid_file, "{}", pid)?;
     pid_file.flush()?;
 
-    // Now that we have the exclusive lock, safely remove existing socket if present
-    // This is safe because we're the only daemon instance that can run now
+    // Holding the daemon lock, any socket left behind is from a crashed daemon
     if socket_path.exists() {
         std::fs::remove_file(&socket_path)?;
     }
@@ -549,78 +1545,322 @@ pub async fn run_daemon() -> Result<(), Box<dyn std::error::Error>> {
     let listener = UnixListener::bind(&socket_path)?;
 
     // Load configuration first
-    let config = crate::config::Config::load_with_logging(true);
+    let mut config = crate::config::Config::load_with_logging(true);
+    crate::audio::configure(&config.sound);
+    let legacy_state_path = get_legacy_state_file_path();
+    match migrate_state_file(&legacy_state_path, &get_state_file_path()) {
+        Ok(true) => println!(
+            "Moved state file from {:?} to {:?}",
+            legacy_state_path,
+            get_state_file_path()
+        ),
+        Ok(false) => {}
+        Err(e) => eprintln!("Failed to move state file {:?}: {}", legacy_state_path, e),
+    }
+    *STATE_STORE.lock().unwrap_or_else(|e| e.into_inner()) = Some(crate::state::open(
+        &config.daemon.state_backend,
+        get_state_file_path(),
+    ));
+    open_history();
 
     // Try to load existing state, fallback to config defaults if not found
     let mut state = load_state().unwrap_or_else(|| {
         println!("No existing state found, starting with config defaults");
+        let (timer, _) = timer_now(&config);
         println!(
             "  Using: work={}min, break={}min, long_break={}min, sessions={}",
-            config.timer.work,
-            config.timer.break_time,
-            config.timer.long_break,
-            config.timer.sessions
+            timer.work, timer.break_time, timer.long_break, timer.sessions
         );
-        TimerState::new(
-            config.timer.work,
-            config.timer.break_time,
-            config.timer.long_break,
-            config.timer.sessions,
-        )
+        let mut state = TimerState::new(
+            timer.work,
+            timer.break_time,
+            timer.long_break,
+            timer.sessions,
+        );
+        state.backfill = backfill_cycle(timer.sessions);
+        state
     });
+    state.day_start = config.stats.day_start_time();
+    state.config_goal = config.goal.daily_pomodoros;
+
+    println!("Tomat daemon listening on {:?}", socket_path);
+    let tcp = bind_tcp(&config.daemon).await;
+
+    // Clean up socket and PID file on exit
+    let cleanup = || {
+        let _ = std::fs::remove_file(&socket_path);
+        let _ = std::fs::remove_file(&pid_file_path);
+    };
+
+    // Set up signal handler for graceful shutdown
+    let mut sigterm = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?;
+    let alarms = crate::alarm::Alarm::spawn();
+    let result = tokio::select! {
+        result = daemon_loop(listener, tcp, &mut state, &mut config, &alarms, trace) => result,
+        _ = tokio::signal::ctrl_c() => {
+            println!("Received interrupt signal, shutting down...");
+            Ok(())
+        }
+        _ = sigterm.recv() => {
+            println!("Received termination signal, shutting down...");
+            Ok(())
+        }
+    };
+
+    // Hooks run in process groups of their own, so they don't go down with us
+    crate::config::terminate_hooks();
+
+    // A stopped daemon no longer ends the phase, so its timer has to go
+    alarms.shutdown().await;
+
+    // A stopped daemon no longer keeps the break message up to date
+    if let Some(path) = &config.focus.lockscreen.message_file {
+        let _ = crate::lockscreen::write_message(path, "");
+    }
+
+    // Clean up before releasing the locks, so we never remove the socket of a
+    // daemon started right after us
+    cleanup();
+    drop(pid_file);
+    drop(lock_file);
+    result
+}
+
+/// How long a client connected over TCP may take to send its request
+const TCP_CLIENT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How many clients may be connected over TCP at once, before they are
+/// answered; further connections are closed right away
+const MAX_TCP_CLIENTS: usize = 16;
+
+/// Listen on `[daemon] tcp_listen`, which is refused without a token
+async fn bind_tcp(config: &crate::config::DaemonConfig) -> Option<TcpListener> {
+    let address = config.tcp_listen.as_deref()?;
+    if config.tcp_token.as_deref().is_none_or(str::is_empty) {
+        eprintln!(
+            "Not listening on {}: [daemon] tcp_listen requires a tcp_token",
+            address
+        );
+        return None;
+    }
+    match TcpListener::bind(address).await {
+        Ok(listener) => {
+            println!("Tomat daemon listening on {}", address);
+            Some(listener)
+        }
+        Err(e) => {
+            eprintln!("Failed to listen on {}: {}", address, e);
+            None
+        }
+    }
+}
+
+/// How many requests may wait for the daemon loop
+const REQUEST_CAPACITY: usize = 64;
+
+/// How long a shutdown waits for its response to reach the client
+const SHUTDOWN_REPLY_TIMEOUT: Duration = Duration::from_secs(1);
+
+async fn daemon_loop(
+    listener: UnixListener,
+    tcp: Option<TcpListener>,
+    state: &mut TimerState,
+    config: &mut crate::config::Config,
+    alarms: &crate::alarm::Alarm,
+    trace: bool,
+) -> Result<(), TomatError> {
+    let mut last_report_date =
+        with_history(|store| Ok(store.get_meta(crate::report::LAST_REPORT_KEY)?))
+            .await
+            .ok()
+            .flatten()
+            .and_then(|date| date.parse::<chrono::NaiveDate>().ok());
+
+    let mut cache = StatusCache::default();
+    let mut metrics = DaemonMetrics::new(crate::timer::current_timestamp());
+    let mut focusing = false;
+    let mut nag_due: Option<u64> = None;
+    let mut alarm: Option<u64> = None;
+    let mut textfile: Option<(PathBuf, String)> = None;
+    let mut fifo: Option<crate::fifo::FifoSink> = None;
+    let mut lockscreen = crate::lockscreen::Lockscreen::default();
+    let mut light: Option<crate::indicator::Light> = None;
+    let mut watcher = watch_config_file(config);
+    let mut reload_due: Option<tokio::time::Instant> = None;
+    let (events, _) = broadcast::channel(EVENT_CAPACITY);
+    let (requests, mut pending) = mpsc::channel(REQUEST_CAPACITY);
+    let tcp_clients = std::sync::Arc::new(Semaphore::new(MAX_TCP_CLIENTS));
+    let mut last_status = state.get_timer_status();
+    let mut tracer = trace.then(|| crate::trace::TransitionTracer::new(state));
+    // What changed the state since the last iteration, for the tracer
+    let mut cause = String::new();
+
+    loop {
+        if let Some(tracer) = tracer.as_mut() {
+            tracer.record(state, &cause);
+        }
+        cause = "daemon".to_string();
+
+        if let Some(behind) = state.resync_clock() {
+            eprintln!(
+                "Clock moved back {}s before the start of the {} phase, resyncing it",
+                behind, state.phase
+            );
+            cache.invalidate();
+            save_state(state);
+            if let Some(tracer) = tracer.as_mut() {
+                tracer.record(state, "clock resync");
+            }
+        }
+
+        // Set up focus mode once each time a work phase starts running, and
+        // undo it when the work phase ends
+        let in_work = matches!(state.phase, crate::timer::Phase::Work);
+        let working = in_work && !state.is_paused;
+        let room = config
+            .focus
+            .hyprland_workspace
+            .clone()
+            .filter(|_| crate::hyprland::is_running());
+        if working && !focusing {
+            if !config.focus.block.is_empty() {
+                tokio::spawn(crate::focus::enforce(
+                    config.focus.clone(),
+                    config.notification.clone(),
+                ));
+            }
+            if let Some(room) = room {
+                tokio::spawn(async move { crate::hyprland::enter_focus_room(&room).await });
+            }
+        } else if focusing
+            && !in_work
+            && let Some(room) = room
+        {
+            tokio::spawn(async move { crate::hyprland::leave_focus_room(&room).await });
+        }
+        focusing = in_work && (focusing || working);
+        lockscreen.update(&config.focus, state);
+
+        // Show the phase on the LED or OpenRGB devices whenever it changes
+        let current = crate::indicator::light(&config.indicator, state);
+        if current != light {
+            if let Some(current) = current {
+                tokio::spawn(crate::indicator::show(config.indicator.clone(), current));
+            }
+            light = current;
+        }
 
-    println!("Tomat daemon listening on {:?}", socket_path);
+        let next_report =
+            crate::report::next_report_time(&config.report, chrono::Local::now(), last_report_date);
+        // Keep the registered phase end in sync with the timer
+        let finish = state
+            .get_finish_time()
+            .filter(|_| config.daemon.register_alarm && !crate::timer::is_testing());
+        if finish != alarm {
+            alarm = finish;
+            alarms.set(finish.map(|finish| (state.phase.clone(), finish)));
+        }
 
-    // Clean up socket and PID file on exit
-    let cleanup = || {
-        let _ = std::fs::remove_file(&socket_path);
-        let _ = std::fs::remove_file(&pid_file_path);
-    };
+        // Keep the metrics textfile in sync with the timer
+        if let Some(path) = &config.metrics.textfile {
+            let current = (path.clone(), crate::prometheus::render(state));
+            if textfile.as_ref() != Some(&current) {
+                if let Err(e) = crate::prometheus::write_textfile(path, &current.1) {
+                    eprintln!("Failed to write metrics to {}: {}", path.display(), e);
+                }
+                textfile = Some(current);
+            }
+        }
 
-    // Set up signal handler for graceful shutdown
-    let result = tokio::select! {
-        result = daemon_loop(listener, &mut state, &config) => result,
-        _ = tokio::signal::ctrl_c() => {
-            println!("Received interrupt signal, shutting down...");
-            Ok(())
+        // Write the status to the named pipe whenever it changes
+        let fifo_path = config.display.fifo.as_deref();
+        if fifo.as_ref().map(crate::fifo::FifoSink::path) != fifo_path {
+            fifo = fifo_path.map(|path| crate::fifo::FifoSink::new(path.to_path_buf()));
+        }
+        if let Some(fifo) = fifo.as_mut() {
+            fifo.update(&crate::fifo::status_line(state, &config.display));
         }
-    };
 
-    // Keep the PID file lock alive until here (by keeping _pid_file in scope)
-    drop(pid_file);
-    cleanup();
-    result
-}
+        // Push what changed to the subscribers and the state change hook
+        let status = state.get_timer_status();
+        if let Some(event) = timer_event(&last_status, &status) {
+            if event != "tick" {
+                execute_state_change_hook(&config.hooks, event, &last_status, &status, state);
+            }
+            let event = serde_json::json!({ "event": event, "status": status });
+            // Sending fails only when nobody is subscribed
+            let _ = events.send(event.to_string());
+        }
+        last_status = status;
+
+        // Keep reminding that the break is over until work is started
+        nag_due = match (config.notification.break_end_nag, state.is_awaiting_work()) {
+            (Some(minutes), true) if minutes > 0 && config.notification.enabled => nag_due
+                .or_else(|| Some(crate::timer::current_timestamp() + u64::from(minutes) * 60)),
+            _ => None,
+        };
+
+        let next_chime = config
+            .sound
+            .interval_chime
+            .filter(|_| config.sound.effective_mode() != crate::config::SoundMode::None)
+            .and_then(|minutes| state.next_chime_time(minutes));
 
-async fn daemon_loop(
-    listener: UnixListener,
-    state: &mut TimerState,
-    config: &crate::config::Config,
-) -> Result<(), Box<dyn std::error::Error>> {
-    loop {
         tokio::select! {
             // Handle incoming connections
             Ok((stream, _)) = listener.accept() => {
-                match handle_client(stream, state, config).await {
-                    Ok(should_shutdown) if should_shutdown => {
-                        println!("Shutdown requested, exiting gracefully");
-                        return Ok(());
-                    }
-                    Err(e) => {
-                        eprintln!("Error handling client: {}", e);
-                    }
-                    _ => {}
+                spawn_client(stream, None, None, None, &requests, &events);
+            }
+
+            // Handle connections over TCP, which have to send the token
+            Ok((stream, _)) = async {
+                match &tcp {
+                    Some(tcp) => tcp.accept().await,
+                    None => std::future::pending().await,
+                }
+            } => {
+                let Ok(permit) = tcp_clients.clone().try_acquire_owned() else {
+                    eprintln!("Too many clients connected over TCP, closing the connection");
+                    continue;
+                };
+                // Without a token in the config (anymore), nobody gets in
+                let token = config.daemon.tcp_token.clone().unwrap_or_default();
+                spawn_client(
+                    stream,
+                    Some(token),
+                    Some(TCP_CLIENT_TIMEOUT),
+                    Some(permit),
+                    &requests,
+                    &events,
+                );
+            }
+
+            // Answer the requests that the clients' tasks read
+            Some(Request { message, received, reply }) = pending.recv() => {
+                cause = format!("client: {} {}", message.command, message.args);
+                let (response, shutdown) =
+                    match answer_request(&message, received, state, config, &mut cache, &mut metrics).await {
+                        Ok(answer) => answer,
+                        Err(e) => {
+                            eprintln!("Error handling client: {}", e);
+                            continue;
+                        }
+                    };
+                if shutdown {
+                    println!("Shutdown requested, exiting gracefully");
+                    let (written, done) = oneshot::channel();
+                    let _ = reply.send(Reply { response, written: Some(written) });
+                    let _ = tokio::time::timeout(SHUTDOWN_REPLY_TIMEOUT, done).await;
+                    return Ok(());
                 }
+                let _ = reply.send(Reply { response, written: None });
             }
 
             // Check timer completion with precise timing
             _ = async {
                 if let Some(finish_timestamp) = state.get_finish_time() {
                     // Timer is running, calculate exact sleep duration
-                    let current_time = std::time::SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap()
-                        .as_secs();
+                    let current_time = crate::timer::current_timestamp();
 
                     if finish_timestamp > current_time {
                         // Timer hasn't finished yet, sleep until it does
@@ -634,49 +1874,229 @@ async fn daemon_loop(
                 }
             } => {
                 if state.is_finished() {
+                    cause = format!("timer: {} phase finished", state.phase);
+                    let finished_phase = state.phase.to_string();
+                    let is_work = matches!(state.phase, crate::timer::Phase::Work);
+                    let task = state.task.clone().filter(|_| is_work);
+                    let tags = if is_work { state.tags.clone() } else { Vec::new() };
+                    let notes = state.notes.take().filter(|_| is_work);
+                    let interruptions = state.pauses;
+                    let ended_at = state.get_finish_time().unwrap_or_default();
+                    let started_at =
+                        ended_at.saturating_sub((state.duration_minutes * 60.0) as u64);
+                    let goal_reached = is_work && state.count_completed_work();
+
                     if let Err(e) = state.next_phase(&config.sound, &config.notification, &config.hooks) {
                         eprintln!("Error during phase transition: {}", e);
                     }
+                    if goal_reached
+                        && let Err(e) = state.announce_goal_reached(&config.notification, &config.hooks)
+                    {
+                        eprintln!("Error announcing the daily goal: {}", e);
+                    }
+                    cache.invalidate();
                     // Save state after automatic phase transition
                     save_state(state);
+
+                    // The preparation and review are not sessions of their own
+                    if !matches!(finished_phase.as_str(), "prepare" | "review") {
+                        let id = record_history(&crate::history::NewSession {
+                            task: task.as_deref(),
+                            tags: &tags,
+                            interruptions,
+                            notes: notes.as_deref(),
+                            ..crate::history::NewSession::new(
+                                &finished_phase,
+                                started_at,
+                                ended_at,
+                                "completed",
+                            )
+                        })
+                        .await;
+                        if is_work {
+                            state.last_work_session = id;
+                            save_state(state);
+                        }
+                    }
+                }
+            }
+
+            // Send the end-of-day report when it is due
+            _ = async {
+                match next_report {
+                    Some(due) => {
+                        let wait = (due - chrono::Local::now()).to_std().unwrap_or_default();
+                        tokio::time::sleep(wait).await;
+                    }
+                    None => std::future::pending::<()>().await,
+                }
+            } => {
+                last_report_date = Some(chrono::Local::now().date_naive());
+                send_daily_report(&config.report).await;
+            }
+
+            // Repeat the break-over notification
+            _ = async {
+                match nag_due {
+                    Some(due) => {
+                        let now = crate::timer::current_timestamp();
+                        tokio::time::sleep(Duration::from_secs(due.saturating_sub(now))).await;
+                    }
+                    None => std::future::pending::<()>().await,
+                }
+            } => {
+                send_break_over_nag(&config.notification);
+                nag_due = None;
+            }
+
+            // Chime at each interval during work
+            _ = async {
+                match next_chime {
+                    Some(due) => {
+                        let now = crate::timer::current_timestamp();
+                        tokio::time::sleep(Duration::from_secs(due.saturating_sub(now))).await;
+                    }
+                    None => std::future::pending::<()>().await,
+                }
+            } => {
+                if !crate::timer::is_testing() {
+                    crate::audio::play_chime(config.sound.volume);
+                }
+            }
+
+            // Note config file changes, waiting for them to settle as editors
+            // may write the file in several steps
+            result = async {
+                match &watcher {
+                    Some(watcher) => watcher.changed().await,
+                    None => std::future::pending().await,
+                }
+            } => {
+                match result {
+                    Ok(()) => reload_due = Some(tokio::time::Instant::now() + RELOAD_DEBOUNCE),
+                    Err(e) => {
+                        eprintln!("Stopped watching the config file: {}", e);
+                        watcher = None;
+                    }
+                }
+            }
+
+            // Reload the config once the changes have settled
+            _ = async {
+                match reload_due {
+                    Some(due) => tokio::time::sleep_until(due).await,
+                    None => std::future::pending::<()>().await,
+                }
+            } => {
+                reload_due = None;
+                cause = "config reload".to_string();
+                reload_config(config);
+                state.day_start = config.stats.day_start_time();
+                state.config_goal = config.goal.daily_pomodoros;
+                if config.daemon.watch_config != watcher.is_some() {
+                    watcher = watch_config_file(config);
                 }
             }
+
+            // Wake up at each new second to refresh the named pipe and tick
+            _ = async {
+                if fifo.is_some() || events.receiver_count() > 0 {
+                    tokio::time::sleep(until_next_second()).await;
+                } else {
+                    std::future::pending::<()>().await;
+                }
+            } => {}
         }
     }
 }
 
-/// Start the daemon in the background
-pub async fn start_daemon() -> Result<(), Box<dyn std::error::Error>> {
-    let pid_file_path = get_pid_file_path();
-    let socket_path = get_socket_path();
+/// Time until the clock reaches the next full second
+fn until_next_second() -> Duration {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    // Wake up just after the second has ticked over
+    Duration::from_secs(1) - Duration::from_nanos(now.subsec_nanos().into())
+        + Duration::from_millis(1)
+}
 
-    // Check if daemon is already running by trying to read and verify PID file
-    if let Ok(pid_str) = std::fs::read_to_string(&pid_file_path)
-        && let Ok(pid) = pid_str.trim().parse::<u32>()
-    {
-        if is_process_running(pid) {
-            println!(
-                "Daemon is already running (PID: {}). Use 'tomat daemon stop' to stop it first.",
-                pid
-            );
-            return Ok(());
-        } else {
-            // Stale PID file found - try to clean it up
-            println!(
-                "Found stale PID file (PID {} no longer running), cleaning up...",
-                pid
-            );
-            let _ = std::fs::remove_file(&pid_file_path);
-            let _ = std::fs::remove_file(&socket_path);
+/// How long the config file has to stay unchanged before it is reloaded
+const RELOAD_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Watch the config file if `[daemon] watch_config` is set
+fn watch_config_file(config: &crate::config::Config) -> Option<crate::watch::FileWatcher> {
+    if !config.daemon.watch_config {
+        return None;
+    }
+    let path = crate::config::Config::config_path()?;
+    match crate::watch::FileWatcher::new(&path) {
+        Ok(watcher) => {
+            println!("Watching {:?} for changes", path);
+            Some(watcher)
+        }
+        Err(e) => {
+            eprintln!("Failed to watch config file {:?}: {}", path, e);
+            None
         }
     }
+}
 
-    // Try to lock the PID file to prevent race conditions with concurrent start attempts
-    // We keep this lock until the spawned daemon creates its own lock
-    let lock_file = File::create(&pid_file_path)?;
-    lock_file
-        .try_lock_exclusive()
-        .map_err(|_| "Another daemon is starting up right now. Please wait and try again.")?;
+/// Re-read the config file after it changed, keeping the current settings if
+/// the new file does not parse
+fn reload_config(config: &mut crate::config::Config) {
+    let Some(path) = crate::config::Config::config_path() else {
+        return;
+    };
+    let message = match crate::config::Config::reload_from(&path) {
+        Ok(new_config) => {
+            if new_config.daemon.state_backend != config.daemon.state_backend {
+                println!("The new state_backend takes effect when the daemon restarts");
+            }
+            *config = new_config;
+            crate::audio::configure(&config.sound);
+            println!("Reloaded config from {:?}", path);
+            "Configuration reloaded".to_string()
+        }
+        Err(e) => {
+            eprintln!("{}. Keeping the current configuration", e);
+            format!("Configuration not reloaded: {}", e)
+        }
+    };
+
+    if config.daemon.reload_notification && !crate::timer::is_testing() {
+        let mut notification =
+            crate::timer::new_notification(&config.notification, "x-tomat.config");
+        notification.summary("Tomat").body(&message);
+        if let Err(e) = notification.show() {
+            eprintln!("Failed to send notification: {}", e);
+        }
+    }
+}
+
+/// Start the daemon in the background
+pub async fn start_daemon() -> Result<(), TomatError> {
+    // A daemon that answers on the socket is running, whatever the PID file says
+    if let Some(pid) = ping_daemon().await {
+        println!(
+            "Daemon is already running (PID: {}). Use 'tomat daemon stop' to stop it first.",
+            pid
+        );
+        return Ok(());
+    }
+
+    spawn_daemon(true).await
+}
+
+/// Start the daemon in the background and wait until it answers, printing
+/// the progress if `report` is set
+async fn spawn_daemon(report: bool) -> Result<(), TomatError> {
+    // The daemon removes leftover files itself once it holds the daemon lock
+    if report
+        && !is_pid_file_locked()
+        && let Some(pid) = read_pid_file()
+    {
+        println!("Found stale PID file (PID {}), cleaning up...", pid);
+    }
 
     // Get the current executable path
     let exe_path = std::env::current_exe()?;
@@ -691,93 +2111,61 @@ pub async fn start_daemon() -> Result<(), Box<dyn std::error::Error>> {
         .spawn()?;
 
     let child_pid = child.id();
-    println!("Started daemon in background (PID: {})", child_pid);
-
-    // Release the lock so the daemon can acquire it
-    // The small time window here is acceptable because the daemon is already running
-    drop(lock_file);
+    if report {
+        println!("Started daemon in background (PID: {})", child_pid);
+    }
 
-    // Poll for daemon startup with timeout (max 2 seconds, check every 10ms)
+    // Wait for a daemon to answer: ours, or one launched at the same time,
+    // which ours adopts
     let start = std::time::Instant::now();
-    let timeout = Duration::from_secs(2);
-    let poll_interval = Duration::from_millis(10);
-
     loop {
-        // Check if socket and PID file exist
-        if socket_path.exists() && pid_file_path.exists() {
-            // Verify this is OUR daemon by checking the PID
-            if let Ok(pid_str) = std::fs::read_to_string(&pid_file_path)
-                && let Ok(pid) = pid_str.trim().parse::<u32>()
-            {
-                if pid == child_pid {
-                    // Our daemon successfully wrote its PID - now verify it responds
-                    match send_command("status", serde_json::Value::Null).await {
-                        Ok(_) => {
-                            println!("Daemon started successfully");
-                            return Ok(());
-                        }
-                        Err(_) if start.elapsed() < timeout => {
-                            // Daemon not ready yet, keep waiting
-                        }
-                        Err(_) => {
-                            return Err(
-                                "Failed to start daemon - socket exists but daemon not responding"
-                                    .into(),
-                            );
-                        }
-                    }
-                } else if start.elapsed() >= timeout {
-                    // Different PID in file and timeout reached - another daemon won
-                    return Err(
-                        format!("Another daemon instance (PID: {}) started first", pid).into(),
-                    );
-                }
-                // else: wrong PID but still time left, keep waiting
+        if let Ok(response) = send_raw(PING_REQUEST).await {
+            let pid = serde_json::from_str::<ServerResponse>(&response)
+                .ok()
+                .and_then(|response| response.data.get("pid")?.as_u64());
+            if report && pid == Some(child_pid.into()) {
+                println!("Daemon started successfully");
+            } else if let Some(pid) = pid.filter(|_| report) {
+                println!(
+                    "Another daemon (PID: {}) started at the same time, using it",
+                    pid
+                );
             }
+            return Ok(());
         }
 
-        if start.elapsed() > timeout {
-            return Err(
-                "Failed to start daemon - socket or PID file not created within timeout".into(),
-            );
+        if start.elapsed() > STARTUP_TIMEOUT {
+            return Err("Failed to start daemon - it did not answer within the timeout".into());
         }
 
-        tokio::time::sleep(poll_interval).await;
+        tokio::time::sleep(Duration::from_millis(10)).await;
     }
 }
 
 /// Stop the running daemon
-pub async fn stop_daemon() -> Result<(), Box<dyn std::error::Error>> {
+pub async fn stop_daemon() -> Result<(), TomatError> {
     let pid_file_path = get_pid_file_path();
-    let socket_path = get_socket_path();
-
-    // Read PID from file
-    let pid_str = match std::fs::read_to_string(&pid_file_path) {
-        Ok(content) => content,
-        Err(_) => {
-            println!("No daemon PID file found");
-            return Ok(());
-        }
-    };
 
-    let pid = match pid_str.trim().parse::<u32>() {
-        Ok(pid) => pid,
-        Err(_) => {
-            println!("Invalid PID in file, cleaning up");
-            let _ = std::fs::remove_file(&pid_file_path);
-            let _ = std::fs::remove_file(&socket_path);
-            return Ok(());
+    // Prefer the PID the daemon reports itself; the PID file is only trusted
+    // while the daemon holds its lock, as its PID may have been reused
+    let pid = match ping_daemon().await.filter(|&pid| pid != 0) {
+        Some(pid) => pid,
+        None => {
+            if !pid_file_path.exists() {
+                println!("No daemon PID file found");
+                return Ok(());
+            }
+            match read_pid_file() {
+                Some(pid) if is_pid_file_locked() => pid,
+                _ => {
+                    println!("Daemon is not running");
+                    remove_stale_files();
+                    return Ok(());
+                }
+            }
         }
     };
 
-    // Check if process is running
-    if !is_process_running(pid) {
-        println!("Daemon is not running, cleaning up stale files");
-        let _ = std::fs::remove_file(&pid_file_path);
-        let _ = std::fs::remove_file(&socket_path);
-        return Ok(());
-    }
-
     // Try graceful shutdown via socket command first
     match send_command("shutdown", serde_json::Value::Null).await {
         Ok(_) => {
@@ -787,8 +2175,6 @@ pub async fn stop_daemon() -> Result<(), Box<dyn std::error::Error>> {
             for _ in 0..50 {
                 if !is_process_running(pid) {
                     println!("Daemon stopped gracefully");
-                    let _ = std::fs::remove_file(&pid_file_path);
-                    let _ = std::fs::remove_file(&socket_path);
                     return Ok(());
                 }
                 tokio::time::sleep(Duration::from_millis(100)).await;
@@ -830,52 +2216,258 @@ pub async fn stop_daemon() -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
-    // Clean up files
-    let _ = std::fs::remove_file(&pid_file_path);
-    let _ = std::fs::remove_file(&socket_path);
-
+    // A daemon that was killed leaves its files behind, which the next daemon
+    // to start removes while holding the lock
     Ok(())
 }
 
-/// Check daemon status
-pub async fn daemon_status() -> Result<(), Box<dyn std::error::Error>> {
+/// Remove the socket and PID file left behind by a crashed daemon
+///
+/// This only happens while holding the daemon lock, so the files of a daemon
+/// that is starting up at the same moment are never removed.
+fn remove_stale_files() {
+    let Ok(lock_file) = File::options()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(get_lock_file_path())
+    else {
+        return;
+    };
+    if lock_file.try_lock_exclusive().is_ok() && !is_pid_file_locked() {
+        println!("Cleaning up stale files");
+        let _ = std::fs::remove_file(get_pid_file_path());
+        let _ = std::fs::remove_file(get_socket_path());
+    }
+}
+
+/// Stop the running daemon, if any, and start a fresh one
+///
+/// Waits for the old daemon to release the socket and PID file lock before
+/// starting, so the new one does not see it as already running.
+pub async fn restart_daemon() -> Result<(), TomatError> {
+    stop_daemon().await?;
+
+    let start = std::time::Instant::now();
+    while ping_daemon().await.is_some() || is_pid_file_locked() {
+        if start.elapsed() >= Duration::from_secs(5) {
+            return Err(
+                "Failed to restart daemon - the old daemon did not release its socket".into(),
+            );
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+
+    start_daemon().await?;
+    match ping_daemon().await {
+        Some(pid) => {
+            println!("Daemon restarted (PID: {})", pid);
+            Ok(())
+        }
+        None => Err("Failed to restart daemon - new daemon is not responding".into()),
+    }
+}
+
+/// Config to start a new cycle with
+///
+/// The config is loaded fresh for each start, unless the daemon already keeps
+/// it up to date by watching the file.
+fn start_config(config: &crate::config::Config) -> crate::config::Config {
+    let mut config = if config.daemon.watch_config {
+        config.clone()
+    } else {
+        crate::config::Config::load()
+    };
+    let (timer, profile) = timer_now(&config);
+    if let Some(profile) = profile {
+        println!(
+            "Starting a cycle with the timer settings of profile '{}'",
+            profile
+        );
+    }
+    config.timer = timer;
+    config
+}
+
+/// The timer settings for now, with the profile of the time of day or the
+/// weekday applied, and the name of that profile
+fn timer_now(config: &crate::config::Config) -> (crate::config::TimerConfig, Option<&str>) {
+    use chrono::Datelike;
+    let today = crate::stats::today(config.stats.day_start_time());
+    let now = chrono::DateTime::from_timestamp(crate::timer::current_timestamp() as i64, 0)
+        .map(|now| now.with_timezone(&chrono::Local).time())
+        .unwrap_or_default();
+    config.timer_at(today.weekday(), now)
+}
+
+/// End of the workday, if it has passed
+fn workday_over(config: &crate::config::Config) -> Option<chrono::NaiveTime> {
+    config
+        .schedule
+        .workday_end_time()
+        .filter(|&end| crate::timer::is_past(end))
+}
+
+/// Reset the cycle and take its durations and behaviour from the timer and
+/// schedule settings
+fn apply_timer_config(state: &mut TimerState, config: &crate::config::Config) {
+    let timer = &config.timer;
+    state.workday_end = config.schedule.workday_end_time();
+    state.work_duration = timer.work;
+    state.break_duration = timer.break_time;
+    state.long_break_duration = timer.long_break;
+    state.sessions_until_long_break = timer.sessions;
+    state.long_break_after_minutes = timer.long_break_after_minutes;
+    state.max_pauses = timer.max_pauses;
+    state.prepare_duration = timer.prepare;
+    state.review_duration = timer.review;
+    state.jitter = timer.jitter;
+    state.day_start = config.stats.day_start_time();
+    state.config_goal = config.goal.daily_pomodoros;
+    state.plan_complete = timer.plan_complete;
+    state.plan_complete_break = timer.plan_complete_break;
+    state.auto_advance = timer.auto_advance.clone();
+    state.current_session_count = 0;
+    state.focused_seconds = 0;
+    state.long_break_postponed = false;
+}
+
+/// Pause the running timer. A pause beyond the pause budget of a work session
+/// voids it instead: the session is recorded as voided and starts over.
+async fn pause_timer(state: &mut TimerState, config: &crate::config::Config) -> ServerResponse {
+    let voided = state.pause_voids_session();
+    if voided {
+        record_unfinished_phase(state, "voided").await;
+        state.void_work();
+    } else {
+        state.pause();
+    }
+
+    // Execute hook
+    execute_hook(&config.hooks, "pause", state);
+
+    // Save state after pausing
+    save_state(state);
+
+    if voided {
+        ServerResponse::ok(format!(
+            "Pause budget of {} exceeded: work session voided. Resume to start it over",
+            state.max_pauses.unwrap_or_default()
+        ))
+    } else {
+        ServerResponse::ok("Timer paused")
+    }
+}
+
+/// Start a fresh cycle with a work session on `task`, or the current task of
+/// the plan, recording the sessions with `tags`
+fn start_cycle(
+    state: &mut TimerState,
+    config: &crate::config::Config,
+    task: Option<String>,
+    tags: Vec<String>,
+    hooks: &crate::config::HooksConfig,
+) {
+    apply_timer_config(state, config);
+    state.continue_backfilled_cycle();
+    state.tags = tags;
+    state.task = task.or_else(|| {
+        state
+            .current_plan_task()
+            .map(|planned| planned.task.clone())
+    });
+
+    state.start_prepare_or_work(true);
+    crate::nagbar::dismiss();
+
+    // Execute work_start hook, or prepare_start when preparing first
+    execute_hook(hooks, state.work_start_event(), state);
+
+    // Save state after starting
+    save_state(state);
+}
+
+/// Why `start` would throw away a session in progress, if it would
+///
+/// Restarting resets the cycle, so it needs `force` once the timer is
+/// running, e.g. to keep a misclick on the bar from wiping the progress.
+fn start_conflict(state: &TimerState) -> Option<String> {
+    let phase = match state.phase {
+        crate::timer::Phase::Idle => return None,
+        crate::timer::Phase::Prepare => "The preparation",
+        crate::timer::Phase::Work => "A work session",
+        crate::timer::Phase::Review => "The review",
+        crate::timer::Phase::Break => "A break",
+        crate::timer::Phase::LongBreak => "A long break",
+    };
+    let progress = format!(
+        "{} of {} sessions done",
+        state.current_session_count, state.sessions_until_long_break
+    );
+    Some(if state.is_paused {
+        format!(
+            "{} is paused ({}). Resume it with 'tomat resume', \
+             or restart the cycle with 'tomat start --force'",
+            phase, progress
+        )
+    } else {
+        format!(
+            "{} is in progress ({}). Restart the cycle with 'tomat start --force'",
+            phase, progress
+        )
+    })
+}
+
+/// Check daemon status, printed as text or, with `json`, as a JSON object
+pub async fn daemon_status(verbose: bool, json: bool) -> Result<(), TomatError> {
     let pid_file_path = get_pid_file_path();
     let socket_path = get_socket_path();
 
-    // Check if PID file exists
-    let pid = match std::fs::read_to_string(&pid_file_path) {
-        Ok(content) => match content.trim().parse::<u32>() {
-            Ok(pid) => pid,
-            Err(_) => {
-                println!("Status: Not running (invalid PID file)");
-                return Ok(());
-            }
-        },
-        Err(_) => {
-            println!("Status: Not running (no PID file)");
-            return Ok(());
+    // A daemon answering on the socket is authoritative
+    let Some(pid) = ping_daemon().await else {
+        // Otherwise the PID file is a hint, valid while a daemon holds its lock
+        let (pid, status) = match read_pid_file() {
+            Some(pid) if is_pid_file_locked() => (Some(pid), "Running but unresponsive"),
+            Some(_) => (None, "Not running (stale PID file)"),
+            None if pid_file_path.exists() => (None, "Not running (invalid PID file)"),
+            None => (None, "Not running (no PID file)"),
+        };
+        if json {
+            let status =
+                serde_json::json!({ "running": pid.is_some(), "pid": pid, "status": status });
+            println!("{}", status);
+        } else if let Some(pid) = pid {
+            println!("Status: {} (PID: {})", status, pid);
+        } else {
+            println!("Status: {}", status);
         }
+        return Ok(());
     };
 
-    // Check if process is actually running
-    if !is_process_running(pid) {
-        println!("Status: Not running (stale PID file)");
+    let response = send_command("stats", serde_json::Value::Null).await?;
+    if !response.success {
+        return Err(response.message.into());
+    }
+    let metrics: DaemonMetrics = serde_json::from_value(response.data)?;
+    let now = crate::timer::current_timestamp();
+
+    if json {
+        let mut status = serde_json::to_value(&metrics)?;
+        status["running"] = true.into();
+        status["pid"] = pid.into();
+        status["socket"] = socket_path.display().to_string().into();
+        status["uptime_seconds"] = now.saturating_sub(metrics.started_at).into();
+        status["requests"] = metrics.requests().into();
+        println!("{}", status);
         return Ok(());
     }
 
-    // Check if socket exists and is responsive
-    if socket_path.exists() {
-        // Try to connect to the daemon
-        match send_command("status", serde_json::Value::Null).await {
-            Ok(_) => {
-                println!("Status: Running (PID: {}, socket: {:?})", pid, socket_path);
-            }
-            Err(_) => {
-                println!("Status: Running but unresponsive (PID: {})", pid);
-            }
-        }
-    } else {
-        println!("Status: Process running but no socket (PID: {})", pid);
+    println!("Status: Running (PID: {}, socket: {:?})", pid, socket_path);
+    println!();
+    print!("{}", metrics.summary_text(now));
+    if verbose {
+        println!();
+        print!("{}", metrics.to_text());
     }
 
     Ok(())
@@ -888,6 +2480,7 @@ fn is_process_running(pid: u32) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::ErrorCode;
 
     #[test]
     fn test_get_socket_path_uses_xdg_runtime_dir() {
@@ -923,6 +2516,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_token_matches() {
+        assert!(token_matches("s3cret", "s3cret"));
+        assert!(!token_matches("s3cret", "s3cre"));
+        assert!(!token_matches("s3cret", "s3creT"));
+        // An empty token never lets anyone in
+        assert!(!token_matches("", ""));
+    }
+
+    #[test]
+    fn test_timer_event() {
+        let mut timer = TimerState::new(25.0, 5.0, 15.0, 4);
+        let idle = timer.get_timer_status();
+        timer.start_work();
+        let work = timer.get_timer_status();
+
+        assert_eq!(timer_event(&idle, &idle), None);
+        assert_eq!(timer_event(&idle, &work), Some("phase_change"));
+        assert_eq!(timer_event(&work, &idle), Some("stop"));
+
+        let paused = TimerStatus {
+            is_paused: true,
+            ..work.clone()
+        };
+        assert_eq!(timer_event(&work, &paused), Some("pause"));
+        assert_eq!(timer_event(&paused, &work), Some("resume"));
+
+        let later = TimerStatus {
+            remaining_seconds: work.remaining_seconds - 1,
+            ..work.clone()
+        };
+        assert_eq!(timer_event(&work, &later), Some("tick"));
+        // A paused countdown does not tick
+        let paused_later = TimerStatus {
+            is_paused: true,
+            ..later
+        };
+        assert_eq!(timer_event(&paused, &paused_later), None);
+    }
+
     #[test]
     fn test_client_message_serialization() {
         let message = ClientMessage {
@@ -931,6 +2564,7 @@ mod tests {
                 "work": 25.0,
                 "break": 5.0
             }),
+            token: None,
         };
 
         let json = serde_json::to_string(&message).unwrap();
@@ -943,11 +2577,10 @@ mod tests {
 
     #[test]
     fn test_server_response_serialization() {
-        let response = ServerResponse {
-            success: true,
-            data: serde_json::json!({"text": "🍅 25:00 ⏸"}),
-            message: "Status retrieved".to_string(),
-        };
+        let response = ServerResponse::with_data(
+            serde_json::json!({"text": "🍅 25:00 ⏸"}),
+            "Status retrieved",
+        );
 
         let json = serde_json::to_string(&response).unwrap();
         let deserialized: ServerResponse = serde_json::from_str(&json).unwrap();
@@ -993,6 +2626,7 @@ mod tests {
                 "sessions": 3,
                 "auto_advance": true
             }),
+            token: None,
         };
 
         let json = serde_json::to_string(&message).unwrap();
@@ -1011,6 +2645,7 @@ mod tests {
         let message = ClientMessage {
             command: "status".to_string(),
             args: serde_json::Value::Null,
+            token: None,
         };
 
         let json = serde_json::to_string(&message).unwrap();
@@ -1022,18 +2657,20 @@ mod tests {
 
     #[test]
     fn test_server_response_error() {
-        let response = ServerResponse {
-            success: false,
-            data: serde_json::Value::Null,
-            message: "Unknown command".to_string(),
-        };
+        let response = ServerResponse::error(&TomatError::UnknownCommand("invalid".to_string()));
 
         let json = serde_json::to_string(&response).unwrap();
         let deserialized: ServerResponse = serde_json::from_str(&json).unwrap();
 
         assert!(!deserialized.success);
-        assert_eq!(deserialized.message, "Unknown command");
+        assert_eq!(deserialized.message, "Unknown command: invalid");
+        assert_eq!(deserialized.error, Some(ErrorCode::UnknownCommand));
         assert!(deserialized.data.is_null());
+
+        // Clients see the error again, including its remediation hint
+        let error = deserialized.into_result().unwrap_err();
+        assert_eq!(error.code(), ErrorCode::UnknownCommand);
+        assert!(error.hint().is_some());
     }
 
     #[test]
@@ -1149,7 +2786,36 @@ mod tests {
     }
 
     #[test]
-    fn test_state_file_path_uses_xdg_runtime_dir() {
+    fn test_ensure_private_dir_creates_and_fixes_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path().join("tomat").join("run");
+
+        ensure_private_dir(&dir).unwrap();
+        let mode = std::fs::metadata(&dir).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+
+        // Loosened permissions are tightened again
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o755)).unwrap();
+        ensure_private_dir(&dir).unwrap();
+        let mode = std::fs::metadata(&dir).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+    }
+
+    #[test]
+    fn test_ensure_private_dir_rejects_symlink() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let target = temp_dir.path().join("target");
+        let link = temp_dir.path().join("link");
+        std::fs::create_dir(&target).unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        assert!(ensure_private_dir(&link).is_err());
+    }
+
+    #[test]
+    fn test_state_file_path_uses_xdg_state_home() {
         let state_path = get_state_file_path();
         let path_str = state_path.to_string_lossy();
 
@@ -1157,5 +2823,33 @@ mod tests {
             path_str.contains("tomat.state"),
             "State file path should end with tomat.state"
         );
+        if let Some(state_dir) = dirs::state_dir() {
+            assert!(state_path.starts_with(state_dir));
+        }
+    }
+
+    #[test]
+    fn test_migrate_state_file() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let legacy = temp_dir.path().join("run").join("tomat.state");
+        let path = temp_dir
+            .path()
+            .join("state")
+            .join("tomat")
+            .join("tomat.state");
+
+        // Nothing to migrate
+        assert!(!migrate_state_file(&legacy, &path).unwrap());
+
+        std::fs::create_dir_all(legacy.parent().unwrap()).unwrap();
+        std::fs::write(&legacy, "old").unwrap();
+        assert!(migrate_state_file(&legacy, &path).unwrap());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "old");
+        assert!(!legacy.exists());
+
+        // An existing state file is never overwritten
+        std::fs::write(&legacy, "older").unwrap();
+        assert!(!migrate_state_file(&legacy, &path).unwrap());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "old");
     }
 }
@@ -0,0 +1,671 @@
+use std::path::{Path, PathBuf};
+
+use crate::cli::InitSystem;
+use crate::error::TomatError;
+
+/// A section of a unit (or desktop entry) file, with its header and lines
+struct Section {
+    header: Option<String>,
+    lines: Vec<String>,
+}
+
+impl Section {
+    /// Replace all assignments of `key` with `lines`, keeping the position of the first one
+    fn replace_key(&mut self, key: &str, lines: Vec<String>) {
+        let first = self
+            .lines
+            .iter()
+            .position(|line| split_assignment(line).is_some_and(|(k, _)| k == key));
+        self.lines
+            .retain(|line| split_assignment(line).is_none_or(|(k, _)| k != key));
+
+        // New keys go after the last non-blank line of the section
+        let index = first.unwrap_or_else(|| {
+            self.lines
+                .iter()
+                .rposition(|line| !line.trim().is_empty())
+                .map_or(0, |i| i + 1)
+        });
+        self.lines.splice(index..index, lines);
+    }
+}
+
+/// Split a `Key=value` line into its trimmed key and value
+fn split_assignment(line: &str) -> Option<(&str, &str)> {
+    let line = line.trim();
+    if line.starts_with('#') || line.starts_with(';') {
+        return None;
+    }
+    line.split_once('=').map(|(k, v)| (k.trim(), v.trim()))
+}
+
+/// Parse an INI-style file into sections
+fn parse_sections(content: &str) -> Vec<Section> {
+    let mut sections = vec![Section {
+        header: None,
+        lines: Vec::new(),
+    }];
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') && trimmed.ends_with(']') {
+            sections.push(Section {
+                header: Some(trimmed.to_string()),
+                lines: Vec::new(),
+            });
+        } else if let Some(section) = sections.last_mut() {
+            section.lines.push(line.to_string());
+        }
+    }
+
+    sections
+}
+
+/// Merge a user-provided snippet into a generated unit file
+///
+/// Keys in the snippet replace all keys of the same name in the same section,
+/// an empty assignment (`Key=`) removes the key, and sections that do not exist
+/// yet are appended. Files without sections (like dinit services) are merged
+/// as a whole. Comments in the snippet are ignored.
+pub fn merge_unit(base: &str, snippet: &str) -> String {
+    let mut sections = parse_sections(base);
+
+    for section in parse_sections(snippet) {
+        let header = section.header;
+
+        // Group the snippet's assignments by key, honoring `Key=` resets
+        let mut groups: Vec<(String, Vec<String>)> = Vec::new();
+        for line in &section.lines {
+            let Some((key, value)) = split_assignment(line) else {
+                continue;
+            };
+            let index = match groups.iter().position(|(k, _)| k == key) {
+                Some(index) => index,
+                None => {
+                    groups.push((key.to_string(), Vec::new()));
+                    groups.len() - 1
+                }
+            };
+            if value.is_empty() {
+                groups[index].1.clear();
+            } else {
+                groups[index].1.push(line.trim().to_string());
+            }
+        }
+
+        // Assignments before any section header (e.g. dinit services) go to the top
+        let target = match sections.iter().position(|s| s.header == header) {
+            Some(index) => index,
+            None => {
+                sections.push(Section {
+                    header,
+                    lines: Vec::new(),
+                });
+                sections.len() - 1
+            }
+        };
+
+        for (key, lines) in groups {
+            sections[target].replace_key(&key, lines);
+        }
+    }
+
+    let blocks: Vec<String> = sections
+        .into_iter()
+        .filter_map(|section| {
+            let mut lines: Vec<String> = section.header.into_iter().collect();
+            lines.extend(section.lines);
+            while lines.last().is_some_and(|line| line.trim().is_empty()) {
+                lines.pop();
+            }
+            (!lines.is_empty()).then(|| lines.join("\n"))
+        })
+        .collect();
+
+    format!("{}\n", blocks.join("\n\n"))
+}
+
+/// Merge the template at `template` (if any) into generated file content
+fn apply_template(content: String, template: Option<&Path>) -> Result<String, TomatError> {
+    match template {
+        Some(path) => {
+            let snippet = std::fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read template '{}': {}", path.display(), e))?;
+            Ok(merge_unit(&content, &snippet))
+        }
+        None => Ok(content),
+    }
+}
+
+/// Ask the user whether an existing file should be overwritten
+fn confirm_overwrite(what: &str, path: &Path) -> Result<bool, TomatError> {
+    use std::io::{self, Write};
+
+    print!(
+        "⚠ {} already exists at: {}\nOverwrite? [y/N]: ",
+        what,
+        path.display()
+    );
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+
+    let response = input.trim().to_lowercase();
+    Ok(response == "y" || response == "yes")
+}
+
+/// Get the user configuration directory, falling back to ~/.config
+fn user_config_dir() -> Result<PathBuf, TomatError> {
+    match dirs::config_dir() {
+        Some(dir) => Ok(dir),
+        None => {
+            // Fallback to HOME/.config if XDG config dir is not available
+            let home = std::env::var("HOME").map_err(|_| "Could not determine home directory")?;
+            Ok(PathBuf::from(home).join(".config"))
+        }
+    }
+}
+
+/// Get the path of the XDG autostart entry for the tomat daemon
+fn autostart_entry_path() -> Result<PathBuf, TomatError> {
+    Ok(user_config_dir()?.join("autostart").join("tomat.desktop"))
+}
+
+/// Generate the XDG autostart entry that runs the given tomat executable
+pub fn autostart_entry(exe_path: &str) -> String {
+    // `daemon start` detaches and is a no-op if the daemon is already running
+    format!(
+        r#"[Desktop Entry]
+Type=Application
+Name=Tomat
+Comment=Pomodoro timer daemon
+Exec="{}" daemon start
+Terminal=false
+NoDisplay=true
+X-GNOME-Autostart-enabled=true
+"#,
+        exe_path
+    )
+}
+
+/// Install an XDG autostart entry that starts the tomat daemon on login
+pub fn install_autostart_entry(
+    force: bool,
+    print: bool,
+    template: Option<&Path>,
+) -> Result<(), TomatError> {
+    use std::fs;
+
+    let exe_path = std::env::current_exe()?;
+    let entry_content = apply_template(autostart_entry(&exe_path.to_string_lossy()), template)?;
+
+    if print {
+        print!("{}", entry_content);
+        return Ok(());
+    }
+
+    let entry_path = autostart_entry_path()?;
+
+    if entry_path.exists() && !force && !confirm_overwrite("Autostart entry", &entry_path)? {
+        println!("Installation cancelled.");
+        return Ok(());
+    }
+
+    if let Some(parent) = entry_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    fs::write(&entry_path, entry_content)?;
+
+    println!("✓ Autostart entry installed to: {}", entry_path.display());
+    println!("\nThe daemon will start with your next graphical session.");
+    println!("\nTo start the daemon now:");
+    println!("  tomat daemon start");
+
+    Ok(())
+}
+
+/// Remove the XDG autostart entry for the tomat daemon
+pub fn uninstall_autostart_entry() -> Result<(), TomatError> {
+    let entry_path = autostart_entry_path()?;
+
+    if !entry_path.exists() {
+        println!("Tomat autostart entry is not installed (file not found)");
+        return Ok(());
+    }
+
+    std::fs::remove_file(&entry_path)?;
+    println!("✓ Autostart entry removed: {}", entry_path.display());
+    println!("\nThe running daemon is not affected; stop it with: tomat daemon stop");
+
+    Ok(())
+}
+
+/// Generate the systemd user unit that runs the given tomat executable
+pub fn systemd_unit(exe_path: &str) -> String {
+    format!(
+        r#"[Unit]
+Description=Tomat Pomodoro server
+After=graphical-session.target
+PartOf=graphical-session.target
+
+[Service]
+Type=simple
+Environment="PATH=%h/.local/bin:%h/.cargo/bin:/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin:/sbin:/bin"
+ExecStart={} daemon run
+Restart=always
+# A daemon that finds another one running adopts it and exits with 75
+SuccessExitStatus=75
+RestartPreventExitStatus=75
+RestartSec=5
+# Sandboxing that does not get in the way of hooks, screen lockers, sounds, or
+# notifications. NoNewPrivileges= and RestrictSUIDSGID= are left out on purpose:
+# they break setuid helpers such as unix_chkpwd (swaylock, hyprlock) and sudo.
+LockPersonality=true
+RestrictRealtime=true
+SystemCallArchitectures=native
+UMask=0077
+
+[Install]
+WantedBy=graphical-session.target
+"#,
+        exe_path
+    )
+}
+
+/// Install systemd user service for tomat daemon
+pub fn install_systemd_service(
+    force: bool,
+    print: bool,
+    template: Option<&Path>,
+) -> Result<(), TomatError> {
+    use std::fs;
+
+    // Get the current executable path
+    let exe_path = std::env::current_exe()?;
+
+    // Generate service file content
+    let service_content = apply_template(systemd_unit(&exe_path.to_string_lossy()), template)?;
+
+    if print {
+        print!("{}", service_content);
+        return Ok(());
+    }
+
+    // Create systemd user directory using XDG config directory
+    let systemd_dir = user_config_dir()?.join("systemd").join("user");
+
+    fs::create_dir_all(&systemd_dir)?;
+
+    // Write service file
+    let service_path = systemd_dir.join("tomat.service");
+
+    // Check if service file already exists (unless --force is used)
+    if service_path.exists() && !force && !confirm_overwrite("Service file", &service_path)? {
+        println!("Installation cancelled.");
+        return Ok(());
+    }
+
+    fs::write(&service_path, service_content)?;
+
+    println!(
+        "✓ Systemd service file installed to: {}",
+        service_path.display()
+    );
+
+    // Reload systemd and enable service
+    let reload_result = std::process::Command::new("systemctl")
+        .args(["--user", "daemon-reload"])
+        .status();
+
+    match reload_result {
+        Ok(status) if status.success() => {
+            println!("✓ Systemd daemon reloaded");
+
+            let enable_result = std::process::Command::new("systemctl")
+                .args(["--user", "enable", "tomat.service"])
+                .status();
+
+            match enable_result {
+                Ok(status) if status.success() => {
+                    println!("✓ Tomat service enabled");
+                    println!("\nService installed successfully!");
+                    println!("\nTo start the daemon:");
+                    println!("  systemctl --user start tomat.service");
+                    println!("\nTo check status:");
+                    println!("  systemctl --user status tomat.service");
+                    println!("\nTo enable auto-start on login:");
+                    println!("  loginctl enable-linger $USER");
+                }
+                Ok(_) => {
+                    eprintln!("⚠ Warning: Failed to enable tomat.service");
+                    eprintln!(
+                        "You can enable it manually with: systemctl --user enable tomat.service"
+                    );
+                }
+                Err(e) => {
+                    eprintln!("⚠ Warning: Failed to run systemctl enable: {}", e);
+                    eprintln!(
+                        "You can enable it manually with: systemctl --user enable tomat.service"
+                    );
+                }
+            }
+        }
+        Ok(_) => {
+            eprintln!("⚠ Warning: Failed to reload systemd daemon");
+            eprintln!("You can reload manually with: systemctl --user daemon-reload");
+        }
+        Err(e) => {
+            eprintln!("⚠ Warning: Failed to run systemctl daemon-reload: {}", e);
+            eprintln!("Systemctl might not be available or you might not be using systemd");
+        }
+    }
+
+    Ok(())
+}
+
+/// Uninstall systemd user service for tomat daemon
+pub fn uninstall_systemd_service() -> Result<(), TomatError> {
+    use std::fs;
+
+    // Use XDG config directory consistently
+    let service_path = init_service_path(InitSystem::Systemd)?;
+
+    // Check if service file exists
+    if !service_path.exists() {
+        println!("Tomat service is not installed (service file not found)");
+        return Ok(());
+    }
+
+    // Try to stop and disable the service first
+    let stop_result = std::process::Command::new("systemctl")
+        .args(["--user", "stop", "tomat.service"])
+        .status();
+
+    match stop_result {
+        Ok(status) if status.success() => println!("✓ Tomat service stopped"),
+        Ok(_) => eprintln!("⚠ Warning: Failed to stop tomat.service (might not be running)"),
+        Err(e) => eprintln!("⚠ Warning: Failed to run systemctl stop: {}", e),
+    }
+
+    let disable_result = std::process::Command::new("systemctl")
+        .args(["--user", "disable", "tomat.service"])
+        .status();
+
+    match disable_result {
+        Ok(status) if status.success() => println!("✓ Tomat service disabled"),
+        Ok(_) => eprintln!("⚠ Warning: Failed to disable tomat.service"),
+        Err(e) => eprintln!("⚠ Warning: Failed to run systemctl disable: {}", e),
+    }
+
+    // Remove service file
+    match fs::remove_file(&service_path) {
+        Ok(()) => {
+            println!("✓ Service file removed: {}", service_path.display());
+
+            // Reload systemd
+            let reload_result = std::process::Command::new("systemctl")
+                .args(["--user", "daemon-reload"])
+                .status();
+
+            match reload_result {
+                Ok(status) if status.success() => println!("✓ Systemd daemon reloaded"),
+                Ok(_) => eprintln!("⚠ Warning: Failed to reload systemd daemon"),
+                Err(e) => eprintln!("⚠ Warning: Failed to run systemctl daemon-reload: {}", e),
+            }
+
+            println!("\nTomat service uninstalled successfully!");
+        }
+        Err(e) => {
+            eprintln!("Failed to remove service file: {}", e);
+            return Err(e.into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Generate the user service definition for a non-systemd init system
+pub fn init_service(init: InitSystem, exe_path: &str) -> String {
+    match init {
+        InitSystem::Systemd => systemd_unit(exe_path),
+        InitSystem::Openrc => format!(
+            r#"#!/sbin/openrc-run
+
+description="Tomat Pomodoro server"
+supervisor=supervise-daemon
+command="{}"
+command_args="daemon run"
+respawn_delay=5
+"#,
+            exe_path
+        ),
+        InitSystem::Runit => format!(
+            r#"#!/bin/sh
+exec 2>&1
+exec "{}" daemon run
+"#,
+            exe_path
+        ),
+        InitSystem::Dinit => format!(
+            r#"type = process
+command = {} daemon run
+restart = true
+restart-delay = 5
+"#,
+            exe_path
+        ),
+    }
+}
+
+/// Get the path of the user service file for a non-systemd init system
+fn init_service_path(init: InitSystem) -> Result<PathBuf, TomatError> {
+    let config_dir = user_config_dir()?;
+    Ok(match init {
+        InitSystem::Systemd => config_dir
+            .join("systemd")
+            .join("user")
+            .join("tomat.service"),
+        InitSystem::Openrc => config_dir.join("rc").join("init.d").join("tomat"),
+        // runit services are directories; turnstile supervises ~/.config/service
+        InitSystem::Runit => config_dir.join("service").join("tomat").join("run"),
+        InitSystem::Dinit => config_dir.join("dinit.d").join("tomat"),
+    })
+}
+
+/// Run a service manager command, reporting success or a warning
+fn run_service_command(args: &[&str], success: &str) {
+    match std::process::Command::new(args[0])
+        .args(&args[1..])
+        .status()
+    {
+        Ok(status) if status.success() => println!("✓ {}", success),
+        Ok(_) => eprintln!("⚠ Warning: '{}' failed", args.join(" ")),
+        Err(e) => eprintln!("⚠ Warning: Failed to run {}: {}", args[0], e),
+    }
+}
+
+/// Install a user service for OpenRC, runit, or dinit
+pub fn install_init_service(
+    init: InitSystem,
+    force: bool,
+    print: bool,
+    template: Option<&Path>,
+) -> Result<(), TomatError> {
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    let exe_path = std::env::current_exe()?;
+    let content = init_service(init, &exe_path.to_string_lossy());
+
+    // Only key/value service files can be merged with a template
+    let content = match init {
+        InitSystem::Openrc | InitSystem::Runit if template.is_some() => {
+            return Err("--template is not supported for shell script services".into());
+        }
+        _ => apply_template(content, template)?,
+    };
+
+    if print {
+        print!("{}", content);
+        return Ok(());
+    }
+
+    let service_path = init_service_path(init)?;
+
+    if service_path.exists() && !force && !confirm_overwrite("Service file", &service_path)? {
+        println!("Installation cancelled.");
+        return Ok(());
+    }
+
+    if let Some(parent) = service_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&service_path, content)?;
+
+    if matches!(init, InitSystem::Openrc | InitSystem::Runit) {
+        fs::set_permissions(&service_path, fs::Permissions::from_mode(0o755))?;
+    }
+
+    println!("✓ Service file installed to: {}", service_path.display());
+
+    match init {
+        InitSystem::Openrc => {
+            run_service_command(
+                &["rc-update", "--user", "add", "tomat", "default"],
+                "Tomat service added to the default runlevel",
+            );
+            println!("\nTo start the daemon:");
+            println!("  rc-service --user tomat start");
+        }
+        InitSystem::Runit => {
+            println!("\nThe service starts automatically if ~/.config/service is supervised");
+            println!("(e.g. by turnstile). Otherwise link it into your runsvdir:");
+            println!("  ln -s ~/.config/service/tomat \"$SVDIR\"");
+        }
+        InitSystem::Dinit => {
+            run_service_command(&["dinitctl", "enable", "tomat"], "Tomat service enabled");
+            println!("\nTo check status:");
+            println!("  dinitctl status tomat");
+        }
+        InitSystem::Systemd => {}
+    }
+
+    Ok(())
+}
+
+/// Stop and remove a user service for OpenRC, runit, or dinit
+pub fn uninstall_init_service(init: InitSystem) -> Result<(), TomatError> {
+    let service_path = init_service_path(init)?;
+
+    if !service_path.exists() {
+        println!("Tomat service is not installed (service file not found)");
+        return Ok(());
+    }
+
+    match init {
+        InitSystem::Openrc => {
+            run_service_command(
+                &["rc-service", "--user", "tomat", "stop"],
+                "Tomat service stopped",
+            );
+            run_service_command(
+                &["rc-update", "--user", "del", "tomat", "default"],
+                "Tomat service removed from the default runlevel",
+            );
+            std::fs::remove_file(&service_path)?;
+        }
+        InitSystem::Runit => {
+            let service_dir = service_path.parent().unwrap_or(&service_path);
+            run_service_command(
+                &["sv", "down", &service_dir.to_string_lossy()],
+                "Tomat service stopped",
+            );
+            std::fs::remove_dir_all(service_dir)?;
+        }
+        InitSystem::Dinit => {
+            run_service_command(&["dinitctl", "stop", "tomat"], "Tomat service stopped");
+            run_service_command(&["dinitctl", "disable", "tomat"], "Tomat service disabled");
+            std::fs::remove_file(&service_path)?;
+        }
+        InitSystem::Systemd => return uninstall_systemd_service(),
+    }
+
+    println!("✓ Service file removed: {}", service_path.display());
+    println!("\nTomat service uninstalled successfully!");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_systemd_unit_uses_executable() {
+        let unit = systemd_unit("/usr/bin/tomat");
+        assert!(unit.contains("ExecStart=/usr/bin/tomat daemon run"));
+        assert!(unit.contains("PartOf=graphical-session.target"));
+        assert!(unit.contains("RestartPreventExitStatus=75"));
+        assert!(!unit.contains("ExecStartPre"));
+        assert!(!unit.contains("PassEnvironment"));
+        assert!(!unit.contains("NoNewPrivileges=true"));
+        assert!(!unit.contains("RestrictSUIDSGID=true"));
+    }
+
+    #[test]
+    fn test_init_services_use_executable() {
+        let openrc = init_service(InitSystem::Openrc, "/usr/bin/tomat");
+        assert!(openrc.starts_with("#!/sbin/openrc-run\n"));
+        assert!(openrc.contains("command=\"/usr/bin/tomat\""));
+        assert!(openrc.contains("command_args=\"daemon run\""));
+
+        let runit = init_service(InitSystem::Runit, "/usr/bin/tomat");
+        assert!(runit.starts_with("#!/bin/sh\n"));
+        assert!(runit.contains("exec \"/usr/bin/tomat\" daemon run"));
+
+        let dinit = init_service(InitSystem::Dinit, "/usr/bin/tomat");
+        assert!(dinit.contains("type = process"));
+        assert!(dinit.contains("command = /usr/bin/tomat daemon run"));
+    }
+
+    #[test]
+    fn test_merge_unit_dinit_without_sections() {
+        let dinit = init_service(InitSystem::Dinit, "/usr/bin/tomat");
+        let merged = merge_unit(&dinit, "restart-delay = 10\nlogfile = /tmp/tomat.log\n");
+        assert!(merged.contains("restart-delay = 10"));
+        assert!(!merged.contains("restart-delay = 5"));
+        assert!(merged.ends_with("logfile = /tmp/tomat.log\n"));
+    }
+
+    #[test]
+    fn test_merge_unit_overrides_and_appends() {
+        let base = "[Unit]\nDescription=Tomat\n\n[Service]\nExecStart=tomat daemon run\nRestart=always\nRestartSec=5\n";
+        let snippet = "# My overrides\n[Service]\nRestart=on-failure\nEnvironment=FOO=1\n\n[X-Custom]\nKey=value\n";
+
+        let merged = merge_unit(base, snippet);
+        assert_eq!(
+            merged,
+            "[Unit]\nDescription=Tomat\n\n[Service]\nExecStart=tomat daemon run\nRestart=on-failure\nRestartSec=5\nEnvironment=FOO=1\n\n[X-Custom]\nKey=value\n"
+        );
+    }
+
+    #[test]
+    fn test_merge_unit_empty_assignment_removes_key() {
+        let base = "[Service]\nNoNewPrivileges=true\nUMask=0077\nExecStart=tomat daemon run\n";
+        let snippet = "[Service]\nUMask=\nExecStart=\nExecStart=/opt/tomat daemon run\n";
+
+        let merged = merge_unit(base, snippet);
+        assert_eq!(
+            merged,
+            "[Service]\nNoNewPrivileges=true\nExecStart=/opt/tomat daemon run\n"
+        );
+    }
+
+    #[test]
+    fn test_merge_unit_without_snippet_keeps_generated_unit() {
+        let unit = systemd_unit("/usr/bin/tomat");
+        assert_eq!(merge_unit(&unit, ""), unit);
+    }
+}
@@ -0,0 +1,240 @@
+use std::path::PathBuf;
+
+/// Theme that every sound theme falls back to
+const FALLBACK_THEME: &str = "freedesktop";
+
+/// File extensions of theme sounds, in order of preference
+const EXTENSIONS: [&str; 3] = ["oga", "ogg", "wav"];
+
+/// A sound found in a sound theme
+#[derive(Debug, PartialEq)]
+pub enum ThemeSound {
+    File(PathBuf),
+    /// The theme turns this sound off with a `.disabled` file
+    Disabled,
+}
+
+/// Whether a per-transition sound setting names a theme sound rather than a
+/// file, such as "complete" or "alarm-clock-elapsed"
+pub fn is_sound_name(setting: &str) -> bool {
+    !setting.is_empty() && !setting.contains(['/', '.'])
+}
+
+/// Look up a sound by its freedesktop name in the installed sound themes
+pub fn lookup(theme: &str, name: &str) -> Option<ThemeSound> {
+    lookup_in(&sound_dirs(), theme, name)
+}
+
+/// Directories that contain sound themes, most important first
+fn sound_dirs() -> Vec<PathBuf> {
+    let data_dirs = std::env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+
+    dirs::data_dir()
+        .into_iter()
+        .chain(std::env::split_paths(&data_dirs))
+        .map(|dir| dir.join("sounds"))
+        .collect()
+}
+
+/// Find a sound in a theme and the themes it inherits from. A name that is
+/// not found is made less specific, e.g. "alarm-clock-elapsed" falls back to
+/// "alarm-clock" and then "alarm".
+fn lookup_in(sound_dirs: &[PathBuf], theme: &str, name: &str) -> Option<ThemeSound> {
+    let themes = theme_chain(sound_dirs, theme);
+
+    let mut name = name;
+    loop {
+        for theme in &themes {
+            for dir in theme_dirs(sound_dirs, theme) {
+                if dir.join(format!("{}.disabled", name)).exists() {
+                    return Some(ThemeSound::Disabled);
+                }
+                if let Some(path) = EXTENSIONS
+                    .iter()
+                    .map(|ext| dir.join(format!("{}.{}", name, ext)))
+                    .find(|path| path.is_file())
+                {
+                    return Some(ThemeSound::File(path));
+                }
+            }
+        }
+        name = &name[..name.rfind('-')?];
+    }
+}
+
+/// The theme followed by the themes it inherits from, ending with the
+/// fallback theme
+fn theme_chain(sound_dirs: &[PathBuf], theme: &str) -> Vec<String> {
+    let mut chain = vec![theme.to_string()];
+    let mut i = 0;
+    while let Some(theme) = chain.get(i) {
+        let parents = read_index(sound_dirs, theme)
+            .map(|index| index_list(&index, "Inherits"))
+            .unwrap_or_default();
+        for parent in parents {
+            if !chain.contains(&parent) {
+                chain.push(parent);
+            }
+        }
+        i += 1;
+    }
+    if !chain.iter().any(|theme| theme == FALLBACK_THEME) {
+        chain.push(FALLBACK_THEME.to_string());
+    }
+    chain
+}
+
+/// Directories of a theme that hold sounds, in every sound directory that
+/// has the theme
+fn theme_dirs(sound_dirs: &[PathBuf], theme: &str) -> Vec<PathBuf> {
+    sound_dirs
+        .iter()
+        .map(|dir| dir.join(theme))
+        .filter(|dir| dir.is_dir())
+        .flat_map(|dir| {
+            let subdirs = std::fs::read_to_string(dir.join("index.theme"))
+                .map(|index| index_list(&index, "Directories"))
+                .unwrap_or_default();
+            let subdirs = if subdirs.is_empty() {
+                vec!["stereo".to_string()]
+            } else {
+                subdirs
+            };
+            subdirs.into_iter().map(move |subdir| dir.join(subdir))
+        })
+        .collect()
+}
+
+/// Contents of the first index.theme of a theme
+fn read_index(sound_dirs: &[PathBuf], theme: &str) -> Option<String> {
+    sound_dirs
+        .iter()
+        .find_map(|dir| std::fs::read_to_string(dir.join(theme).join("index.theme")).ok())
+}
+
+/// Comma-separated values of a key in the `[Sound Theme]` section of an
+/// index.theme file
+fn index_list(index: &str, key: &str) -> Vec<String> {
+    let mut in_section = false;
+    for line in index.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_section = line == "[Sound Theme]";
+        } else if let Some((name, value)) = line.split_once('=')
+            && in_section
+            && name.trim() == key
+        {
+            return value
+                .split(',')
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+    }
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn write(path: &Path, contents: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn test_is_sound_name() {
+        assert!(is_sound_name("complete"));
+        assert!(is_sound_name("alarm-clock-elapsed"));
+        assert!(!is_sound_name("/usr/share/sounds/bell.wav"));
+        assert!(!is_sound_name("bell.oga"));
+        assert!(!is_sound_name(""));
+    }
+
+    #[test]
+    fn test_index_list() {
+        let index = "[Sound Theme]\nName=Ocean\nInherits=freedesktop, base\n\
+            Directories=stereo\n\n[stereo]\nOutputProfile=stereo\nInherits=ignored\n";
+        assert_eq!(index_list(index, "Inherits"), vec!["freedesktop", "base"]);
+        assert_eq!(index_list(index, "Directories"), vec!["stereo"]);
+        assert!(index_list(index, "Comment").is_empty());
+    }
+
+    #[test]
+    fn test_lookup_follows_inheritance_and_fallbacks() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let user = temp_dir.path().join("user");
+        let system = temp_dir.path().join("system");
+        let dirs = [user.clone(), system.clone()];
+
+        write(
+            &user.join("ocean/index.theme"),
+            "[Sound Theme]\nInherits=base\nDirectories=stereo\n",
+        );
+        write(&user.join("ocean/stereo/bell.disabled"), "");
+        write(&user.join("ocean/stereo/message.wav"), "");
+        write(&system.join("base/stereo/complete.ogg"), "");
+        write(&system.join("base/stereo/complete.oga"), "");
+        write(&system.join("freedesktop/stereo/bell.oga"), "");
+        write(
+            &system.join("freedesktop/stereo/alarm-clock-elapsed.oga"),
+            "",
+        );
+        write(&system.join("freedesktop/stereo/message.oga"), "");
+
+        // The theme's own sound wins over inherited ones
+        assert_eq!(
+            lookup_in(&dirs, "ocean", "message"),
+            Some(ThemeSound::File(user.join("ocean/stereo/message.wav")))
+        );
+        // Inherited themes are searched, preferring .oga files
+        assert_eq!(
+            lookup_in(&dirs, "ocean", "complete"),
+            Some(ThemeSound::File(system.join("base/stereo/complete.oga")))
+        );
+        // The fallback theme comes last
+        assert_eq!(
+            lookup_in(&dirs, "ocean", "alarm-clock-elapsed"),
+            Some(ThemeSound::File(
+                system.join("freedesktop/stereo/alarm-clock-elapsed.oga")
+            ))
+        );
+        // A disabled sound is not looked up any further
+        assert_eq!(
+            lookup_in(&dirs, "ocean", "bell"),
+            Some(ThemeSound::Disabled)
+        );
+        // More specific names fall back to shorter ones
+        assert_eq!(
+            lookup_in(&dirs, "ocean", "message-new-instant"),
+            Some(ThemeSound::File(user.join("ocean/stereo/message.wav")))
+        );
+        assert_eq!(lookup_in(&dirs, "ocean", "dialog-warning"), None);
+        // Unknown themes use the fallback theme
+        assert_eq!(
+            lookup_in(&dirs, "missing", "bell"),
+            Some(ThemeSound::File(system.join("freedesktop/stereo/bell.oga")))
+        );
+    }
+
+    #[test]
+    fn test_theme_chain_ignores_cycles() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dirs = [temp_dir.path().to_path_buf()];
+        write(
+            &temp_dir.path().join("a/index.theme"),
+            "[Sound Theme]\nInherits=b\n",
+        );
+        write(
+            &temp_dir.path().join("b/index.theme"),
+            "[Sound Theme]\nInherits=a\n",
+        );
+
+        assert_eq!(theme_chain(&dirs, "a"), vec!["a", "b", "freedesktop"]);
+    }
+}
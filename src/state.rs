@@ -0,0 +1,199 @@
+use std::path::PathBuf;
+
+use crate::config::StateBackend;
+use crate::error::TomatError;
+use crate::history::HistoryStore;
+use crate::timer::TimerState;
+
+/// Metadata key of the timer state in the history database
+const STATE_KEY: &str = "timer_state";
+
+/// Storage for the daemon's timer state between restarts
+pub trait StateStore: Send {
+    /// Load the saved state, if there is one
+    fn load(&mut self) -> Result<Option<TimerState>, TomatError>;
+    /// Save the current state
+    fn save(&mut self, state: &TimerState) -> Result<(), TomatError>;
+    /// Forget the saved state, e.g. after it failed to parse
+    fn clear(&mut self) -> Result<(), TomatError>;
+    /// Where the state is kept, for log messages
+    fn describe(&self) -> String;
+}
+
+/// JSON file in the runtime directory
+pub struct FileStore {
+    path: PathBuf,
+}
+
+impl FileStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+impl StateStore for FileStore {
+    fn load(&mut self) -> Result<Option<TimerState>, TomatError> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        let contents = std::fs::read_to_string(&self.path)?;
+        Ok(Some(serde_json::from_str(&contents)?))
+    }
+
+    fn save(&mut self, state: &TimerState) -> Result<(), TomatError> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string_pretty(state)?)?;
+        Ok(())
+    }
+
+    fn clear(&mut self) -> Result<(), TomatError> {
+        match std::fs::remove_file(&self.path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn describe(&self) -> String {
+        format!("{:?}", self.path)
+    }
+}
+
+/// Row in the metadata table of the history database
+pub struct SqliteStore {
+    store: HistoryStore,
+}
+
+impl SqliteStore {
+    pub fn new(store: HistoryStore) -> Self {
+        Self { store }
+    }
+}
+
+impl StateStore for SqliteStore {
+    fn load(&mut self) -> Result<Option<TimerState>, TomatError> {
+        match self.store.get_meta(STATE_KEY)? {
+            Some(json) => Ok(Some(serde_json::from_str(&json)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn save(&mut self, state: &TimerState) -> Result<(), TomatError> {
+        self.store
+            .set_meta(STATE_KEY, &serde_json::to_string(state)?)?;
+        Ok(())
+    }
+
+    fn clear(&mut self) -> Result<(), TomatError> {
+        self.store.delete_meta(STATE_KEY)?;
+        Ok(())
+    }
+
+    fn describe(&self) -> String {
+        "the history database".to_string()
+    }
+}
+
+/// Nothing is persisted, every daemon starts fresh
+#[derive(Default)]
+pub struct MemoryStore;
+
+impl StateStore for MemoryStore {
+    fn load(&mut self) -> Result<Option<TimerState>, TomatError> {
+        Ok(None)
+    }
+
+    fn save(&mut self, _state: &TimerState) -> Result<(), TomatError> {
+        Ok(())
+    }
+
+    fn clear(&mut self) -> Result<(), TomatError> {
+        Ok(())
+    }
+
+    fn describe(&self) -> String {
+        "memory".to_string()
+    }
+}
+
+/// Open the configured state store, using `file_path` for the file backend
+///
+/// Falls back to the file if the history database cannot be opened, so a
+/// broken database does not also lose the timer state.
+pub fn open(backend: &StateBackend, file_path: PathBuf) -> Box<dyn StateStore> {
+    match backend {
+        StateBackend::File => Box::new(FileStore::new(file_path)),
+        StateBackend::Sqlite => match HistoryStore::open_default() {
+            Ok(store) => Box::new(SqliteStore::new(store)),
+            Err(e) => {
+                eprintln!(
+                    "Failed to open history database for timer state: {}. Using the state file instead.",
+                    e
+                );
+                Box::new(FileStore::new(file_path))
+            }
+        },
+        StateBackend::Memory => Box::new(MemoryStore),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_state() -> TimerState {
+        let mut state = TimerState::new(30.0, 10.0, 20.0, 3);
+        state.current_session_count = 2;
+        state
+    }
+
+    #[test]
+    fn test_file_store_round_trip() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut store = FileStore::new(temp_dir.path().join("tomat.state"));
+        assert!(store.load().unwrap().is_none());
+
+        store.save(&sample_state()).unwrap();
+        let loaded = store.load().unwrap().unwrap();
+        assert_eq!(loaded.work_duration, 30.0);
+        assert_eq!(loaded.current_session_count, 2);
+
+        store.clear().unwrap();
+        assert!(store.load().unwrap().is_none());
+        store.clear().unwrap();
+    }
+
+    #[test]
+    fn test_file_store_rejects_corrupted_state() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("tomat.state");
+        std::fs::write(&path, "{ not json").unwrap();
+
+        let mut store = FileStore::new(path);
+        assert!(store.load().is_err());
+    }
+
+    #[test]
+    fn test_sqlite_store_round_trip() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let history = HistoryStore::open(&temp_dir.path().join("history.db")).unwrap();
+        let mut store = SqliteStore::new(history);
+        assert!(store.load().unwrap().is_none());
+
+        store.save(&sample_state()).unwrap();
+        let loaded = store.load().unwrap().unwrap();
+        assert_eq!(loaded.break_duration, 10.0);
+        assert_eq!(loaded.current_session_count, 2);
+
+        store.clear().unwrap();
+        assert!(store.load().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_memory_store_keeps_nothing() {
+        let mut store = MemoryStore;
+        store.save(&sample_state()).unwrap();
+        assert!(store.load().unwrap().is_none());
+    }
+}
@@ -0,0 +1,841 @@
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveTime};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use crate::cli::StatsGroup;
+use crate::config::{EfficiencyBasis, StatsConfig};
+use crate::error::TomatError;
+use crate::history::{HistoryStore, SessionRecord, day_bounds, format_duration, session_date};
+
+/// Completed work of a local day, or of a task or tag
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Focus {
+    pub sessions: u32,
+    pub seconds: u64,
+}
+
+/// Planned and actual work of a set of work sessions, finished or not
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Effort {
+    pub started: u32,
+    pub completed: u32,
+    pub worked_seconds: u64,
+    pub planned_seconds: u64,
+    pub interruptions: u32,
+}
+
+impl Effort {
+    /// Sum up the given work sessions
+    pub fn of<'a>(sessions: impl IntoIterator<Item = &'a SessionRecord>) -> Self {
+        let mut effort = Self::default();
+        for session in sessions {
+            effort.started += 1;
+            effort.completed += u32::from(session.outcome == "completed");
+            effort.worked_seconds += session.duration_seconds;
+            effort.planned_seconds += session.planned_seconds;
+            effort.interruptions += session.interruptions;
+        }
+        effort
+    }
+
+    /// Average number of pauses per work session
+    pub fn interruptions_per_session(&self) -> f64 {
+        if self.started == 0 {
+            return 0.0;
+        }
+        f64::from(self.interruptions) / f64::from(self.started)
+    }
+
+    /// The focus efficiency in percent by the configured definition, or
+    /// None without work sessions
+    pub fn efficiency(&self, config: &StatsConfig) -> Option<f64> {
+        if self.started == 0 {
+            return None;
+        }
+        let base = match config.efficiency {
+            EfficiencyBasis::Minutes if self.planned_seconds == 0 => 100.0,
+            EfficiencyBasis::Minutes => {
+                self.worked_seconds as f64 / self.planned_seconds as f64 * 100.0
+            }
+            EfficiencyBasis::Sessions => {
+                f64::from(self.completed) / f64::from(self.started) * 100.0
+            }
+        };
+        let penalty = config.pause_penalty * self.interruptions_per_session();
+        Some((base - penalty).clamp(0.0, 100.0))
+    }
+}
+
+/// Cell shown in the heatmap, from no focus time to the most
+const CELL: &str = "■";
+/// 256-color palette entries for the heatmap levels, grey for empty days
+const LEVEL_COLORS: [u8; 5] = [237, 22, 28, 34, 40];
+/// Shades for the heatmap levels when colors are off
+const LEVEL_SHADES: [char; 5] = ['·', '░', '▒', '▓', '█'];
+/// Weekday labels of the heatmap rows, Monday first
+const ROW_LABELS: [&str; 7] = ["Mon", "", "Wed", "", "Fri", "", "Sun"];
+/// Label of the sessions without a task or tag
+const UNLABELED: &str = "(none)";
+
+/// The work sessions started in the local days `from..=to`, days starting
+/// at `day_start`, including the ones ended early
+pub fn work_sessions(
+    store: &HistoryStore,
+    from: NaiveDate,
+    to: NaiveDate,
+    day_start: NaiveTime,
+) -> rusqlite::Result<Vec<SessionRecord>> {
+    // Sessions done in another timezone can fall on a day up to a day away
+    // from the current one, so look a day further and sort them out by date
+    let (start, _) = day_bounds(from, day_start);
+    let (_, end) = day_bounds(to, day_start);
+    let mut sessions = store.sessions_between(start.saturating_sub(86400), end + 86400)?;
+    sessions.retain(|session| {
+        session.phase == "work"
+            && session_date(session, day_start).is_some_and(|date| (from..=to).contains(&date))
+    });
+    Ok(sessions)
+}
+
+/// The current day, which starts at `day_start`
+pub fn today(day_start: NaiveTime) -> NaiveDate {
+    (Local::now().naive_local() - day_start.signed_duration_since(NaiveTime::MIN)).date()
+}
+
+/// Whether a work session ran until its end
+fn is_completed(session: &&SessionRecord) -> bool {
+    session.outcome == "completed"
+}
+
+/// Sum the completed work sessions of each local day, days starting at
+/// `day_start`
+pub fn daily_focus(sessions: &[SessionRecord], day_start: NaiveTime) -> BTreeMap<NaiveDate, Focus> {
+    let mut days = BTreeMap::new();
+    for session in sessions.iter().filter(is_completed) {
+        let Some(date) = session_date(session, day_start) else {
+            continue;
+        };
+        let day: &mut Focus = days.entry(date).or_default();
+        day.sessions += 1;
+        day.seconds += session.duration_seconds;
+    }
+    days
+}
+
+/// Sum the completed work sessions of each task or tag, most focused first.
+/// Sessions with several tags count towards each of them.
+pub fn focus_by(sessions: &[SessionRecord], group: StatsGroup) -> Vec<(String, Focus)> {
+    let mut groups: BTreeMap<String, Focus> = BTreeMap::new();
+    for session in sessions.iter().filter(is_completed) {
+        let labels = match group {
+            StatsGroup::Task => session.task.iter().cloned().collect(),
+            StatsGroup::Tag => session.tags.clone(),
+        };
+        let labels = if labels.is_empty() {
+            vec![UNLABELED.to_string()]
+        } else {
+            labels
+        };
+        for label in labels {
+            let focus = groups.entry(label).or_default();
+            focus.sessions += 1;
+            focus.seconds += session.duration_seconds;
+        }
+    }
+
+    let mut groups: Vec<(String, Focus)> = groups.into_iter().collect();
+    groups.sort_by_key(|(_, focus)| std::cmp::Reverse(focus.seconds));
+    groups
+}
+
+/// Render the focus time per task or tag as a table, with the share of the
+/// total focus time of each
+pub fn render_breakdown(
+    sessions: &[SessionRecord],
+    group: StatsGroup,
+    from: NaiveDate,
+    today: NaiveDate,
+) -> String {
+    let (name, header) = match group {
+        StatsGroup::Task => ("task", "TASK"),
+        StatsGroup::Tag => ("tag", "TAG"),
+    };
+    let mut text = format!("Focus by {} from {} to {}\n\n", name, from, today);
+    let total: u64 = sessions
+        .iter()
+        .filter(is_completed)
+        .map(|session| session.duration_seconds)
+        .sum();
+    if total == 0 {
+        text.push_str("No completed work sessions");
+        return text;
+    }
+
+    let groups = focus_by(sessions, group);
+    let width = groups
+        .iter()
+        .map(|(label, _)| label.chars().count())
+        .chain([header.len()])
+        .max()
+        .unwrap_or_default();
+    let _ = writeln!(
+        text,
+        "{:<width$}  {:>9}  {:>8}  {:>5}",
+        header, "POMODOROS", "FOCUSED", "SHARE"
+    );
+    for (label, focus) in &groups {
+        let _ = writeln!(
+            text,
+            "{:<width$}  {:>9}  {:>8}  {:>4}%",
+            label,
+            focus.sessions,
+            format_duration(focus.seconds),
+            (focus.seconds * 100 + total / 2) / total
+        );
+    }
+    text.truncate(text.trim_end().len());
+    text
+}
+
+/// Heatmap level of a day, from 0 (no focus) to 4 (as much as the best day)
+fn level(seconds: u64, max: u64) -> usize {
+    if seconds == 0 || max == 0 {
+        return 0;
+    }
+    (seconds * 4).div_ceil(max).clamp(1, 4) as usize
+}
+
+/// A heatmap cell of the given level
+fn cell(level: usize, color: bool) -> String {
+    if color {
+        format!("\x1b[38;5;{}m{}\x1b[0m", LEVEL_COLORS[level], CELL)
+    } else {
+        LEVEL_SHADES[level].to_string()
+    }
+}
+
+/// First day of the last `weeks` calendar weeks up to `today`, a Monday
+fn first_monday(today: NaiveDate, weeks: u32) -> NaiveDate {
+    today
+        - Duration::days(i64::from(today.weekday().num_days_from_monday()))
+        - Duration::weeks(i64::from(weeks) - 1)
+}
+
+/// Render the focus time of the last `weeks` weeks up to `today` as a
+/// calendar heatmap, with a column per week and a row per weekday
+pub fn render_heatmap(
+    days: &BTreeMap<NaiveDate, Focus>,
+    today: NaiveDate,
+    weeks: u32,
+    color: bool,
+) -> String {
+    let first_monday = first_monday(today, weeks);
+    let mondays: Vec<NaiveDate> = (0..weeks)
+        .map(|week| first_monday + Duration::weeks(i64::from(week)))
+        .collect();
+    let max = days
+        .range(first_monday..=today)
+        .map(|(_, day)| day.seconds)
+        .max()
+        .unwrap_or(0);
+
+    // Month names above the first week starting in that month, if they fit
+    let mut months = String::new();
+    for (week, monday) in mondays.iter().enumerate() {
+        let column = week * 2;
+        let new_month = week == 0 || monday.month() != mondays[week - 1].month();
+        if new_month && months.chars().count() <= column {
+            let padding = column - months.chars().count();
+            months.push_str(&" ".repeat(padding));
+            months.push_str(&monday.format("%b").to_string());
+        }
+    }
+    let mut text = format!("    {}\n", months.trim_end());
+
+    for (row, label) in ROW_LABELS.iter().enumerate() {
+        let _ = write!(text, "{:<4}", label);
+        for monday in &mondays {
+            let date = *monday + Duration::days(row as i64);
+            if date > today {
+                break;
+            }
+            let seconds = days.get(&date).map_or(0, |day| day.seconds);
+            let _ = write!(text, "{} ", cell(level(seconds, max), color));
+        }
+        text.truncate(text.trim_end().len());
+        text.push('\n');
+    }
+
+    let legend: Vec<String> = (0..LEVEL_COLORS.len())
+        .map(|level| cell(level, color))
+        .collect();
+    let _ = write!(text, "\n    Less {} More", legend.join(" "));
+    text
+}
+
+/// Format an efficiency for display, or "-" when there is none
+fn format_efficiency(efficiency: Option<f64>) -> String {
+    efficiency.map_or_else(
+        || "-".to_string(),
+        |efficiency| format!("{:.0}%", efficiency),
+    )
+}
+
+/// Render the focus efficiency of each day with work sessions as a table,
+/// with the totals of `from..=today` below
+pub fn render_efficiency(
+    sessions: &[SessionRecord],
+    config: &StatsConfig,
+    from: NaiveDate,
+    today: NaiveDate,
+) -> String {
+    let definition = match config.efficiency {
+        EfficiencyBasis::Minutes => "minutes worked out of minutes planned",
+        EfficiencyBasis::Sessions => "work sessions completed out of sessions started",
+    };
+    let mut text = format!(
+        "Focus efficiency from {} to {} ({})\n\n",
+        from, today, definition
+    );
+    if sessions.is_empty() {
+        text.push_str("No work sessions");
+        return text;
+    }
+
+    let day_start = config.day_start_time();
+    let mut days: BTreeMap<NaiveDate, Vec<&SessionRecord>> = BTreeMap::new();
+    for session in sessions {
+        if let Some(date) = session_date(session, day_start) {
+            days.entry(date).or_default().push(session);
+        }
+    }
+
+    let _ = writeln!(
+        text,
+        "{:<10}  {:>8}  {:>8}  {:>8}  {:>6}  {:>10}",
+        "DATE", "SESSIONS", "WORKED", "PLANNED", "PAUSES", "EFFICIENCY"
+    );
+    let mut row = |label: &str, effort: Effort| {
+        let _ = writeln!(
+            text,
+            "{:<10}  {:>8}  {:>8}  {:>8}  {:>6.1}  {:>10}",
+            label,
+            format!("{}/{}", effort.completed, effort.started),
+            format_duration(effort.worked_seconds),
+            format_duration(effort.planned_seconds),
+            effort.interruptions_per_session(),
+            format_efficiency(effort.efficiency(config))
+        );
+    };
+    for (date, sessions) in &days {
+        row(&date.to_string(), Effort::of(sessions.iter().copied()));
+    }
+    row("Total", Effort::of(sessions));
+    text.truncate(text.trim_end().len());
+    text
+}
+
+/// Render totals of the focus time in `days`, covering `from..=today`, and
+/// the efficiency of the work sessions in that time
+pub fn summary(
+    days: &BTreeMap<NaiveDate, Focus>,
+    effort: &Effort,
+    config: &StatsConfig,
+    from: NaiveDate,
+    today: NaiveDate,
+) -> String {
+    let days_in_range = || days.range(from..=today);
+    let sessions: u32 = days_in_range().map(|(_, day)| day.sessions).sum();
+    let seconds: u64 = days_in_range().map(|(_, day)| day.seconds).sum();
+    let active_days = days_in_range().filter(|(_, day)| day.seconds > 0).count() as u64;
+    let best_day = days_in_range()
+        .max_by_key(|(date, day)| (day.seconds, std::cmp::Reverse(**date)))
+        .filter(|(_, day)| day.seconds > 0)
+        .map(|(date, day)| format!("{} ({})", date, format_duration(day.seconds)))
+        .unwrap_or_else(|| "-".to_string());
+
+    // Today only breaks the streak once it is over
+    let focused = |date: NaiveDate| days.get(&date).is_some_and(|day| day.seconds > 0);
+    let mut streak = 0;
+    let mut date = today;
+    if !focused(today) {
+        date = date.pred_opt().unwrap_or(date);
+    }
+    while date >= from && focused(date) {
+        streak += 1;
+        date = match date.pred_opt() {
+            Some(date) => date,
+            None => break,
+        };
+    }
+
+    format!(
+        "Focus from {} to {}\n\n\
+         Pomodoros completed: {}\n\
+         Focused time:        {}\n\
+         Active days:         {}\n\
+         Average per day:     {}\n\
+         Best day:            {}\n\
+         Current streak:      {} day{}\n\
+         Efficiency:          {}\n\
+         Pauses per session:  {:.1}",
+        from,
+        today,
+        sessions,
+        format_duration(seconds),
+        active_days,
+        format_duration(seconds.checked_div(active_days).unwrap_or(0)),
+        best_day,
+        streak,
+        if streak == 1 { "" } else { "s" },
+        format_efficiency(effort.efficiency(config)),
+        effort.interruptions_per_session(),
+    )
+}
+
+/// Render the pomodoros and focus time of today, this week and this month
+fn periods(days: &BTreeMap<NaiveDate, Focus>, today: NaiveDate) -> String {
+    let month_start = today.with_day(1).unwrap_or(today);
+    [
+        ("Today:", today),
+        ("This week:", first_monday(today, 1)),
+        ("This month:", month_start),
+    ]
+    .iter()
+    .map(|(label, from)| {
+        let (sessions, seconds) = days
+            .range(*from..=today)
+            .fold((0, 0), |(sessions, seconds), (_, day)| {
+                (sessions + day.sessions, seconds + day.seconds)
+            });
+        format!(
+            "{:<12}{} pomodoro{} ({})",
+            label,
+            sessions,
+            if sessions == 1 { "" } else { "s" },
+            format_duration(seconds)
+        )
+    })
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+/// Render the statistics of the last `weeks` calendar weeks, this week
+/// included: the summary, the breakdown by task or tag, or the efficiency of
+/// each day, with the heatmap on top if asked for
+pub fn show_stats(
+    weeks: u32,
+    heatmap: bool,
+    by: Option<StatsGroup>,
+    efficiency: bool,
+    config: &StatsConfig,
+    color: bool,
+) -> Result<String, TomatError> {
+    let day_start = config.day_start_time();
+    let today = today(day_start);
+    let from = first_monday(today, weeks);
+    let store = HistoryStore::open_default()?;
+    let sessions = work_sessions(&store, from, today, day_start)?;
+    let days = daily_focus(&sessions, day_start);
+
+    let mut text = String::new();
+    if heatmap {
+        text.push_str(&render_heatmap(&days, today, weeks, color));
+        text.push_str("\n\n");
+    }
+    if efficiency {
+        text.push_str(&render_efficiency(&sessions, config, from, today));
+    } else if let Some(group) = by {
+        text.push_str(&render_breakdown(&sessions, group, from, today));
+    } else {
+        // The month may have started before the weeks covered
+        let month_start = today.with_day(1).unwrap_or(today);
+        let month = if month_start < from {
+            daily_focus(
+                &work_sessions(&store, month_start, today, day_start)?,
+                day_start,
+            )
+        } else {
+            days.clone()
+        };
+        text.push_str(&periods(&month, today));
+        text.push_str("\n\n");
+
+        let effort = Effort::of(&sessions);
+        text.push_str(&summary(&days, &effort, config, from, today));
+    }
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::{NewSession, local_day_bounds};
+    use chrono::{TimeZone, Timelike};
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn focus(minutes: &[(&str, u64)]) -> BTreeMap<NaiveDate, Focus> {
+        minutes
+            .iter()
+            .map(|&(day, minutes)| {
+                (
+                    date(day),
+                    Focus {
+                        sessions: (minutes / 25) as u32,
+                        seconds: minutes * 60,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_levels_scale_to_the_best_day() {
+        assert_eq!(level(0, 100), 0);
+        assert_eq!(level(1, 100), 1);
+        assert_eq!(level(25, 100), 1);
+        assert_eq!(level(26, 100), 2);
+        assert_eq!(level(75, 100), 3);
+        assert_eq!(level(100, 100), 4);
+        assert_eq!(level(0, 0), 0);
+    }
+
+    fn session(task: Option<&str>, tags: &[&str], minutes: u64) -> SessionRecord {
+        SessionRecord {
+            id: 0,
+            phase: "work".to_string(),
+            started_at: 0,
+            ended_at: minutes * 60,
+            duration_seconds: minutes * 60,
+            outcome: "completed".to_string(),
+            task: task.map(str::to_string),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            planned_seconds: minutes * 60,
+            interruptions: 0,
+            utc_offset: None,
+            notes: None,
+        }
+    }
+
+    /// Work sessions of a day: two completed, one with pauses, and one
+    /// skipped after 10 of 25 minutes
+    fn day_of_work() -> [SessionRecord; 3] {
+        [
+            session(None, &[], 25),
+            SessionRecord {
+                interruptions: 2,
+                ..session(None, &[], 25)
+            },
+            SessionRecord {
+                outcome: "skipped".to_string(),
+                planned_seconds: 25 * 60,
+                interruptions: 1,
+                ..session(None, &[], 10)
+            },
+        ]
+    }
+
+    #[test]
+    fn test_daily_focus_counts_completed_work() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = HistoryStore::open(&temp_dir.path().join("history.db")).unwrap();
+        let today = Local::now().date_naive();
+        let (start, _) = local_day_bounds(today);
+
+        store
+            .record(&NewSession::new(
+                "work",
+                start + 100,
+                start + 1600,
+                "completed",
+            ))
+            .unwrap();
+        store
+            .record(&NewSession::new(
+                "work",
+                start + 1900,
+                start + 3400,
+                "completed",
+            ))
+            .unwrap();
+        store
+            .record(&NewSession::new(
+                "work",
+                start + 3700,
+                start + 3800,
+                "skipped",
+            ))
+            .unwrap();
+        store
+            .record(&NewSession::new(
+                "break",
+                start + 1600,
+                start + 1900,
+                "completed",
+            ))
+            .unwrap();
+
+        let days = daily_focus(
+            &work_sessions(&store, today, today, NaiveTime::MIN).unwrap(),
+            NaiveTime::MIN,
+        );
+        assert_eq!(
+            days.get(&today),
+            Some(&Focus {
+                sessions: 2,
+                seconds: 3000
+            })
+        );
+        assert_eq!(days.len(), 1);
+    }
+
+    #[test]
+    fn test_days_start_at_day_start_in_the_recorded_timezone() {
+        // 2026-03-04 01:30 UTC, which is 10:30 the same day in Tokyo
+        let started_at = 1772587800;
+        let late = SessionRecord {
+            started_at,
+            utc_offset: Some(0),
+            ..session(None, &[], 25)
+        };
+        let tokyo = SessionRecord {
+            started_at,
+            utc_offset: Some(9 * 3600),
+            ..session(None, &[], 50)
+        };
+        let sessions = [late, tokyo];
+
+        let days = daily_focus(&sessions, NaiveTime::MIN);
+        assert_eq!(days.len(), 1);
+        assert_eq!(days[&date("2026-03-04")].sessions, 2);
+
+        // Work after midnight counts towards the previous day until 04:00
+        let day_start = NaiveTime::from_hms_opt(4, 0, 0).unwrap();
+        let days = daily_focus(&sessions, day_start);
+        assert_eq!(days[&date("2026-03-03")].seconds, 25 * 60);
+        assert_eq!(days[&date("2026-03-04")].seconds, 50 * 60);
+    }
+
+    #[test]
+    fn test_work_sessions_respect_day_start() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = HistoryStore::open(&temp_dir.path().join("history.db")).unwrap();
+        let yesterday = Local::now().date_naive().pred_opt().unwrap();
+        let day_start = NaiveTime::from_hms_opt(4, 0, 0).unwrap();
+
+        // 02:00 and 06:00 local time yesterday
+        for hour in [2, 6] {
+            let at = Local
+                .from_local_datetime(&yesterday.and_hms_opt(hour, 0, 0).unwrap())
+                .earliest()
+                .unwrap()
+                .timestamp() as u64;
+            store
+                .record(&NewSession::new("work", at, at + 1500, "completed"))
+                .unwrap();
+        }
+
+        let sessions = work_sessions(&store, yesterday, yesterday, day_start).unwrap();
+        assert_eq!(sessions.len(), 1);
+        let started = Local
+            .timestamp_opt(sessions[0].started_at as i64, 0)
+            .unwrap();
+        assert_eq!(started.hour(), 6);
+        assert_eq!(
+            sessions[0].utc_offset,
+            Some(started.offset().local_minus_utc())
+        );
+    }
+
+    #[test]
+    fn test_render_heatmap() {
+        // A Wednesday, so the last column ends after three days
+        let today = date("2026-03-04");
+        let days = focus(&[("2026-02-23", 100), ("2026-03-02", 25), ("2026-03-04", 50)]);
+
+        let heatmap = render_heatmap(&days, today, 3, false);
+        let lines: Vec<&str> = heatmap.lines().collect();
+        assert_eq!(lines[0], "    Feb Mar");
+        assert_eq!(lines[1], "Mon · █ ░");
+        assert_eq!(lines[2], "    · · ·");
+        assert_eq!(lines[3], "Wed · · ▒");
+        // Days after today are left empty
+        assert_eq!(lines[4], "    · ·");
+        assert_eq!(lines[7], "Sun · ·");
+        assert_eq!(lines[9], "    Less · ░ ▒ ▓ █ More");
+
+        // Colored cells use the 256-color palette
+        let heatmap = render_heatmap(&days, today, 3, true);
+        assert!(heatmap.contains("\x1b[38;5;40m■\x1b[0m"));
+        assert!(heatmap.contains("\x1b[38;5;237m■\x1b[0m"));
+    }
+
+    #[test]
+    fn test_summary() {
+        let today = date("2026-03-04");
+        let days = focus(&[
+            ("2026-02-20", 50),
+            ("2026-03-02", 75),
+            ("2026-03-03", 100),
+            ("2026-03-04", 25),
+        ]);
+
+        let effort = Effort::of(&day_of_work());
+        let config = StatsConfig::default();
+        let text = summary(&days, &effort, &config, date("2026-03-01"), today);
+        assert!(text.starts_with("Focus from 2026-03-01 to 2026-03-04"));
+        // Days before the period are left out
+        assert!(text.contains("Pomodoros completed: 8\n"));
+        assert!(text.contains("Focused time:        3h 20m\n"));
+        assert!(text.contains("Active days:         3\n"));
+        assert!(text.contains("Average per day:     1h 06m\n"));
+        assert!(text.contains("Best day:            2026-03-03 (1h 40m)\n"));
+        assert!(text.contains("Current streak:      3 days\n"));
+        assert!(text.contains("Efficiency:          80%\n"));
+        assert!(text.ends_with("Pauses per session:  1.0"));
+
+        // Today without focus yet keeps yesterday's streak
+        let days = focus(&[("2026-03-03", 25)]);
+        let text = summary(
+            &days,
+            &Effort::default(),
+            &config,
+            date("2026-03-01"),
+            today,
+        );
+        assert!(text.contains("Current streak:      1 day\n"));
+        assert!(text.contains("Efficiency:          -\n"));
+    }
+
+    #[test]
+    fn test_periods() {
+        // A Wednesday
+        let today = date("2026-03-04");
+        let days = focus(&[
+            ("2026-02-27", 50),
+            ("2026-03-01", 75),
+            ("2026-03-03", 100),
+            ("2026-03-04", 25),
+        ]);
+
+        assert_eq!(
+            periods(&days, today),
+            "Today:      1 pomodoro (25m)\n\
+             This week:  5 pomodoros (2h 05m)\n\
+             This month: 8 pomodoros (3h 20m)"
+        );
+    }
+
+    #[test]
+    fn test_efficiency_definitions() {
+        let effort = Effort::of(&day_of_work());
+        assert_eq!(effort.started, 3);
+        assert_eq!(effort.completed, 2);
+        assert_eq!(effort.worked_seconds, 60 * 60);
+        assert_eq!(effort.planned_seconds, 75 * 60);
+        assert_eq!(effort.interruptions_per_session(), 1.0);
+
+        let mut config = StatsConfig::default();
+        assert_eq!(effort.efficiency(&config), Some(80.0));
+        config.pause_penalty = 5.0;
+        assert_eq!(effort.efficiency(&config), Some(75.0));
+        config.efficiency = EfficiencyBasis::Sessions;
+        config.pause_penalty = 0.0;
+        let efficiency = effort.efficiency(&config).unwrap();
+        assert!((efficiency - 200.0 / 3.0).abs() < 1e-9);
+
+        // The penalty cannot make it negative
+        config.pause_penalty = 100.0;
+        assert_eq!(effort.efficiency(&config), Some(0.0));
+        assert_eq!(Effort::default().efficiency(&config), None);
+    }
+
+    #[test]
+    fn test_render_efficiency() {
+        let config = StatsConfig::default();
+        let text = render_efficiency(
+            &day_of_work(),
+            &config,
+            date("2026-03-02"),
+            date("2026-03-04"),
+        );
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(
+            lines[0],
+            "Focus efficiency from 2026-03-02 to 2026-03-04 (minutes worked out of minutes planned)"
+        );
+        assert_eq!(
+            lines[2].split_whitespace().collect::<Vec<_>>(),
+            [
+                "DATE",
+                "SESSIONS",
+                "WORKED",
+                "PLANNED",
+                "PAUSES",
+                "EFFICIENCY"
+            ]
+        );
+        // All sessions started on the same day, so it matches the total
+        assert_eq!(lines.len(), 5);
+        assert_eq!(
+            lines[3].split_whitespace().skip(1).collect::<Vec<_>>(),
+            ["2/3", "1h", "00m", "1h", "15m", "1.0", "80%"]
+        );
+        assert!(lines[4].starts_with("Total "));
+        assert_eq!(lines[4][10..], lines[3][10..]);
+
+        let text = render_efficiency(&[], &config, date("2026-03-02"), date("2026-03-04"));
+        assert!(text.ends_with("No work sessions"));
+    }
+
+    #[test]
+    fn test_focus_by_tag_and_task() {
+        let sessions = [
+            session(Some("chapter 2"), &["thesis", "writing"], 50),
+            session(Some("chapter 2"), &["thesis"], 25),
+            session(None, &[], 25),
+        ];
+
+        let tags = focus_by(&sessions, StatsGroup::Tag);
+        let tags: Vec<(&str, u32, u64)> = tags
+            .iter()
+            .map(|(tag, focus)| (tag.as_str(), focus.sessions, focus.seconds / 60))
+            .collect();
+        assert_eq!(
+            tags,
+            [("thesis", 2, 75), ("writing", 1, 50), ("(none)", 1, 25)]
+        );
+
+        let tasks = focus_by(&sessions, StatsGroup::Task);
+        assert_eq!(tasks[0].0, "chapter 2");
+        assert_eq!(tasks[0].1.sessions, 2);
+        assert_eq!(tasks[1].0, "(none)");
+    }
+
+    #[test]
+    fn test_render_breakdown() {
+        let sessions = [
+            session(Some("chapter 2"), &["thesis"], 75),
+            session(None, &[], 25),
+        ];
+        let text = render_breakdown(
+            &sessions,
+            StatsGroup::Task,
+            date("2026-03-02"),
+            date("2026-03-04"),
+        );
+        assert_eq!(
+            text,
+            "Focus by task from 2026-03-02 to 2026-03-04\n\n\
+             TASK       POMODOROS   FOCUSED  SHARE\n\
+             chapter 2          1    1h 15m    75%\n\
+             (none)             1       25m    25%"
+        );
+
+        let text = render_breakdown(&[], StatsGroup::Tag, date("2026-03-02"), date("2026-03-04"));
+        assert!(text.ends_with("No completed work sessions"));
+    }
+}
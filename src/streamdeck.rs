@@ -0,0 +1,446 @@
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+
+use crate::ServerResponse;
+use crate::config::DisplayConfig;
+use crate::error::TomatError;
+use crate::server::send_command;
+
+/// Key showing the countdown, which toggles the timer when pressed
+pub const ACTION_TIMER: &str = "io.github.jolars.tomat.timer";
+/// Key that toggles the timer
+pub const ACTION_TOGGLE: &str = "io.github.jolars.tomat.toggle";
+/// Key that skips to the next phase
+pub const ACTION_SKIP: &str = "io.github.jolars.tomat.skip";
+
+/// WebSocket opcodes
+const OP_CONTINUATION: u8 = 0x0;
+const OP_TEXT: u8 = 0x1;
+const OP_CLOSE: u8 = 0x8;
+const OP_PING: u8 = 0x9;
+const OP_PONG: u8 = 0xa;
+
+/// The arguments the Stream Deck application starts a plugin with
+#[derive(Debug, PartialEq)]
+pub struct Launch {
+    pub port: u16,
+    pub plugin_uuid: String,
+    pub register_event: String,
+}
+
+/// Parse the `-port`, `-pluginUUID` and `-registerEvent` arguments passed by
+/// the Stream Deck application, ignoring others such as `-info`
+pub fn parse_launch_args(args: &[String]) -> Result<Launch, TomatError> {
+    let mut values: HashMap<&str, &str> = HashMap::new();
+    for pair in args.chunks(2) {
+        if let [name, value] = pair {
+            values.insert(name.trim_start_matches('-'), value);
+        }
+    }
+    let get = |name: &str| {
+        values
+            .get(name)
+            .map(|value| value.to_string())
+            .ok_or_else(|| {
+                TomatError::InvalidArgument(format!(
+                    "Missing -{} (streamdeck-bridge is started by the Stream Deck application)",
+                    name
+                ))
+            })
+    };
+    let port = get("port")?;
+    Ok(Launch {
+        port: port
+            .parse()
+            .map_err(|_| TomatError::InvalidArgument(format!("Invalid -port '{}'", port)))?,
+        plugin_uuid: get("pluginUUID")?,
+        register_event: get("registerEvent")?,
+    })
+}
+
+/// Daemon command run when a key of an action is pressed
+fn command_for(action: &str) -> Option<&'static str> {
+    match action {
+        ACTION_TIMER | ACTION_TOGGLE => Some("toggle"),
+        ACTION_SKIP => Some("skip"),
+        _ => None,
+    }
+}
+
+/// Encode bytes as standard base64, for the handshake key
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::new();
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &byte)| n | u32::from(byte) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Bytes that only need to differ between connections, for the handshake
+/// key and frame masks
+fn nonce() -> [u8; 16] {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    (nanos ^ u128::from(std::process::id()) << 64).to_le_bytes()
+}
+
+/// A masked client frame, as clients have to send them
+fn encode_frame(opcode: u8, payload: &[u8], mask: [u8; 4]) -> Vec<u8> {
+    let mut frame = vec![0x80 | opcode];
+    match payload.len() {
+        len @ 0..=125 => frame.push(0x80 | len as u8),
+        len @ 126..=0xffff => {
+            frame.push(0x80 | 126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(0x80 | 127);
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(&mask);
+    frame.extend(
+        payload
+            .iter()
+            .enumerate()
+            .map(|(i, byte)| byte ^ mask[i % 4]),
+    );
+    frame
+}
+
+/// Read a frame, returning its opcode, its unmasked payload, and whether it
+/// is the last fragment of a message
+async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R) -> std::io::Result<(u8, Vec<u8>, bool)> {
+    let mut header = [0u8; 2];
+    reader.read_exact(&mut header).await?;
+    let fin = header[0] & 0x80 != 0;
+    let opcode = header[0] & 0x0f;
+    let len = match header[1] & 0x7f {
+        126 => u64::from(reader.read_u16().await?),
+        127 => reader.read_u64().await?,
+        len => u64::from(len),
+    };
+    let mask = if header[1] & 0x80 != 0 {
+        let mut mask = [0u8; 4];
+        reader.read_exact(&mut mask).await?;
+        Some(mask)
+    } else {
+        None
+    };
+    let mut payload = vec![0u8; len as usize];
+    reader.read_exact(&mut payload).await?;
+    if let Some(mask) = mask {
+        for (i, byte) in payload.iter_mut().enumerate() {
+            *byte ^= mask[i % 4];
+        }
+    }
+    Ok((opcode, payload, fin))
+}
+
+/// A message received from the Stream Deck application
+#[derive(Debug, PartialEq)]
+enum Incoming {
+    Text(String),
+    Ping(Vec<u8>),
+}
+
+/// Read messages until the connection closes, joining fragmented ones
+async fn read_messages<R: AsyncRead + Unpin>(mut reader: R, messages: mpsc::Sender<Incoming>) {
+    let mut text = Vec::new();
+    loop {
+        let Ok((opcode, payload, fin)) = read_frame(&mut reader).await else {
+            return;
+        };
+        let message = match opcode {
+            OP_TEXT | OP_CONTINUATION => {
+                text.extend_from_slice(&payload);
+                if !fin {
+                    continue;
+                }
+                Incoming::Text(String::from_utf8_lossy(&std::mem::take(&mut text)).into_owned())
+            }
+            OP_PING => Incoming::Ping(payload),
+            OP_CLOSE => return,
+            _ => continue,
+        };
+        if messages.send(message).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Open a WebSocket connection to the Stream Deck application
+async fn connect(port: u16) -> Result<TcpStream, TomatError> {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await?;
+    let request = format!(
+        "GET / HTTP/1.1\r\n\
+         Host: 127.0.0.1:{}\r\n\
+         Upgrade: websocket\r\n\
+         Connection: Upgrade\r\n\
+         Sec-WebSocket-Key: {}\r\n\
+         Sec-WebSocket-Version: 13\r\n\r\n",
+        port,
+        base64(&nonce())
+    );
+    stream.write_all(request.as_bytes()).await?;
+
+    // The application only sends frames once the plugin registered, so
+    // nothing but the response headers can be buffered here
+    let mut reader = BufReader::new(&mut stream);
+    let mut status = String::new();
+    reader.read_line(&mut status).await?;
+    if !status.starts_with("HTTP/1.1 101") {
+        return Err(TomatError::Other(format!(
+            "Stream Deck refused the connection: {}",
+            status.trim()
+        )));
+    }
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await? == 0 || line.trim().is_empty() {
+            break;
+        }
+    }
+    if !reader.buffer().is_empty() {
+        return Err(TomatError::Other(
+            "Unexpected data after the WebSocket handshake".to_string(),
+        ));
+    }
+    Ok(stream)
+}
+
+/// Sends text messages to the Stream Deck application
+struct Sender<W> {
+    writer: W,
+}
+
+impl<W: tokio::io::AsyncWrite + Unpin> Sender<W> {
+    async fn send_frame(&mut self, opcode: u8, payload: &[u8]) -> std::io::Result<()> {
+        let nonce = nonce();
+        let mask = [nonce[0], nonce[1], nonce[2], nonce[3]];
+        self.writer
+            .write_all(&encode_frame(opcode, payload, mask))
+            .await
+    }
+
+    async fn send(&mut self, message: &Value) -> std::io::Result<()> {
+        self.send_frame(OP_TEXT, message.to_string().as_bytes())
+            .await
+    }
+}
+
+/// The message setting the title of a key
+fn set_title(context: &str, title: &str) -> Value {
+    json!({
+        "event": "setTitle",
+        "context": context,
+        "payload": { "title": title, "target": 0 },
+    })
+}
+
+/// Run as the plugin of the Stream Deck application: keep the countdown on
+/// timer keys up to date, and run the command of a key when it is pressed
+pub async fn run(
+    launch: &Launch,
+    format: Option<&str>,
+    display: &DisplayConfig,
+) -> Result<(), TomatError> {
+    let stream = connect(launch.port).await?;
+    let (reader, writer) = stream.into_split();
+    let mut sender = Sender { writer };
+    sender
+        .send(&json!({ "event": launch.register_event, "uuid": launch.plugin_uuid }))
+        .await?;
+
+    let (messages_tx, mut messages) = mpsc::channel(16);
+    tokio::spawn(read_messages(reader, messages_tx));
+
+    // Action of each key shown, and the title last set on timer keys
+    let mut keys: HashMap<String, String> = HashMap::new();
+    let mut titles: HashMap<String, String> = HashMap::new();
+    let mut tick = tokio::time::interval(std::time::Duration::from_secs(1));
+
+    loop {
+        tokio::select! {
+            message = messages.recv() => {
+                let Some(message) = message else {
+                    // The Stream Deck application quit
+                    return Ok(());
+                };
+                let text = match message {
+                    Incoming::Ping(payload) => {
+                        sender.send_frame(OP_PONG, &payload).await?;
+                        continue;
+                    }
+                    Incoming::Text(text) => text,
+                };
+                let Ok(event) = serde_json::from_str::<Value>(&text) else {
+                    continue;
+                };
+                let action = event["action"].as_str().unwrap_or_default();
+                let context = event["context"].as_str().unwrap_or_default().to_string();
+                match event["event"].as_str().unwrap_or_default() {
+                    "willAppear" => {
+                        keys.insert(context.clone(), action.to_string());
+                        titles.remove(&context);
+                    }
+                    "willDisappear" => {
+                        keys.remove(&context);
+                        titles.remove(&context);
+                        continue;
+                    }
+                    "keyDown" => {
+                        let Some(command) = command_for(action) else {
+                            continue;
+                        };
+                        let result = send_command(command, Value::Null)
+                            .await
+                            .and_then(ServerResponse::into_result);
+                        if result.is_err() {
+                            sender
+                                .send(&json!({ "event": "showAlert", "context": context }))
+                                .await?;
+                        }
+                    }
+                    _ => continue,
+                }
+            }
+            _ = tick.tick() => {}
+        }
+
+        // Bring the countdown on timer keys up to date
+        let text = crate::dwm::timer_text(format, display).await;
+        for (context, action) in &keys {
+            if action == ACTION_TIMER && titles.get(context) != Some(&text) {
+                sender.send(&set_title(context, &text)).await?;
+                titles.insert(context.clone(), text.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_launch_args() {
+        let launch = parse_launch_args(&args(&[
+            "-port",
+            "28196",
+            "-pluginUUID",
+            "ABC123",
+            "-registerEvent",
+            "registerPlugin",
+            "-info",
+            "{\"application\":{}}",
+        ]))
+        .unwrap();
+        assert_eq!(
+            launch,
+            Launch {
+                port: 28196,
+                plugin_uuid: "ABC123".to_string(),
+                register_event: "registerPlugin".to_string(),
+            }
+        );
+
+        assert!(parse_launch_args(&args(&["-port", "28196"])).is_err());
+        assert!(
+            parse_launch_args(&args(&[
+                "-port",
+                "x",
+                "-pluginUUID",
+                "A",
+                "-registerEvent",
+                "r"
+            ]))
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_command_for() {
+        assert_eq!(command_for(ACTION_TIMER), Some("toggle"));
+        assert_eq!(command_for(ACTION_TOGGLE), Some("toggle"));
+        assert_eq!(command_for(ACTION_SKIP), Some("skip"));
+        assert_eq!(command_for("com.example.other"), None);
+    }
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64(&[0xff; 16]).len(), 24);
+    }
+
+    #[tokio::test]
+    async fn test_frames_round_trip() {
+        let long = vec![b'x'; 300];
+        for payload in [&b"hello"[..], &long] {
+            let frame = encode_frame(OP_TEXT, payload, [1, 2, 3, 4]);
+            // Client frames are masked
+            assert_eq!(frame[1] & 0x80, 0x80);
+            let (opcode, decoded, fin) = read_frame(&mut &frame[..]).await.unwrap();
+            assert_eq!((opcode, fin), (OP_TEXT, true));
+            assert_eq!(decoded, payload);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_messages_joins_fragments() {
+        // Unmasked server frames: "a" continued by "b", a ping, and a close
+        let data: &[u8] = &[
+            OP_TEXT,
+            1,
+            b'a',
+            0x80,
+            1,
+            b'b',
+            0x80 | OP_PING,
+            1,
+            b'p',
+            0x80 | OP_CLOSE,
+            0,
+        ];
+        let (tx, mut rx) = mpsc::channel(4);
+        read_messages(data, tx).await;
+        assert_eq!(rx.recv().await, Some(Incoming::Text("ab".to_string())));
+        assert_eq!(rx.recv().await, Some(Incoming::Ping(b"p".to_vec())));
+        assert_eq!(rx.recv().await, None);
+    }
+
+    #[test]
+    fn test_set_title() {
+        assert_eq!(
+            set_title("ctx", "24:59"),
+            json!({
+                "event": "setTitle",
+                "context": "ctx",
+                "payload": { "title": "24:59", "target": 0 }
+            })
+        );
+    }
+}
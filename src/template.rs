@@ -0,0 +1,200 @@
+/// Render a text template, looking up placeholder values with `lookup`.
+///
+/// Placeholders are written `{name}` and may take modifiers separated by
+/// colons, e.g. `{phase:upper}` or `{time:lpad6}`:
+///
+/// - `upper` / `lower`: change the case
+/// - `padN`: pad with spaces on the right to at least N characters
+/// - `lpadN`: pad with spaces on the left to at least N characters
+/// - `?(text)`: render `text` (itself a template) only if the value is not
+///   empty, e.g. `{task:?( 📝 {task})}`
+///
+/// Unknown placeholders and malformed modifiers are left as written.
+pub fn render(template: &str, lookup: &dyn Fn(&str) -> Option<String>) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        match parse_placeholder(rest) {
+            Some((placeholder, len)) => {
+                match placeholder.render(lookup) {
+                    Some(text) => output.push_str(&text),
+                    None => output.push_str(&rest[..len]),
+                }
+                rest = &rest[len..];
+            }
+            None => {
+                output.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    output.push_str(rest);
+    output
+}
+
+struct Placeholder<'a> {
+    name: &'a str,
+    modifiers: Vec<&'a str>,
+    conditional: Option<&'a str>,
+}
+
+impl Placeholder<'_> {
+    /// Render the placeholder, or `None` if it should be left as written
+    fn render(&self, lookup: &dyn Fn(&str) -> Option<String>) -> Option<String> {
+        if let Some(body) = self.conditional {
+            let value = lookup(self.name).unwrap_or_default();
+            return Some(if value.is_empty() {
+                String::new()
+            } else {
+                render(body, lookup)
+            });
+        }
+
+        let mut value = lookup(self.name)?;
+        for modifier in &self.modifiers {
+            value = apply_modifier(value, modifier)?;
+        }
+        Some(value)
+    }
+}
+
+/// Parse a placeholder at the start of `input` (which begins with '{'),
+/// returning it and its length in bytes
+fn parse_placeholder(input: &str) -> Option<(Placeholder<'_>, usize)> {
+    let inner = &input[1..];
+    let name_len = inner
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(inner.len());
+    let name = &inner[..name_len];
+    if name.is_empty() {
+        return None;
+    }
+
+    let after_name = &inner[name_len..];
+    if after_name.starts_with('}') {
+        let placeholder = Placeholder {
+            name,
+            modifiers: Vec::new(),
+            conditional: None,
+        };
+        return Some((placeholder, name_len + 2));
+    }
+
+    let spec = after_name.strip_prefix(':')?;
+    if let Some(body) = spec.strip_prefix("?(") {
+        let body_len = matching_paren(body)?;
+        if !body[body_len + 1..].starts_with('}') {
+            return None;
+        }
+        let placeholder = Placeholder {
+            name,
+            modifiers: Vec::new(),
+            conditional: Some(&body[..body_len]),
+        };
+        // '{' + name + ":?(" + body + ")}"
+        return Some((placeholder, 1 + name_len + 3 + body_len + 2));
+    }
+
+    let spec_len = spec.find(['}', '{'])?;
+    if !spec[spec_len..].starts_with('}') {
+        return None;
+    }
+    let placeholder = Placeholder {
+        name,
+        modifiers: spec[..spec_len].split(':').collect(),
+        conditional: None,
+    };
+    Some((placeholder, 1 + name_len + 1 + spec_len + 1))
+}
+
+/// Find the ')' closing a conditional body, allowing nested parentheses
+fn matching_paren(body: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in body.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return Some(i),
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+fn apply_modifier(value: String, modifier: &str) -> Option<String> {
+    let pad = |width: &str| width.parse::<usize>().ok();
+    let fill = |value: &str, width: usize| " ".repeat(width.saturating_sub(value.chars().count()));
+
+    match modifier {
+        "upper" => Some(value.to_uppercase()),
+        "lower" => Some(value.to_lowercase()),
+        _ => {
+            if let Some(width) = modifier.strip_prefix("lpad").and_then(pad) {
+                Some(format!("{}{}", fill(&value, width), value))
+            } else {
+                let width = modifier.strip_prefix("pad").and_then(pad)?;
+                Some(format!("{}{}", value, fill(&value, width)))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "time" => Some("5:00".to_string()),
+            "phase" => Some("Work".to_string()),
+            "task" => Some("write spec".to_string()),
+            "empty" => Some(String::new()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_plain_placeholders() {
+        assert_eq!(render("{phase} {time}", &lookup), "Work 5:00");
+        assert_eq!(render("no placeholders", &lookup), "no placeholders");
+        assert_eq!(render("", &lookup), "");
+    }
+
+    #[test]
+    fn test_modifiers() {
+        assert_eq!(render("{phase:upper}", &lookup), "WORK");
+        assert_eq!(render("{phase:lower}", &lookup), "work");
+        assert_eq!(render("[{time:pad6}]", &lookup), "[5:00  ]");
+        assert_eq!(render("[{time:lpad6}]", &lookup), "[  5:00]");
+        assert_eq!(render("[{time:pad2}]", &lookup), "[5:00]");
+        assert_eq!(render("{phase:upper:pad6}|", &lookup), "WORK  |");
+    }
+
+    #[test]
+    fn test_conditionals() {
+        assert_eq!(
+            render("{time}{task:?( 📝 {task})}", &lookup),
+            "5:00 📝 write spec"
+        );
+        assert_eq!(render("{time}{empty:?( 📝 {empty})}", &lookup), "5:00");
+        // Unknown names count as empty
+        assert_eq!(render("{time}{missing:?( ({missing}))}", &lookup), "5:00");
+        // Nested parentheses and modifiers inside the body
+        assert_eq!(render("{task:?(({task:upper}))}", &lookup), "(WRITE SPEC)");
+    }
+
+    #[test]
+    fn test_malformed_input_is_kept() {
+        assert_eq!(render("{unknown} {time}", &lookup), "{unknown} 5:00");
+        assert_eq!(render("{phase:bogus}", &lookup), "{phase:bogus}");
+        assert_eq!(render("{ {time}", &lookup), "{ 5:00");
+        assert_eq!(render("{time", &lookup), "{time");
+        assert_eq!(render("{task:?(unclosed}", &lookup), "{task:?(unclosed}");
+        assert_eq!(render("}{}", &lookup), "}{}");
+    }
+}
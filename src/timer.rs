@@ -1,12 +1,11 @@
 use notify_rust::Notification;
 use serde::{Deserialize, Serialize};
-use std::fs;
-use std::io::Write;
-use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::audio::SoundType;
-use crate::config::{AutoAdvanceMode, NotificationConfig, SoundConfig};
+use crate::config::{AutoAdvanceMode, NotificationConfig, PlanCompleteAction, SoundConfig};
+use crate::error::TomatError;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
 #[serde(rename_all = "lowercase")]
@@ -15,6 +14,14 @@ pub enum Format {
     Waybar,
     Plain,
     I3statusRs,
+    /// Plain text colored with ANSI escapes, for zellij status plugins
+    Zellij,
+    /// Compact JSON for desk displays and microcontrollers
+    Display,
+    /// Full text, short text and color on three lines, for i3blocks
+    I3blocks,
+    /// Plain text on a single line, for dwmblocks
+    Dwmblocks,
 }
 
 impl std::str::FromStr for Format {
@@ -25,8 +32,13 @@ impl std::str::FromStr for Format {
             "waybar" => Ok(Format::Waybar),
             "plain" => Ok(Format::Plain),
             "i3status-rs" => Ok(Format::I3statusRs),
+            "zellij" => Ok(Format::Zellij),
+            "display" => Ok(Format::Display),
+            "i3blocks" => Ok(Format::I3blocks),
+            "dwmblocks" => Ok(Format::Dwmblocks),
             _ => Err(format!(
-                "Unknown format: '{}'. Supported formats: waybar, plain, i3status-rs",
+                "Unknown format: '{}'. Supported formats: waybar, plain, i3status-rs, zellij, \
+                 display, i3blocks, dwmblocks",
                 s
             )),
         }
@@ -36,76 +48,189 @@ impl std::str::FromStr for Format {
 // Embed the icon file at compile time
 static ICON_DATA: &[u8] = include_bytes!("../assets/icon.png");
 
+/// The icon of a notification, as chosen by `[notification] icon`
+#[derive(Debug, Clone, PartialEq)]
+pub enum NotificationIcon {
+    /// The embedded icon, sent as image data
+    Embedded,
+    /// An icon from the icon theme, or an icon file
+    Named(String),
+}
+
 /// Get the appropriate icon for notifications based on configuration
-fn get_notification_icon(
-    config: &NotificationConfig,
-) -> Result<String, Box<dyn std::error::Error>> {
+pub fn get_notification_icon(config: &NotificationConfig) -> NotificationIcon {
     match config.icon.as_str() {
-        "auto" => {
-            // Use embedded icon
-            let icon_path = get_cached_icon_path()?;
-            icon_path
-                .to_str()
-                .ok_or("Icon path contains invalid UTF-8".into())
-                .map(|s| s.to_string())
-        }
-        "theme" => {
-            // Use system theme icon
-            Ok("timer".to_string())
-        }
+        "auto" => NotificationIcon::Embedded,
+        "theme" => NotificationIcon::Named("timer".to_string()),
         custom_path => {
-            // Use custom icon path
-            let path = PathBuf::from(custom_path);
-            if path.exists() {
-                Ok(custom_path.to_string())
+            if std::path::Path::new(custom_path).exists() {
+                NotificationIcon::Named(custom_path.to_string())
             } else {
                 // Fall back to embedded icon if custom path doesn't exist
                 eprintln!(
                     "Warning: Custom icon path '{}' not found, falling back to embedded icon",
                     custom_path
                 );
-                let icon_path = get_cached_icon_path()?;
-                icon_path
-                    .to_str()
-                    .ok_or("Icon path contains invalid UTF-8".into())
-                    .map(|s| s.to_string())
+                NotificationIcon::Embedded
             }
         }
     }
 }
 
-/// Get the path to the cached icon file, creating it if necessary
-fn get_cached_icon_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
-    // Use XDG cache directory
-    let cache_dir = match dirs::cache_dir() {
-        Some(dir) => dir.join("tomat"),
-        None => {
-            // Fallback to ~/.cache/tomat if XDG cache dir is not available
-            match dirs::home_dir() {
-                Some(home) => home.join(".cache").join("tomat"),
-                None => return Err("Could not determine cache directory".into()),
-            }
+/// The embedded icon as image data, decoded the first time it is needed
+fn embedded_icon() -> Option<&'static notify_rust::Image> {
+    static ICON: std::sync::OnceLock<Option<notify_rust::Image>> = std::sync::OnceLock::new();
+    ICON.get_or_init(|| {
+        decode_icon(ICON_DATA)
+            .inspect_err(|e| eprintln!("Warning: Failed to decode the embedded icon: {}", e))
+            .ok()
+    })
+    .as_ref()
+}
+
+/// Decode a PNG into image data for the `image-data` hint
+fn decode_icon(data: &[u8]) -> Result<notify_rust::Image, TomatError> {
+    let mut decoder = png::Decoder::new(std::io::Cursor::new(data));
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(|e| e.to_string())?;
+    let mut pixels = vec![0; reader.output_buffer_size().ok_or("Icon is too large")?];
+    let info = reader.next_frame(&mut pixels).map_err(|e| e.to_string())?;
+    pixels.truncate(info.buffer_size());
+
+    let (width, height) = (info.width as i32, info.height as i32);
+    match info.color_type {
+        png::ColorType::Rgba => notify_rust::Image::from_rgba(width, height, pixels),
+        png::ColorType::Rgb => notify_rust::Image::from_rgb(width, height, pixels),
+        other => return Err(format!("Unsupported icon color type {:?}", other).into()),
+    }
+    .map_err(|e| e.to_string().into())
+}
+
+/// Notification category for phase changes
+pub const PHASE_CATEGORY: &str = "x-tomat.phase";
+
+/// Create a notification that notification servers group under tomat
+///
+/// The app name and `desktop-entry` hint let GNOME and KDE file notifications
+/// under the app, with its icon and per-app do-not-disturb settings, and the
+/// category lets servers such as mako style phase changes and focus warnings
+/// separately.
+pub fn new_notification(config: &NotificationConfig, category: &str) -> Notification {
+    let mut notification = Notification::new();
+    notification
+        .appname("Tomat")
+        .hint(notify_rust::Hint::DesktopEntry("tomat".to_string()))
+        .hint(notify_rust::Hint::Category(category.to_string()))
+        .timeout(config.timeout as i32);
+    set_notification_icon(&mut notification, config);
+    notification
+}
+
+/// Action of the notification at the end of a work session that asks for notes
+const NOTE_ACTION: &str = "note";
+
+/// Run the note prompt and return the trimmed note it printed, if any
+fn read_note(prompt: &[String]) -> Option<String> {
+    let (program, args) = prompt.split_first()?;
+    let output = match std::process::Command::new(program)
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .output()
+    {
+        Ok(output) => output,
+        Err(e) => {
+            eprintln!("Failed to run the note prompt: {}", e);
+            return None;
         }
     };
+    // A cancelled prompt exits with an error
+    if !output.status.success() {
+        return None;
+    }
+    let note = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!note.is_empty()).then_some(note)
+}
 
-    // Create cache directory if it doesn't exist
-    fs::create_dir_all(&cache_dir)?;
+/// Ask for a note with the prompt command and save it with `tomat note`
+fn take_note(prompt: &[String]) {
+    let Some(note) = read_note(prompt) else {
+        return;
+    };
+    let tomat = std::env::current_exe().unwrap_or_else(|_| "tomat".into());
+    if let Err(e) = std::process::Command::new(tomat)
+        .args(["note", &note])
+        .stdout(std::process::Stdio::null())
+        .status()
+    {
+        eprintln!("Failed to save the note: {}", e);
+    }
+}
 
-    let icon_path = cache_dir.join("icon.png");
+/// Describe whether notifications can be shown, for `tomat daemon status`
+pub fn describe_notifications(config: &NotificationConfig) -> String {
+    if !config.enabled {
+        return "off".to_string();
+    }
+    if is_testing() {
+        return "suppressed while testing".to_string();
+    }
+    match notify_rust::get_server_information() {
+        Ok(server) => format!("on, shown by {} {}", server.name, server.version),
+        Err(e) => format!("on, but no notification server: {}", e),
+    }
+}
 
-    // Write icon file if it doesn't exist or if it's outdated
-    if !icon_path.exists() || is_icon_outdated(&icon_path)? {
-        let mut file = fs::File::create(&icon_path)?;
-        file.write_all(ICON_DATA)?;
+/// Set the notification icon from the configuration: the embedded icon goes
+/// in the `image-data` hint, and icon files also in the `image-path` hint
+/// that some notification servers prefer
+fn set_notification_icon(notification: &mut Notification, config: &NotificationConfig) {
+    match get_notification_icon(config) {
+        NotificationIcon::Named(icon) => {
+            notification.icon(&icon);
+            if icon.starts_with('/') {
+                notification.hint(notify_rust::Hint::ImagePath(icon));
+            }
+        }
+        NotificationIcon::Embedded => match embedded_icon() {
+            Some(image) => {
+                notification.hint(notify_rust::Hint::ImageData(image.clone()));
+            }
+            None => {
+                notification.icon("timer");
+            }
+        },
     }
+}
 
-    Ok(icon_path)
+/// A task in the day plan, worked on for a number of pomodoros
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PlannedTask {
+    pub task: String,
+    pub pomodoros: u32,
+    /// Work sessions finished on this task so far
+    #[serde(default)]
+    pub completed: u32,
+}
+
+impl PlannedTask {
+    pub fn is_done(&self) -> bool {
+        self.completed >= self.pomodoros
+    }
 }
 
-/// Check if the cached icon file is outdated compared to the embedded data
-fn is_icon_outdated(icon_path: &PathBuf) -> Result<bool, Box<dyn std::error::Error>> {
-    let existing_data = fs::read(icon_path)?;
-    Ok(existing_data != ICON_DATA)
+/// Progress of a cycle found in the history, for continuing it after the
+/// daemon restarted without saved state
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct CycleProgress {
+    /// Work sessions completed since the last long break
+    pub sessions: u32,
+    /// Seconds worked in those sessions
+    pub focused_seconds: u64,
+    /// When the first of those sessions started
+    pub started_at: u64,
+    /// End of the day the sessions were done on, after which a new cycle starts
+    pub valid_until: u64,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -118,6 +243,74 @@ pub struct TimerState {
     pub long_break_duration: f32,
     pub sessions_until_long_break: u32,
     pub current_session_count: u32,
+    /// Focused minutes after which a long break is due regardless of sessions
+    #[serde(default)]
+    pub long_break_after_minutes: Option<f32>,
+    /// Pauses allowed per work session before pausing voids it
+    #[serde(default)]
+    pub max_pauses: Option<u32>,
+    /// Minutes of preparation before each work session, 0 for none
+    #[serde(default)]
+    pub prepare_duration: f32,
+    /// Minutes of review after each work session, 0 for none
+    #[serde(default)]
+    pub review_duration: f32,
+    /// Fraction by which work sessions and breaks are randomly made longer
+    /// or shorter, 0 for none
+    #[serde(default)]
+    pub jitter: f32,
+    /// Break phase and minutes that start when the review is over
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub break_after_review: Option<(Phase, f32)>,
+    /// History id of the last recorded work session, which `tomat note`
+    /// writes to until the next work session starts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_work_session: Option<i64>,
+    /// Notes on the work session in progress, recorded with it when it ends
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    /// Seconds worked since the last long break
+    #[serde(default)]
+    pub focused_seconds: u64,
+    /// Break seconds skipped with `skip --merge`, added to the next long break
+    #[serde(default)]
+    pub banked_break_seconds: u64,
+    /// End the current work session with a normal break although a long
+    /// break is due, set with `postpone-long-break`
+    #[serde(default)]
+    pub long_break_postponed: bool,
+    /// When the first work session since the last long break started
+    #[serde(default)]
+    pub cycle_started_at: Option<u64>,
+    /// Hide the countdown and session details in status output
+    #[serde(default)]
+    pub privacy: bool,
+    /// What the user is working on, set with `start --task`
+    #[serde(default)]
+    pub task: Option<String>,
+    /// Tags recorded with the work sessions, set with `start --tag`
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Number of times the current phase was paused
+    #[serde(default)]
+    pub pauses: u32,
+    /// Cycle from the history that the next `start` continues
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backfill: Option<CycleProgress>,
+    /// Tasks planned for the day, worked through in order by `start`
+    #[serde(default)]
+    pub plan: Vec<PlannedTask>,
+    /// Local time of day after which breaks end the cycle instead of
+    /// starting another work session
+    #[serde(default)]
+    pub workday_end: Option<chrono::NaiveTime>,
+    /// What to do when the plan is done
+    #[serde(default)]
+    pub plan_complete: PlanCompleteAction,
+    /// Minutes of the long break after the plan is done, twice the long
+    /// break if unset
+    #[serde(default)]
+    pub plan_complete_break: Option<f32>,
     pub auto_advance: AutoAdvanceMode,
     pub is_paused: bool,
     /// Elapsed seconds when timer was paused (to preserve progress on resume)
@@ -126,6 +319,24 @@ pub struct TimerState {
     /// Hook that should be executed when timer resumes from paused state after phase transition
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub pending_hook: Option<String>,
+    /// When the last phase transition happened, to throttle alerts
+    #[serde(skip)]
+    pub last_transition_at: Option<u64>,
+    /// Work sessions completed on `completed_date`
+    #[serde(default)]
+    pub completed_today: u32,
+    /// Day the completed work sessions were counted for
+    #[serde(default)]
+    pub completed_date: Option<chrono::NaiveDate>,
+    /// Local time of day at which a new day starts for `completed_today`
+    #[serde(default)]
+    pub day_start: chrono::NaiveTime,
+    /// Daily goal set with `tomat goal`, in place of the one in the config
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub goal_override: Option<u32>,
+    /// Daily goal from the config
+    #[serde(skip)]
+    pub config_goal: Option<u32>,
 }
 
 /// Raw timer status data - pure state, no presentation
@@ -137,16 +348,36 @@ pub struct TimerStatus {
     pub duration_minutes: f32,          // Total duration of current phase
     pub current_session: u32,           // Current session number (1-based)
     pub sessions_until_long_break: u32, // Total sessions before long break
+    #[serde(default)]
+    pub privacy: bool, // Whether privacy mode hides the details
+    #[serde(default)]
+    pub task: Option<String>, // What the user is working on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pauses_left: Option<u32>, // Pause budget left in a work session
+    #[serde(default)]
+    pub banked_break_seconds: u64, // Break time banked for later
+    #[serde(default)]
+    pub completed_today: u32, // Work sessions completed today
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily_goal: Option<u32>, // Work sessions to complete each day
 }
 
+/// Shown in place of the countdown while privacy mode is on
+pub const PRIVACY_TEXT: &str = "focusing";
+
+/// Version of the JSON status output structure, bumped on breaking changes
+pub const STATUS_SCHEMA_VERSION: u32 = 1;
+
 #[derive(Serialize)]
 #[serde(untagged)]
 pub enum StatusOutput {
     Waybar {
         text: String,
+        alt: String,
         tooltip: String,
         class: String,
         percentage: f64,
+        schema_version: u32,
     },
     I3statusRs {
         text: String,
@@ -156,14 +387,138 @@ pub enum StatusOutput {
         icon: Option<String>,
         #[serde(skip_serializing_if = "Option::is_none")]
         state: Option<String>,
+        schema_version: u32,
+    },
+    /// Few fixed fields that are cheap to parse on e-ink and ESPHome gadgets
+    Display {
+        phase: String,
+        time: String,
+        percent: u8,
+        paused: bool,
+        schema_version: u32,
     },
+    /// Text printed as it is, for the plain and zellij formats
     Plain(String),
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+impl StatusOutput {
+    /// JSON Schema describing the status output of the given format
+    pub fn schema(format: &Format) -> serde_json::Value {
+        let schema_version = serde_json::json!({
+            "type": "integer",
+            "const": STATUS_SCHEMA_VERSION,
+            "description": "Version of this output structure"
+        });
+
+        match format {
+            Format::Waybar => serde_json::json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "title": "tomat waybar status",
+                "type": "object",
+                "properties": {
+                    "text": {"type": "string", "description": "Formatted display text"},
+                    "alt": {
+                        "type": "string",
+                        "description": "Alternative display text, for waybar's format-alt"
+                    },
+                    "tooltip": {"type": "string", "description": "Tooltip with phase details"},
+                    "class": {
+                        "type": "string",
+                        "enum": [
+                            "idle", "prepare", "prepare-paused", "work", "work-paused", "review",
+                            "review-paused", "break", "break-paused", "long-break",
+                            "long-break-paused"
+                        ],
+                        "description": "CSS class for the current phase"
+                    },
+                    "percentage": {
+                        "type": "number",
+                        "minimum": 0,
+                        "maximum": 100,
+                        "description": "Progress through the current phase"
+                    },
+                    "schema_version": schema_version
+                },
+                "required": ["text", "alt", "tooltip", "class", "percentage", "schema_version"]
+            }),
+            Format::I3statusRs => serde_json::json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "title": "tomat i3status-rs status",
+                "type": "object",
+                "properties": {
+                    "text": {"type": "string", "description": "Formatted display text"},
+                    "short_text": {"type": "string", "description": "Short display text"},
+                    "icon": {"type": "string", "description": "i3status-rs icon name"},
+                    "state": {
+                        "type": "string",
+                        "enum": ["Idle", "Info", "Good", "Warning", "Critical"],
+                        "description": "i3status-rs block state"
+                    },
+                    "schema_version": schema_version
+                },
+                "required": ["text", "schema_version"]
+            }),
+            Format::Display => serde_json::json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "title": "tomat display status",
+                "type": "object",
+                "properties": {
+                    "phase": {
+                        "type": "string",
+                        "enum": ["idle", "work", "break", "long_break"],
+                        "description": "Current phase"
+                    },
+                    "time": {
+                        "type": "string",
+                        "description": "Remaining time as MM:SS, or \"focusing\" in privacy mode"
+                    },
+                    "percent": {
+                        "type": "integer",
+                        "minimum": 0,
+                        "maximum": 100,
+                        "description": "Whole percent of the current phase that has passed"
+                    },
+                    "paused": {"type": "boolean", "description": "Whether the timer is paused"},
+                    "schema_version": schema_version
+                },
+                "required": ["phase", "time", "percent", "paused", "schema_version"]
+            }),
+            Format::Plain => serde_json::json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "title": "tomat plain status",
+                "type": "string",
+                "description": "Plain text output has no versioned structure"
+            }),
+            Format::Zellij => serde_json::json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "title": "tomat zellij status",
+                "type": "string",
+                "description": "Plain text colored with ANSI escapes, no versioned structure"
+            }),
+            Format::I3blocks => serde_json::json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "title": "tomat i3blocks status",
+                "type": "string",
+                "description": "Full text, short text and color on three lines, no versioned structure"
+            }),
+            Format::Dwmblocks => serde_json::json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "title": "tomat dwmblocks status",
+                "type": "string",
+                "description": "Plain text on a single line, no versioned structure"
+            }),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub enum Phase {
     Idle,
+    /// Short warm-up before a work session, e.g. to review the task
+    Prepare,
     Work,
+    /// Short look back after a work session, before its break
+    Review,
     Break,
     LongBreak,
 }
@@ -172,7 +527,9 @@ impl std::fmt::Display for Phase {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Phase::Idle => write!(f, "idle"),
+            Phase::Prepare => write!(f, "prepare"),
             Phase::Work => write!(f, "work"),
+            Phase::Review => write!(f, "review"),
             Phase::Break => write!(f, "break"),
             Phase::LongBreak => write!(f, "long_break"),
         }
@@ -190,34 +547,183 @@ impl TimerState {
             long_break_duration: long_break,
             sessions_until_long_break: sessions,
             current_session_count: 0,
+            long_break_after_minutes: None,
+            max_pauses: None,
+            prepare_duration: 0.0,
+            review_duration: 0.0,
+            jitter: 0.0,
+            break_after_review: None,
+            last_work_session: None,
+            notes: None,
+            focused_seconds: 0,
+            banked_break_seconds: 0,
+            long_break_postponed: false,
+            cycle_started_at: None,
+            privacy: false,
+            task: None,
+            tags: Vec::new(),
+            pauses: 0,
+            backfill: None,
+            plan: Vec::new(),
+            workday_end: None,
+            plan_complete: PlanCompleteAction::default(),
+            plan_complete_break: None,
             auto_advance: AutoAdvanceMode::None,
             is_paused: false,
             paused_elapsed_seconds: None,
             pending_hook: None,
+            last_transition_at: None,
+            completed_today: 0,
+            completed_date: None,
+            day_start: chrono::NaiveTime::MIN,
+            goal_override: None,
+            config_goal: None,
+        }
+    }
+
+    /// `minutes` randomly made longer or shorter by up to the jitter, in
+    /// whole seconds
+    fn jittered(&self, minutes: f32) -> f32 {
+        let jitter = self.jitter.clamp(0.0, 0.5);
+        if jitter == 0.0 {
+            return minutes;
         }
+        (minutes * (1.0 + jitter * random_unit()) * 60.0).round() / 60.0
     }
 
     pub fn start_work(&mut self) {
         self.phase = Phase::Work;
-        self.duration_minutes = self.work_duration;
+        self.duration_minutes = self.jittered(self.work_duration);
+        self.start_time = current_timestamp();
+        self.is_paused = false;
+        self.pauses = 0;
+        self.last_work_session = None;
+        self.notes = None;
+    }
+
+    /// Start the preparation before the next work session, or the work
+    /// session itself if there is none. Waits to be resumed unless `run`.
+    pub fn start_prepare_or_work(&mut self, run: bool) {
+        if self.prepare_duration > 0.0 {
+            self.phase = Phase::Prepare;
+            self.duration_minutes = self.prepare_duration;
+        } else {
+            self.phase = Phase::Work;
+            self.duration_minutes = self.jittered(self.work_duration);
+        }
+        self.start_time = current_timestamp();
+        self.is_paused = !run;
+        self.pauses = 0;
+        self.last_work_session = None;
+        self.notes = None;
+    }
+
+    /// Hook event for the start of the phase that
+    /// [`start_prepare_or_work`](Self::start_prepare_or_work) started
+    pub fn work_start_event(&self) -> &'static str {
+        if matches!(self.phase, Phase::Prepare) {
+            "prepare_start"
+        } else {
+            "work_start"
+        }
+    }
+
+    /// Put the review in front of the break that was just set up, keeping
+    /// the break for when the review is over
+    fn begin_review(&mut self) {
+        self.break_after_review = Some((self.phase.clone(), self.duration_minutes));
+        self.phase = Phase::Review;
+        self.duration_minutes = self.review_duration;
+    }
+
+    /// Start the break that follows the review. Returns whether it is a long
+    /// break.
+    fn start_reviewed_break(&mut self) -> bool {
+        let (phase, minutes) = self
+            .break_after_review
+            .take()
+            .unwrap_or((Phase::Break, self.break_duration));
+        let long = matches!(phase, Phase::LongBreak);
+        self.phase = phase;
+        self.duration_minutes = minutes;
         self.start_time = current_timestamp();
         self.is_paused = false;
+        long
     }
 
     fn start_break(&mut self) {
         self.phase = Phase::Break;
-        self.duration_minutes = self.break_duration;
+        self.duration_minutes = self.jittered(self.break_duration);
         self.start_time = current_timestamp();
         self.is_paused = false;
     }
 
-    fn start_long_break(&mut self) {
+    fn start_long_break(&mut self, plan_done: bool) {
         self.phase = Phase::LongBreak;
-        self.duration_minutes = self.long_break_duration;
+        self.duration_minutes = self.take_long_break_minutes(plan_done);
         self.start_time = current_timestamp();
         self.is_paused = false;
     }
 
+    /// Long break duration including banked break time, which is used up.
+    /// The break after finishing the plan is extended.
+    fn take_long_break_minutes(&mut self, plan_done: bool) -> f32 {
+        let banked = std::mem::take(&mut self.banked_break_seconds);
+        let minutes = if plan_done {
+            self.plan_complete_break
+                .unwrap_or(2.0 * self.long_break_duration)
+        } else {
+            self.long_break_duration
+        };
+        self.jittered(minutes) + banked as f32 / 60.0
+    }
+
+    /// Cancel the current break and start working right away, banking the
+    /// remaining break time for the next long break. Returns the banked seconds.
+    pub fn merge_break(&mut self) -> u64 {
+        let remaining = self.get_remaining_seconds();
+        self.banked_break_seconds += remaining;
+        self.paused_elapsed_seconds = None;
+        self.pending_hook = None;
+        self.start_work();
+        remaining
+    }
+
+    /// Spend the banked break time now: a work session ends with a break as
+    /// long as the bank, and a break is extended by it. Returns the spent
+    /// seconds.
+    pub fn take_banked_break(&mut self) -> Result<u64, TomatError> {
+        if self.banked_break_seconds == 0 {
+            return Err(TomatError::InvalidState(
+                "No banked break time. Bank break time with 'tomat skip --merge'.".to_string(),
+            ));
+        }
+        let minutes = self.banked_break_seconds as f32 / 60.0;
+        match self.phase {
+            Phase::Idle => Err(TomatError::InvalidState(
+                "Cannot take a break when timer is idle. Use 'tomat start' first.".to_string(),
+            )),
+            Phase::Prepare => Err(TomatError::InvalidState(
+                "Cannot take a break before the work session has started".to_string(),
+            )),
+            Phase::Review => Err(TomatError::InvalidState(
+                "The break starts right after the review. Take the banked time then.".to_string(),
+            )),
+            Phase::Work => {
+                self.pauses = 0;
+                self.paused_elapsed_seconds = None;
+                self.pending_hook = None;
+                self.start_break();
+                self.duration_minutes = minutes;
+                Ok(std::mem::take(&mut self.banked_break_seconds))
+            }
+            Phase::Break | Phase::LongBreak => {
+                self.duration_minutes += minutes;
+                Ok(std::mem::take(&mut self.banked_break_seconds))
+            }
+        }
+    }
+
     pub fn get_remaining_seconds(&self) -> u64 {
         // Idle phase shows upcoming work duration
         if matches!(self.phase, Phase::Idle) {
@@ -235,12 +741,117 @@ impl TimerState {
             return (self.duration_minutes * 60.0) as u64;
         }
 
-        let elapsed = current_timestamp() - self.start_time;
+        // A clock that is behind the start time (set back, or fast-forwarded
+        // in an earlier daemon) counts as no time elapsed until the daemon
+        // resyncs the start with `resync_clock`
+        let elapsed = current_timestamp().saturating_sub(self.start_time);
         let total_duration = (self.duration_minutes * 60.0) as u64;
 
         total_duration.saturating_sub(elapsed)
     }
 
+    /// Seconds spent in the current phase so far
+    pub fn get_elapsed_seconds(&self) -> u64 {
+        let total_duration = (self.duration_minutes * 60.0) as u64;
+        total_duration.saturating_sub(self.get_remaining_seconds())
+    }
+
+    /// Continue the cycle found in the history, if it is still from today.
+    /// Only the first cycle started after the daemon restarted continues it.
+    pub fn continue_backfilled_cycle(&mut self) {
+        if let Some(progress) = self.backfill.take()
+            && current_timestamp() < progress.valid_until
+        {
+            self.current_session_count = progress.sessions;
+            self.focused_seconds = progress.focused_seconds;
+            self.cycle_started_at = Some(progress.started_at);
+        }
+    }
+
+    /// The first planned task that still has pomodoros left
+    pub fn current_plan_task(&self) -> Option<&PlannedTask> {
+        self.plan.iter().find(|planned| !planned.is_done())
+    }
+
+    /// Count a finished work session towards the planned task being worked
+    /// on, and move on to the next planned task once its budget is spent.
+    /// Returns whether this finished the last task of the plan.
+    fn advance_plan(&mut self) -> bool {
+        let Some(planned) = self.plan.iter_mut().find(|planned| !planned.is_done()) else {
+            return false;
+        };
+        if self.task.as_deref() != Some(planned.task.as_str()) {
+            return false;
+        }
+        planned.completed += 1;
+        if !planned.is_done() {
+            return false;
+        }
+        self.task = self.current_plan_task().map(|planned| planned.task.clone());
+        self.task.is_none()
+    }
+
+    /// Whether the next break should be a long one, by sessions or focused time
+    fn is_long_break_due(&self) -> bool {
+        self.current_session_count >= self.sessions_until_long_break
+            || self
+                .long_break_after_minutes
+                .is_some_and(|minutes| self.focused_seconds as f32 >= minutes * 60.0)
+    }
+
+    /// Whether the finished work session earns a long break, which it does
+    /// not once after the long break has been postponed
+    fn take_long_break_due(&mut self) -> bool {
+        self.is_long_break_due() && !std::mem::take(&mut self.long_break_postponed)
+    }
+
+    /// Whether the current work session will end with a long break
+    fn is_long_break_next(&self) -> bool {
+        let duration = (self.duration_minutes * 60.0) as u64;
+        self.current_session_count + 1 >= self.sessions_until_long_break
+            || self
+                .long_break_after_minutes
+                .is_some_and(|minutes| (self.focused_seconds + duration) as f32 >= minutes * 60.0)
+    }
+
+    /// Take a normal break instead of the pending long break, and the long
+    /// break after one more work session
+    ///
+    /// During the work session before a long break, that session ends with a
+    /// normal break. During a long break, the long break turns into a normal
+    /// one, and any banked break time is kept for the next long break.
+    pub fn postpone_long_break(&mut self) -> Result<(), TomatError> {
+        match self.phase {
+            Phase::Work if self.is_long_break_next() => {
+                self.long_break_postponed = true;
+                Ok(())
+            }
+            Phase::LongBreak => {
+                let extra_minutes = (self.duration_minutes - self.long_break_duration).max(0.0);
+                self.banked_break_seconds += (extra_minutes * 60.0) as u64;
+                self.phase = Phase::Break;
+                self.duration_minutes = self.jittered(self.break_duration);
+                // The cycle is complete, so the next work session ends it again
+                self.current_session_count = self.sessions_until_long_break;
+                if self.pending_hook.as_deref() == Some("long_break_start") {
+                    self.pending_hook = Some("break_start".to_string());
+                }
+                Ok(())
+            }
+            Phase::Idle => Err(TomatError::InvalidState(
+                "Cannot postpone the long break when timer is idle. Use 'tomat start' first."
+                    .to_string(),
+            )),
+            Phase::Review => Err(TomatError::InvalidState(
+                "Cannot postpone the long break during the review. Try again once it has started."
+                    .to_string(),
+            )),
+            Phase::Prepare | Phase::Work | Phase::Break => Err(TomatError::InvalidState(
+                "No long break is coming up after this session".to_string(),
+            )),
+        }
+    }
+
     pub fn is_finished(&self) -> bool {
         // Idle phase is never "finished" - it's a stopped state
         if matches!(self.phase, Phase::Idle) {
@@ -263,68 +874,124 @@ impl TimerState {
         }
     }
 
+    /// Move the start of a running phase to now if the wall clock has been
+    /// set back before it, returning how many seconds it was moved back by.
+    ///
+    /// Without this, the countdown would stand still until the clock caught
+    /// up with the old start time.
+    pub fn resync_clock(&mut self) -> Option<u64> {
+        if matches!(self.phase, Phase::Idle) || self.is_paused {
+            return None;
+        }
+        let now = current_timestamp();
+        let behind = self
+            .start_time
+            .checked_sub(now)
+            .filter(|&behind| behind > 0)?;
+        self.start_time = now;
+        Some(behind)
+    }
+
+    /// Timestamp of the next interval chime in a running work phase
+    ///
+    /// Chimes fall on every `interval_minutes` of elapsed work time, except
+    /// at the very end of the phase where the transition sound plays instead.
+    pub fn next_chime_time(&self, interval_minutes: u32) -> Option<u64> {
+        if !matches!(self.phase, Phase::Work) || self.is_paused || interval_minutes == 0 {
+            return None;
+        }
+        let interval = u64::from(interval_minutes) * 60;
+        let elapsed = current_timestamp().saturating_sub(self.start_time);
+        let offset = (elapsed / interval + 1) * interval;
+        let total_duration = (self.duration_minutes * 60.0) as u64;
+        (offset < total_duration).then(|| self.start_time + offset)
+    }
+
     pub fn next_phase(
         &mut self,
         sound_config: &SoundConfig,
         notification_config: &NotificationConfig,
         hooks_config: &crate::config::HooksConfig,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    ) -> Result<(), TomatError> {
         // Cannot transition from Idle - must use start command
         if matches!(self.phase, Phase::Idle) {
             return Err("Cannot transition from Idle phase. Use 'tomat start' first.".into());
         }
 
+        // A nagbar from the previous transition is obsolete now
+        crate::nagbar::dismiss();
+
         // Execute "end" hook for the current phase BEFORE transitioning
         let end_hook_event = match self.phase {
             Phase::Idle => unreachable!("Idle phase handled above"),
+            Phase::Prepare => "prepare_end",
             Phase::Work => "work_end",
+            Phase::Review => "review_end",
             Phase::Break => "break_end",
             Phase::LongBreak => "long_break_end",
         };
 
         // Execute end hook synchronously (always execute, never defer)
-        if tokio::runtime::Handle::try_current().is_ok() {
-            let hooks = hooks_config.clone();
-            let phase_str = self.phase.to_string();
-            let remaining = self.get_remaining_seconds();
-            let session_count = self.current_session_count;
-            let auto_advance = format!("{:?}", self.auto_advance).to_lowercase();
-            let event = end_hook_event.to_string();
-
-            tokio::spawn(async move {
-                hooks
-                    .execute_hook(&event, &phase_str, remaining, session_count, &auto_advance)
-                    .await;
-            });
-        }
+        self.spawn_hook(hooks_config, end_hook_event);
+        self.pauses = 0;
 
         // Now handle the phase transition
+        let was_work = matches!(self.phase, Phase::Work);
+        let mut summary = None;
+        let mut plan_done = false;
         let (message, sound_type, start_hook_event) = match self.phase {
             Phase::Idle => unreachable!("Idle phase handled above"),
             Phase::Work => {
+                if self.current_session_count == 0 {
+                    self.cycle_started_at = Some(self.start_time);
+                }
                 self.current_session_count += 1;
+                self.focused_seconds += self.get_elapsed_seconds();
+                plan_done = self.advance_plan();
 
                 let (sound_type, start_hook_event, message) =
-                    if self.current_session_count >= self.sessions_until_long_break {
+                    if plan_done && self.plan_complete == PlanCompleteAction::Stop {
+                        summary = Some(long_break_summary(
+                            self.current_session_count,
+                            self.focused_seconds,
+                            self.cycle_started_at.take(),
+                        ));
+                        self.stop();
+                        (
+                            SoundType::WorkToLongBreak,
+                            "stop",
+                            &notification_config.plan_complete_message,
+                        )
+                    } else if plan_done || self.take_long_break_due() {
+                        summary = Some(long_break_summary(
+                            self.current_session_count,
+                            self.focused_seconds,
+                            self.cycle_started_at.take(),
+                        ));
                         self.current_session_count = 0;
+                        self.focused_seconds = 0;
                         if self.auto_advance.should_advance(true) {
-                            self.start_long_break();
+                            self.start_long_break(plan_done);
                         } else {
                             self.phase = Phase::LongBreak;
-                            self.duration_minutes = self.long_break_duration;
+                            self.duration_minutes = self.take_long_break_minutes(plan_done);
                             self.is_paused = true;
                         }
                         (
                             SoundType::WorkToLongBreak,
                             "long_break_start",
-                            &notification_config.long_break_message,
+                            if plan_done {
+                                &notification_config.plan_complete_message
+                            } else {
+                                &notification_config.long_break_message
+                            },
                         )
                     } else {
                         if self.auto_advance.should_advance(true) {
                             self.start_break();
                         } else {
                             self.phase = Phase::Break;
-                            self.duration_minutes = self.break_duration;
+                            self.duration_minutes = self.jittered(self.break_duration);
                             self.is_paused = true;
                         }
                         (
@@ -336,65 +1003,113 @@ impl TimerState {
 
                 (message, sound_type, start_hook_event)
             }
-            Phase::Break => {
-                if self.auto_advance.should_advance(false) {
-                    self.start_work();
-                } else {
-                    self.phase = Phase::Work;
-                    self.duration_minutes = self.work_duration;
-                    self.is_paused = true;
-                }
+            Phase::Break | Phase::LongBreak if self.is_workday_over() => {
+                self.stop();
+                (
+                    &notification_config.wrap_up_message,
+                    SoundType::BreakToWork,
+                    "stop",
+                )
+            }
+            Phase::Break | Phase::LongBreak => {
+                self.start_prepare_or_work(self.auto_advance.should_advance(false));
                 (
                     &notification_config.break_message,
                     SoundType::BreakToWork,
-                    "work_start",
+                    self.work_start_event(),
                 )
             }
-            Phase::LongBreak => {
-                if self.auto_advance.should_advance(false) {
-                    self.start_work();
+            // The review always runs straight into the break
+            Phase::Review => {
+                if self.start_reviewed_break() {
+                    (
+                        &notification_config.long_break_message,
+                        SoundType::WorkToLongBreak,
+                        "long_break_start",
+                    )
                 } else {
-                    self.phase = Phase::Work;
-                    self.duration_minutes = self.work_duration;
-                    self.is_paused = true;
+                    (
+                        &notification_config.work_message,
+                        SoundType::WorkToBreak,
+                        "break_start",
+                    )
                 }
+            }
+            // Preparation always runs straight into the work session
+            Phase::Prepare => {
+                self.start_work();
                 (
-                    &notification_config.break_message,
-                    SoundType::BreakToWork,
+                    &notification_config.prepare_message,
+                    SoundType::PrepareToWork,
                     "work_start",
                 )
             }
         };
 
+        // The review comes between a work session and its break
+        let (message, start_hook_event) = if was_work
+            && self.review_duration > 0.0
+            && matches!(self.phase, Phase::Break | Phase::LongBreak)
+        {
+            self.begin_review();
+            (&notification_config.review_message, "review_start")
+        } else {
+            (message, start_hook_event)
+        };
+
+        // Rapid skips only alert for the first transition
+        let alert = self.should_alert(current_timestamp(), notification_config.throttle);
+
         // Play sound if enabled and not testing
-        if sound_config.effective_mode() != crate::config::SoundMode::None && !is_testing() {
+        if alert && sound_config.effective_mode() != crate::config::SoundMode::None && !is_testing()
+        {
             self.play_transition_sound(sound_config, sound_type)?;
         }
 
+        let body = match &summary {
+            Some(summary) => format!("{}\n{}", message, summary),
+            None => message.clone(),
+        };
+
         // Send notification (existing code)
-        if !is_testing() && notification_config.enabled {
-            self.send_notification(message, notification_config)?;
+        if alert && !is_testing() && notification_config.enabled && !message.is_empty() {
+            let urgency = match sound_type {
+                SoundType::BreakToWork => notification_config.effective_break_end_urgency(),
+                _ => notification_config.urgency.clone(),
+            };
+            // Offer to take notes on the work session that just ended
+            let note_prompt = notification_config
+                .note_prompt
+                .as_deref()
+                .filter(|prompt| was_work && !prompt.is_empty());
+            self.send_notification(&body, urgency, notification_config, note_prompt)?;
+        }
+
+        // Mirror the phase change to the phone, independently of desktop notifications
+        if alert
+            && !is_testing()
+            && !message.is_empty()
+            && notification_config.kdeconnect.sends(start_hook_event)
+            && let Some(device) = notification_config.kdeconnect.device.clone()
+            && tokio::runtime::Handle::try_current().is_ok()
+        {
+            tokio::spawn(crate::kdeconnect::send(device, body));
+        }
+
+        // Ask for confirmation via nagbar when the new phase waits for the user
+        if !is_testing() && notification_config.nagbar && self.is_paused {
+            crate::nagbar::show(&self.phase, message);
+        }
+
+        if plan_done {
+            self.spawn_hook(hooks_config, "plan_complete");
         }
 
         // Execute "start" hook asynchronously only if timer is running (not paused)
         // If paused, store the hook to be executed when user resumes
         if !self.is_paused {
             // Timer is running, execute hook immediately
-            // Only spawn async task if we have a Tokio runtime (not in unit tests)
-            if tokio::runtime::Handle::try_current().is_ok() {
-                let hooks = hooks_config.clone();
-                let phase_str = self.phase.to_string();
-                let remaining = self.get_remaining_seconds();
-                let session_count = self.current_session_count;
-                let auto_advance = format!("{:?}", self.auto_advance).to_lowercase();
-                let event = start_hook_event.to_string();
-
-                tokio::spawn(async move {
-                    hooks
-                        .execute_hook(&event, &phase_str, remaining, session_count, &auto_advance)
-                        .await;
-                });
-            }
+            self.spawn_hook(hooks_config, start_hook_event);
         } else {
             // Timer is paused, store hook for later execution on resume
             self.pending_hook = Some(start_hook_event.to_string());
@@ -403,11 +1118,67 @@ impl TimerState {
         Ok(())
     }
 
+    /// Whether the workday is over, so no more work sessions should start
+    fn is_workday_over(&self) -> bool {
+        self.workday_end.is_some_and(is_past)
+    }
+
+    /// Whether a phase transition at `now` should play a sound and notify,
+    /// which it should not within `throttle` seconds of the previous one
+    fn should_alert(&mut self, now: u64, throttle: u32) -> bool {
+        let previous = self.last_transition_at.replace(now);
+        previous.is_none_or(|previous| now.saturating_sub(previous) >= u64::from(throttle))
+    }
+
+    /// Run a hook in the background with the current state
+    ///
+    /// Only spawns a task if there is a Tokio runtime (not in unit tests).
+    fn spawn_hook(&self, hooks_config: &crate::config::HooksConfig, event: &str) {
+        if tokio::runtime::Handle::try_current().is_err() {
+            return;
+        }
+
+        let hooks = hooks_config.clone();
+        let env = self.hook_env();
+        let event = event.to_string();
+
+        tokio::spawn(async move {
+            hooks.execute_hook(&event, &env).await;
+        });
+    }
+
+    /// The environment hooks get for the current state
+    pub fn hook_env(&self) -> crate::config::HookEnv {
+        crate::config::HookEnv {
+            phase: self.phase.to_string(),
+            remaining_seconds: self.get_remaining_seconds(),
+            session_count: self.current_session_count,
+            auto_advance: self.auto_advance.to_string(),
+            task: self.task.clone(),
+            tags: self.tags.clone(),
+        }
+    }
+
     fn play_transition_sound(
         &self,
         config: &SoundConfig,
         sound_type: SoundType,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    ) -> Result<(), TomatError> {
+        let custom_file = match sound_type {
+            SoundType::PrepareToWork => &config.prepare_to_work,
+            SoundType::WorkToBreak => &config.work_to_break,
+            SoundType::BreakToWork => &config.break_to_work,
+            SoundType::WorkToLongBreak => &config.work_to_long_break,
+        };
+
+        // A transition set to "none" stays silent whatever the mode
+        if custom_file
+            .as_deref()
+            .is_some_and(crate::config::is_sound_off)
+        {
+            return Ok(());
+        }
+
         match config.effective_mode() {
             crate::config::SoundMode::None => {
                 // No sound
@@ -417,38 +1188,72 @@ impl TimerState {
                 crate::audio::play_system_beep();
                 return Ok(());
             }
+            crate::config::SoundMode::Synth => {
+                crate::audio::play_synth(sound_type, &config.synth, config.volume);
+                return Ok(());
+            }
             crate::config::SoundMode::Embedded => {
                 // Continue with embedded/custom sound logic below
             }
         }
 
         // Check for custom sound file first
-        let custom_file = match sound_type {
-            SoundType::WorkToBreak => &config.work_to_break,
-            SoundType::BreakToWork => &config.break_to_work,
-            SoundType::WorkToLongBreak => &config.work_to_long_break,
-        };
-
-        if let Some(file_path) = custom_file {
-            // Try custom file first
-            if let Err(e) = crate::audio::play_custom_file(file_path, config.volume) {
-                eprintln!("Failed to play custom sound '{}': {}", file_path, e);
-                // Fallback to embedded sound
+        match custom_file {
+            Some(name) if config.theme_sounds && crate::sound_theme::is_sound_name(name) => {
+                self.play_theme_sound(config, name, sound_type)?;
+            }
+            Some(file_path) => {
+                // Try custom file first
+                if let Err(e) = crate::audio::play_custom_file(file_path, config.volume) {
+                    eprintln!("Failed to play custom sound '{}': {}", file_path, e);
+                    // Fallback to embedded sound
+                    self.try_embedded_sound(config, sound_type)?;
+                }
+            }
+            None if config.theme_sounds => {
+                self.play_theme_sound(config, sound_type.theme_name(), sound_type)?;
+            }
+            None => {
+                // Use embedded sound
                 self.try_embedded_sound(config, sound_type)?;
             }
-        } else {
-            // Use embedded sound
-            self.try_embedded_sound(config, sound_type)?;
         }
 
         Ok(())
     }
 
+    /// Play a sound from the configured sound theme, falling back to the
+    /// embedded sound if no installed theme has it
+    fn play_theme_sound(
+        &self,
+        config: &SoundConfig,
+        name: &str,
+        sound_type: SoundType,
+    ) -> Result<(), TomatError> {
+        match crate::sound_theme::lookup(&config.theme, name) {
+            Some(crate::sound_theme::ThemeSound::File(path)) => {
+                if let Err(e) = crate::audio::play_custom_file(&path, config.volume) {
+                    eprintln!("Failed to play theme sound {:?}: {}", path, e);
+                    self.try_embedded_sound(config, sound_type)?;
+                }
+            }
+            Some(crate::sound_theme::ThemeSound::Disabled) => {}
+            None => {
+                eprintln!(
+                    "Sound '{}' not found in sound theme '{}'",
+                    name, config.theme
+                );
+                self.try_embedded_sound(config, sound_type)?;
+            }
+        }
+        Ok(())
+    }
+
     fn try_embedded_sound(
         &self,
         config: &SoundConfig,
         sound_type: SoundType,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    ) -> Result<(), TomatError> {
         if let Err(e) = crate::audio::play_embedded_sound(sound_type, config.volume) {
             eprintln!("Failed to play embedded sound: {}", e);
             // Final fallback to system beep
@@ -460,36 +1265,39 @@ impl TimerState {
     fn send_notification(
         &self,
         message: &str,
+        urgency: crate::config::NotificationUrgency,
         config: &NotificationConfig,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+        note_prompt: Option<&[String]>,
+    ) -> Result<(), TomatError> {
         // Skip notifications during testing
         if is_testing() {
             return Ok(());
         }
 
-        let mut notification = Notification::new();
+        let mut notification = new_notification(config, PHASE_CATEGORY);
         notification
             .summary("Tomat")
             .body(message)
-            .timeout(config.timeout as i32)
-            .urgency(config.urgency.clone().into());
-
-        // Use configured icon
-        match get_notification_icon(config) {
-            Ok(icon) => {
-                notification.icon(&icon);
-            }
-            Err(e) => {
-                eprintln!(
-                    "Warning: Failed to get notification icon: {}, falling back to 'timer'",
-                    e
-                );
-                notification.icon("timer");
-            }
+            .urgency(urgency.into());
+        if note_prompt.is_some() {
+            notification.action(NOTE_ACTION, "Add note");
         }
 
-        if let Err(e) = notification.show() {
-            eprintln!("Failed to send notification: {}", e);
+        match notification.show() {
+            Ok(handle) => {
+                if let Some(prompt) = note_prompt {
+                    let prompt = prompt.to_vec();
+                    // Waiting for the action blocks until the notification closes
+                    std::thread::spawn(move || {
+                        handle.wait_for_action(|action| {
+                            if action == NOTE_ACTION {
+                                take_note(&prompt);
+                            }
+                        })
+                    });
+                }
+            }
+            Err(e) => eprintln!("Failed to send notification: {}", e),
         }
 
         Ok(())
@@ -515,26 +1323,110 @@ impl TimerState {
         }
     }
 
-    pub fn pause(&mut self) {
-        if !self.is_paused {
-            // Store elapsed time so we can restore it on resume
-            let elapsed = current_timestamp() - self.start_time;
-            self.paused_elapsed_seconds = Some(elapsed);
-            self.is_paused = true;
+    /// Whether a break has ended and the next work session waits to be started
+    pub fn is_awaiting_work(&self) -> bool {
+        matches!(
+            (&self.phase, self.pending_hook.as_deref()),
+            (Phase::Work, Some("work_start")) | (Phase::Prepare, Some("prepare_start"))
+        ) && self.is_paused
+    }
+
+    /// Work sessions completed today, for days starting at `day_start`
+    pub fn completed_today(&self) -> u32 {
+        if self.completed_date == Some(crate::stats::today(self.day_start)) {
+            self.completed_today
+        } else {
+            0
         }
     }
 
-    pub fn stop(&mut self) {
-        self.phase = Phase::Idle;
-        self.start_time = 0;
-        self.duration_minutes = 0.0;
-        self.current_session_count = 0;
-        self.is_paused = false;
-        self.paused_elapsed_seconds = None;
-        self.pending_hook = None;
+    /// Count a work session that ran until its end towards today. Returns
+    /// whether this reached the daily goal.
+    pub fn count_completed_work(&mut self) -> bool {
+        self.completed_today = self.completed_today() + 1;
+        self.completed_date = Some(crate::stats::today(self.day_start));
+        self.daily_goal() == Some(self.completed_today)
     }
 
-    /// Get raw timer status data for client-side formatting
+    /// Work sessions to complete each day, if there is a goal
+    pub fn daily_goal(&self) -> Option<u32> {
+        self.goal_override
+            .or(self.config_goal)
+            .filter(|&goal| goal > 0)
+    }
+
+    /// Congratulate on reaching the daily goal with a notification and the
+    /// `goal_reached` hook
+    pub fn announce_goal_reached(
+        &self,
+        notification_config: &NotificationConfig,
+        hooks_config: &crate::config::HooksConfig,
+    ) -> Result<(), TomatError> {
+        if notification_config.enabled && !notification_config.goal_message.is_empty() {
+            self.send_notification(
+                &notification_config.goal_message,
+                notification_config.urgency.clone(),
+                notification_config,
+                None,
+            )?;
+        }
+        self.spawn_hook(hooks_config, "goal_reached");
+        Ok(())
+    }
+
+    /// Pauses left in the work session, if there is a pause budget
+    pub fn pauses_left(&self) -> Option<u32> {
+        self.max_pauses
+            .filter(|_| matches!(self.phase, Phase::Work))
+            .map(|max| max.saturating_sub(self.pauses))
+    }
+
+    /// Whether pausing now would exceed the pause budget of the work session
+    pub fn pause_voids_session(&self) -> bool {
+        !self.is_paused && !self.is_awaiting_work() && self.pauses_left() == Some(0)
+    }
+
+    /// Throw away the progress of the work session, which waits paused to be
+    /// started over
+    pub fn void_work(&mut self) {
+        self.start_time = current_timestamp();
+        self.paused_elapsed_seconds = Some(0);
+        self.is_paused = true;
+        self.pauses = 0;
+    }
+
+    pub fn pause(&mut self) {
+        if !self.is_paused {
+            // Store elapsed time so we can restore it on resume
+            let elapsed = current_timestamp().saturating_sub(self.start_time);
+            self.paused_elapsed_seconds = Some(elapsed);
+            self.is_paused = true;
+            self.pauses += 1;
+        }
+    }
+
+    pub fn stop(&mut self) {
+        self.phase = Phase::Idle;
+        self.start_time = 0;
+        self.duration_minutes = 0.0;
+        self.current_session_count = 0;
+        self.focused_seconds = 0;
+        self.banked_break_seconds = 0;
+        self.long_break_postponed = false;
+        self.cycle_started_at = None;
+        self.task = None;
+        self.tags.clear();
+        self.pauses = 0;
+        self.backfill = None;
+        self.is_paused = false;
+        self.paused_elapsed_seconds = None;
+        self.pending_hook = None;
+        self.break_after_review = None;
+        self.last_work_session = None;
+        self.notes = None;
+    }
+
+    /// Get raw timer status data for client-side formatting
     pub fn get_timer_status(&self) -> TimerStatus {
         TimerStatus {
             phase: self.phase.clone(),
@@ -547,6 +1439,12 @@ impl TimerState {
             },
             current_session: self.current_session_count + 1,
             sessions_until_long_break: self.sessions_until_long_break,
+            privacy: self.privacy,
+            task: self.task.clone(),
+            pauses_left: self.pauses_left(),
+            banked_break_seconds: self.banked_break_seconds,
+            completed_today: self.completed_today(),
+            daily_goal: self.daily_goal(),
         }
     }
 
@@ -556,11 +1454,23 @@ impl TimerState {
         status: &TimerStatus,
         format: &Format,
         text_template: &str,
-        icons: &crate::config::DisplayIcons,
+        alt_template: &str,
+        display: &crate::config::DisplayConfig,
     ) -> StatusOutput {
+        let icons = &display.icons;
+        let tooltip_detail = display.tooltip;
         // Derive presentation data from raw state
         let (icon, phase_name, class) = match status.phase {
-            Phase::Idle => (icons.work.as_str(), "Idle", "idle"),
+            Phase::Idle => (icons.idle.as_deref().unwrap_or(&icons.work), "Idle", "idle"),
+            Phase::Prepare => (
+                icons.prepare.as_str(),
+                "Prepare",
+                if status.is_paused {
+                    "prepare-paused"
+                } else {
+                    "prepare"
+                },
+            ),
             Phase::Work => (
                 icons.work.as_str(),
                 "Work",
@@ -570,6 +1480,15 @@ impl TimerState {
                     "work"
                 },
             ),
+            Phase::Review => (
+                icons.review.as_str(),
+                "Review",
+                if status.is_paused {
+                    "review-paused"
+                } else {
+                    "review"
+                },
+            ),
             Phase::Break => (
                 icons.break_icon.as_str(),
                 "Break",
@@ -598,13 +1517,20 @@ impl TimerState {
             icons.play.as_str()
         };
 
-        let time_str = format!(
-            "{:02}:{:02}",
-            status.remaining_seconds / 60,
-            status.remaining_seconds % 60
-        );
+        // Privacy mode only applies while a session is running
+        let private = status.privacy && !matches!(status.phase, Phase::Idle);
 
-        let session_str = if matches!(status.phase, Phase::Work) {
+        let time_str = if private {
+            PRIVACY_TEXT.to_string()
+        } else {
+            format!(
+                "{:02}:{:02}",
+                status.remaining_seconds / 60,
+                status.remaining_seconds % 60
+            )
+        };
+
+        let session_str = if matches!(status.phase, Phase::Work) && !private {
             format!(
                 "{}/{}",
                 status.current_session, status.sessions_until_long_break
@@ -625,7 +1551,7 @@ impl TimerState {
         // Calculate percentage for progress bars
         let total_duration = (status.duration_minutes * 60.0) as u64;
         let elapsed = total_duration.saturating_sub(status.remaining_seconds);
-        let percentage = if matches!(status.phase, Phase::Idle) || status.is_paused {
+        let percentage = if matches!(status.phase, Phase::Idle) || status.is_paused || private {
             0.0
         } else if total_duration > 0 {
             (elapsed as f64 / total_duration as f64) * 100.0
@@ -633,38 +1559,108 @@ impl TimerState {
             100.0
         };
 
-        // Build tooltip
-        let tooltip = if matches!(status.phase, Phase::Idle) {
-            format!(
-                "Ready to start - {:.1}min work session",
-                status.duration_minutes
-            )
-        } else if status.is_paused {
+        // The task is hidden in privacy mode
+        let task = status
+            .task
+            .as_deref()
+            .filter(|_| !private)
+            .unwrap_or_default();
+        let task_info = if task.is_empty() {
+            String::new()
+        } else {
+            format!("\nTask: {}", task)
+        };
+        let completed_info = match status.daily_goal {
+            Some(goal) => format!("\n{}/{} today", status.completed_today, goal),
+            None if status.completed_today > 0 => {
+                format!("\nCompleted today: {}", status.completed_today)
+            }
+            None => String::new(),
+        };
+        let pauses_info = match status.pauses_left {
+            Some(left) if !private => format!("\nPauses left: {}", left),
+            _ => String::new(),
+        };
+        let banked_info = if status.banked_break_seconds > 0 && !private {
             format!(
-                "{}{} - {:.1}min (Paused)",
-                phase_name, sessions_info, status.duration_minutes
+                "\nBanked break: {}",
+                crate::history::format_duration(status.banked_break_seconds)
             )
         } else {
+            String::new()
+        };
+
+        // Apply text templates
+        let completed_str = status.completed_today.to_string();
+        let goal_str = status
+            .daily_goal
+            .map(|goal| goal.to_string())
+            .unwrap_or_default();
+        let sessions_total_str = status.sessions_until_long_break.to_string();
+        let percentage_str = format!("{:.0}", percentage);
+        let render = |template: &str| {
+            crate::template::render(template, &|name| {
+                let value = match name {
+                    "icon" => icon,
+                    "time" => &time_str,
+                    "state" | "state_symbol" => state_symbol,
+                    "phase" => phase_name,
+                    "session" => &session_str,
+                    "sessions_total" => &sessions_total_str,
+                    "percentage" => &percentage_str,
+                    "task" => task,
+                    "completed" => &completed_str,
+                    "goal" => &goal_str,
+                    _ => return None,
+                };
+                Some(value.to_string())
+            })
+        };
+
+        // Build tooltip
+        let paused_info = if status.is_paused { " (Paused)" } else { "" };
+        let tooltip = if private {
+            "Focusing".to_string()
+        } else if let Some(template) = &display.tooltip_format {
+            render(template)
+        } else if tooltip_detail == crate::config::TooltipDetail::Minimal {
+            format!("{}{}", phase_name, paused_info)
+        } else if matches!(status.phase, Phase::Idle) {
             format!(
-                "{}{} - {:.1}min",
-                phase_name, sessions_info, status.duration_minutes
+                "Ready to start - {:.1}min work session{}",
+                status.duration_minutes, completed_info
             )
+        } else {
+            let mut tooltip = format!(
+                "{}{} - {:.1}min{}{}{}{}{}",
+                phase_name,
+                sessions_info,
+                status.duration_minutes,
+                paused_info,
+                task_info,
+                completed_info,
+                pauses_info,
+                banked_info
+            );
+            if tooltip_detail == crate::config::TooltipDetail::Full {
+                tooltip.push_str(&format!("\nRemaining: {}", time_str));
+                if !status.is_paused {
+                    tooltip.push_str(&format!("\nEnds at {}", end_time(status.remaining_seconds)));
+                }
+            }
+            tooltip
         };
 
-        // Apply text template
-        let display_text = text_template
-            .replace("{icon}", icon)
-            .replace("{time}", &time_str)
-            .replace("{state}", state_symbol)
-            .replace("{phase}", phase_name)
-            .replace("{session}", &session_str);
+        let display_text = render(text_template);
 
         match format {
             Format::Waybar => StatusOutput::Waybar {
                 text: display_text,
+                alt: render(alt_template),
                 tooltip,
                 class: class.to_string(),
                 percentage,
+                schema_version: STATUS_SCHEMA_VERSION,
             },
             Format::I3statusRs => {
                 // Map timer states to i3status-rs states
@@ -673,6 +1669,7 @@ impl TimerState {
                 } else {
                     match status.phase {
                         Phase::Idle => "Info",
+                        Phase::Prepare | Phase::Review => "Warning",
                         Phase::Work => "Critical",
                         _ => "Good",
                     }
@@ -683,22 +1680,175 @@ impl TimerState {
                     short_text: Some(display_text),
                     icon: None,
                     state: Some(i3status_state.to_string()),
+                    schema_version: STATUS_SCHEMA_VERSION,
+                }
+            }
+            Format::Display => {
+                // Unlike the waybar percentage, a paused phase keeps its
+                // progress so that a slow display does not jump back to empty
+                let percent = if matches!(status.phase, Phase::Idle) || private {
+                    0
+                } else {
+                    (elapsed * 100)
+                        .checked_div(total_duration)
+                        .map_or(100, |percent| percent.min(100) as u8)
+                };
+
+                StatusOutput::Display {
+                    phase: status.phase.to_string(),
+                    time: time_str,
+                    percent,
+                    paused: status.is_paused,
+                    schema_version: STATUS_SCHEMA_VERSION,
                 }
             }
             Format::Plain => StatusOutput::Plain(display_text),
+            Format::Zellij => StatusOutput::Plain(ansi_colored(
+                &display_text,
+                display.colors.color_for(&status.phase, status.is_paused),
+            )),
+            // i3blocks reads the full text, short text and color from the
+            // first three lines
+            Format::I3blocks => StatusOutput::Plain(format!(
+                "{}\n{}\n{}",
+                display_text.replace('\n', " "),
+                render("{time}"),
+                display.colors.color_for(&status.phase, status.is_paused)
+            )),
+            // dwmblocks shows everything it reads, so keep to one line
+            Format::Dwmblocks => {
+                StatusOutput::Plain(display_text.lines().collect::<Vec<_>>().join(" "))
+            }
         }
     }
 }
 
-fn is_testing() -> bool {
+/// Color text with a 24-bit ANSI escape for a "#rrggbb" color, leaving it
+/// as it is if the color is invalid or the text empty
+fn ansi_colored(text: &str, color: &str) -> String {
+    let rgb = color
+        .strip_prefix('#')
+        .filter(|hex| hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+        .and_then(|hex| u32::from_str_radix(hex, 16).ok());
+    match rgb {
+        Some(rgb) if !text.is_empty() => format!(
+            "\x1b[38;2;{};{};{}m{}\x1b[0m",
+            rgb >> 16,
+            (rgb >> 8) & 0xff,
+            rgb & 0xff,
+            text
+        ),
+        _ => text.to_string(),
+    }
+}
+
+/// Describe a finished cycle of work sessions, e.g.
+/// "4 pomodoros done, 100 min focused since 09:00"
+fn long_break_summary(sessions: u32, focused_seconds: u64, since: Option<u64>) -> String {
+    let mut summary = format!(
+        "{} pomodoro{} done, {} min focused",
+        sessions,
+        if sessions == 1 { "" } else { "s" },
+        focused_seconds / 60
+    );
+    if let Some(started) = since.and_then(|ts| chrono::DateTime::from_timestamp(ts as i64, 0)) {
+        let started = started.with_timezone(&chrono::Local);
+        summary.push_str(&format!(" since {}", started.format("%H:%M")));
+    }
+    summary
+}
+
+/// Describe the day plan, one task per line with its progress and a marker
+/// for finished tasks and the task being worked on
+pub fn format_plan(plan: &[PlannedTask]) -> String {
+    if plan.is_empty() {
+        return "No tasks planned".to_string();
+    }
+
+    let current = plan.iter().position(|planned| !planned.is_done());
+    let width = plan
+        .iter()
+        .map(|planned| planned.task.chars().count())
+        .max()
+        .unwrap_or_default();
+    plan.iter()
+        .enumerate()
+        .map(|(i, planned)| {
+            let marker = if planned.is_done() {
+                "✓"
+            } else if Some(i) == current {
+                "▶"
+            } else {
+                " "
+            };
+            format!(
+                "{} {:width$}  {}/{}",
+                marker,
+                planned.task,
+                planned.completed,
+                planned.pomodoros,
+                width = width
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn is_testing() -> bool {
     std::env::var("TOMAT_TESTING").is_ok()
 }
 
-fn current_timestamp() -> u64 {
+/// Whether development features such as `tomat simulate` are enabled, which
+/// they are with TOMAT_DEV set and in tests
+pub fn is_dev_mode() -> bool {
+    std::env::var("TOMAT_DEV").is_ok() || is_testing()
+}
+
+/// Seconds the clock has been fast-forwarded by with `tomat simulate`
+static CLOCK_OFFSET: AtomicU64 = AtomicU64::new(0);
+
+/// Move the clock forward by `seconds`, returning the new total offset
+pub fn advance_clock(seconds: u64) -> u64 {
+    CLOCK_OFFSET.fetch_add(seconds, Ordering::Relaxed) + seconds
+}
+
+/// Set the total offset of the clock to `seconds` ahead of real time
+pub fn set_clock_offset(seconds: u64) {
+    CLOCK_OFFSET.store(seconds, Ordering::Relaxed);
+}
+
+/// A random number in [-1, 1), from the randomly seeded std hasher
+fn random_unit() -> f32 {
+    use std::hash::{BuildHasher, Hasher};
+    let bits = std::collections::hash_map::RandomState::new()
+        .build_hasher()
+        .finish();
+    (bits >> 40) as f32 / (1u64 << 23) as f32 - 1.0
+}
+
+pub fn current_timestamp() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap()
         .as_secs()
+        + CLOCK_OFFSET.load(Ordering::Relaxed)
+}
+
+/// Local time of day `seconds` from now, as HH:MM
+fn end_time(seconds: u64) -> String {
+    chrono::DateTime::from_timestamp((current_timestamp() + seconds) as i64, 0)
+        .map(|end| {
+            end.with_timezone(&chrono::Local)
+                .format("%H:%M")
+                .to_string()
+        })
+        .unwrap_or_default()
+}
+
+/// Whether the local time of day has reached `time`
+pub fn is_past(time: chrono::NaiveTime) -> bool {
+    chrono::DateTime::from_timestamp(current_timestamp() as i64, 0)
+        .is_some_and(|now| now.with_timezone(&chrono::Local).time() >= time)
 }
 
 #[cfg(test)]
@@ -864,9 +2014,432 @@ mod tests {
             .unwrap();
 
         assert!(matches!(timer.phase, Phase::LongBreak));
-        assert!(timer.is_paused);
-        assert_eq!(timer.duration_minutes, 15.0);
-        assert_eq!(timer.current_session_count, 0); // Reset after long break
+        assert!(timer.is_paused);
+        assert_eq!(timer.duration_minutes, 15.0);
+        assert_eq!(timer.current_session_count, 0); // Reset after long break
+    }
+
+    #[test]
+    fn test_next_phase_work_to_long_break_after_focused_minutes() {
+        setup_test_env();
+        let mut timer = TimerState::new(25.0, 5.0, 15.0, 4);
+        timer.long_break_after_minutes = Some(45.0);
+
+        // First session: 25 focused minutes, not enough for a long break
+        timer.start_work();
+        timer.start_time = current_timestamp() - 25 * 60;
+        timer
+            .next_phase(
+                &SoundConfig::default(),
+                &NotificationConfig::default(),
+                &crate::config::HooksConfig::default(),
+            )
+            .unwrap();
+        assert!(matches!(timer.phase, Phase::Break));
+        assert_eq!(timer.focused_seconds, 25 * 60);
+
+        // Second session crosses 45 minutes before the session count does
+        timer.start_work();
+        timer.start_time = current_timestamp() - 25 * 60;
+        timer
+            .next_phase(
+                &SoundConfig::default(),
+                &NotificationConfig::default(),
+                &crate::config::HooksConfig::default(),
+            )
+            .unwrap();
+        assert!(matches!(timer.phase, Phase::LongBreak));
+        assert_eq!(timer.current_session_count, 0);
+        assert_eq!(timer.focused_seconds, 0);
+    }
+
+    #[test]
+    fn test_long_break_summary() {
+        assert_eq!(
+            long_break_summary(4, 100 * 60 + 30, None),
+            "4 pomodoros done, 100 min focused"
+        );
+        assert_eq!(
+            long_break_summary(1, 25 * 60, None),
+            "1 pomodoro done, 25 min focused"
+        );
+
+        let start = chrono::Local::now()
+            .date_naive()
+            .and_hms_opt(9, 0, 0)
+            .unwrap()
+            .and_local_timezone(chrono::Local)
+            .unwrap();
+        assert_eq!(
+            long_break_summary(4, 6000, Some(start.timestamp() as u64)),
+            "4 pomodoros done, 100 min focused since 09:00"
+        );
+    }
+
+    #[test]
+    fn test_cycle_start_tracked_until_long_break() {
+        setup_test_env();
+        let mut timer = TimerState::new(25.0, 5.0, 15.0, 2);
+        timer.auto_advance = AutoAdvanceMode::All;
+        timer.start_work();
+        timer.start_time = current_timestamp() - 25 * 60;
+        let first_start = timer.start_time;
+        timer
+            .next_phase(
+                &SoundConfig::default(),
+                &NotificationConfig::default(),
+                &crate::config::HooksConfig::default(),
+            )
+            .unwrap();
+        assert_eq!(timer.cycle_started_at, Some(first_start));
+
+        timer.start_work();
+        timer.start_time = current_timestamp() - 25 * 60;
+        timer
+            .next_phase(
+                &SoundConfig::default(),
+                &NotificationConfig::default(),
+                &crate::config::HooksConfig::default(),
+            )
+            .unwrap();
+        assert!(matches!(timer.phase, Phase::LongBreak));
+        assert_eq!(timer.cycle_started_at, None);
+    }
+
+    #[test]
+    fn test_awaiting_work_after_break() {
+        setup_test_env();
+        let mut timer = TimerState::new(25.0, 5.0, 15.0, 4);
+        timer.auto_advance = crate::config::AutoAdvanceMode::None;
+        timer.start_break();
+        assert!(!timer.is_awaiting_work());
+
+        timer
+            .next_phase(
+                &SoundConfig::default(),
+                &NotificationConfig::default(),
+                &crate::config::HooksConfig::default(),
+            )
+            .unwrap();
+        assert!(timer.is_awaiting_work());
+
+        timer.resume();
+        assert!(!timer.is_awaiting_work());
+
+        // Pausing during work is not waiting for a break to end
+        timer.pause();
+        assert!(!timer.is_awaiting_work());
+    }
+
+    #[test]
+    fn test_next_chime_time() {
+        setup_test_env();
+        let mut timer = TimerState::new(25.0, 5.0, 15.0, 4);
+        assert_eq!(timer.next_chime_time(10), None);
+
+        timer.start_work();
+        timer.start_time = current_timestamp() - 3 * 60;
+        assert_eq!(timer.next_chime_time(10), Some(timer.start_time + 10 * 60));
+
+        timer.start_time = current_timestamp() - 10 * 60;
+        assert_eq!(timer.next_chime_time(10), Some(timer.start_time + 20 * 60));
+
+        // No chime on top of the end-of-phase sound
+        timer.start_time = current_timestamp() - 20 * 60;
+        assert_eq!(timer.next_chime_time(5), None);
+        assert_eq!(timer.next_chime_time(0), None);
+
+        timer.start_break();
+        assert_eq!(timer.next_chime_time(1), None);
+    }
+
+    #[test]
+    fn test_merge_break_banks_time_for_long_break() {
+        setup_test_env();
+        let mut timer = TimerState::new(25.0, 5.0, 15.0, 2);
+
+        // Skip a break with 3 minutes left
+        timer.phase = Phase::Break;
+        timer.duration_minutes = 5.0;
+        timer.start_time = current_timestamp() - 2 * 60;
+        timer.current_session_count = 1;
+
+        let banked = timer.merge_break();
+        assert_eq!(banked, 3 * 60);
+        assert!(matches!(timer.phase, Phase::Work));
+        assert!(!timer.is_paused);
+        assert_eq!(timer.banked_break_seconds, 3 * 60);
+
+        // The next long break is extended by the banked time
+        timer
+            .next_phase(
+                &SoundConfig::default(),
+                &NotificationConfig::default(),
+                &crate::config::HooksConfig::default(),
+            )
+            .unwrap();
+        assert!(matches!(timer.phase, Phase::LongBreak));
+        assert_eq!(timer.duration_minutes, 18.0);
+        assert_eq!(timer.banked_break_seconds, 0);
+    }
+
+    #[test]
+    fn test_take_banked_break() {
+        setup_test_env();
+        let mut timer = TimerState::new(25.0, 5.0, 15.0, 4);
+        timer.start_work();
+        assert!(timer.take_banked_break().is_err());
+
+        timer.banked_break_seconds = 3 * 60;
+        assert_eq!(timer.get_timer_status().banked_break_seconds, 3 * 60);
+        assert_eq!(timer.take_banked_break().unwrap(), 3 * 60);
+        assert!(matches!(timer.phase, Phase::Break));
+        assert_eq!(timer.duration_minutes, 3.0);
+        assert_eq!(timer.banked_break_seconds, 0);
+        // The work session did not count
+        assert_eq!(timer.current_session_count, 0);
+
+        // During a break, the break is extended
+        timer.banked_break_seconds = 90;
+        timer.take_banked_break().unwrap();
+        assert!(matches!(timer.phase, Phase::Break));
+        assert_eq!(timer.duration_minutes, 4.5);
+    }
+
+    #[test]
+    fn test_resync_clock_after_backwards_jump() {
+        let mut state = TimerState::new(25.0, 5.0, 15.0, 4);
+        assert_eq!(state.resync_clock(), None);
+
+        state.start_work();
+        assert_eq!(state.resync_clock(), None);
+
+        // The clock was set back an hour after the phase started
+        state.start_time = current_timestamp() + 3600;
+        assert_eq!(state.get_remaining_seconds(), 25 * 60);
+        assert!(state.resync_clock().is_some_and(|behind| behind >= 3599));
+        assert!(state.start_time <= current_timestamp());
+        assert_eq!(state.resync_clock(), None);
+
+        // Pausing with the start ahead of the clock does not underflow
+        state.start_time = current_timestamp() + 60;
+        state.pause();
+        assert_eq!(state.paused_elapsed_seconds, Some(0));
+    }
+
+    #[test]
+    fn test_should_alert_throttles_rapid_transitions() {
+        let mut state = TimerState::new(25.0, 5.0, 15.0, 4);
+        assert!(state.should_alert(100, 2));
+        assert!(!state.should_alert(101, 2));
+        // Each quiet transition extends the quiet period
+        assert!(!state.should_alert(102, 2));
+        assert!(state.should_alert(104, 2));
+
+        // A throttle of 0 alerts for every transition
+        assert!(state.should_alert(110, 0));
+    }
+
+    #[test]
+    fn test_postpone_long_break() {
+        setup_test_env();
+        let mut timer = TimerState::new(25.0, 5.0, 15.0, 2);
+        timer.auto_advance = AutoAdvanceMode::All;
+        let next_phase = |timer: &mut TimerState| {
+            timer
+                .next_phase(
+                    &SoundConfig::default(),
+                    &NotificationConfig::default(),
+                    &crate::config::HooksConfig::default(),
+                )
+                .unwrap();
+        };
+
+        timer.start_work();
+        assert!(timer.postpone_long_break().is_err());
+        next_phase(&mut timer);
+        assert!(timer.postpone_long_break().is_err());
+        next_phase(&mut timer);
+
+        // The last session of the cycle ends with a normal break
+        timer.postpone_long_break().unwrap();
+        next_phase(&mut timer);
+        assert!(matches!(timer.phase, Phase::Break));
+        next_phase(&mut timer);
+        next_phase(&mut timer);
+        assert!(matches!(timer.phase, Phase::LongBreak));
+
+        // A long break that has begun turns into a normal break, keeping
+        // banked time for the next long break
+        timer.duration_minutes += 3.0;
+        timer.postpone_long_break().unwrap();
+        assert!(matches!(timer.phase, Phase::Break));
+        assert_eq!(timer.duration_minutes, 5.0);
+        assert_eq!(timer.banked_break_seconds, 180);
+        next_phase(&mut timer);
+        next_phase(&mut timer);
+        assert!(matches!(timer.phase, Phase::LongBreak));
+        assert_eq!(timer.duration_minutes, 18.0);
+    }
+
+    #[test]
+    fn test_break_ends_cycle_after_workday_end() {
+        setup_test_env();
+        let mut timer = TimerState::new(25.0, 5.0, 15.0, 4);
+        timer.auto_advance = AutoAdvanceMode::All;
+        let next_phase = |timer: &mut TimerState| {
+            timer
+                .next_phase(
+                    &SoundConfig::default(),
+                    &NotificationConfig::default(),
+                    &crate::config::HooksConfig::default(),
+                )
+                .unwrap();
+        };
+
+        // Before the end of the workday the cycle carries on
+        timer.workday_end =
+            chrono::NaiveTime::from_hms_opt(23, 59, 59).filter(|&end| !is_past(end));
+        timer.start_work();
+        next_phase(&mut timer);
+        next_phase(&mut timer);
+        assert!(matches!(timer.phase, Phase::Work));
+
+        // A work session still ends with its break, but the break ends the
+        // cycle once the workday is over
+        timer.workday_end = chrono::NaiveTime::from_hms_opt(0, 0, 0);
+        next_phase(&mut timer);
+        assert!(matches!(timer.phase, Phase::Break));
+        next_phase(&mut timer);
+        assert!(matches!(timer.phase, Phase::Idle));
+        assert_eq!(timer.current_session_count, 0);
+    }
+
+    #[test]
+    fn test_plan_advances_to_next_task() {
+        setup_test_env();
+        let mut timer = TimerState::new(25.0, 5.0, 15.0, 4);
+        timer.auto_advance = AutoAdvanceMode::All;
+        timer.plan = vec![
+            PlannedTask {
+                task: "write spec".to_string(),
+                pomodoros: 2,
+                completed: 0,
+            },
+            PlannedTask {
+                task: "review".to_string(),
+                pomodoros: 1,
+                completed: 0,
+            },
+        ];
+        timer.task = timer
+            .current_plan_task()
+            .map(|planned| planned.task.clone());
+        timer.start_work();
+
+        let finish_work = |timer: &mut TimerState| {
+            timer
+                .next_phase(
+                    &SoundConfig::default(),
+                    &NotificationConfig::default(),
+                    &crate::config::HooksConfig::default(),
+                )
+                .unwrap();
+            timer.start_work();
+        };
+
+        finish_work(&mut timer);
+        assert_eq!(timer.plan[0].completed, 1);
+        assert_eq!(timer.task.as_deref(), Some("write spec"));
+
+        finish_work(&mut timer);
+        assert!(timer.plan[0].is_done());
+        assert_eq!(timer.task.as_deref(), Some("review"));
+
+        finish_work(&mut timer);
+        assert!(timer.current_plan_task().is_none());
+        assert_eq!(timer.task, None);
+
+        // Sessions on other tasks do not count towards the plan
+        timer.plan.push(PlannedTask {
+            task: "emails".to_string(),
+            pomodoros: 1,
+            completed: 0,
+        });
+        timer.task = Some("meeting".to_string());
+        finish_work(&mut timer);
+        assert_eq!(timer.plan[2].completed, 0);
+        assert_eq!(timer.task.as_deref(), Some("meeting"));
+    }
+
+    #[test]
+    fn test_plan_complete_takes_extended_long_break_or_stops() {
+        setup_test_env();
+        let planned_timer = || {
+            let mut timer = TimerState::new(25.0, 5.0, 15.0, 4);
+            timer.plan = vec![PlannedTask {
+                task: "write spec".to_string(),
+                pomodoros: 1,
+                completed: 0,
+            }];
+            timer.task = Some("write spec".to_string());
+            timer.start_work();
+            timer
+        };
+        let next_phase = |timer: &mut TimerState| {
+            timer
+                .next_phase(
+                    &SoundConfig::default(),
+                    &NotificationConfig::default(),
+                    &crate::config::HooksConfig::default(),
+                )
+                .unwrap();
+        };
+
+        // A long break is taken early, twice as long as usual
+        let mut timer = planned_timer();
+        next_phase(&mut timer);
+        assert!(matches!(timer.phase, Phase::LongBreak));
+        assert_eq!(timer.duration_minutes, 30.0);
+        assert_eq!(timer.current_session_count, 0);
+
+        let mut timer = planned_timer();
+        timer.plan_complete_break = Some(45.0);
+        next_phase(&mut timer);
+        assert_eq!(timer.duration_minutes, 45.0);
+
+        let mut timer = planned_timer();
+        timer.plan_complete = PlanCompleteAction::Stop;
+        next_phase(&mut timer);
+        assert!(matches!(timer.phase, Phase::Idle));
+        assert!(timer.plan[0].is_done());
+    }
+
+    #[test]
+    fn test_format_plan() {
+        assert_eq!(format_plan(&[]), "No tasks planned");
+
+        let plan = [
+            PlannedTask {
+                task: "write spec".to_string(),
+                pomodoros: 2,
+                completed: 2,
+            },
+            PlannedTask {
+                task: "review".to_string(),
+                pomodoros: 3,
+                completed: 1,
+            },
+            PlannedTask {
+                task: "emails".to_string(),
+                pomodoros: 1,
+                completed: 0,
+            },
+        ];
+        assert_eq!(
+            format_plan(&plan),
+            "✓ write spec  2/2\n▶ review      1/3\n  emails      0/1"
+        );
     }
 
     #[test]
@@ -918,17 +2491,22 @@ mod tests {
             &timer_status,
             &Format::default(),
             "{icon} {time} {state}",
-            &crate::config::DisplayIcons::default(),
+            "{session}",
+            &crate::config::DisplayConfig::default(),
         );
 
         match status {
             StatusOutput::Waybar {
                 text,
+                alt,
                 class,
                 tooltip,
                 percentage,
+                schema_version,
             } => {
+                assert_eq!(schema_version, STATUS_SCHEMA_VERSION);
                 assert_eq!(text, "🍅 25:00 ⏹");
+                assert_eq!(alt, "");
                 assert_eq!(class, "idle");
                 assert_eq!(tooltip, "Ready to start - 25.0min work session");
                 assert_eq!(percentage, 0.0);
@@ -947,17 +2525,22 @@ mod tests {
             &timer_status,
             &Format::default(),
             "{icon} {time} {state}",
-            &crate::config::DisplayIcons::default(),
+            "{session}",
+            &crate::config::DisplayConfig::default(),
         );
 
         match status {
             StatusOutput::Waybar {
                 text,
+                alt,
                 class,
                 tooltip,
                 percentage,
+                schema_version,
             } => {
+                assert_eq!(schema_version, STATUS_SCHEMA_VERSION);
                 assert!(text.starts_with("🍅"));
+                assert_eq!(alt, "1/4");
                 assert!(text.ends_with("▶"));
                 assert_eq!(class, "work");
                 assert!(tooltip.contains("Work (1/4)"));
@@ -980,7 +2563,8 @@ mod tests {
             &timer_status,
             &Format::default(),
             "{icon} {time} {state}",
-            &crate::config::DisplayIcons::default(),
+            "{session}",
+            &crate::config::DisplayConfig::default(),
         );
 
         match status {
@@ -1012,7 +2596,8 @@ mod tests {
             &timer_status,
             &Format::default(),
             "{icon} {time} {state}",
-            &crate::config::DisplayIcons::default(),
+            "{session}",
+            &crate::config::DisplayConfig::default(),
         );
 
         match status {
@@ -1030,6 +2615,124 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_jitter_randomizes_phase_lengths() {
+        let mut timer = TimerState::new(25.0, 5.0, 15.0, 4);
+        timer.start_work();
+        assert_eq!(timer.duration_minutes, 25.0);
+
+        timer.jitter = 0.1;
+        let mut lengths = std::collections::HashSet::new();
+        for _ in 0..20 {
+            timer.start_work();
+            let seconds = timer.duration_minutes * 60.0;
+            assert!((1350.0..=1650.0).contains(&seconds), "Got {}", seconds);
+            assert_eq!(seconds, seconds.round());
+            lengths.insert(seconds as u32);
+        }
+        assert!(lengths.len() > 1);
+
+        // The jitter is capped at half the length
+        timer.jitter = 3.0;
+        timer.start_break();
+        assert!((2.5..=7.5).contains(&timer.duration_minutes));
+    }
+
+    #[test]
+    fn test_prepare_phase_before_work() {
+        setup_test_env();
+        let mut timer = TimerState::new(25.0, 5.0, 15.0, 4);
+        let (sound, notification, hooks) = (
+            SoundConfig::default(),
+            NotificationConfig::default(),
+            crate::config::HooksConfig::default(),
+        );
+        timer.start_prepare_or_work(true);
+        assert!(
+            matches!(timer.phase, Phase::Work),
+            "No preparation by default"
+        );
+
+        timer.prepare_duration = 2.0;
+        timer.start_prepare_or_work(true);
+        assert!(matches!(timer.phase, Phase::Prepare));
+        assert_eq!(timer.duration_minutes, 2.0);
+        assert_eq!(timer.work_start_event(), "prepare_start");
+
+        // The preparation runs into work, which does not wait to be resumed
+        timer.next_phase(&sound, &notification, &hooks).unwrap();
+        assert!(matches!(timer.phase, Phase::Work));
+        assert!(!timer.is_paused);
+        assert_eq!(timer.current_session_count, 0);
+
+        // After a break, the preparation waits to be started
+        timer.next_phase(&sound, &notification, &hooks).unwrap();
+        timer.next_phase(&sound, &notification, &hooks).unwrap();
+        assert!(matches!(timer.phase, Phase::Prepare));
+        assert!(timer.is_paused);
+        assert!(timer.is_awaiting_work());
+
+        let status = TimerState::format_status(
+            &timer.get_timer_status(),
+            &Format::default(),
+            "{icon} {time} {state}",
+            "{session}",
+            &crate::config::DisplayConfig::default(),
+        );
+        match status {
+            StatusOutput::Waybar { text, class, .. } => {
+                assert_eq!(text, "📋 02:00 ⏸");
+                assert_eq!(class, "prepare-paused");
+            }
+            _ => panic!("Expected Waybar format for default"),
+        }
+    }
+
+    #[test]
+    fn test_review_phase_before_break() {
+        setup_test_env();
+        let mut timer = TimerState::new(25.0, 5.0, 15.0, 2);
+        timer.auto_advance = AutoAdvanceMode::All;
+        timer.review_duration = 3.0;
+        let (sound, notification, hooks) = (
+            SoundConfig::default(),
+            NotificationConfig::default(),
+            crate::config::HooksConfig::default(),
+        );
+        timer.start_work();
+
+        timer.next_phase(&sound, &notification, &hooks).unwrap();
+        assert!(matches!(timer.phase, Phase::Review));
+        assert_eq!(timer.duration_minutes, 3.0);
+        assert_eq!(timer.current_session_count, 1);
+
+        timer.next_phase(&sound, &notification, &hooks).unwrap();
+        assert!(matches!(timer.phase, Phase::Break));
+        assert_eq!(timer.duration_minutes, 5.0);
+
+        // The long break is kept for after the review as well
+        timer.next_phase(&sound, &notification, &hooks).unwrap();
+        timer.next_phase(&sound, &notification, &hooks).unwrap();
+        assert!(matches!(timer.phase, Phase::Review));
+        timer.next_phase(&sound, &notification, &hooks).unwrap();
+        assert!(matches!(timer.phase, Phase::LongBreak));
+        assert_eq!(timer.duration_minutes, 15.0);
+        assert_eq!(timer.break_after_review, None);
+    }
+
+    #[test]
+    fn test_read_note() {
+        let prompt = |args: &[&str]| args.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            read_note(&prompt(&["echo", " finished draft "])).as_deref(),
+            Some("finished draft")
+        );
+        // Empty or cancelled prompts take no note
+        assert_eq!(read_note(&prompt(&["echo"])), None);
+        assert_eq!(read_note(&prompt(&["false"])), None);
+        assert_eq!(read_note(&[]), None);
+    }
+
     #[test]
     fn test_session_count_increments_correctly() {
         setup_test_env();
@@ -1102,7 +2805,8 @@ mod tests {
             &timer_status,
             &Format::default(),
             "{time} - {phase}",
-            &crate::config::DisplayIcons::default(),
+            "{session}",
+            &crate::config::DisplayConfig::default(),
         );
 
         match status {
@@ -1119,7 +2823,8 @@ mod tests {
             &timer_status,
             &Format::default(),
             "[{session}] {icon}",
-            &crate::config::DisplayIcons::default(),
+            "{session}",
+            &crate::config::DisplayConfig::default(),
         );
 
         match status {
@@ -1191,26 +2896,151 @@ mod tests {
     }
 
     #[test]
-    fn test_icon_path_creation() {
-        // Test that the icon path function works and creates the cache directory
-        let icon_path = get_cached_icon_path().expect("Should be able to get icon path");
+    fn test_continue_backfilled_cycle() {
+        let mut timer = TimerState::new(25.0, 5.0, 15.0, 4);
+        let progress = CycleProgress {
+            sessions: 2,
+            focused_seconds: 3000,
+            started_at: 1000,
+            valid_until: current_timestamp() + 3600,
+        };
 
-        // The icon file should exist after calling get_cached_icon_path
-        assert!(icon_path.exists(), "Icon file should be created");
+        timer.backfill = Some(progress);
+        timer.continue_backfilled_cycle();
+        assert_eq!(timer.current_session_count, 2);
+        assert_eq!(timer.focused_seconds, 3000);
+        assert_eq!(timer.cycle_started_at, Some(1000));
+        // Only the first cycle continues it
+        assert_eq!(timer.backfill, None);
 
-        // The icon file should have the correct extension
-        assert_eq!(icon_path.extension().unwrap(), "png");
+        // Progress from an earlier day is dropped
+        let mut timer = TimerState::new(25.0, 5.0, 15.0, 4);
+        timer.backfill = Some(CycleProgress {
+            valid_until: current_timestamp() - 1,
+            ..progress
+        });
+        timer.continue_backfilled_cycle();
+        assert_eq!(timer.current_session_count, 0);
+        assert_eq!(timer.backfill, None);
+    }
 
-        // The icon file should contain the embedded data
-        let file_data = std::fs::read(&icon_path).expect("Should be able to read icon file");
-        assert_eq!(
-            file_data, ICON_DATA,
-            "Icon file should contain the embedded data"
+    #[test]
+    fn test_pauses_are_counted_per_phase() {
+        setup_test_env();
+        let mut timer = TimerState::new(25.0, 5.0, 15.0, 4);
+        timer.auto_advance = AutoAdvanceMode::All;
+        timer.start_work();
+
+        timer.pause();
+        // Pausing a paused timer is not another interruption
+        timer.pause();
+        timer.resume();
+        timer.pause();
+        assert_eq!(timer.pauses, 2);
+
+        timer
+            .next_phase(
+                &SoundConfig::default(),
+                &NotificationConfig::default(),
+                &crate::config::HooksConfig::default(),
+            )
+            .unwrap();
+        assert!(matches!(timer.phase, Phase::Break));
+        assert_eq!(timer.pauses, 0);
+    }
+
+    #[test]
+    fn test_completed_today_resets_with_the_day() {
+        let mut timer = TimerState::new(25.0, 5.0, 15.0, 4);
+        assert_eq!(timer.completed_today(), 0);
+
+        timer.count_completed_work();
+        timer.count_completed_work();
+        assert_eq!(timer.completed_today(), 2);
+        assert_eq!(timer.get_timer_status().completed_today, 2);
+
+        // A count from an earlier day is not carried over
+        timer.completed_date = timer.completed_date.and_then(|date| date.pred_opt());
+        assert_eq!(timer.completed_today(), 0);
+        timer.count_completed_work();
+        assert_eq!(timer.completed_today(), 1);
+    }
+
+    #[test]
+    fn test_daily_goal() {
+        let mut timer = TimerState::new(25.0, 5.0, 15.0, 4);
+        assert_eq!(timer.daily_goal(), None);
+        assert!(!timer.count_completed_work());
+
+        timer.config_goal = Some(2);
+        assert!(timer.count_completed_work());
+        assert!(!timer.count_completed_work(), "Only reached once");
+
+        // The goal set with `tomat goal` wins, and 0 turns it off
+        timer.goal_override = Some(5);
+        assert_eq!(timer.get_timer_status().daily_goal, Some(5));
+        timer.goal_override = Some(0);
+        assert_eq!(timer.daily_goal(), None);
+    }
+
+    #[test]
+    fn test_pause_budget() {
+        setup_test_env();
+        let mut timer = TimerState::new(25.0, 5.0, 15.0, 4);
+        assert_eq!(timer.pauses_left(), None);
+
+        timer.max_pauses = Some(1);
+        assert_eq!(timer.pauses_left(), None, "No budget outside of work");
+        timer.start_work();
+        assert_eq!(timer.pauses_left(), Some(1));
+        assert!(!timer.pause_voids_session());
+
+        timer.pause();
+        timer.resume();
+        assert_eq!(timer.get_timer_status().pauses_left, Some(0));
+        assert!(timer.pause_voids_session());
+
+        timer.start_time -= 600;
+        timer.void_work();
+        assert!(timer.is_paused);
+        assert_eq!(timer.pauses_left(), Some(1));
+        assert_eq!(timer.get_remaining_seconds(), 25 * 60);
+    }
+
+    #[test]
+    fn test_embedded_icon_is_sent_as_image_data() {
+        let image = embedded_icon().expect("The embedded icon should decode");
+        assert!(decode_icon(b"not a png").is_err());
+
+        let config = NotificationConfig::default();
+        let notification = new_notification(&config, PHASE_CATEGORY);
+        assert!(
+            notification
+                .hints
+                .contains(&notify_rust::Hint::ImageData(image.clone()))
         );
+        assert!(notification.icon.is_empty());
+    }
 
-        // Calling get_cached_icon_path again should not change the file
-        let icon_path2 = get_cached_icon_path().expect("Should be able to get icon path again");
-        assert_eq!(icon_path, icon_path2, "Icon path should be consistent");
+    #[test]
+    fn test_new_notification_groups_under_app() {
+        let config = NotificationConfig {
+            icon: "theme".to_string(),
+            ..Default::default()
+        };
+        let notification = new_notification(&config, PHASE_CATEGORY);
+        assert_eq!(notification.appname, "Tomat");
+        assert_eq!(notification.icon, "timer");
+        assert!(
+            notification
+                .hints
+                .contains(&notify_rust::Hint::DesktopEntry("tomat".to_string()))
+        );
+        assert!(
+            notification
+                .hints
+                .contains(&notify_rust::Hint::Category(PHASE_CATEGORY.to_string()))
+        );
     }
 
     #[test]
@@ -1222,19 +3052,16 @@ mod tests {
             icon: "auto".to_string(),
             ..Default::default()
         };
-        let icon = get_notification_icon(&config).expect("Should get auto icon");
-        assert!(
-            icon.ends_with("icon.png"),
-            "Auto icon should be cached icon path"
-        );
+        let icon = get_notification_icon(&config);
+        assert_eq!(icon, NotificationIcon::Embedded);
 
         // Test "theme" mode
         let config = NotificationConfig {
             icon: "theme".to_string(),
             ..Default::default()
         };
-        let icon = get_notification_icon(&config).expect("Should get theme icon");
-        assert_eq!(icon, "timer", "Theme icon should be 'timer'");
+        let icon = get_notification_icon(&config);
+        assert_eq!(icon, NotificationIcon::Named("timer".to_string()));
 
         // Test custom path mode (with existing file)
         let temp_icon = std::env::temp_dir().join("test_icon.png");
@@ -1244,13 +3071,20 @@ mod tests {
             icon: temp_icon.to_str().unwrap().to_string(),
             ..Default::default()
         };
-        let icon = get_notification_icon(&config).expect("Should get custom icon");
+        let icon = get_notification_icon(&config);
         assert_eq!(
             icon,
-            temp_icon.to_str().unwrap(),
+            NotificationIcon::Named(temp_icon.to_str().unwrap().to_string()),
             "Custom icon should match path"
         );
 
+        // A missing icon file falls back to the embedded icon
+        let config = NotificationConfig {
+            icon: "/nonexistent/icon.png".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(get_notification_icon(&config), NotificationIcon::Embedded);
+
         // Clean up
         std::fs::remove_file(&temp_icon).ok();
     }
@@ -1339,4 +3173,298 @@ mod tests {
         assert!(matches!(timer.phase, Phase::LongBreak));
         assert!(!timer.is_paused); // Should be running
     }
+
+    #[test]
+    fn test_progress_placeholders_and_tooltip_template() {
+        let mut timer = TimerState::new(25.0, 5.0, 15.0, 4);
+        timer.start_work();
+        timer.start_time -= 5 * 60;
+        let display = crate::config::DisplayConfig {
+            tooltip_format: Some("{phase}: {percentage}% of {sessions_total}".to_string()),
+            ..Default::default()
+        };
+
+        let status = TimerState::format_status(
+            &timer.get_timer_status(),
+            &Format::Waybar,
+            "{state_symbol} {sessions_total}",
+            "{session}",
+            &display,
+        );
+        match status {
+            StatusOutput::Waybar { text, tooltip, .. } => {
+                assert_eq!(text, "▶ 4");
+                assert_eq!(tooltip, "Work: 20% of 4");
+            }
+            _ => panic!("Expected Waybar format"),
+        }
+    }
+
+    #[test]
+    fn test_task_in_template_and_tooltip() {
+        let mut timer = TimerState::new(25.0, 5.0, 15.0, 4);
+        timer.start_work();
+        timer.is_paused = true;
+        timer.paused_elapsed_seconds = Some(0);
+        let template = "{phase:upper}{task:?( 📝 {task})}";
+
+        let status = TimerState::format_status(
+            &timer.get_timer_status(),
+            &Format::Waybar,
+            template,
+            "{session}",
+            &crate::config::DisplayConfig::default(),
+        );
+        assert!(matches!(status, StatusOutput::Waybar { text, .. } if text == "WORK"));
+
+        timer.task = Some("write spec".to_string());
+        let status = TimerState::format_status(
+            &timer.get_timer_status(),
+            &Format::Waybar,
+            template,
+            "{session}",
+            &crate::config::DisplayConfig::default(),
+        );
+        match status {
+            StatusOutput::Waybar { text, tooltip, .. } => {
+                assert_eq!(text, "WORK 📝 write spec");
+                assert_eq!(tooltip, "Work (1/4) - 25.0min (Paused)\nTask: write spec");
+            }
+            _ => panic!("Expected Waybar format"),
+        }
+
+        // Privacy mode hides the task
+        timer.privacy = true;
+        let status = TimerState::format_status(
+            &timer.get_timer_status(),
+            &Format::Plain,
+            template,
+            "{session}",
+            &crate::config::DisplayConfig::default(),
+        );
+        assert!(matches!(status, StatusOutput::Plain(text) if text == "WORK"));
+    }
+
+    #[test]
+    fn test_tooltip_detail_levels() {
+        use crate::config::TooltipDetail;
+
+        let mut timer = TimerState::new(25.0, 5.0, 15.0, 4);
+        timer.start_work();
+        timer.is_paused = true;
+        timer.paused_elapsed_seconds = Some(0);
+        timer.task = Some("write spec".to_string());
+        let tooltip = |timer: &TimerState, detail| match TimerState::format_status(
+            &timer.get_timer_status(),
+            &Format::Waybar,
+            "{time}",
+            "{session}",
+            &crate::config::DisplayConfig {
+                tooltip: detail,
+                ..Default::default()
+            },
+        ) {
+            StatusOutput::Waybar { tooltip, .. } => tooltip,
+            _ => panic!("Expected Waybar format"),
+        };
+
+        assert_eq!(tooltip(&timer, TooltipDetail::Minimal), "Work (Paused)");
+        assert_eq!(
+            tooltip(&timer, TooltipDetail::Full),
+            "Work (1/4) - 25.0min (Paused)\nTask: write spec\nRemaining: 25:00"
+        );
+
+        // While running, the full tooltip also says when the phase ends
+        timer.is_paused = false;
+        timer.paused_elapsed_seconds = None;
+        assert_eq!(tooltip(&timer, TooltipDetail::Minimal), "Work");
+        assert!(tooltip(&timer, TooltipDetail::Full).contains("\nEnds at "));
+
+        // Privacy mode hides the details at every level
+        timer.privacy = true;
+        assert_eq!(tooltip(&timer, TooltipDetail::Full), "Focusing");
+    }
+
+    #[test]
+    fn test_i3blocks_and_dwmblocks_formats() {
+        let mut timer = TimerState::new(25.0, 5.0, 15.0, 4);
+        timer.start_work();
+        let display = crate::config::DisplayConfig::default();
+        let plain = |format: &Format, template: &str| match TimerState::format_status(
+            &timer.get_timer_status(),
+            format,
+            template,
+            "{session}",
+            &display,
+        ) {
+            StatusOutput::Plain(text) => text,
+            _ => panic!("Expected plain text"),
+        };
+
+        assert_eq!(
+            plain(&Format::I3blocks, "{icon} {time} {state}"),
+            "🍅 25:00 ▶\n25:00\n#f38ba8"
+        );
+        // Line breaks in the template would shift the short text and color
+        assert_eq!(
+            plain(&Format::I3blocks, "{phase}\n{time}"),
+            "Work 25:00\n25:00\n#f38ba8"
+        );
+        assert_eq!(plain(&Format::Dwmblocks, "{phase}\n{time}"), "Work 25:00");
+    }
+
+    #[test]
+    fn test_zellij_format() {
+        let mut timer = TimerState::new(25.0, 5.0, 15.0, 4);
+        timer.start_work();
+        timer.is_paused = true;
+        timer.paused_elapsed_seconds = Some(0);
+        let display = crate::config::DisplayConfig::default();
+        let zellij = |timer: &TimerState| match TimerState::format_status(
+            &timer.get_timer_status(),
+            &Format::Zellij,
+            "{time}",
+            "{session}",
+            &display,
+        ) {
+            StatusOutput::Plain(text) => text,
+            _ => panic!("Expected plain text"),
+        };
+
+        // Paused phases use the paused color
+        assert_eq!(zellij(&timer), "\x1b[38;2;249;226;175m25:00\x1b[0m");
+
+        timer.phase = Phase::Break;
+        timer.is_paused = false;
+        assert!(zellij(&timer).starts_with("\x1b[38;2;166;227;161m"));
+
+        assert_eq!(ansi_colored("text", "not a color"), "text");
+        assert_eq!(ansi_colored("", "#ffffff"), "");
+    }
+
+    #[test]
+    fn test_display_format() {
+        let status = TimerStatus {
+            phase: Phase::LongBreak,
+            is_paused: true,
+            remaining_seconds: 600,
+            duration_minutes: 15.0,
+            current_session: 4,
+            sessions_until_long_break: 4,
+            privacy: false,
+            task: None,
+            pauses_left: None,
+            banked_break_seconds: 0,
+            completed_today: 0,
+            daily_goal: None,
+        };
+        let display = |status: &TimerStatus| {
+            serde_json::to_string(&TimerState::format_status(
+                status,
+                &Format::Display,
+                "{icon} {time}",
+                "{session}",
+                &crate::config::DisplayConfig::default(),
+            ))
+            .unwrap()
+        };
+
+        // Paused phases keep their progress
+        assert_eq!(
+            display(&status),
+            r#"{"phase":"long_break","time":"10:00","percent":33,"paused":true,"schema_version":1}"#
+        );
+
+        let status = TimerStatus {
+            phase: Phase::Work,
+            is_paused: false,
+            privacy: true,
+            ..status
+        };
+        assert_eq!(
+            display(&status),
+            r#"{"phase":"work","time":"focusing","percent":0,"paused":false,"schema_version":1}"#
+        );
+    }
+
+    #[test]
+    fn test_privacy_mode_hides_details() {
+        let mut timer = TimerState::new(25.0, 5.0, 15.0, 4);
+        timer.privacy = true;
+
+        // Idle status is not affected
+        let status = TimerState::format_status(
+            &timer.get_timer_status(),
+            &Format::Waybar,
+            "{time}",
+            "{session}",
+            &crate::config::DisplayConfig::default(),
+        );
+        assert!(matches!(status, StatusOutput::Waybar { text, .. } if text == "25:00"));
+
+        timer.start_work();
+        let status = TimerState::format_status(
+            &timer.get_timer_status(),
+            &Format::Waybar,
+            "{icon} {time} {session}",
+            "{session}",
+            &crate::config::DisplayConfig::default(),
+        );
+        match status {
+            StatusOutput::Waybar {
+                text,
+                tooltip,
+                class,
+                percentage,
+                ..
+            } => {
+                assert_eq!(text, "🍅 focusing ");
+                assert_eq!(tooltip, "Focusing");
+                assert_eq!(class, "work");
+                assert_eq!(percentage, 0.0);
+            }
+            _ => panic!("Expected Waybar format"),
+        }
+    }
+
+    #[test]
+    fn test_status_schema_matches_output() {
+        let timer_status = TimerStatus {
+            phase: Phase::Work,
+            is_paused: false,
+            remaining_seconds: 600,
+            duration_minutes: 25.0,
+            current_session: 1,
+            sessions_until_long_break: 4,
+            privacy: false,
+            task: None,
+            pauses_left: None,
+            banked_break_seconds: 0,
+            completed_today: 0,
+            daily_goal: None,
+        };
+
+        for format in [Format::Waybar, Format::I3statusRs] {
+            let output = TimerState::format_status(
+                &timer_status,
+                &format,
+                "{icon} {time}",
+                "{session}",
+                &crate::config::DisplayConfig::default(),
+            );
+            let value = serde_json::to_value(&output).unwrap();
+            let schema = StatusOutput::schema(&format);
+
+            assert_eq!(value["schema_version"], STATUS_SCHEMA_VERSION);
+
+            // Every emitted field is described, and every required field is emitted
+            let properties = schema["properties"].as_object().unwrap();
+            for key in value.as_object().unwrap().keys() {
+                assert!(properties.contains_key(key), "{} missing from schema", key);
+            }
+            for key in schema["required"].as_array().unwrap() {
+                assert!(value.get(key.as_str().unwrap()).is_some());
+            }
+        }
+    }
 }
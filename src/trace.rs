@@ -0,0 +1,79 @@
+use serde_json::Value;
+
+use crate::timer::TimerState;
+
+/// Logs every change of the timer state with its cause, for
+/// `tomat daemon run --trace-transitions`
+pub struct TransitionTracer {
+    last: Value,
+}
+
+impl TransitionTracer {
+    pub fn new(state: &TimerState) -> Self {
+        Self {
+            last: serde_json::to_value(state).unwrap_or_default(),
+        }
+    }
+
+    /// Print the fields that changed since the last call, if any, with a
+    /// timestamp and what changed them
+    pub fn record(&mut self, state: &TimerState, cause: &str) {
+        let current = serde_json::to_value(state).unwrap_or_default();
+        let changes = changes(&self.last, &current);
+        if !changes.is_empty() {
+            println!(
+                "[{}] {}",
+                chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f"),
+                cause
+            );
+            for change in changes {
+                println!("  {}", change);
+            }
+        }
+        self.last = current;
+    }
+}
+
+/// The top-level fields that differ between two serialized states, as
+/// "field: old -> new"
+fn changes(old: &Value, new: &Value) -> Vec<String> {
+    let (Some(old), Some(new)) = (old.as_object(), new.as_object()) else {
+        return Vec::new();
+    };
+    let mut fields: Vec<&String> = old.keys().chain(new.keys()).collect();
+    fields.sort();
+    fields.dedup();
+
+    fields
+        .into_iter()
+        .filter_map(|field| {
+            let before = old.get(field).unwrap_or(&Value::Null);
+            let after = new.get(field).unwrap_or(&Value::Null);
+            (before != after).then(|| format!("{}: {} -> {}", field, before, after))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changes() {
+        let mut state = TimerState::new(25.0, 5.0, 15.0, 4);
+        let old = serde_json::to_value(&state).unwrap();
+        assert!(changes(&old, &old).is_empty());
+
+        state.start_work();
+        state.task = Some("report".to_string());
+        let new = serde_json::to_value(&state).unwrap();
+        let changes = changes(&old, &new);
+        assert!(changes.contains(&r#"phase: "Idle" -> "Work""#.to_string()));
+        assert!(changes.contains(&r#"task: null -> "report""#.to_string()));
+        assert!(
+            !changes
+                .iter()
+                .any(|change| change.starts_with("work_duration"))
+        );
+    }
+}
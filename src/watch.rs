@@ -0,0 +1,123 @@
+use std::ffi::{CString, OsString};
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+use tokio::io::unix::AsyncFd;
+
+use crate::error::TomatError;
+
+/// Directory events that can change a file in it. Editors often save by
+/// writing a new file and renaming it over the old one, so the directory is
+/// watched rather than the file itself.
+const WATCH_MASK: u32 = libc::IN_CLOSE_WRITE
+    | libc::IN_MOVED_TO
+    | libc::IN_MOVED_FROM
+    | libc::IN_CREATE
+    | libc::IN_DELETE;
+
+/// Size of the fixed part of an inotify event, which is followed by the name
+const EVENT_HEADER: usize = std::mem::size_of::<libc::inotify_event>();
+
+/// Watches a single file for changes with inotify
+pub struct FileWatcher {
+    fd: AsyncFd<OwnedFd>,
+    name: OsString,
+}
+
+impl FileWatcher {
+    pub fn new(path: &Path) -> Result<Self, TomatError> {
+        let name = path
+            .file_name()
+            .ok_or_else(|| TomatError::InvalidArgument(format!("{:?} is not a file", path)))?
+            .to_os_string();
+        let dir = path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        let dir = CString::new(dir.as_os_str().as_bytes())
+            .map_err(|_| TomatError::InvalidArgument(format!("Invalid path {:?}", dir)))?;
+
+        let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+        if fd < 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+        if unsafe { libc::inotify_add_watch(fd.as_raw_fd(), dir.as_ptr(), WATCH_MASK) } < 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+
+        Ok(Self {
+            fd: AsyncFd::new(fd)?,
+            name,
+        })
+    }
+
+    /// Wait until the watched file is written, replaced or removed
+    pub async fn changed(&self) -> Result<(), TomatError> {
+        let mut buf = [0u8; 4096];
+        loop {
+            let mut guard = self.fd.readable().await?;
+            let read = guard.try_io(|fd| {
+                let n = unsafe { libc::read(fd.as_raw_fd(), buf.as_mut_ptr().cast(), buf.len()) };
+                if n < 0 {
+                    Err(std::io::Error::last_os_error())
+                } else {
+                    Ok(n as usize)
+                }
+            });
+            let Ok(len) = read else {
+                continue;
+            };
+            if event_names(&buf[..len?]).any(|name| name == self.name.as_bytes()) {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Names of the files in a buffer of inotify events
+fn event_names(buf: &[u8]) -> impl Iterator<Item = &[u8]> {
+    let mut offset = 0;
+    std::iter::from_fn(move || {
+        let header = buf.get(offset..offset + EVENT_HEADER)?;
+        let len = u32::from_ne_bytes(header[12..16].try_into().ok()?) as usize;
+        let name = buf.get(offset + EVENT_HEADER..offset + EVENT_HEADER + len)?;
+        offset += EVENT_HEADER + len;
+        // The name is padded with NUL bytes
+        Some(name.split(|&b| b == 0).next().unwrap_or_default())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(name: &str, padded_len: usize) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&1i32.to_ne_bytes());
+        bytes.extend_from_slice(&libc::IN_CLOSE_WRITE.to_ne_bytes());
+        bytes.extend_from_slice(&0u32.to_ne_bytes());
+        bytes.extend_from_slice(&(padded_len as u32).to_ne_bytes());
+        let mut name = name.as_bytes().to_vec();
+        name.resize(padded_len, 0);
+        bytes.extend_from_slice(&name);
+        bytes
+    }
+
+    #[test]
+    fn test_event_names() {
+        let mut buf = event("config.toml", 16);
+        buf.extend(event(".config.toml.swp", 32));
+        buf.extend(event("", 0));
+
+        let names: Vec<&[u8]> = event_names(&buf).collect();
+        assert_eq!(
+            names,
+            vec![&b"config.toml"[..], &b".config.toml.swp"[..], &b""[..]]
+        );
+
+        // A truncated event is ignored
+        assert_eq!(event_names(&buf[..EVENT_HEADER + 4]).count(), 0);
+    }
+}
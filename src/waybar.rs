@@ -0,0 +1,67 @@
+use std::io::Write;
+
+use serde_json::json;
+
+use crate::ServerResponse;
+use crate::config::DisplayConfig;
+use crate::error::TomatError;
+use crate::server::send_command;
+use crate::timer::{Format, STATUS_SCHEMA_VERSION, StatusOutput, TimerState, TimerStatus};
+
+/// Waybar JSON for the current status. Without a daemon, the text is empty,
+/// which hides the module.
+async fn status_line(format: Option<&str>, display: &DisplayConfig) -> Result<String, TomatError> {
+    let status = send_command("status", json!({ "output": "waybar" }))
+        .await
+        .and_then(ServerResponse::into_result)
+        .and_then(|response| Ok(serde_json::from_value::<TimerStatus>(response.data)?));
+    let output = match status {
+        Ok(status) => {
+            let (template, alt_template) = display.templates_at(
+                &status.phase,
+                status.is_paused,
+                format,
+                crate::timer::current_timestamp(),
+            );
+            TimerState::format_status(&status, &Format::Waybar, template, alt_template, display)
+        }
+        Err(_) => StatusOutput::Waybar {
+            text: String::new(),
+            alt: String::new(),
+            tooltip: String::new(),
+            class: String::new(),
+            percentage: 0.0,
+            schema_version: STATUS_SCHEMA_VERSION,
+        },
+    };
+    Ok(serde_json::to_string(&output)?)
+}
+
+/// Print a waybar JSON line whenever the status changes, checking every
+/// `interval` seconds. A running timer changes once a second; an idle or
+/// paused one prints nothing until it is toggled. The daemon may come and go.
+/// Returns when waybar closes the pipe.
+pub async fn run(
+    format: Option<&str>,
+    interval: f64,
+    display: &DisplayConfig,
+) -> Result<(), TomatError> {
+    let interval = std::time::Duration::from_secs_f64(interval);
+    let mut last_line = None;
+
+    loop {
+        let line = status_line(format, display).await?;
+        if last_line.as_ref() != Some(&line) {
+            let mut stdout = std::io::stdout().lock();
+            if writeln!(stdout, "{}", line)
+                .and_then(|_| stdout.flush())
+                .is_err()
+            {
+                return Ok(());
+            }
+            last_line = Some(line);
+        }
+
+        tokio::time::sleep(interval).await;
+    }
+}
@@ -0,0 +1,75 @@
+use super::common::TestDaemon;
+use tomat_client::{Client, Phase, StartOptions};
+
+#[test]
+fn test_client_library_against_daemon() -> Result<(), Box<dyn std::error::Error>> {
+    let daemon = TestDaemon::start()?;
+    let client = Client::new(daemon._temp_dir.path().join("tomat.sock"));
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+
+    runtime.block_on(async {
+        assert!(client.ping().await? > 0);
+
+        let status = client.status().await?;
+        assert_eq!(status.phase, Phase::Idle);
+
+        let mut events = client.subscribe().await?;
+        client
+            .start(&StartOptions {
+                work: Some(7.0),
+                task: Some("bindings".to_string()),
+                ..Default::default()
+            })
+            .await?;
+        let status = client.status().await?;
+        assert_eq!(status.phase, Phase::Work);
+        assert_eq!(status.duration_minutes, 7.0);
+        assert_eq!(status.task.as_deref(), Some("bindings"));
+        let event = events.next().await?.expect("the daemon is running");
+        assert_eq!(event.event, "phase_change");
+        assert_eq!(event.status.phase, Phase::Work);
+
+        client.pause().await?;
+        assert!(client.status().await?.is_paused);
+        client.toggle().await?;
+        assert!(!client.status().await?.is_paused);
+
+        client.skip(false).await?;
+        assert_eq!(client.status().await?.phase, Phase::Break);
+
+        client.privacy(true).await?;
+        assert!(client.status().await?.privacy);
+
+        client.plan_add("docs", 2).await?;
+        let plan = client.plan().await?;
+        assert_eq!(plan.len(), 1);
+        assert_eq!((plan[0].task.as_str(), plan[0].pomodoros), ("docs", 2));
+        client.plan_clear().await?;
+        assert!(client.plan().await?.is_empty());
+
+        let goal = client.set_goal(Some(6)).await?;
+        assert_eq!(goal.goal, Some(6));
+        assert_eq!(client.goal().await?, goal);
+        assert_eq!(client.set_goal(None).await?.goal, None);
+
+        let stats = client.stats().await?;
+        assert!(stats.commands.contains_key("status"));
+
+        client.stop().await?;
+        // Errors carry the daemon's error code
+        let error = client.skip(false).await.unwrap_err();
+        assert!(matches!(
+            error,
+            tomat_client::Error::Daemon {
+                code: Some(tomat_client::ErrorCode::InvalidState),
+                ..
+            }
+        ));
+
+        Ok::<_, tomat_client::Error>(())
+    })?;
+
+    Ok(())
+}
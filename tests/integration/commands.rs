@@ -1,5 +1,7 @@
 use super::common::TestDaemon;
 use std::process::Command;
+use std::thread;
+use std::time::Duration;
 
 #[test]
 fn test_negative_duration_rejected() -> Result<(), Box<dyn std::error::Error>> {
@@ -84,3 +86,692 @@ fn test_zero_sessions_rejected() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+#[test]
+fn test_error_responses_carry_codes() -> Result<(), Box<dyn std::error::Error>> {
+    let daemon = TestDaemon::start()?;
+
+    let response = daemon.send_raw(r#"{"command":"frobnicate","args":null}"#)?;
+    assert_eq!(response["success"], false);
+    assert_eq!(response["error"], "unknown_command");
+    assert_eq!(response["message"], "Unknown command: frobnicate");
+
+    let response = daemon.send_raw(r#"{"command":"skip","args":null}"#)?;
+    assert_eq!(response["error"], "invalid_state");
+
+    let response = daemon.send_raw(r#"{"command":"start","args":{"work":0}}"#)?;
+    assert_eq!(response["error"], "invalid_argument");
+
+    let response = daemon.send_raw("not json")?;
+    assert_eq!(response["error"], "protocol");
+
+    // Successful responses have no error code
+    let response = daemon.send_raw(r#"{"command":"status","args":null}"#)?;
+    assert_eq!(response["success"], true);
+    assert!(response.get("error").is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_daemon_not_running_hint() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+
+    let output = Command::new(TestDaemon::get_binary_path())
+        .args(["skip"])
+        .env("XDG_RUNTIME_DIR", temp_dir.path())
+        .output()?;
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Failed to connect to daemon"),
+        "Got: {}",
+        stderr
+    );
+    assert!(stderr.contains("Hint:"), "Got: {}", stderr);
+    assert!(stderr.contains("tomat daemon start"), "Got: {}", stderr);
+
+    Ok(())
+}
+
+#[test]
+fn test_log_records_offline_session() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let history_path = temp_dir.path().join("history.db");
+
+    // Logging works without a running daemon
+    let output = Command::new(TestDaemon::get_binary_path())
+        .args([
+            "log",
+            "50m",
+            "--task",
+            "reading",
+            "--at",
+            "2026-01-05 14:00",
+        ])
+        .env("XDG_RUNTIME_DIR", temp_dir.path())
+        .env("TOMAT_HISTORY", &history_path)
+        .output()?;
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Logged 50m work session (reading) starting 2026-01-05 14:00"),
+        "Got: {}",
+        stdout
+    );
+    assert!(history_path.exists());
+
+    // Invalid input is rejected
+    for args in [
+        vec!["log", "soon"],
+        vec!["log", "25m", "--at", "noon"],
+        vec!["log", "25m", "--at", "2999-01-01 09:00"],
+    ] {
+        let output = Command::new(TestDaemon::get_binary_path())
+            .args(&args)
+            .env("XDG_RUNTIME_DIR", temp_dir.path())
+            .env("TOMAT_HISTORY", &history_path)
+            .output()?;
+        assert!(!output.status.success(), "{:?} should fail", args);
+        assert!(String::from_utf8_lossy(&output.stderr).contains("Error:"));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_history_edit_and_delete() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let history_path = temp_dir.path().join("history.db");
+    let tomat = |args: &[&str]| {
+        Command::new(TestDaemon::get_binary_path())
+            .args(args)
+            .env("XDG_RUNTIME_DIR", temp_dir.path())
+            .env("TOMAT_HISTORY", &history_path)
+            .output()
+    };
+
+    let output = tomat(&["log", "50m", "--task", "test", "--at", "2026-01-05 14:00"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let id = stdout
+        .trim()
+        .trim_end_matches(')')
+        .rsplit(' ')
+        .next()
+        .unwrap()
+        .to_string();
+
+    let output = tomat(&["history", "list", "--date", "2026-01-05"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("14:00"), "Got: {}", stdout);
+    assert!(stdout.contains("50m"), "Got: {}", stdout);
+
+    let output = tomat(&["history", "edit", &id, "--duration", "20m", "--task", "x"])?;
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("20m work session (x)"), "Got: {}", stdout);
+
+    let output = tomat(&["history", "delete", &id])?;
+    assert!(output.status.success());
+
+    let output = tomat(&["history", "list", "--date", "2026-01-05"])?;
+    assert!(String::from_utf8_lossy(&output.stdout).contains("No sessions recorded"));
+
+    // Unknown ids are reported as errors
+    let output = tomat(&["history", "delete", &id])?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No session with id"));
+
+    Ok(())
+}
+
+#[test]
+fn test_stats_heatmap() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let history_path = temp_dir.path().join("history.db");
+    let tomat = |args: &[&str]| {
+        Command::new(TestDaemon::get_binary_path())
+            .args(args)
+            .env("XDG_RUNTIME_DIR", temp_dir.path())
+            .env("TOMAT_HISTORY", &history_path)
+            .env("NO_COLOR", "1")
+            .output()
+    };
+
+    tomat(&["log", "50m"])?;
+    tomat(&["log", "25m"])?;
+
+    let output = tomat(&["stats", "--heatmap", "--weeks", "2"])?;
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert!(lines[1].starts_with("Mon "), "Got: {}", stdout);
+    assert!(lines[7].starts_with("Sun "), "Got: {}", stdout);
+    // The day of both sessions is the best one, without colors off a terminal
+    assert!(stdout.contains('█'), "Got: {}", stdout);
+    assert!(!stdout.contains('\x1b'), "Got: {}", stdout);
+    assert!(stdout.contains("Less · ░ ▒ ▓ █ More"), "Got: {}", stdout);
+    assert!(
+        stdout.contains("Today:      2 pomodoros (1h 15m)"),
+        "Got: {}",
+        stdout
+    );
+    assert!(stdout.contains("Pomodoros completed: 2"), "Got: {}", stdout);
+    assert!(
+        stdout.contains("Focused time:        1h 15m"),
+        "Got: {}",
+        stdout
+    );
+
+    // Without --heatmap only the period totals and the summary are shown
+    let output = tomat(&["stats"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("Today: "), "Got: {}", stdout);
+    assert!(!stdout.contains("Mon "), "Got: {}", stdout);
+    assert!(stdout.contains("\n\nFocus from "), "Got: {}", stdout);
+
+    Ok(())
+}
+
+#[test]
+fn test_stats_export_plot() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let history_path = temp_dir.path().join("history.db");
+    let tomat = |args: &[&str]| {
+        Command::new(TestDaemon::get_binary_path())
+            .args(args)
+            .current_dir(temp_dir.path())
+            .env("XDG_RUNTIME_DIR", temp_dir.path())
+            .env("TOMAT_HISTORY", &history_path)
+            .output()
+    };
+
+    tomat(&["log", "1s"])?;
+
+    let output = tomat(&["stats", "--export-plot", "svg"])?;
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("tomat-focus.svg"), "Got: {}", stdout);
+    let svg = std::fs::read_to_string(temp_dir.path().join("tomat-focus.svg"))?;
+    assert!(svg.contains("Focus per day from "), "Got: {}", svg);
+
+    let png_path = temp_dir.path().join("chart.png");
+    let output = tomat(&[
+        "stats",
+        "--export-plot",
+        "png",
+        "--plot-file",
+        png_path.to_str().unwrap(),
+    ])?;
+    assert!(output.status.success());
+    assert!(std::fs::read(&png_path)?.starts_with(b"\x89PNG"));
+
+    // The chart replaces the other statistics
+    let output = tomat(&["stats", "--export-plot", "svg", "--heatmap"])?;
+    assert!(!output.status.success());
+
+    Ok(())
+}
+
+#[test]
+fn test_stats_efficiency() -> Result<(), Box<dyn std::error::Error>> {
+    let daemon = TestDaemon::start()?;
+
+    // A work session skipped after 4 of 10 minutes, with a pause
+    daemon.send_command(&["start", "--work", "10"])?;
+    daemon.send_command(&["simulate", "4m"])?;
+    daemon.send_command(&["pause"])?;
+    daemon.send_command(&["resume"])?;
+    daemon.send_command(&["skip"])?;
+
+    // Skipping the break records nothing, the next work session completes
+    daemon.send_command(&["skip"])?;
+    daemon.send_command(&["resume"])?;
+    daemon.send_command(&["simulate"])?;
+    thread::sleep(Duration::from_millis(300));
+
+    let output = Command::new(TestDaemon::get_binary_path())
+        .args(["stats", "--efficiency"])
+        .env("XDG_RUNTIME_DIR", daemon._temp_dir.path())
+        .env("XDG_DATA_HOME", daemon._temp_dir.path())
+        .output()?;
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let total: Vec<&str> = stdout
+        .lines()
+        .last()
+        .unwrap_or_default()
+        .split_whitespace()
+        .collect();
+    assert_eq!(
+        total,
+        ["Total", "1/2", "14m", "20m", "0.5", "70%"],
+        "Got: {}",
+        stdout
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_history_records_skipped_and_stopped_breaks() -> Result<(), Box<dyn std::error::Error>> {
+    let daemon = TestDaemon::start()?;
+    let history = || -> Result<String, Box<dyn std::error::Error>> {
+        let output = Command::new(TestDaemon::get_binary_path())
+            .args(["history", "list"])
+            .env("XDG_RUNTIME_DIR", daemon._temp_dir.path())
+            .env("XDG_DATA_HOME", daemon._temp_dir.path())
+            .output()?;
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    };
+    // The duration and outcome of the first session of a phase
+    fn row<'a>(history: &'a str, phase: &str) -> Option<Vec<&'a str>> {
+        history
+            .lines()
+            .find(|line| line.split_whitespace().nth(2) == Some(phase))
+            .map(|line| line.split_whitespace().skip(3).take(2).collect())
+    }
+
+    // A break skipped after 2 of 5 minutes
+    daemon.send_command(&["start", "--work", "10", "--break", "5", "--sessions", "2"])?;
+    daemon.send_command(&["simulate"])?;
+    thread::sleep(Duration::from_millis(300));
+    assert_eq!(daemon.get_status()?["class"], "break-paused");
+    daemon.send_command(&["resume"])?;
+    daemon.send_command(&["simulate", "2m"])?;
+    daemon.send_command(&["skip"])?;
+
+    let stdout = history()?;
+    assert_eq!(
+        row(&stdout, "break"),
+        Some(vec!["2m", "skipped"]),
+        "Got: {}",
+        stdout
+    );
+
+    // A long break stopped after 3 minutes
+    daemon.send_command(&["resume"])?;
+    daemon.send_command(&["simulate"])?;
+    thread::sleep(Duration::from_millis(300));
+    assert_eq!(daemon.get_status()?["class"], "long-break-paused");
+    daemon.send_command(&["resume"])?;
+    daemon.send_command(&["simulate", "3m"])?;
+    daemon.send_command(&["stop"])?;
+
+    let stdout = history()?;
+    assert_eq!(
+        row(&stdout, "long_break"),
+        Some(vec!["3m", "stopped"]),
+        "Got: {}",
+        stdout
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_stats_by_tag() -> Result<(), Box<dyn std::error::Error>> {
+    let daemon = TestDaemon::start()?;
+
+    // Tags cannot contain the separator used in the history
+    let response = daemon.send_raw(r#"{"command":"start","args":{"tags":["a,b"]}}"#)?;
+    assert_eq!(response["error"], "invalid_argument");
+
+    daemon.send_command(&[
+        "start", "--work", "10", "--task", "spec", "--tag", "thesis", "--tag", "writing",
+    ])?;
+    daemon.send_command(&["simulate"])?;
+    thread::sleep(Duration::from_millis(300));
+
+    let tomat = |args: &[&str]| {
+        Command::new(TestDaemon::get_binary_path())
+            .args(args)
+            .env("XDG_RUNTIME_DIR", daemon._temp_dir.path())
+            .env("XDG_DATA_HOME", daemon._temp_dir.path())
+            .output()
+    };
+    tomat(&["log", "5m", "--tag", "thesis"])?;
+
+    let output = tomat(&["stats", "--by", "tag"])?;
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let rows: Vec<Vec<&str>> = stdout
+        .lines()
+        .skip(3)
+        .map(|line| line.split_whitespace().collect())
+        .collect();
+    assert_eq!(
+        rows,
+        [
+            vec!["thesis", "2", "15m", "100%"],
+            vec!["writing", "1", "10m", "67%"]
+        ],
+        "Got: {}",
+        stdout
+    );
+
+    let output = tomat(&["stats", "--by", "task"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("spec "), "Got: {}", stdout);
+    assert!(stdout.contains("(none) "), "Got: {}", stdout);
+
+    // The tags are listed with the sessions
+    let output = tomat(&["history", "list"])?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("spec #thesis #writing"), "Got: {}", stdout);
+
+    Ok(())
+}
+
+#[test]
+fn test_review_phase_takes_notes() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let config_path = temp_dir.path().join("config.toml");
+    std::fs::write(&config_path, "[timer]\nreview = 2\n")?;
+    let daemon = TestDaemon::start_with_config(Some(&config_path))?;
+
+    let response = daemon.send_raw(r#"{"command":"note","args":{"text":"too early"}}"#)?;
+    assert_eq!(response["error"], "invalid_state");
+    daemon.send_command(&["start", "--work", "10"])?;
+
+    // The work session ends with a review, which waits to be started
+    daemon.wait_for_completion(10)?;
+    let status = daemon.get_status()?;
+    assert_eq!(status["text"], "📝 02:00 ⏸");
+    assert_eq!(status["class"], "review-paused");
+
+    let message = daemon.send_command(&["note", "wrote the intro"])?;
+    assert!(
+        message
+            .as_str()
+            .unwrap()
+            .starts_with("Notes saved to session"),
+        "Got: {}",
+        message
+    );
+    let output = Command::new(TestDaemon::get_binary_path())
+        .args(["history", "list"])
+        .env("XDG_DATA_HOME", daemon._temp_dir.path())
+        .output()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("wrote the intro"), "Got: {}", stdout);
+    assert!(!stdout.contains("review"), "Got: {}", stdout);
+
+    // Skipping the review starts the break right away
+    daemon.send_command(&["skip"])?;
+    let status = daemon.get_status()?;
+    assert_eq!(status["class"], "break");
+
+    Ok(())
+}
+
+#[test]
+fn test_note_on_current_session() -> Result<(), Box<dyn std::error::Error>> {
+    let daemon = TestDaemon::start()?;
+
+    daemon.send_command(&["start", "--work", "10"])?;
+    let message = daemon.send_command(&["note", "finished draft"])?;
+    assert_eq!(message, "Notes saved to the current session");
+
+    // The notes are recorded with the session when it ends
+    daemon.wait_for_completion(10)?;
+    let output = Command::new(TestDaemon::get_binary_path())
+        .args(["history", "list"])
+        .env("XDG_DATA_HOME", daemon._temp_dir.path())
+        .output()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("finished draft"), "Got: {}", stdout);
+
+    // During the break, notes go to the session that just ended
+    let message = daemon.send_command(&["note", "and the outline"])?;
+    assert!(
+        message
+            .as_str()
+            .unwrap()
+            .starts_with("Notes saved to session"),
+        "Got: {}",
+        message
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_oneshot_runs_without_daemon() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let history_path = temp_dir.path().join("history.db");
+
+    let output = Command::new(TestDaemon::get_binary_path())
+        .args(["oneshot", "0.02"])
+        .env("XDG_RUNTIME_DIR", temp_dir.path())
+        .env("TOMAT_HISTORY", &history_path)
+        .env("TOMAT_TESTING", "1")
+        .output()?;
+
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty(), "Progress should go to stderr");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("00:0"), "Got: {}", stderr);
+    assert!(stderr.contains("Done: 0.02min"), "Got: {}", stderr);
+
+    // The session is recorded in the history
+    let output = Command::new(TestDaemon::get_binary_path())
+        .args(["history", "list"])
+        .env("TOMAT_HISTORY", &history_path)
+        .output()?;
+    assert!(String::from_utf8_lossy(&output.stdout).contains("completed"));
+
+    // Invalid durations fail right away, also ones too long to represent
+    for minutes in ["0", "601", "1e38", "NaN"] {
+        let output = Command::new(TestDaemon::get_binary_path())
+            .args(["oneshot", "--work", minutes])
+            .env("XDG_RUNTIME_DIR", temp_dir.path())
+            .env("TOMAT_HISTORY", &history_path)
+            .output()?;
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("Work duration must be"), "Got: {}", stderr);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_raw_protocol_message() -> Result<(), Box<dyn std::error::Error>> {
+    let daemon = TestDaemon::start()?;
+
+    let response = daemon.send_command(&["raw", r#"{"command":"start","args":{"work":7}}"#])?;
+    assert_eq!(response["success"], true);
+
+    // Multi-line messages from stdin are sent on a single line
+    let mut child = Command::new(TestDaemon::get_binary_path())
+        .args(["raw", "-"])
+        .env("XDG_RUNTIME_DIR", daemon._temp_dir.path())
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .spawn()?;
+    {
+        use std::io::Write;
+        let mut stdin = child.stdin.take().unwrap();
+        stdin.write_all(b"{\n  \"command\": \"status\",\n  \"args\": null\n}\n")?;
+    }
+    let output = child.wait_with_output()?;
+    let response: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(response["data"]["duration_minutes"], 7.0);
+
+    // Errors from the daemon are printed, not interpreted
+    let response = daemon.send_command(&["raw", r#"{"command":"frobnicate","args":null}"#])?;
+    assert_eq!(response["success"], false);
+    assert_eq!(response["error"], "unknown_command");
+
+    assert!(daemon.send_command(&["raw", "not json"]).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_plan_queue() -> Result<(), Box<dyn std::error::Error>> {
+    let daemon = TestDaemon::start()?;
+    let task = || -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        let response = daemon.send_raw(r#"{"command":"status","args":null}"#)?;
+        Ok(response["data"]["task"].clone())
+    };
+
+    let response = daemon.send_command(&["plan", "show"])?;
+    assert_eq!(response.as_str(), Some("No tasks planned"));
+
+    daemon.send_command(&["plan", "add", "write spec", "2"])?;
+    daemon.send_command(&["plan", "add", "review"])?;
+    assert!(
+        daemon
+            .send_command(&["plan", "add", "nothing", "0"])
+            .is_err()
+    );
+
+    // Start picks up the first planned task
+    daemon.send_command(&["start"])?;
+    assert_eq!(task()?, "write spec");
+
+    // Work sessions count towards the task until its pomodoros are spent
+    daemon.send_command(&["skip"])?;
+    daemon.send_command(&["skip"])?;
+    assert_eq!(task()?, "write spec");
+    daemon.send_command(&["skip"])?;
+    assert_eq!(task()?, "review");
+
+    let response = daemon.send_command(&["plan", "show"])?;
+    assert_eq!(
+        response.as_str(),
+        Some("✓ write spec  2/2\n▶ review      0/1")
+    );
+
+    // An explicit task takes precedence over the plan
+    daemon.send_command(&["start", "--force", "--task", "meeting"])?;
+    assert_eq!(task()?, "meeting");
+
+    let response = daemon.send_command(&["plan", "clear"])?;
+    assert_eq!(response.as_str(), Some("Plan cleared"));
+    daemon.send_command(&["start", "--force"])?;
+    assert_eq!(task()?, serde_json::Value::Null);
+
+    Ok(())
+}
+
+#[test]
+fn test_porcelain_output() -> Result<(), Box<dyn std::error::Error>> {
+    let daemon = TestDaemon::start()?;
+    let porcelain = |args: &[&str]| -> Result<(bool, String), Box<dyn std::error::Error>> {
+        let output = Command::new(TestDaemon::get_binary_path())
+            .arg("--porcelain")
+            .args(args)
+            .env("XDG_RUNTIME_DIR", daemon._temp_dir.path())
+            .env("TOMAT_HISTORY", daemon._temp_dir.path().join("logged.db"))
+            .output()?;
+        Ok((output.status.success(), String::from_utf8(output.stdout)?))
+    };
+
+    let (success, output) = porcelain(&["start", "--work", "25", "--task", "Write ✍️ report"])?;
+    assert!(success);
+    assert!(output.starts_with("result=ok\nmessage=Pomodoro started: 25.0min work"));
+    assert!(output.contains("\nphase=work\npaused=false\n"));
+    assert!(output.contains("\ntask=Write report\n"));
+    assert!(
+        output.is_ascii(),
+        "porcelain output has no emoji: {}",
+        output
+    );
+
+    let (success, output) = porcelain(&["status"])?;
+    assert!(success);
+    assert!(output.starts_with("result=ok\nphase=work\n"));
+    assert!(output.contains("\nsession=1\nsessions=4\n"));
+
+    let (success, output) = porcelain(&["pause"])?;
+    assert!(success);
+    assert!(output.contains("message=Timer paused\n"));
+    assert!(output.contains("paused=true\n"));
+
+    // Errors are lines on stdout too, and fail the command
+    let (success, output) = porcelain(&["start", "--work", "10"])?;
+    assert!(!success);
+    assert!(output.starts_with("result=error\nerror=invalid_state\nmessage="));
+
+    let (success, output) = porcelain(&["stats"])?;
+    assert!(!success);
+    assert!(output.starts_with("result=error\nerror=invalid_argument\n"));
+
+    let (success, output) = porcelain(&["log", "25m", "--task", "reading"])?;
+    assert!(success);
+    assert!(output.starts_with("result=ok\nmessage="));
+    assert!(output.lines().any(|line| line.starts_with("id=")));
+
+    Ok(())
+}
+
+#[test]
+fn test_pause_budget_voids_session() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let config_path = temp_dir.path().join("config.toml");
+    std::fs::write(&config_path, "[timer]\nmax_pauses = 1\n")?;
+    let daemon = TestDaemon::start_with_config(Some(&config_path))?;
+
+    daemon.send_command(&["start"])?;
+    let tooltip = daemon.send_command(&["status", "--tooltip-only"])?;
+    assert!(tooltip.as_str().unwrap().ends_with("Pauses left: 1"));
+
+    daemon.send_command(&["pause"])?;
+    daemon.send_command(&["resume"])?;
+    let tooltip = daemon.send_command(&["status", "--tooltip-only"])?;
+    assert!(tooltip.as_str().unwrap().ends_with("Pauses left: 0"));
+
+    let response = daemon.send_raw(r#"{"command":"pause","args":null}"#)?;
+    assert!(
+        response["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("Pause budget of 1 exceeded: work session voided")
+    );
+
+    // The session starts over, with its budget restored
+    let status = daemon.send_raw(r#"{"command":"status","args":{"output":"plain"}}"#)?;
+    assert_eq!(status["data"]["is_paused"], true);
+    assert_eq!(status["data"]["remaining_seconds"], 25 * 60);
+    assert_eq!(status["data"]["pauses_left"], 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_away_abandons_the_session() -> Result<(), Box<dyn std::error::Error>> {
+    let daemon = TestDaemon::start()?;
+    let history = || -> Result<String, Box<dyn std::error::Error>> {
+        let output = Command::new(TestDaemon::get_binary_path())
+            .args(["history", "list"])
+            .env("XDG_DATA_HOME", daemon._temp_dir.path())
+            .output()?;
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    };
+
+    let response = daemon.send_raw(r#"{"command":"away","args":{"idle_seconds":60}}"#)?;
+    assert_eq!(response["error"], "invalid_state");
+
+    // Away for 2 of the 5 minutes worked
+    daemon.send_command(&["start", "--work", "10"])?;
+    daemon.fast_forward(5 * 60)?;
+    let message = daemon.send_command(&["away", "--idle", "2m"])?;
+    assert_eq!(message, "Timer stopped after 2m away");
+    assert_eq!(daemon.get_status()?["class"], "idle");
+    let stdout = history()?;
+    assert!(stdout.contains("abandoned"), "Got: {}", stdout);
+    assert!(stdout.contains("3m"), "Got: {}", stdout);
+
+    // A session that ran to its end while away is not a pomodoro
+    daemon.send_command(&["start", "--work", "10"])?;
+    daemon.wait_for_completion(10)?;
+    daemon.send_command(&["away", "--idle", "15m"])?;
+    let stdout = history()?;
+    assert!(!stdout.contains("completed"), "Got: {}", stdout);
+
+    Ok(())
+}
@@ -1,4 +1,5 @@
 use serde_json::Value;
+use std::cell::Cell;
 use std::process::{Child, Command, Stdio};
 use std::thread;
 use std::time::Duration;
@@ -9,6 +10,8 @@ pub struct TestDaemon {
     pub _temp_dir: TempDir,
     pub daemon_process: Child,
     pub config_path: Option<std::path::PathBuf>,
+    /// Seconds the daemon's clock has been fast-forwarded by
+    time_offset: Cell<u64>,
 }
 
 impl TestDaemon {
@@ -56,6 +59,8 @@ impl TestDaemon {
         cmd.arg("daemon")
             .arg("run") // Use the internal run command for testing
             .env("XDG_RUNTIME_DIR", temp_dir.path())
+            .env("XDG_DATA_HOME", temp_dir.path())
+            .env("XDG_STATE_HOME", temp_dir.path())
             .env("TOMAT_TESTING", "1") // Disable notifications during testing
             .stdout(Stdio::null())
             .stderr(Stdio::null());
@@ -83,6 +88,7 @@ impl TestDaemon {
             _temp_dir: temp_dir,
             daemon_process,
             config_path: config_path.map(|p| p.to_path_buf()),
+            time_offset: Cell::new(0),
         })
     }
 
@@ -121,6 +127,20 @@ impl TestDaemon {
         }
     }
 
+    /// Send a raw protocol line to the daemon socket and return the parsed response
+    pub fn send_raw(&self, line: &str) -> Result<Value, Box<dyn std::error::Error>> {
+        use std::io::{BufRead, Write};
+        use std::os::unix::net::UnixStream;
+
+        let mut stream = UnixStream::connect(self._temp_dir.path().join("tomat.sock"))?;
+        stream.write_all(format!("{}\n", line).as_bytes())?;
+        stream.flush()?;
+
+        let mut response = String::new();
+        std::io::BufReader::new(stream).read_line(&mut response)?;
+        Ok(serde_json::from_str(&response)?)
+    }
+
     /// Send shutdown command directly via socket for fast graceful shutdown
     fn shutdown_gracefully(&self) -> bool {
         use std::io::{BufRead, Write};
@@ -150,57 +170,43 @@ impl TestDaemon {
         self.send_command(&["status"])
     }
 
-    /// Wait for timer to complete and transition (paused for auto_advance=false, continued for auto_advance=true)
+    /// Move the daemon's clock `seconds` further ahead with `set_time_offset`
+    pub fn fast_forward(&self, seconds: u64) -> Result<(), Box<dyn std::error::Error>> {
+        let offset = self.time_offset.get() + seconds;
+        let response = self.send_raw(&format!(
+            r#"{{"command":"set_time_offset","args":{{"seconds":{}}}}}"#,
+            offset
+        ))?;
+        if response["success"] != true {
+            return Err(format!("Failed to set time offset: {}", response).into());
+        }
+        self.time_offset.set(offset);
+        Ok(())
+    }
+
+    /// Fast-forward to the end of the running phase and wait for the
+    /// transition (paused for auto_advance=false, continued for auto_advance=true)
     pub fn wait_for_completion(&self, max_wait: u64) -> Result<(), Box<dyn std::error::Error>> {
+        let status = self.send_raw(r#"{"command":"status","args":null}"#)?;
+        let initial_phase = status["data"]["phase"].clone();
+        let remaining = status["data"]["remaining_seconds"]
+            .as_u64()
+            .ok_or("Timer is not running")?;
+        self.fast_forward(remaining)?;
+
         let start = std::time::Instant::now();
         let max_duration = Duration::from_secs(max_wait);
-
-        // For auto_advance=false timers, we wait for paused state
-        // For auto_advance=true timers, we wait for phase change
-        let mut initial_phase: Option<String> = None;
-        let mut timer_completed = false;
-
         loop {
             if start.elapsed() > max_duration {
                 return Err("Timeout waiting for timer completion".into());
             }
 
-            let status = self.get_status()?;
-
-            // Record initial phase
-            if initial_phase.is_none()
-                && let Some(class) = status.get("class").and_then(|v| v.as_str())
-            {
-                initial_phase = Some(class.to_string());
-            }
-
-            // Check if timer shows 00:00 (completed but not yet transitioned)
-            if let Some(text) = status.get("text").and_then(|v| v.as_str())
-                && text.contains("00:00")
-                && !timer_completed
-            {
-                // Timer reached 00:00, wait a moment for automatic transition
-                thread::sleep(Duration::from_millis(1500));
-                timer_completed = true;
-                continue;
-            }
-
-            // Check for state after completion
-            if timer_completed
-                && let Some(class) = status.get("class").and_then(|v| v.as_str())
-                && let Some(ref initial) = initial_phase
-            {
-                // Check for auto_advance=false: should be paused in new phase
-                if class.contains("paused") && !class.contains(initial) {
-                    return Ok(()); // Successfully transitioned to paused state
-                }
-                // Check for auto_advance=true: should be running in new phase
-                if !class.contains("paused") && !class.contains(initial) {
-                    return Ok(()); // Successfully transitioned to running state
-                }
+            let status = self.send_raw(r#"{"command":"status","args":null}"#)?;
+            if status["data"]["phase"] != initial_phase {
+                return Ok(());
             }
 
-            thread::sleep(Duration::from_millis(100));
+            thread::sleep(Duration::from_millis(20));
         }
     }
 }
@@ -1,5 +1,8 @@
 use super::common::TestDaemon;
-use std::process::Command;
+use std::io::{BufRead, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
 
 #[test]
 fn test_daemon_lifecycle() -> Result<(), Box<dyn std::error::Error>> {
@@ -86,3 +89,797 @@ fn test_daemon_start_when_already_running() -> Result<(), Box<dyn std::error::Er
 
     Ok(())
 }
+
+#[test]
+fn test_simultaneous_daemons_adopt_one() -> Result<(), Box<dyn std::error::Error>> {
+    let binary_path = TestDaemon::get_binary_path();
+    let temp_dir = tempfile::tempdir()?;
+
+    // A socket left behind by a crash must not get in the way either
+    std::fs::write(temp_dir.path().join("tomat.sock"), "")?;
+
+    // Daemons launched at once, as by systemd and a compositor's autostart
+    let mut daemons = (0..3)
+        .map(|_| {
+            Command::new(&binary_path)
+                .args(["daemon", "run"])
+                .env("XDG_RUNTIME_DIR", temp_dir.path())
+                .env("XDG_DATA_HOME", temp_dir.path())
+                .env("XDG_STATE_HOME", temp_dir.path())
+                .env("TOMAT_TESTING", "1")
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    thread::sleep(Duration::from_millis(1500));
+
+    let mut running = Vec::new();
+    for mut daemon in daemons.drain(..) {
+        if daemon.try_wait()?.is_some() {
+            let output = daemon.wait_with_output()?;
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            assert_eq!(output.status.code(), Some(75), "stdout: {}", stdout);
+            assert!(
+                stdout.contains("already running") && stdout.contains("adopting it"),
+                "Got: {}",
+                stdout
+            );
+        } else {
+            running.push(daemon);
+        }
+    }
+    assert_eq!(running.len(), 1, "Exactly one daemon should keep running");
+
+    let status = Command::new(&binary_path)
+        .args(["daemon", "status"])
+        .env("XDG_RUNTIME_DIR", temp_dir.path())
+        .output()?;
+    let stdout = String::from_utf8_lossy(&status.stdout);
+    assert!(
+        stdout.contains(&format!("Running (PID: {},", running[0].id())),
+        "Got: {}",
+        stdout
+    );
+
+    for mut daemon in running {
+        daemon.kill()?;
+        daemon.wait()?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_daemon_ignores_reused_pid_in_stale_pid_file() -> Result<(), Box<dyn std::error::Error>> {
+    let binary_path = TestDaemon::get_binary_path();
+    let temp_dir = tempfile::tempdir()?;
+    let run = |args: &[&str]| {
+        Command::new(&binary_path)
+            .args(args)
+            .env("XDG_RUNTIME_DIR", temp_dir.path())
+            .env("XDG_DATA_HOME", temp_dir.path())
+            .env("XDG_STATE_HOME", temp_dir.path())
+            .env("TOMAT_TESTING", "1")
+            .output()
+    };
+
+    // A PID file left by a crash, whose PID now belongs to a live process
+    std::fs::write(
+        temp_dir.path().join("tomat.pid"),
+        std::process::id().to_string(),
+    )?;
+
+    let status = run(&["daemon", "status"])?;
+    let stdout = String::from_utf8_lossy(&status.stdout);
+    assert!(
+        stdout.contains("Not running (stale PID file)"),
+        "Got: {}",
+        stdout
+    );
+    let status = run(&["daemon", "status", "--output", "json"])?;
+    let status: serde_json::Value = serde_json::from_slice(&status.stdout)?;
+    assert_eq!(status["running"], false);
+    assert_eq!(status["status"], "Not running (stale PID file)");
+
+    let start = run(&["daemon", "start"])?;
+    let running = run(&["daemon", "status"])?;
+    let stop = run(&["daemon", "stop"])?;
+
+    let start_stdout = String::from_utf8_lossy(&start.stdout);
+    assert!(
+        start_stdout.contains("Daemon started successfully"),
+        "stdout: {}, stderr: {}",
+        start_stdout,
+        String::from_utf8_lossy(&start.stderr)
+    );
+    let running_stdout = String::from_utf8_lossy(&running.stdout);
+    assert!(
+        running_stdout.contains("Status: Running"),
+        "Got: {}",
+        running_stdout
+    );
+    assert!(
+        !running_stdout.contains(&format!("PID: {},", std::process::id())),
+        "Got: {}",
+        running_stdout
+    );
+    assert!(stop.status.success());
+
+    Ok(())
+}
+
+#[test]
+fn test_daemon_restart() -> Result<(), Box<dyn std::error::Error>> {
+    let binary_path = TestDaemon::get_binary_path();
+    let temp_dir = tempfile::tempdir()?;
+    let run = |args: &[&str]| {
+        Command::new(&binary_path)
+            .args(args)
+            .env("XDG_RUNTIME_DIR", temp_dir.path())
+            .env("XDG_DATA_HOME", temp_dir.path())
+            .env("XDG_STATE_HOME", temp_dir.path())
+            .env("TOMAT_TESTING", "1")
+            .output()
+    };
+    let daemon_pid = || {
+        std::fs::read_to_string(temp_dir.path().join("tomat.pid"))
+            .map(|pid| pid.trim().to_string())
+            .unwrap_or_default()
+    };
+
+    // Without a running daemon, restart starts one
+    let first = run(&["daemon", "restart"])?;
+    let first_pid = daemon_pid();
+    let started = run(&["start", "--work", "10"])?;
+    let second = run(&["daemon", "restart"])?;
+    let second_pid = daemon_pid();
+    let status = run(&["status", "--output", "plain"])?;
+    let stop = run(&["daemon", "stop"])?;
+
+    for output in [&first, &started, &second, &stop] {
+        assert!(
+            output.status.success(),
+            "stderr: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    let stdout = String::from_utf8_lossy(&second.stdout);
+    assert!(stdout.contains("Daemon restarted"), "Got: {}", stdout);
+    assert!(!first_pid.is_empty());
+    assert_ne!(first_pid, second_pid);
+
+    // The running timer survives the restart
+    let status = String::from_utf8_lossy(&status.stdout);
+    assert!(status.contains("🍅"), "Got: {}", status);
+    assert!(!status.contains("⏸"), "Got: {}", status);
+
+    Ok(())
+}
+
+#[test]
+fn test_restart_continues_cycle_from_history() -> Result<(), Box<dyn std::error::Error>> {
+    let binary_path = TestDaemon::get_binary_path();
+    let temp_dir = tempfile::tempdir()?;
+    let run = |args: &[&str]| {
+        Command::new(&binary_path)
+            .args(args)
+            .env("XDG_RUNTIME_DIR", temp_dir.path())
+            .env("XDG_DATA_HOME", temp_dir.path())
+            .env("XDG_STATE_HOME", temp_dir.path())
+            .env("TOMAT_TESTING", "1")
+            .output()
+    };
+
+    // Two pomodoros done today, but no saved timer state. They are kept short
+    // so that they are from today even right after midnight.
+    run(&["log", "1s"])?;
+    run(&["log", "1s"])?;
+
+    run(&["daemon", "restart"])?;
+    run(&["start"])?;
+    let status = run(&["status", "--output", "plain", "--format", "{session}"])?;
+    run(&["stop"])?;
+    // A cycle started later begins from scratch
+    run(&["start"])?;
+    let restarted = run(&["status", "--output", "plain", "--format", "{session}"])?;
+    run(&["daemon", "stop"])?;
+
+    assert_eq!(String::from_utf8_lossy(&status.stdout).trim(), "3/4");
+    assert_eq!(String::from_utf8_lossy(&restarted.stdout).trim(), "1/4");
+
+    Ok(())
+}
+
+#[test]
+fn test_daemon_install_autostart() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let binary_path = TestDaemon::get_binary_path();
+    let entry_path = temp_dir.path().join("autostart").join("tomat.desktop");
+
+    let output = Command::new(&binary_path)
+        .args(["daemon", "install", "--autostart", "--force"])
+        .env("XDG_CONFIG_HOME", temp_dir.path())
+        .output()?;
+    assert!(output.status.success(), "Install should succeed");
+
+    let content = std::fs::read_to_string(&entry_path)?;
+    assert!(content.starts_with("[Desktop Entry]"));
+    assert!(content.contains("Type=Application"));
+    assert!(content.contains("\" daemon start"));
+
+    // The systemd service must not be touched
+    assert!(!temp_dir.path().join("systemd").exists());
+
+    let output = Command::new(&binary_path)
+        .args(["daemon", "uninstall", "--autostart"])
+        .env("XDG_CONFIG_HOME", temp_dir.path())
+        .output()?;
+    assert!(output.status.success(), "Uninstall should succeed");
+    assert!(!entry_path.exists(), "Autostart entry should be removed");
+
+    Ok(())
+}
+
+#[test]
+fn test_daemon_install_print_with_template() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let binary_path = TestDaemon::get_binary_path();
+
+    let template_path = temp_dir.path().join("override.conf");
+    std::fs::write(&template_path, "[Service]\nRestart=on-failure\nUMask=\n")?;
+
+    let output = Command::new(&binary_path)
+        .args(["daemon", "install", "--print", "--template"])
+        .arg(&template_path)
+        .env("XDG_CONFIG_HOME", temp_dir.path())
+        .output()?;
+    assert!(output.status.success(), "Print should succeed");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("[Service]"));
+    assert!(stdout.contains("daemon run"));
+    assert!(stdout.contains("Restart=on-failure"));
+    assert!(!stdout.contains("Restart=always"));
+    assert!(!stdout.contains("UMask="));
+
+    // Nothing should have been written
+    assert!(!temp_dir.path().join("systemd").exists());
+
+    Ok(())
+}
+
+#[test]
+fn test_daemon_install_init_systems() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let binary_path = TestDaemon::get_binary_path();
+
+    let output = Command::new(&binary_path)
+        .args(["daemon", "install", "--init", "runit", "--force"])
+        .env("XDG_CONFIG_HOME", temp_dir.path())
+        .output()?;
+    assert!(output.status.success(), "runit install should succeed");
+
+    let run_path = temp_dir.path().join("service").join("tomat").join("run");
+    let content = std::fs::read_to_string(&run_path)?;
+    assert!(content.starts_with("#!/bin/sh"));
+    assert!(content.contains("daemon run"));
+
+    use std::os::unix::fs::PermissionsExt;
+    let mode = std::fs::metadata(&run_path)?.permissions().mode();
+    assert!(mode & 0o111 != 0, "run script should be executable");
+
+    let output = Command::new(&binary_path)
+        .args(["daemon", "install", "--init", "dinit", "--print"])
+        .env("XDG_CONFIG_HOME", temp_dir.path())
+        .output()?;
+    assert!(output.status.success(), "dinit print should succeed");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("type = process"));
+    assert!(!temp_dir.path().join("dinit.d").exists());
+
+    Ok(())
+}
+
+#[test]
+fn test_daemon_status_verbose_reports_timings() -> Result<(), Box<dyn std::error::Error>> {
+    let daemon = TestDaemon::start()?;
+
+    daemon.send_command(&["start", "--work", "1"])?;
+
+    // Repeated polling within the same second is served from the status cache
+    let first = daemon.get_status()?;
+    let second = daemon.get_status()?;
+    assert_eq!(first["class"], second["class"]);
+
+    // A state change must not return a stale cached status
+    daemon.send_command(&["pause"])?;
+    let paused = daemon.get_status()?;
+    assert_eq!(paused["class"], "work-paused");
+
+    let output = daemon.send_command(&["daemon", "status", "--verbose"])?;
+    let text = output.as_str().unwrap_or_default();
+    assert!(text.contains("Status: Running"), "Got: {}", text);
+    assert!(text.contains("Status cache:"), "Got: {}", text);
+    assert!(text.contains("status"), "Got: {}", text);
+    assert!(text.contains("pause"), "Got: {}", text);
+
+    Ok(())
+}
+
+#[test]
+fn test_daemon_status_reports_daemon_details() -> Result<(), Box<dyn std::error::Error>> {
+    let config_dir = tempfile::tempdir()?;
+    let config_path = config_dir.path().join("config.toml");
+    std::fs::write(&config_path, "[timer]\nwork = 1\n")?;
+    let daemon = TestDaemon::start_with_config(Some(&config_path))?;
+
+    let output = daemon.send_command(&["daemon", "status"])?;
+    let text = output.as_str().unwrap_or_default();
+    assert!(text.contains("Last transition: none yet"), "Got: {}", text);
+    assert!(
+        text.contains("Notifications:   suppressed"),
+        "Got: {}",
+        text
+    );
+    assert!(!text.contains("Status cache:"), "Got: {}", text);
+
+    daemon.send_command(&["start"])?;
+    daemon.wait_for_completion(5)?;
+
+    let status = daemon.send_command(&["daemon", "status", "--output", "json"])?;
+    assert_eq!(status["running"], true);
+    assert!(status["pid"].as_u64().is_some());
+    assert!(status["uptime_seconds"].as_u64().is_some());
+    assert!(status["requests"].as_u64().unwrap_or_default() >= 3);
+    assert!(status["last_transition_at"].as_u64().is_some());
+    assert_eq!(status["config_file"], config_path.display().to_string());
+    assert!(!status["audio"].as_str().unwrap_or_default().is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_daemon_state_backend() -> Result<(), Box<dyn std::error::Error>> {
+    let config_dir = tempfile::tempdir()?;
+
+    for (backend, keeps_file) in [("file", true), ("sqlite", false), ("memory", false)] {
+        let config_path = config_dir.path().join(format!("{}.toml", backend));
+        std::fs::write(
+            &config_path,
+            format!("[daemon]\nstate_backend = \"{}\"\n", backend),
+        )?;
+        let daemon = TestDaemon::start_with_config(Some(&config_path))?;
+
+        daemon.send_command(&["start", "--work", "10"])?;
+        let state_file = daemon._temp_dir.path().join("tomat").join("tomat.state");
+        assert_eq!(state_file.exists(), keeps_file, "backend {}", backend);
+
+        let history = daemon._temp_dir.path().join("tomat").join("history.db");
+        if backend == "sqlite" {
+            assert!(history.exists(), "state should go to the history database");
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_daemon_watch_config() -> Result<(), Box<dyn std::error::Error>> {
+    let config_dir = tempfile::tempdir()?;
+    let config_path = config_dir.path().join("config.toml");
+    std::fs::write(
+        &config_path,
+        "[daemon]\nwatch_config = true\n\n[timer]\nwork = 10\n",
+    )?;
+    let daemon = TestDaemon::start_with_config(Some(&config_path))?;
+
+    // Save by renaming a new file over the old one, as many editors do
+    let new_path = config_dir.path().join("config.toml.new");
+    std::fs::write(
+        &new_path,
+        "[daemon]\nwatch_config = true\n\n[timer]\nwork = 20\n",
+    )?;
+    std::fs::rename(&new_path, &config_path)?;
+    thread::sleep(Duration::from_secs(1));
+
+    daemon.send_command(&["start"])?;
+    let status = daemon.get_status()?;
+    let tooltip = status["tooltip"].as_str().unwrap_or_default();
+    assert!(tooltip.contains("20.0min"), "Got: {}", tooltip);
+    daemon.send_command(&["stop"])?;
+
+    // A broken config keeps the current settings
+    std::fs::write(&config_path, "[timer\nwork = 30\n")?;
+    thread::sleep(Duration::from_secs(1));
+
+    daemon.send_command(&["start"])?;
+    let status = daemon.get_status()?;
+    let tooltip = status["tooltip"].as_str().unwrap_or_default();
+    assert!(tooltip.contains("20.0min"), "Got: {}", tooltip);
+
+    Ok(())
+}
+
+#[test]
+fn test_daemon_migrates_state_file_from_runtime_dir() -> Result<(), Box<dyn std::error::Error>> {
+    let binary_path = TestDaemon::get_binary_path();
+    let runtime_dir = tempfile::tempdir()?;
+    let state_dir = tempfile::tempdir()?;
+    let run = |args: &[&str]| {
+        Command::new(&binary_path)
+            .args(args)
+            .env("XDG_RUNTIME_DIR", runtime_dir.path())
+            .env("XDG_DATA_HOME", state_dir.path())
+            .env("XDG_STATE_HOME", state_dir.path())
+            .env("TOMAT_TESTING", "1")
+            .output()
+    };
+
+    // The state is kept in the state directory, not the runtime directory
+    run(&["daemon", "start"])?;
+    run(&["start", "--work", "7"])?;
+    run(&["daemon", "stop"])?;
+    let state_file = state_dir.path().join("tomat").join("tomat.state");
+    assert!(state_file.exists());
+
+    // A state file left in the runtime directory by an earlier version is moved
+    let legacy_file = runtime_dir.path().join("tomat.state");
+    std::fs::rename(&state_file, &legacy_file)?;
+    run(&["daemon", "start"])?;
+    let status = run(&["status"])?;
+    run(&["daemon", "stop"])?;
+
+    let status = String::from_utf8_lossy(&status.stdout);
+    assert!(status.contains("7.0min"), "Got: {}", status);
+    assert!(state_file.exists());
+    assert!(!legacy_file.exists());
+
+    Ok(())
+}
+
+#[test]
+fn test_metrics_textfile() -> Result<(), Box<dyn std::error::Error>> {
+    let config_dir = tempfile::tempdir()?;
+    let config_path = config_dir.path().join("config.toml");
+    let textfile = config_dir.path().join("tomat.prom");
+    std::fs::write(
+        &config_path,
+        format!(
+            "[metrics]\ntextfile = {:?}\n",
+            textfile.display().to_string()
+        ),
+    )?;
+    let daemon = TestDaemon::start_with_config(Some(&config_path))?;
+
+    // The file follows the timer shortly after each change
+    let wait_for = |line: &str| -> bool {
+        (0..50).any(|_| {
+            thread::sleep(Duration::from_millis(20));
+            std::fs::read_to_string(&textfile).is_ok_and(|metrics| metrics.contains(line))
+        })
+    };
+    assert!(wait_for("tomat_phase{phase=\"idle\"} 1\n"));
+
+    daemon.send_command(&["start", "--work", "10"])?;
+    assert!(wait_for("tomat_phase{phase=\"work\"} 1\n"));
+    assert!(wait_for("tomat_phase_duration_seconds 600\n"));
+
+    daemon.send_command(&["pause"])?;
+    assert!(wait_for("tomat_paused 1\n"));
+
+    daemon.send_command(&["skip"])?;
+    assert!(wait_for("tomat_phase{phase=\"break\"} 1\n"));
+    assert!(wait_for("tomat_sessions_completed 1\n"));
+
+    Ok(())
+}
+
+#[test]
+fn test_lockscreen_message_during_long_break() -> Result<(), Box<dyn std::error::Error>> {
+    let config_dir = tempfile::tempdir()?;
+    let config_path = config_dir.path().join("config.toml");
+    let message_file = config_dir.path().join("lockscreen").join("message");
+    std::fs::write(
+        &config_path,
+        format!(
+            "[focus.lockscreen]\nmessage_file = {:?}\n",
+            message_file.display().to_string()
+        ),
+    )?;
+    let daemon = TestDaemon::start_with_config(Some(&config_path))?;
+
+    let wait_for = |check: &dyn Fn(&str) -> bool| -> bool {
+        (0..50).any(|_| {
+            thread::sleep(Duration::from_millis(20));
+            std::fs::read_to_string(&message_file).is_ok_and(|message| check(&message))
+        })
+    };
+    assert!(wait_for(&|message| message.is_empty()));
+
+    // The only session of the cycle is followed by the long break
+    daemon.send_command(&["start", "--sessions", "1"])?;
+    daemon.send_command(&["skip"])?;
+    daemon.send_command(&["resume"])?;
+    assert!(wait_for(&|message| message.starts_with("Break until ")));
+
+    daemon.send_command(&["stop"])?;
+    assert!(wait_for(&|message| message.is_empty()));
+
+    Ok(())
+}
+
+#[test]
+fn test_indicator_led_follows_phase() -> Result<(), Box<dyn std::error::Error>> {
+    let config_dir = tempfile::tempdir()?;
+    let config_path = config_dir.path().join("config.toml");
+    let led = config_dir.path().join("led");
+    std::fs::create_dir(&led)?;
+    std::fs::write(led.join("max_brightness"), "255\n")?;
+    std::fs::write(led.join("multi_intensity"), "0 0 0\n")?;
+    std::fs::write(
+        &config_path,
+        format!("[indicator]\nsysfs = {:?}\n", led.display().to_string()),
+    )?;
+    let daemon = TestDaemon::start_with_config(Some(&config_path))?;
+
+    let wait_for = |color: &str, brightness: &str| -> bool {
+        (0..50).any(|_| {
+            thread::sleep(Duration::from_millis(20));
+            let read = |file: &str| std::fs::read_to_string(led.join(file)).unwrap_or_default();
+            read("multi_intensity") == color && read("brightness") == brightness
+        })
+    };
+
+    daemon.send_command(&["start"])?;
+    assert!(wait_for("255 0 0", "255"));
+
+    daemon.send_command(&["skip"])?;
+    assert!(wait_for("0 255 0", "255"));
+
+    // Black while idle turns the LED off
+    daemon.send_command(&["stop"])?;
+    assert!(wait_for("0 0 0", "0"));
+
+    Ok(())
+}
+
+#[test]
+fn test_client_spawns_daemon() -> Result<(), Box<dyn std::error::Error>> {
+    let binary_path = TestDaemon::get_binary_path();
+    let temp_dir = tempfile::tempdir()?;
+    let config_path = temp_dir.path().join("config.toml");
+    std::fs::write(&config_path, "[daemon]\nauto_spawn = true\n")?;
+    let run = |args: &[&str], config: bool| {
+        let mut cmd = Command::new(&binary_path);
+        cmd.args(args)
+            .env("XDG_RUNTIME_DIR", temp_dir.path())
+            .env("XDG_DATA_HOME", temp_dir.path())
+            .env("XDG_STATE_HOME", temp_dir.path())
+            .env("TOMAT_TESTING", "1");
+        if config {
+            cmd.env("TOMAT_CONFIG", &config_path);
+        }
+        cmd.output()
+    };
+
+    // Without the option, a missing daemon is an error
+    let status = run(&["status", "--output", "plain"], false)?;
+    assert!(String::from_utf8_lossy(&status.stderr).contains("not running"));
+
+    // The client starts the daemon and only prints the status
+    let status = run(&["--spawn-daemon", "status", "--output", "plain"], false)?;
+    assert!(
+        status.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&status.stderr)
+    );
+    assert_eq!(String::from_utf8_lossy(&status.stdout).trim(), "🍅 25:00 ⏹");
+    assert!(run(&["daemon", "stop"], false)?.status.success());
+
+    // The same through the config file
+    let start = run(&["start"], true)?;
+    assert!(
+        start.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&start.stderr)
+    );
+    assert!(String::from_utf8_lossy(&start.stdout).starts_with("Pomodoro started"));
+    assert!(run(&["daemon", "stop"], false)?.status.success());
+
+    // Daemon commands and stopping the timer never start it
+    let stop = run(&["stop"], true)?;
+    assert!(String::from_utf8_lossy(&stop.stderr).contains("not running"));
+    let status = run(&["daemon", "status", "--output", "json"], true)?;
+    let status: serde_json::Value = serde_json::from_slice(&status.stdout)?;
+    assert_eq!(status["running"], false);
+
+    Ok(())
+}
+
+#[test]
+fn test_tcp_listen_with_token() -> Result<(), Box<dyn std::error::Error>> {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")?
+        .local_addr()?
+        .port();
+    let temp_dir = tempfile::tempdir()?;
+    let config_path = temp_dir.path().join("config.toml");
+    std::fs::write(
+        &config_path,
+        format!(
+            "[daemon]\ntcp_listen = \"127.0.0.1:{}\"\ntcp_token = \"s3cret\"\n",
+            port
+        ),
+    )?;
+    let daemon = TestDaemon::start_with_config(Some(&config_path))?;
+    daemon.send_command(&["start", "--task", "remote"])?;
+
+    // A client elsewhere has no socket, only the address and the token
+    let remote = |token: &str| {
+        Command::new(TestDaemon::get_binary_path())
+            .args(["status", "--output", "plain", "--format", "{phase} {task}"])
+            .env("XDG_RUNTIME_DIR", temp_dir.path())
+            .env("TOMAT_ADDRESS", format!("127.0.0.1:{}", port))
+            .env("TOMAT_TOKEN", token)
+            .output()
+    };
+
+    let output = remote("s3cret")?;
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).trim(),
+        "Work remote"
+    );
+
+    let output = remote("guess")?;
+    assert!(output.stdout.is_empty());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("Missing or wrong token"),
+        "Got: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // Remote clients may look at the timer, but not control it
+    let output = Command::new(TestDaemon::get_binary_path())
+        .arg("stop")
+        .env("XDG_RUNTIME_DIR", temp_dir.path())
+        .env("TOMAT_ADDRESS", format!("127.0.0.1:{}", port))
+        .env("TOMAT_TOKEN", "s3cret")
+        .output()?;
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("not allowed over TCP"),
+        "Got: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(daemon.get_status()?["class"], "work");
+
+    // A request without end is cut off before the token is even checked
+    let mut stream = std::net::TcpStream::connect(("127.0.0.1", port))?;
+    stream.write_all(&vec![b'a'; 64 * 1024])?;
+    let mut response = String::new();
+    std::io::BufReader::new(stream).read_line(&mut response)?;
+    let response: serde_json::Value = serde_json::from_str(&response)?;
+    assert_eq!(response["error"], "invalid_argument");
+
+    Ok(())
+}
+
+#[test]
+fn test_subscribe_pushes_events() -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::BufRead;
+
+    let mut daemon = TestDaemon::start()?;
+
+    let mut subscriber = Command::new(TestDaemon::get_binary_path())
+        .arg("subscribe")
+        .env("XDG_RUNTIME_DIR", daemon._temp_dir.path())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .spawn()?;
+    let mut events = std::io::BufReader::new(subscriber.stdout.take().unwrap())
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(&line.unwrap()).unwrap());
+    // Give the subscription time to reach the daemon
+    thread::sleep(Duration::from_millis(200));
+
+    daemon.send_command(&["start"])?;
+    thread::sleep(Duration::from_millis(1200));
+    daemon.send_command(&["pause"])?;
+    daemon.send_command(&["stop"])?;
+
+    let event = events.next().unwrap();
+    assert_eq!(event["event"], "phase_change");
+    assert_eq!(event["status"]["phase"], "Work");
+
+    // The running countdown ticks until it is paused
+    let mut ticks = 0;
+    let event = loop {
+        let event = events.next().unwrap();
+        if event["event"] != "tick" {
+            break event;
+        }
+        ticks += 1;
+    };
+    assert!(ticks >= 1, "Got no ticks");
+    assert_eq!(event["event"], "pause");
+    assert_eq!(event["status"]["is_paused"], true);
+    assert_eq!(events.next().unwrap()["event"], "stop");
+
+    // Subscribers are done when the daemon stops
+    daemon.daemon_process.kill()?;
+    daemon.daemon_process.wait()?;
+    assert!(events.next().is_none());
+    subscriber.wait()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_slow_client_does_not_block_others() -> Result<(), Box<dyn std::error::Error>> {
+    let daemon = TestDaemon::start()?;
+
+    // A client that connects but never sends its request
+    let _stalled =
+        std::os::unix::net::UnixStream::connect(daemon._temp_dir.path().join("tomat.sock"))?;
+    thread::sleep(Duration::from_millis(100));
+
+    let started = std::time::Instant::now();
+    daemon.send_command(&["start", "--work", "10"])?;
+    let status = daemon.send_command(&["status", "--output", "plain", "--format", "{time}"])?;
+    assert!(
+        matches!(status.as_str(), Some("10:00" | "09:59")),
+        "Got: {}",
+        status
+    );
+    assert!(started.elapsed() < Duration::from_secs(2));
+
+    Ok(())
+}
+
+#[test]
+fn test_trace_transitions() -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::BufRead;
+
+    let temp_dir = tempfile::tempdir()?;
+    let mut daemon = Command::new(TestDaemon::get_binary_path())
+        .args(["daemon", "run", "--trace-transitions"])
+        .env("XDG_RUNTIME_DIR", temp_dir.path())
+        .env("XDG_DATA_HOME", temp_dir.path())
+        .env("XDG_STATE_HOME", temp_dir.path())
+        .env("TOMAT_TESTING", "1")
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .spawn()?;
+    thread::sleep(Duration::from_millis(200));
+
+    let start = Command::new(TestDaemon::get_binary_path())
+        .args(["start", "--task", "report"])
+        .env("XDG_RUNTIME_DIR", temp_dir.path())
+        .output()?;
+    assert!(start.status.success());
+
+    thread::sleep(Duration::from_millis(300));
+    daemon.kill()?;
+    daemon.wait()?;
+    let output: Vec<String> = std::io::BufReader::new(daemon.stdout.take().unwrap())
+        .lines()
+        .collect::<Result<_, _>>()?;
+
+    // Each change names its cause, then lists the fields that changed
+    let start = output
+        .iter()
+        .position(|line| line.contains("client: start"))
+        .expect("The start should be traced");
+    assert!(output[start].starts_with('['), "Got: {}", output[start]);
+    let changes: Vec<&str> = output[start + 1..]
+        .iter()
+        .map(String::as_str)
+        .take_while(|line| line.starts_with("  "))
+        .collect();
+    assert!(
+        changes.contains(&r#"  phase: "Idle" -> "Work""#),
+        "Got: {:?}",
+        changes
+    );
+    assert!(changes.contains(&r#"  task: null -> "report""#));
+
+    Ok(())
+}
@@ -269,6 +269,35 @@ fn test_watch_command_outputs_continuously() -> Result<(), Box<dyn std::error::E
     Ok(())
 }
 
+#[test]
+fn test_status_follow_with_subsecond_interval() -> Result<(), Box<dyn std::error::Error>> {
+    let daemon = TestDaemon::start()?;
+    daemon.send_command(&["start", "--work", "0.2"])?;
+
+    let mut follow_process = Command::new(TestDaemon::get_binary_path())
+        .args(["status", "--follow", "--class-only", "--interval", "0.1"])
+        .env("XDG_RUNTIME_DIR", daemon._temp_dir.path())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .spawn()?;
+
+    thread::sleep(Duration::from_secs(1));
+    follow_process.kill()?;
+    let output = follow_process.wait_with_output()?;
+
+    // The status flags apply to every update
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert!(
+        lines.len() >= 5,
+        "Expected an update every 0.1s, got {} lines",
+        lines.len()
+    );
+    assert!(lines.iter().all(|line| *line == "work"), "Got: {}", stdout);
+
+    Ok(())
+}
+
 #[test]
 fn test_watch_command_respects_interval() -> Result<(), Box<dyn std::error::Error>> {
     let daemon = TestDaemon::start()?;
@@ -350,3 +379,585 @@ fn test_watch_command_exits_when_daemon_stops() -> Result<(), Box<dyn std::error
 
     Ok(())
 }
+
+#[test]
+fn test_status_schema_version_and_schema_flag() -> Result<(), Box<dyn std::error::Error>> {
+    let daemon = TestDaemon::start()?;
+
+    let status = daemon.send_command(&["status"])?;
+    assert_eq!(
+        status["schema_version"], 1,
+        "Waybar output should be versioned"
+    );
+
+    let status = daemon.send_command(&["status", "--output", "i3status-rs"])?;
+    assert_eq!(
+        status["schema_version"], 1,
+        "i3status-rs output should be versioned"
+    );
+
+    // --schema does not need the daemon
+    let output = Command::new(TestDaemon::get_binary_path())
+        .args(["status", "--schema"])
+        .output()?;
+    assert!(output.status.success());
+
+    let schema: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(schema["type"], "object");
+    assert!(schema["properties"].get("schema_version").is_some());
+    assert!(schema["properties"].get("percentage").is_some());
+
+    Ok(())
+}
+
+#[test]
+fn test_privacy_mode_hides_countdown() -> Result<(), Box<dyn std::error::Error>> {
+    let daemon = TestDaemon::start()?;
+
+    daemon.send_command(&["start"])?;
+    daemon.send_command(&["privacy", "on"])?;
+
+    let status = daemon.get_status()?;
+    let text = status.get("text").and_then(|v| v.as_str()).unwrap();
+    assert!(text.contains("focusing"), "Got: {}", text);
+    assert!(!text.contains("25:00") && !text.contains("24:5"));
+    assert_eq!(
+        status.get("tooltip").and_then(|v| v.as_str()),
+        Some("Focusing")
+    );
+
+    // Tracking continues while hidden, and survives a restart of the session
+    daemon.send_command(&["stop"])?;
+    daemon.send_command(&["start"])?;
+    let status = daemon.get_status()?;
+    assert!(status["text"].as_str().unwrap().contains("focusing"));
+
+    daemon.send_command(&["privacy", "off"])?;
+    let status = daemon.get_status()?;
+    let text = status.get("text").and_then(|v| v.as_str()).unwrap();
+    assert!(!text.contains("focusing"), "Got: {}", text);
+
+    Ok(())
+}
+
+#[test]
+fn test_watch_interactive_keys() -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::Write;
+
+    let daemon = TestDaemon::start()?;
+    daemon.send_command(&["start"])?;
+
+    let mut watch_process = Command::new(TestDaemon::get_binary_path())
+        .args(["watch", "--output", "plain", "--interactive"])
+        .env("XDG_RUNTIME_DIR", daemon._temp_dir.path())
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .spawn()?;
+
+    // Space pauses the running timer
+    let mut stdin = watch_process.stdin.take().unwrap();
+    stdin.write_all(b" ")?;
+    stdin.flush()?;
+    thread::sleep(Duration::from_millis(500));
+
+    let status = daemon.get_status()?;
+    assert_eq!(
+        status.get("class").and_then(|v| v.as_str()),
+        Some("work-paused")
+    );
+
+    // q quits the watch loop on its own
+    stdin.write_all(b"q")?;
+    stdin.flush()?;
+    let start = std::time::Instant::now();
+    while watch_process.try_wait()?.is_none() {
+        assert!(
+            start.elapsed() < Duration::from_secs(5),
+            "watch should exit on q"
+        );
+        thread::sleep(Duration::from_millis(50));
+    }
+    assert!(watch_process.wait()?.success());
+
+    Ok(())
+}
+
+#[test]
+fn test_status_tooltip_and_class_only() -> Result<(), Box<dyn std::error::Error>> {
+    let daemon = TestDaemon::start()?;
+
+    let class = daemon.send_command(&["status", "--class-only"])?;
+    assert_eq!(class.as_str(), Some("idle"));
+
+    daemon.send_command(&["start", "--work", "10"])?;
+    daemon.send_command(&["pause"])?;
+
+    let class = daemon.send_command(&["status", "--class-only"])?;
+    assert_eq!(class.as_str(), Some("work-paused"));
+
+    let tooltip = daemon.send_command(&["status", "--tooltip-only"])?;
+    assert_eq!(tooltip.as_str(), Some("Work (1/4) - 10.0min (Paused)"));
+
+    // The flags select a waybar field, so they cannot be combined with --output
+    assert!(
+        daemon
+            .send_command(&["status", "--class-only", "--output", "plain"])
+            .is_err()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_task_label_and_template_modifiers() -> Result<(), Box<dyn std::error::Error>> {
+    let daemon = TestDaemon::start()?;
+    let template = "{phase:upper}{task:?( 📝 {task})}";
+
+    daemon.send_command(&["start"])?;
+    let status = daemon.send_command(&["status", "--output", "plain", "--format", template])?;
+    assert_eq!(status.as_str(), Some("WORK"));
+
+    daemon.send_command(&["start", "--force", "--task", "write spec"])?;
+    let status = daemon.send_command(&["status", "--output", "plain", "--format", template])?;
+    assert_eq!(status.as_str(), Some("WORK 📝 write spec"));
+
+    let tooltip = daemon.send_command(&["status", "--tooltip-only"])?;
+    assert!(tooltip.as_str().unwrap().ends_with("Task: write spec"));
+
+    Ok(())
+}
+
+#[test]
+fn test_completed_today_counter() -> Result<(), Box<dyn std::error::Error>> {
+    let daemon = TestDaemon::start()?;
+    let template = "🍅 {completed}/8";
+
+    daemon.send_command(&["start", "--auto-advance", "all"])?;
+    let status = daemon.send_command(&["status", "--output", "plain", "--format", template])?;
+    assert_eq!(status.as_str(), Some("🍅 0/8"));
+
+    daemon.wait_for_completion(5)?;
+    let status = daemon.send_command(&["status", "--output", "plain", "--format", template])?;
+    assert_eq!(status.as_str(), Some("🍅 1/8"));
+    let tooltip = daemon.send_command(&["status", "--tooltip-only"])?;
+    assert!(tooltip.as_str().unwrap().contains("\nCompleted today: 1"));
+
+    // Skipped work sessions do not count
+    daemon.send_command(&["skip"])?;
+    daemon.send_command(&["skip"])?;
+    let status =
+        daemon.send_command(&["status", "--output", "i3status-rs", "--format", template])?;
+    assert_eq!(status["text"], "🍅 1/8");
+
+    Ok(())
+}
+
+#[test]
+fn test_per_phase_display_formats() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let config_path = temp_dir.path().join("config.toml");
+    std::fs::write(
+        &config_path,
+        r#"
+[display]
+text_format = "{icon} {time}"
+
+[display.formats]
+work = "W"
+break = "BREAK {phase:upper}"
+paused = "P {phase}"
+"#,
+    )?;
+    let daemon = TestDaemon::start_with_config(Some(&config_path))?;
+
+    daemon.send_command(&["start", "--auto-advance", "all"])?;
+    let status = daemon.send_command(&["status", "--output", "plain"])?;
+    assert_eq!(status.as_str(), Some("W"));
+
+    daemon.send_command(&["skip"])?;
+    let status = daemon.send_command(&["status", "--output", "plain"])?;
+    assert_eq!(status.as_str(), Some("BREAK BREAK"));
+
+    daemon.send_command(&["pause"])?;
+    let status = daemon.send_command(&["status", "--output", "plain"])?;
+    assert_eq!(status.as_str(), Some("P Break"));
+
+    // An explicit --format still wins
+    let status = daemon.send_command(&["status", "--output", "plain", "--format", "{phase}"])?;
+    assert_eq!(status.as_str(), Some("Break"));
+
+    Ok(())
+}
+
+#[test]
+fn test_waybar_alt_text() -> Result<(), Box<dyn std::error::Error>> {
+    let daemon = TestDaemon::start()?;
+
+    daemon.send_command(&["start"])?;
+    let status = daemon.send_command(&["status"])?;
+    assert_eq!(status["alt"], "1/4");
+
+    let temp_dir = tempfile::tempdir()?;
+    let config_path = temp_dir.path().join("config.toml");
+    std::fs::write(
+        &config_path,
+        r#"
+[display]
+text_format = "{time}"
+alt_format = "{phase:upper} {session}"
+"#,
+    )?;
+    let daemon = TestDaemon::start_with_config(Some(&config_path))?;
+
+    daemon.send_command(&["start", "--work", "1"])?;
+    let status = daemon.send_command(&["status"])?;
+    assert_eq!(status["text"], "01:00");
+    assert_eq!(status["alt"], "WORK 1/4");
+
+    Ok(())
+}
+
+#[test]
+fn test_display_profiles() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let config_path = temp_dir.path().join("config.toml");
+    std::fs::write(
+        &config_path,
+        r#"
+[display]
+text_format = "{icon} {time}"
+
+[display.profiles.laptop]
+text_format = "{icon}"
+tooltip = "minimal"
+"#,
+    )?;
+    let daemon = TestDaemon::start_with_config(Some(&config_path))?;
+    daemon.send_command(&["start", "--auto-advance", "all"])?;
+
+    // Two bars can show the same daemon differently
+    let laptop = daemon.send_command(&["status", "--profile", "laptop"])?;
+    assert_eq!(laptop["text"], "🍅");
+    assert_eq!(laptop["tooltip"], "Work");
+
+    let desk = daemon.send_command(&["status", "--profile", "full"])?;
+    assert!(desk["text"].as_str().unwrap().starts_with("🍅 "));
+    let tooltip = desk["tooltip"].as_str().unwrap();
+    assert!(tooltip.starts_with("Work (1/4) - 25.0min\nRemaining: "));
+    assert!(tooltip.contains("\nEnds at "), "Got: {}", tooltip);
+
+    // Unknown profiles are an error
+    let status = daemon.send_command(&["status", "--profile", "phone"])?;
+    assert!(status.is_null());
+
+    Ok(())
+}
+
+#[test]
+fn test_output_format_profile_and_tooltip_format() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let config_path = temp_dir.path().join("config.toml");
+    std::fs::write(
+        &config_path,
+        r#"
+[display]
+text_format = "{icon} {time}"
+tooltip_format = "{phase} {state_symbol} {percentage}%"
+
+[display.profiles.plain]
+text_format = "{phase} {session} of {sessions_total}"
+"#,
+    )?;
+    let daemon = TestDaemon::start_with_config(Some(&config_path))?;
+    daemon.send_command(&["start", "--auto-advance", "all", "--sessions", "3"])?;
+
+    let status = daemon.get_status()?;
+    assert_eq!(status["tooltip"], "Work ▶ 0%");
+
+    // The profile named after the output format is used without --profile
+    let plain = daemon.send_command(&["status", "--output", "plain"])?;
+    assert_eq!(plain.as_str(), Some("Work 1/3 of 3"));
+    let plain = daemon.send_command(&["status", "--output", "plain", "--profile", "full"])?;
+    assert!(plain.as_str().unwrap().starts_with("🍅 "));
+
+    Ok(())
+}
+
+#[test]
+fn test_rofi_blocks() -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::Write;
+
+    let daemon = TestDaemon::start()?;
+
+    let mut rofi_process = Command::new(TestDaemon::get_binary_path())
+        .arg("rofi-blocks")
+        .env("XDG_RUNTIME_DIR", daemon._temp_dir.path())
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .spawn()?;
+
+    // Selecting an entry runs its command, and closing stdin ends the mode
+    let mut stdin = rofi_process.stdin.take().unwrap();
+    writeln!(stdin, r#"{{"name":"SELECT_ENTRY","data":"start"}}"#)?;
+    drop(stdin);
+    let output = rofi_process.wait_with_output()?;
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout)?;
+    let updates: Vec<serde_json::Value> = stdout
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<Result<_, _>>()?;
+    assert_eq!(updates.first().unwrap()["lines"][0]["data"], "start");
+    let last = updates.last().unwrap();
+    assert_eq!(last["lines"][0]["text"], "Pause");
+    assert!(last["message"].as_str().unwrap().contains("Work (1/4)"));
+    assert_eq!(daemon.get_status()?["class"], "work");
+
+    Ok(())
+}
+
+#[test]
+fn test_status_fifo() -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::BufRead;
+
+    let temp_dir = tempfile::tempdir()?;
+    let config_path = temp_dir.path().join("config.toml");
+    let fifo_path = temp_dir.path().join("tomat.fifo");
+    std::fs::write(
+        &config_path,
+        format!(
+            "[display]\ntext_format = \"{{phase}} {{time}}\"\nfifo = {:?}\n",
+            fifo_path.display().to_string()
+        ),
+    )?;
+    let daemon = TestDaemon::start_with_config(Some(&config_path))?;
+
+    // The daemon creates the pipe and writes to it once a reader connects
+    let start = std::time::Instant::now();
+    while !fifo_path.exists() {
+        assert!(start.elapsed() < Duration::from_secs(5), "no pipe created");
+        thread::sleep(Duration::from_millis(20));
+    }
+    let (sender, lines) = std::sync::mpsc::channel();
+    thread::spawn(move || {
+        let pipe = std::io::BufReader::new(std::fs::File::open(fifo_path).unwrap());
+        for line in pipe.lines() {
+            if sender.send(line.unwrap()).is_err() {
+                break;
+            }
+        }
+    });
+
+    let timeout = Duration::from_secs(5);
+    assert_eq!(lines.recv_timeout(timeout)?, "Idle 25:00");
+    daemon.send_command(&["start", "--work", "10"])?;
+    let line = lines.recv_timeout(timeout)?;
+    assert!(
+        matches!(line.as_str(), "Work 10:00" | "Work 09:59"),
+        "Got: {}",
+        line
+    );
+
+    // The countdown is written as it ticks
+    let line = lines.recv_timeout(timeout)?;
+    assert!(line.starts_with("Work 09:5"), "Got: {}", line);
+
+    Ok(())
+}
+
+#[test]
+fn test_dwm_root_name() -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::BufRead;
+
+    let daemon = TestDaemon::start()?;
+
+    let mut dwm_process = Command::new(TestDaemon::get_binary_path())
+        .args(["dwm", "--print", "--format", "{phase} {time}"])
+        .args([
+            "--with",
+            "echo Mon",
+            "--separator",
+            " :: ",
+            "--interval",
+            "0.1",
+        ])
+        .env("XDG_RUNTIME_DIR", daemon._temp_dir.path())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .spawn()?;
+    let mut lines = std::io::BufReader::new(dwm_process.stdout.take().unwrap()).lines();
+
+    // Idle ignores --format and uses the idle display
+    assert_eq!(lines.next().unwrap()?, "🍅 25:00 ⏹ :: Mon");
+
+    // Only changes are written
+    daemon.send_command(&["start", "--work", "10"])?;
+    let line = lines.next().unwrap()?;
+    assert!(
+        matches!(line.as_str(), "Work 10:00 :: Mon" | "Work 09:59 :: Mon"),
+        "Got: {}",
+        line
+    );
+
+    dwm_process.kill()?;
+    dwm_process.wait()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_i3bar_protocol_and_clicks() -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::{BufRead, Write};
+
+    let daemon = TestDaemon::start()?;
+
+    let mut i3bar_process = Command::new(TestDaemon::get_binary_path())
+        .args(["i3bar", "--format", "{phase} {time}", "--interval", "0.1"])
+        .env("XDG_RUNTIME_DIR", daemon._temp_dir.path())
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .spawn()?;
+    let mut stdin = i3bar_process.stdin.take().unwrap();
+    let mut lines = std::io::BufReader::new(i3bar_process.stdout.take().unwrap()).lines();
+
+    let header: serde_json::Value = serde_json::from_str(&lines.next().unwrap()?)?;
+    assert_eq!(header["version"], 1);
+    assert_eq!(header["click_events"], true);
+    assert_eq!(lines.next().unwrap()?, "[");
+    let blocks: serde_json::Value = serde_json::from_str(&lines.next().unwrap()?)?;
+    assert_eq!(blocks[0]["name"], "tomat");
+    assert_eq!(blocks[0]["full_text"], "🍅 25:00 ⏹");
+
+    // A left click starts the timer
+    writeln!(stdin, "[")?;
+    writeln!(stdin, r#"{{"name":"tomat","button":1}}"#)?;
+    let line = lines.next().unwrap()?;
+    let blocks: serde_json::Value = serde_json::from_str(line.strip_prefix(',').unwrap())?;
+    let text = blocks[0]["full_text"].as_str().unwrap();
+    assert!(matches!(text, "Work 25:00" | "Work 24:59"), "Got: {}", text);
+    assert_eq!(daemon.get_status()?["class"], "work");
+
+    i3bar_process.kill()?;
+    i3bar_process.wait()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_zellij_output_and_prompt() -> Result<(), Box<dyn std::error::Error>> {
+    let daemon = TestDaemon::start()?;
+
+    // Nothing to show while idle, in the text or JSON prompt
+    let prompt = daemon.send_command(&["prompt"])?;
+    assert!(prompt.is_null());
+    let prompt = daemon.send_command(&["prompt", "--json"])?;
+    assert_eq!(prompt["text"], "");
+    assert_eq!(prompt["class"], "idle");
+
+    daemon.send_command(&["start", "--work", "10"])?;
+    daemon.send_command(&["pause"])?;
+
+    let prompt = daemon.send_command(&["prompt", "--format", "{phase} {time}"])?;
+    assert_eq!(prompt.as_str(), Some("Work 10:00"));
+    let prompt = daemon.send_command(&["prompt", "--json"])?;
+    assert_eq!(prompt["class"], "work-paused");
+    assert_eq!(prompt["color"], "#f9e2af");
+
+    let status = daemon.send_command(&["status", "--output", "zellij", "--format", "{time}"])?;
+    assert_eq!(status.as_str(), Some("\x1b[38;2;249;226;175m10:00\x1b[0m"));
+
+    Ok(())
+}
+
+#[test]
+fn test_i3blocks_and_dwmblocks_output() -> Result<(), Box<dyn std::error::Error>> {
+    let daemon = TestDaemon::start()?;
+    daemon.send_command(&["start", "--work", "10"])?;
+
+    let status = daemon.send_command(&["status", "--output", "i3blocks"])?;
+    assert_eq!(status.as_str(), Some("🍅 10:00 ▶\n10:00\n#f38ba8"));
+
+    let status = daemon.send_command(&["status", "--output", "dwmblocks", "--format", "{time}"])?;
+    assert_eq!(status.as_str(), Some("10:00"));
+
+    Ok(())
+}
+
+#[test]
+fn test_prompt_without_daemon() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let output = Command::new(TestDaemon::get_binary_path())
+        .arg("prompt")
+        .env("XDG_RUNTIME_DIR", temp_dir.path())
+        .output()?;
+
+    // A prompt must not break without a daemon
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(output.stderr.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_display_output() -> Result<(), Box<dyn std::error::Error>> {
+    let daemon = TestDaemon::start()?;
+
+    let status = daemon.send_command(&["status", "--output", "display"])?;
+    assert_eq!(status["phase"], "idle");
+    assert_eq!(status["percent"], 0);
+
+    daemon.send_command(&["start", "--work", "10"])?;
+    daemon.send_command(&["pause"])?;
+
+    let status = daemon.send_command(&["status", "--output", "display"])?;
+    assert_eq!(status["phase"], "work");
+    assert_eq!(status["time"], "10:00");
+    assert_eq!(status["paused"], true);
+    assert_eq!(status["schema_version"], 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_waybar_stream() -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::BufRead;
+
+    let mut daemon = TestDaemon::start()?;
+
+    let mut waybar_process = Command::new(TestDaemon::get_binary_path())
+        .args(["waybar", "--format", "{time}", "--interval", "0.1"])
+        .env("XDG_RUNTIME_DIR", daemon._temp_dir.path())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .spawn()?;
+    let mut lines = std::io::BufReader::new(waybar_process.stdout.take().unwrap()).lines();
+
+    let line: serde_json::Value = serde_json::from_str(&lines.next().unwrap()?)?;
+    assert_eq!(line["class"], "idle");
+
+    // An idle timer prints nothing until it is started
+    thread::sleep(Duration::from_millis(300));
+    daemon.send_command(&["start", "--work", "10"])?;
+    daemon.send_command(&["pause"])?;
+    let mut line: serde_json::Value = serde_json::from_str(&lines.next().unwrap()?)?;
+    if line["class"] == "work" {
+        line = serde_json::from_str(&lines.next().unwrap()?)?;
+    }
+    assert_eq!(line["class"], "work-paused");
+
+    // Without a daemon the module is hidden
+    daemon.daemon_process.kill()?;
+    daemon.daemon_process.wait()?;
+    let line: serde_json::Value = serde_json::from_str(&lines.next().unwrap()?)?;
+    assert_eq!(line["text"], "");
+
+    waybar_process.kill()?;
+    waybar_process.wait()?;
+
+    Ok(())
+}
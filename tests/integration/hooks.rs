@@ -86,7 +86,9 @@ cmd = "{}"
     clear_hook_marker(&temp_path, "work_hook_marker");
 
     // Let work session complete
-    thread::sleep(Duration::from_secs(7));
+    daemon
+        .wait_for_completion(5)
+        .expect("Work session did not complete");
 
     // Timer should have transitioned to Break (paused)
     let status = daemon.get_status().expect("Failed to get status");
@@ -99,9 +101,6 @@ cmd = "{}"
         class
     );
 
-    // Wait for break to complete
-    thread::sleep(Duration::from_secs(4));
-
     // Skip to trigger transition to work (will be paused)
     daemon.send_command(&["skip"]).expect("Failed to skip");
 
@@ -168,7 +167,9 @@ cmd = "{}"
     clear_hook_marker(&temp_path, "work_hook_marker");
 
     // Let work session complete
-    thread::sleep(Duration::from_secs(7));
+    daemon
+        .wait_for_completion(5)
+        .expect("Work session did not complete");
 
     // Timer should have transitioned to Break and be running
     let status = daemon.get_status().expect("Failed to get status");
@@ -180,7 +181,9 @@ cmd = "{}"
     );
 
     // Wait for break to complete - should auto-advance to Work
-    thread::sleep(Duration::from_secs(4));
+    daemon
+        .wait_for_completion(5)
+        .expect("Break did not complete");
 
     // Wait a bit for hook to execute
     thread::sleep(Duration::from_millis(500));
@@ -226,7 +229,9 @@ cmd = "{}"
         .expect("Failed to start timer");
 
     // Let work session complete
-    thread::sleep(Duration::from_secs(7));
+    daemon
+        .wait_for_completion(5)
+        .expect("Work session did not complete");
 
     // Timer should have transitioned to Break (paused)
     let status = daemon.get_status().expect("Failed to get status");
@@ -293,7 +298,9 @@ cmd = "{}"
         .expect("Failed to start timer");
 
     // Let work session complete
-    thread::sleep(Duration::from_secs(7));
+    daemon
+        .wait_for_completion(5)
+        .expect("Work session did not complete");
 
     // Both hooks should have been executed
     thread::sleep(Duration::from_millis(500));
@@ -347,10 +354,14 @@ cmd = "{}"
         .expect("Failed to start timer");
 
     // Let work complete and transition to break
-    thread::sleep(Duration::from_secs(7));
+    daemon
+        .wait_for_completion(5)
+        .expect("Work session did not complete");
 
     // Let break complete
-    thread::sleep(Duration::from_secs(4));
+    daemon
+        .wait_for_completion(5)
+        .expect("Break did not complete");
 
     // Both hooks should have been executed
     thread::sleep(Duration::from_millis(500));
@@ -398,7 +409,9 @@ cmd = "{}"
         .expect("Failed to start timer");
 
     // Let work session complete
-    thread::sleep(Duration::from_secs(7));
+    daemon
+        .wait_for_completion(5)
+        .expect("Work session did not complete");
 
     // End hook should have executed even though timer is now paused
     thread::sleep(Duration::from_millis(500));
@@ -500,3 +513,330 @@ cmd = "{}"
         "work_end hook should have executed after skip"
     );
 }
+
+/// Whether the process is running, not counting zombies waiting to be reaped
+fn process_running(pid: &str) -> bool {
+    fs::read_to_string(format!("/proc/{}/stat", pid))
+        .map(|stat| {
+            stat.rsplit_once(") ")
+                .is_some_and(|(_, rest)| !rest.starts_with('Z'))
+        })
+        .unwrap_or(false)
+}
+
+#[test]
+fn test_hook_background_processes_are_terminated() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let temp_path = temp_dir.path();
+    let work_pid_file = temp_path.join("work.pid");
+    let pause_pid_file = temp_path.join("pause.pid");
+
+    // Both hooks leave a `sleep` running in the background and exit at once
+    let config_path = temp_path.join("config.toml");
+    let config_content = format!(
+        r#"
+[hooks.on_work_start]
+cmd = "sh"
+args = ["-c", "sleep 30 & echo $! > {}"]
+timeout = 1
+
+[hooks.on_pause]
+cmd = "sh"
+args = ["-c", "sleep 30 & echo $! > {}"]
+timeout = 0
+"#,
+        work_pid_file.display(),
+        pause_pid_file.display()
+    );
+    fs::write(&config_path, config_content).expect("Failed to write config");
+
+    let daemon = TestDaemon::start_with_config(Some(&config_path)).expect("Failed to start daemon");
+    daemon
+        .send_command(&["start"])
+        .expect("Failed to start timer");
+    daemon.send_command(&["pause"]).expect("Failed to pause");
+    thread::sleep(Duration::from_millis(500));
+
+    let work_pid = fs::read_to_string(&work_pid_file).expect("work hook should have run");
+    let pause_pid = fs::read_to_string(&pause_pid_file).expect("pause hook should have run");
+    let (work_pid, pause_pid) = (work_pid.trim(), pause_pid.trim());
+    assert!(process_running(work_pid));
+    assert!(process_running(pause_pid));
+
+    // The background process shares the hook's timeout
+    thread::sleep(Duration::from_secs(3));
+    assert!(
+        !process_running(work_pid),
+        "background process of a timed hook should be terminated"
+    );
+
+    // Without a timeout, it runs until the daemon stops
+    assert!(process_running(pause_pid));
+    drop(daemon);
+    thread::sleep(Duration::from_millis(500));
+    assert!(
+        !process_running(pause_pid),
+        "background process of a hook should be terminated with the daemon"
+    );
+}
+
+#[test]
+fn test_plan_complete_hook_and_stop() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let temp_path = temp_dir.path().to_path_buf();
+
+    let hook_script = create_hook_script(&temp_path, "plan_hook.sh", "plan_hook_marker");
+    let config_path = temp_path.join("config.toml");
+    let config_content = format!(
+        r#"
+[timer]
+plan_complete = "stop"
+
+[hooks.on_plan_complete]
+cmd = "{}"
+"#,
+        hook_script.display()
+    );
+    fs::write(&config_path, config_content).expect("Failed to write config");
+
+    let daemon = TestDaemon::start_with_config(Some(&config_path)).expect("Failed to start daemon");
+    daemon
+        .send_command(&["plan", "add", "write spec", "1"])
+        .expect("Failed to plan");
+    daemon
+        .send_command(&["start"])
+        .expect("Failed to start timer");
+
+    // Finishing the last planned task stops the timer
+    daemon.send_command(&["skip"]).expect("Failed to skip");
+    let status = daemon.get_status().expect("Failed to get status");
+    assert_eq!(status["class"], "idle");
+
+    thread::sleep(Duration::from_millis(500));
+    assert!(
+        hook_was_executed(&temp_path, "plan_hook_marker"),
+        "on_plan_complete hook should execute when the plan is done"
+    );
+}
+
+#[test]
+fn test_hook_sees_auto_advance_mode() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let marker = temp_dir.path().join("auto_advance_marker");
+
+    let config_path = temp_dir.path().join("config.toml");
+    let config_content = format!(
+        r#"
+[hooks.on_work_start]
+cmd = "sh"
+args = ["-c", "echo \"$TOMAT_AUTO_ADVANCE\" > {}"]
+"#,
+        marker.display()
+    );
+    fs::write(&config_path, config_content).expect("Failed to write config");
+
+    let daemon = TestDaemon::start_with_config(Some(&config_path)).expect("Failed to start daemon");
+
+    // The mode is passed to hooks as it is written in the config
+    daemon
+        .send_command(&["start", "--auto-advance", "to-break"])
+        .expect("Failed to start timer");
+    thread::sleep(Duration::from_millis(500));
+    let mode = fs::read_to_string(&marker).expect("on_work_start hook should have executed");
+    assert_eq!(mode.trim(), "to-break");
+
+    // Unknown modes are rejected instead of falling back to the config
+    let response = daemon
+        .send_raw(r#"{"command":"start","args":{"auto_advance":"sometimes","force":true}}"#)
+        .expect("Failed to send start");
+    assert_eq!(response["success"], false);
+    assert_eq!(response["error"], "invalid_argument");
+}
+
+#[test]
+fn test_phase_hook_filters() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let marker = temp_dir.path().join("phase_marker");
+
+    let config_path = temp_dir.path().join("config.toml");
+    let config_content = format!(
+        r#"
+[timer]
+auto_advance = "all"
+
+[hooks.on_phase_start]
+cmd = "sh"
+args = ["-c", "echo \"$TOMAT_EVENT\" >> {}"]
+phases = ["break", "long_break"]
+"#,
+        marker.display()
+    );
+    fs::write(&config_path, config_content).expect("Failed to write config");
+
+    let daemon = TestDaemon::start_with_config(Some(&config_path)).expect("Failed to start daemon");
+
+    // The generic hook skips the work phase
+    daemon
+        .send_command(&["start"])
+        .expect("Failed to start timer");
+    thread::sleep(Duration::from_millis(300));
+    assert!(!marker.exists(), "Hook should not run for work");
+
+    daemon
+        .wait_for_completion(5)
+        .expect("Work phase should complete");
+    thread::sleep(Duration::from_millis(300));
+    let events = fs::read_to_string(&marker).expect("on_phase_start hook should have executed");
+    assert_eq!(events.trim(), "break_start");
+}
+
+#[test]
+fn test_state_change_hook_reads_json() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let marker = temp_dir.path().join("state_marker");
+
+    let config_path = temp_dir.path().join("config.toml");
+    let config_content = format!(
+        r#"
+[hooks.on_state_change]
+cmd = "sh"
+args = ["-c", "echo \"$TOMAT_EVENT $(cat)\" >> {}"]
+"#,
+        marker.display()
+    );
+    fs::write(&config_path, config_content).expect("Failed to write config");
+
+    let daemon = TestDaemon::start_with_config(Some(&config_path)).expect("Failed to start daemon");
+
+    daemon
+        .send_command(&["start", "--task", "report"])
+        .expect("Failed to start timer");
+    thread::sleep(Duration::from_millis(300));
+    daemon.send_command(&["pause"]).expect("Failed to pause");
+    thread::sleep(Duration::from_millis(300));
+
+    let changes = fs::read_to_string(&marker).expect("on_state_change hook should have executed");
+    let changes: Vec<(&str, serde_json::Value)> = changes
+        .lines()
+        .map(|line| {
+            let (event, json) = line.split_once(' ').unwrap();
+            (event, serde_json::from_str(json).unwrap())
+        })
+        .collect();
+    assert_eq!(changes.len(), 2, "Got: {:?}", changes);
+
+    let (event, change) = &changes[0];
+    assert_eq!(*event, "phase_change");
+    assert_eq!(change["event"], "phase_change");
+    assert_eq!(change["old"]["phase"], "Idle");
+    assert_eq!(change["new"]["phase"], "Work");
+    assert_eq!(change["new"]["task"], "report");
+
+    let (event, change) = &changes[1];
+    assert_eq!(*event, "pause");
+    assert_eq!(change["old"]["is_paused"], false);
+    assert_eq!(change["new"]["is_paused"], true);
+}
+
+#[test]
+fn test_hooks_receive_session_label() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let marker = temp_dir.path().join("label_marker");
+
+    let config_path = temp_dir.path().join("config.toml");
+    let config_content = format!(
+        r#"
+[hooks.on_pause]
+cmd = "sh"
+args = ["-c", "echo \"$TOMAT_TASK|$TOMAT_TAGS\" >> {}"]
+"#,
+        marker.display()
+    );
+    fs::write(&config_path, config_content).expect("Failed to write config");
+
+    let daemon = TestDaemon::start_with_config(Some(&config_path)).expect("Failed to start daemon");
+
+    // Only a session in progress can be labeled
+    let response = daemon
+        .send_raw(r#"{"command":"annotate","args":{"task":"writing"}}"#)
+        .expect("Failed to annotate");
+    assert_eq!(response["error"], "invalid_state");
+
+    daemon
+        .send_command(&["start", "--label", "writing", "--project", "client-x"])
+        .expect("Failed to start timer");
+    daemon.send_command(&["pause"]).expect("Failed to pause");
+    thread::sleep(Duration::from_millis(300));
+
+    // Relabeling keeps the tags unless new ones are given
+    daemon
+        .send_command(&["annotate", "code review"])
+        .expect("Failed to annotate");
+    daemon.send_command(&["resume"]).expect("Failed to resume");
+    daemon.send_command(&["pause"]).expect("Failed to pause");
+    thread::sleep(Duration::from_millis(300));
+
+    daemon
+        .send_command(&["annotate", "", "--project", "a", "--project", "b"])
+        .expect("Failed to annotate");
+    daemon.send_command(&["resume"]).expect("Failed to resume");
+    daemon.send_command(&["pause"]).expect("Failed to pause");
+    thread::sleep(Duration::from_millis(300));
+
+    let labels = fs::read_to_string(&marker).expect("on_pause hook should have executed");
+    assert_eq!(
+        labels.lines().collect::<Vec<_>>(),
+        ["writing|client-x", "code review|client-x", "|a,b"]
+    );
+}
+
+#[test]
+fn test_goal_reached_hook() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let marker = temp_dir.path().join("goal_marker");
+
+    let config_path = temp_dir.path().join("config.toml");
+    let config_content = format!(
+        r#"
+[timer]
+auto_advance = "all"
+
+[goal]
+daily_pomodoros = 1
+
+[hooks.on_goal_reached]
+cmd = "sh"
+args = ["-c", "echo \"$TOMAT_EVENT\" >> {}"]
+"#,
+        marker.display()
+    );
+    fs::write(&config_path, config_content).expect("Failed to write config");
+
+    let daemon = TestDaemon::start_with_config(Some(&config_path)).expect("Failed to start daemon");
+
+    let message = daemon.send_command(&["goal"]).expect("Failed to get goal");
+    assert_eq!(message.as_str(), Some("0/1 pomodoros today"));
+
+    daemon
+        .send_command(&["start"])
+        .expect("Failed to start timer");
+    daemon
+        .wait_for_completion(5)
+        .expect("Work phase should complete");
+    thread::sleep(Duration::from_millis(300));
+    let events = fs::read_to_string(&marker).expect("on_goal_reached hook should have executed");
+    assert_eq!(events.trim(), "goal_reached");
+
+    let status = daemon.get_status().expect("Failed to get status");
+    assert!(status["tooltip"].as_str().unwrap().contains("\n1/1 today"));
+
+    // A goal set for the daemon replaces the one from the config
+    let message = daemon
+        .send_command(&["goal", "3"])
+        .expect("Failed to set goal");
+    assert_eq!(message.as_str(), Some("1/3 pomodoros today"));
+    let message = daemon
+        .send_command(&["goal", "--reset"])
+        .expect("Failed to reset goal");
+    assert_eq!(message.as_str(), Some("1/1 pomodoros today"));
+}
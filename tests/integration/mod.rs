@@ -1,4 +1,5 @@
 // Integration test modules
+pub mod client;
 pub mod commands;
 pub mod common;
 pub mod daemon;
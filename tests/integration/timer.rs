@@ -191,8 +191,8 @@ fn test_resume_paused_timer() -> Result<(), Box<dyn std::error::Error>> {
     let text = status.get("text").and_then(|v| v.as_str()).unwrap();
     let paused_time = text;
 
-    // Wait a moment to ensure time would advance if not paused
-    thread::sleep(Duration::from_millis(1100));
+    // Move the clock on so the time would advance if not paused
+    daemon.fast_forward(2)?;
 
     // Time should remain the same while paused
     let status = daemon.get_status()?;
@@ -201,7 +201,7 @@ fn test_resume_paused_timer() -> Result<(), Box<dyn std::error::Error>> {
 
     // Resume and verify timer advances
     daemon.send_command(&["resume"])?;
-    thread::sleep(Duration::from_millis(1100));
+    daemon.fast_forward(2)?;
 
     let status = daemon.get_status()?;
     let text = status.get("text").and_then(|v| v.as_str()).unwrap();
@@ -339,6 +339,68 @@ sessions = 3
     Ok(())
 }
 
+#[test]
+fn test_timer_profile_of_the_weekday() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let config_path = temp_dir.path().join("config.toml");
+    std::fs::write(
+        &config_path,
+        r#"
+[timer]
+work = 42.0
+
+[profiles.everyday]
+days = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"]
+work = 30.0
+"#,
+    )?;
+    let daemon = TestDaemon::start_with_config(Some(&config_path))?;
+
+    daemon.send_command(&["start"])?;
+    let status = daemon.get_status()?;
+    assert_eq!(status["text"], "🍅 30:00 ▶");
+
+    // Explicit durations win over the profile
+    daemon.send_command(&["start", "--force", "--work", "10"])?;
+    let status = daemon.get_status()?;
+    assert_eq!(status["text"], "🍅 10:00 ▶");
+
+    Ok(())
+}
+
+#[test]
+fn test_schedule_preset_of_the_time_of_day() -> Result<(), Box<dyn std::error::Error>> {
+    let now = chrono::Local::now();
+    let range = format!(
+        "{}-{}",
+        (now - chrono::Duration::hours(1)).format("%H:%M"),
+        (now + chrono::Duration::hours(1)).format("%H:%M")
+    );
+    let temp_dir = tempfile::tempdir()?;
+    let config_path = temp_dir.path().join("config.toml");
+    std::fs::write(
+        &config_path,
+        format!(
+            r#"
+[schedule.presets]
+"{}" = "focus"
+
+[profiles.focus]
+work = 45.0
+break = 10.0
+"#,
+            range
+        ),
+    )?;
+    let daemon = TestDaemon::start_with_config(Some(&config_path))?;
+
+    daemon.send_command(&["start"])?;
+    let status = daemon.get_status()?;
+    assert_eq!(status["text"], "🍅 45:00 ▶");
+
+    Ok(())
+}
+
 #[test]
 fn test_toggle_uses_config_auto_advance() -> Result<(), Box<dyn std::error::Error>> {
     use std::io::Write;
@@ -378,3 +440,299 @@ auto_advance = "all"
 
     Ok(())
 }
+
+#[test]
+fn test_toggle_starts_like_start() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let config_path = temp_dir.path().join("config.toml");
+    std::fs::write(
+        &config_path,
+        "[timer]\nwork = 10\nsessions = 1\nplan_complete = \"stop\"\n",
+    )?;
+    let daemon = TestDaemon::start_with_config(Some(&config_path))?;
+    daemon.send_command(&["plan", "add", "write spec"])?;
+
+    // Toggling an idle timer picks up the planned task and the plan settings
+    daemon.send_command(&["toggle"])?;
+    let status =
+        daemon.send_command(&["status", "--output", "plain", "--format", "{time} {task}"])?;
+    assert_eq!(status.as_str(), Some("10:00 write spec"));
+
+    daemon.wait_for_completion(5)?;
+    let status = daemon.get_status()?;
+    assert_eq!(status["class"], "idle");
+
+    Ok(())
+}
+
+#[test]
+fn test_postpone_long_break() -> Result<(), Box<dyn std::error::Error>> {
+    let daemon = TestDaemon::start()?;
+    daemon.send_command(&["start", "--work", "1", "--sessions", "2"])?;
+
+    // Nothing to postpone in the first session
+    let response = daemon.send_raw(r#"{"command":"postpone_long_break","args":null}"#)?;
+    assert_eq!(response["error"], "invalid_state");
+
+    daemon.send_command(&["skip"])?;
+    daemon.send_command(&["skip"])?;
+    let message = daemon.send_command(&["postpone-long-break"])?;
+    assert_eq!(
+        message.as_str(),
+        Some("Long break postponed until after the next work session")
+    );
+
+    daemon.send_command(&["skip"])?;
+    let status = daemon.get_status()?;
+    assert_eq!(status["class"], "break-paused");
+
+    // The long break follows the next work session
+    daemon.send_command(&["skip"])?;
+    daemon.send_command(&["skip"])?;
+    let status = daemon.get_status()?;
+    assert_eq!(status["class"], "long-break-paused");
+
+    Ok(())
+}
+
+#[test]
+fn test_skip_merge_banks_break_for_long_break() -> Result<(), Box<dyn std::error::Error>> {
+    let daemon = TestDaemon::start()?;
+
+    daemon.send_command(&[
+        "start",
+        "--work",
+        "1",
+        "--break",
+        "5",
+        "--long-break",
+        "15",
+        "--sessions",
+        "2",
+    ])?;
+
+    // Merging is only allowed during a break
+    let response = daemon.send_raw(r#"{"command":"skip","args":{"merge":true}}"#)?;
+    assert_eq!(response["success"], false);
+    assert_eq!(response["error"], "invalid_state");
+
+    daemon.send_command(&["skip"])?;
+    let message = daemon.send_command(&["skip", "--merge"])?;
+    assert!(
+        message
+            .as_str()
+            .unwrap_or_default()
+            .contains("added to the next long break"),
+        "unexpected message: {}",
+        message
+    );
+
+    // Work starts immediately, not paused
+    let status = daemon.get_status()?;
+    assert_eq!(status.get("class").and_then(|v| v.as_str()), Some("work"));
+
+    // The next long break includes the banked 5 minutes
+    daemon.send_command(&["skip"])?;
+    let status = daemon.get_status()?;
+    let tooltip = status.get("tooltip").and_then(|v| v.as_str()).unwrap();
+    assert!(
+        tooltip.contains("20.0min"),
+        "unexpected tooltip: {}",
+        tooltip
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_break_spends_banked_time() -> Result<(), Box<dyn std::error::Error>> {
+    let daemon = TestDaemon::start()?;
+
+    daemon.send_command(&["start", "--work", "25", "--break", "5"])?;
+    let response = daemon.send_raw(r#"{"command":"break","args":{"banked":true}}"#)?;
+    assert_eq!(response["error"], "invalid_state");
+
+    daemon.send_command(&["skip"])?;
+    daemon.send_command(&["skip", "--merge"])?;
+    let status = daemon.get_status()?;
+    let tooltip = status["tooltip"].as_str().unwrap();
+    assert!(tooltip.ends_with("Banked break: 5m"), "Got: {}", tooltip);
+
+    // The work session ends with a break as long as the bank
+    let message = daemon.send_command(&["break", "--banked"])?;
+    assert_eq!(message.as_str(), Some("Taking a 5m banked break"));
+    let status = daemon.send_command(&["status", "--output", "plain", "--format", "{phase}"])?;
+    assert_eq!(status.as_str(), Some("Break"));
+    let status = daemon.get_status()?;
+    let tooltip = status["tooltip"].as_str().unwrap();
+    assert!(tooltip.contains("5.0min"), "Got: {}", tooltip);
+    assert!(!tooltip.contains("Banked break"), "Got: {}", tooltip);
+
+    Ok(())
+}
+
+#[test]
+fn test_prepare_phase_runs_into_work() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let config_path = temp_dir.path().join("config.toml");
+    std::fs::write(&config_path, "[timer]\nprepare = 2\n")?;
+    let daemon = TestDaemon::start_with_config(Some(&config_path))?;
+
+    daemon.send_command(&["start"])?;
+    let status = daemon.get_status()?;
+    assert_eq!(status["text"], "📋 02:00 ▶");
+    assert_eq!(status["class"], "prepare");
+
+    // Work starts by itself when the preparation is over
+    daemon.wait_for_completion(10)?;
+    let status = daemon.get_status()?;
+    assert_eq!(status["class"], "work");
+    let phase = daemon.send_command(&["status", "--output", "plain", "--format", "{phase}"])?;
+    assert_eq!(phase.as_str(), Some("Work"));
+
+    // Skipping the break goes to the next preparation
+    daemon.send_command(&["skip"])?;
+    daemon.send_command(&["skip"])?;
+    let status = daemon.get_status()?;
+    assert_eq!(status["class"], "prepare-paused");
+
+    Ok(())
+}
+
+#[test]
+fn test_simulate_fast_forwards_the_clock() -> Result<(), Box<dyn std::error::Error>> {
+    let daemon = TestDaemon::start()?;
+
+    // Nothing to skip to while idle
+    assert!(daemon.send_command(&["simulate"]).is_err());
+
+    daemon.send_command(&["start", "--work", "10", "--break", "5"])?;
+    let message = daemon.send_command(&["simulate", "4m"])?;
+    assert_eq!(
+        message.as_str(),
+        Some("Clock advanced by 240s, now 240s ahead")
+    );
+    let status = daemon.send_command(&["status", "--output", "plain", "--format", "{time}"])?;
+    assert_eq!(status.as_str(), Some("06:00"));
+
+    // Without a duration, the running phase ends right away
+    daemon.send_command(&["simulate"])?;
+    thread::sleep(Duration::from_millis(300));
+    let status = daemon.get_status()?;
+    assert_eq!(status["class"], "break-paused");
+
+    Ok(())
+}
+
+#[test]
+fn test_set_time_offset() -> Result<(), Box<dyn std::error::Error>> {
+    let daemon = TestDaemon::start()?;
+    daemon.send_command(&["start", "--work", "10", "--break", "5"])?;
+
+    let response = daemon.send_raw(r#"{"command":"set_time_offset","args":{"seconds":90}}"#)?;
+    assert_eq!(response["data"]["offset"], 90);
+    let status = daemon.send_command(&["status", "--output", "plain", "--format", "{time}"])?;
+    assert_eq!(status.as_str(), Some("08:30"));
+
+    // The offset is absolute, so setting it again does not add up
+    daemon.send_raw(r#"{"command":"set_time_offset","args":{"seconds":60}}"#)?;
+    let status = daemon.send_command(&["status", "--output", "plain", "--format", "{time}"])?;
+    assert_eq!(status.as_str(), Some("09:00"));
+
+    let response = daemon.send_raw(r#"{"command":"set_time_offset","args":{"seconds":-5}}"#)?;
+    assert_eq!(response["success"], false);
+    assert_eq!(response["error"], "invalid_argument");
+
+    Ok(())
+}
+
+#[test]
+fn test_countdown_continues_after_clock_is_set_back() -> Result<(), Box<dyn std::error::Error>> {
+    let daemon = TestDaemon::start()?;
+
+    // Start an hour ahead, then set the clock back to real time
+    daemon.fast_forward(3600)?;
+    daemon.send_command(&["start", "--work", "10", "--break", "5"])?;
+    daemon.send_raw(r#"{"command":"set_time_offset","args":{"seconds":0}}"#)?;
+    let status = daemon.send_command(&["status", "--output", "plain", "--format", "{time}"])?;
+    assert!(
+        matches!(status.as_str(), Some("10:00" | "09:59")),
+        "Got: {}",
+        status
+    );
+
+    // The countdown runs from the new time instead of waiting an hour
+    daemon.send_raw(r#"{"command":"set_time_offset","args":{"seconds":60}}"#)?;
+    let status = daemon.send_command(&["status", "--output", "plain", "--format", "{time}"])?;
+    assert!(
+        matches!(status.as_str(), Some("09:00" | "08:59")),
+        "Got: {}",
+        status
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_start_needs_force_during_a_session() -> Result<(), Box<dyn std::error::Error>> {
+    let daemon = TestDaemon::start()?;
+    daemon.send_command(&["start", "--work", "10", "--break", "5"])?;
+    daemon.fast_forward(60)?;
+
+    // A second start keeps the session going and says how to restart
+    let response = daemon.send_raw(r#"{"command":"start","args":{"work":20}}"#)?;
+    assert_eq!(response["success"], false);
+    assert_eq!(response["error"], "invalid_state");
+    let message = response["message"].as_str().unwrap_or_default();
+    assert!(message.contains("--force"), "Got: {}", message);
+    let status = daemon.send_command(&["status", "--output", "plain", "--format", "{time}"])?;
+    assert!(
+        matches!(status.as_str(), Some("09:00" | "08:59")),
+        "Got: {}",
+        status
+    );
+
+    // While paused, resuming is suggested as well
+    daemon.send_command(&["skip"])?;
+    let response = daemon.send_raw(r#"{"command":"start","args":null}"#)?;
+    let message = response["message"].as_str().unwrap_or_default();
+    assert!(message.contains("1 of 4 sessions done"), "Got: {}", message);
+    assert!(message.contains("tomat resume"), "Got: {}", message);
+
+    daemon.send_command(&["start", "--force", "--work", "20"])?;
+    let status = daemon.get_status()?;
+    assert_eq!(status["class"], "work");
+    assert_eq!(status["text"], "🍅 20:00 ▶");
+
+    Ok(())
+}
+
+#[test]
+fn test_workday_end_stops_new_work_sessions() -> Result<(), Box<dyn std::error::Error>> {
+    let temp_dir = tempfile::tempdir()?;
+    let config_path = temp_dir.path().join("config.toml");
+    // Midnight has always passed, so the workday is over
+    std::fs::write(&config_path, "[schedule]\nworkday_end = \"00:00\"\n")?;
+    let daemon = TestDaemon::start_with_config(Some(&config_path))?;
+
+    for line in [
+        r#"{"command":"start","args":null}"#,
+        r#"{"command":"toggle","args":null}"#,
+    ] {
+        let response = daemon.send_raw(line)?;
+        assert_eq!(response["error"], "invalid_state");
+        let message = response["message"].as_str().unwrap_or_default();
+        assert!(message.contains("ended at 00:00"), "Got: {}", message);
+    }
+    assert_eq!(daemon.get_status()?["class"], "idle");
+
+    // Working anyway finishes the session and its break, then stops
+    daemon.send_command(&["start", "--force", "--auto-advance", "all"])?;
+    assert_eq!(daemon.get_status()?["class"], "work");
+    daemon.send_command(&["skip"])?;
+    assert_eq!(daemon.get_status()?["class"], "break");
+    daemon.send_command(&["skip"])?;
+    assert_eq!(daemon.get_status()?["class"], "idle");
+
+    Ok(())
+}
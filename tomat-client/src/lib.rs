@@ -0,0 +1,638 @@
+//! Async client for the [tomat](https://github.com/jolars/tomat) Pomodoro
+//! timer daemon.
+//!
+//! The daemon listens on a Unix socket and answers one line-delimited JSON
+//! request per connection. [`Client`] takes care of the framing and offers a
+//! typed function for each protocol command.
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), tomat_client::Error> {
+//! let client = tomat_client::Client::default();
+//! client
+//!     .start(&tomat_client::StartOptions {
+//!         work: Some(50.0),
+//!         ..Default::default()
+//!     })
+//!     .await?;
+//! let status = client.status().await?;
+//! println!("{:?}: {}s left", status.phase, status.remaining_seconds);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::net::UnixStream;
+
+/// Errors returned by the client
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Failed to connect to daemon: it is not running")]
+    NotRunning,
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("Invalid message: {0}")]
+    Protocol(#[from] serde_json::Error),
+    /// The daemon answered, but could not carry out the request
+    #[error("{message}")]
+    Daemon {
+        code: Option<ErrorCode>,
+        message: String,
+    },
+}
+
+/// Machine-readable error codes sent by the daemon
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    DaemonNotRunning,
+    DaemonAlreadyRunning,
+    InvalidArgument,
+    InvalidState,
+    UnknownCommand,
+    Protocol,
+    Io,
+    History,
+    Unauthorized,
+    Internal,
+    /// A code added in a newer version of the daemon
+    #[serde(other)]
+    Other,
+}
+
+/// A request sent to the daemon
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Request {
+    pub command: String,
+    pub args: serde_json::Value,
+}
+
+/// The daemon's answer to a request
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Response {
+    pub success: bool,
+    #[serde(default)]
+    pub data: serde_json::Value,
+    pub message: String,
+    /// Set when `success` is false
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorCode>,
+}
+
+impl Response {
+    /// Turn a failed response into an error
+    pub fn into_result(self) -> Result<Self, Error> {
+        if self.success {
+            Ok(self)
+        } else {
+            Err(Error::Daemon {
+                code: self.error,
+                message: self.message,
+            })
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    Idle,
+    Prepare,
+    Work,
+    Review,
+    Break,
+    LongBreak,
+}
+
+/// State of the timer, as returned by [`Client::status`]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Status {
+    pub phase: Phase,
+    pub is_paused: bool,
+    /// Time remaining in the current phase
+    pub remaining_seconds: u64,
+    /// Total duration of the current phase
+    pub duration_minutes: f32,
+    /// Current session number, starting at 1
+    pub current_session: u32,
+    /// Number of work sessions before a long break
+    pub sessions_until_long_break: u32,
+    /// Whether privacy mode hides the timer details
+    #[serde(default)]
+    pub privacy: bool,
+    /// What the user is working on
+    #[serde(default)]
+    pub task: Option<String>,
+    /// Pauses left in a work session with a pause budget
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pauses_left: Option<u32>,
+    /// Break time banked with `skip --merge`
+    #[serde(default)]
+    pub banked_break_seconds: u64,
+    /// Work sessions completed today
+    #[serde(default)]
+    pub completed_today: u32,
+    /// Work sessions to complete each day, if there is a goal
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily_goal: Option<u32>,
+}
+
+/// Options for [`Client::start`]; anything left out uses the daemon's config
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct StartOptions {
+    /// Work duration in minutes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub work: Option<f32>,
+    /// Break duration in minutes
+    #[serde(rename = "break", skip_serializing_if = "Option::is_none")]
+    pub break_time: Option<f32>,
+    /// Long break duration in minutes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub long_break: Option<f32>,
+    /// Work sessions before a long break
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sessions: Option<u32>,
+    /// "none", "all", "to-break" or "to-work"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_advance: Option<String>,
+    /// What you are working on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task: Option<String>,
+    /// Restart even if a session is in progress, resetting the cycle
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub force: bool,
+}
+
+/// A task in the day plan, as returned by [`Client::plan`]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PlannedTask {
+    pub task: String,
+    pub pomodoros: u32,
+    /// Work sessions finished on the task so far
+    #[serde(default)]
+    pub completed: u32,
+}
+
+/// Handling times of one command
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct CommandTiming {
+    pub count: u64,
+    pub total_micros: u64,
+    pub max_micros: u64,
+}
+
+/// Runtime statistics of the daemon, as returned by [`Client::stats`]
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Stats {
+    /// Unix timestamp of when the daemon started
+    pub started_at: u64,
+    pub status_cache_hits: u64,
+    pub status_cache_misses: u64,
+    pub commands: BTreeMap<String, CommandTiming>,
+}
+
+/// Progress towards the daily goal, as returned by [`Client::goal`]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Goal {
+    /// Work sessions completed today
+    pub completed: u32,
+    /// Work sessions to complete today, if there is a goal
+    pub goal: Option<u32>,
+}
+
+/// An event pushed to subscribers, see [`Client::subscribe`]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Event {
+    /// What happened: `phase_change`, `pause`, `resume`, `stop` or `tick`
+    pub event: String,
+    /// State of the timer after the event
+    pub status: Status,
+}
+
+/// The stream of events of a subscription, which ends when the daemon stops
+#[derive(Debug)]
+pub struct Events {
+    lines: Lines<BufReader<UnixStream>>,
+}
+
+impl Events {
+    /// Wait for the next event, or None once the daemon closed the connection
+    pub async fn next(&mut self) -> Result<Option<Event>, Error> {
+        match self.lines.next_line().await? {
+            Some(line) => Ok(Some(serde_json::from_str(&line)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Client for the daemon listening on a socket
+#[derive(Clone, Debug)]
+pub struct Client {
+    socket_path: PathBuf,
+}
+
+impl Default for Client {
+    /// A client for the daemon at [`socket_path()`]
+    fn default() -> Self {
+        Self::new(socket_path())
+    }
+}
+
+impl Client {
+    pub fn new(socket_path: impl Into<PathBuf>) -> Self {
+        Self {
+            socket_path: socket_path.into(),
+        }
+    }
+
+    pub fn socket_path(&self) -> &Path {
+        &self.socket_path
+    }
+
+    /// Connect to the daemon and send a single protocol line
+    async fn connect(&self, request: &str) -> Result<BufReader<UnixStream>, Error> {
+        let mut stream =
+            UnixStream::connect(&self.socket_path)
+                .await
+                .map_err(|e| match e.kind() {
+                    std::io::ErrorKind::NotFound | std::io::ErrorKind::ConnectionRefused => {
+                        Error::NotRunning
+                    }
+                    _ => e.into(),
+                })?;
+
+        stream.write_all(request.as_bytes()).await?;
+        stream.write_all(b"\n").await?;
+
+        Ok(BufReader::new(stream))
+    }
+
+    /// Send a single protocol line and return the response line
+    pub async fn send_raw(&self, request: &str) -> Result<String, Error> {
+        let mut reader = self.connect(request).await?;
+        let mut response = String::new();
+        reader.read_line(&mut response).await?;
+
+        Ok(response.trim_end().to_string())
+    }
+
+    /// Send a command and return the response, failing if the daemon reports
+    /// an error
+    pub async fn request(&self, command: &str, args: serde_json::Value) -> Result<Response, Error> {
+        let request = Request {
+            command: command.to_string(),
+            args,
+        };
+        let response = self.send_raw(&serde_json::to_string(&request)?).await?;
+        serde_json::from_str::<Response>(&response)?.into_result()
+    }
+
+    /// Send a command without arguments and return the daemon's message
+    async fn simple(&self, command: &str) -> Result<String, Error> {
+        Ok(self
+            .request(command, serde_json::Value::Null)
+            .await?
+            .message)
+    }
+
+    /// Start a new Pomodoro cycle
+    pub async fn start(&self, options: &StartOptions) -> Result<String, Error> {
+        Ok(self
+            .request("start", serde_json::to_value(options)?)
+            .await?
+            .message)
+    }
+
+    /// Stop the timer and return to idle
+    pub async fn stop(&self) -> Result<String, Error> {
+        self.simple("stop").await
+    }
+
+    /// Stop the timer after being away for `idle_seconds`, which do not
+    /// count as work
+    pub async fn away(&self, idle_seconds: u64) -> Result<String, Error> {
+        let args = serde_json::json!({ "idle_seconds": idle_seconds });
+        Ok(self.request("away", args).await?.message)
+    }
+
+    pub async fn status(&self) -> Result<Status, Error> {
+        let response = self.request("status", serde_json::Value::Null).await?;
+        Ok(serde_json::from_value(response.data)?)
+    }
+
+    /// Skip to the next phase; with `merge` during a break, start working now
+    /// and add the rest of the break to the next long break
+    pub async fn skip(&self, merge: bool) -> Result<String, Error> {
+        let args = if merge {
+            serde_json::json!({ "merge": true })
+        } else {
+            serde_json::Value::Null
+        };
+        Ok(self.request("skip", args).await?.message)
+    }
+
+    /// Take a normal break instead of the pending long break, and the long
+    /// break after one more work session
+    pub async fn postpone_long_break(&self) -> Result<String, Error> {
+        self.simple("postpone_long_break").await
+    }
+
+    /// Pause the timer if it is running, resume it otherwise
+    pub async fn toggle(&self) -> Result<String, Error> {
+        self.simple("toggle").await
+    }
+
+    /// Spend the banked break time now, ending a work session early or
+    /// extending a break
+    pub async fn take_banked_break(&self) -> Result<String, Error> {
+        Ok(self
+            .request("break", serde_json::json!({ "banked": true }))
+            .await?
+            .message)
+    }
+
+    pub async fn pause(&self) -> Result<String, Error> {
+        self.simple("pause").await
+    }
+
+    pub async fn resume(&self) -> Result<String, Error> {
+        self.simple("resume").await
+    }
+
+    /// Turn privacy mode, which hides the timer details in status output, on
+    /// or off
+    pub async fn privacy(&self, enabled: bool) -> Result<String, Error> {
+        Ok(self
+            .request("privacy", serde_json::json!({ "enabled": enabled }))
+            .await?
+            .message)
+    }
+
+    /// Change the task label of the session in progress; an empty one clears
+    /// it. Tags, if given, replace the current ones.
+    pub async fn annotate(&self, task: &str, tags: Option<&[&str]>) -> Result<String, Error> {
+        let mut args = serde_json::json!({ "task": task });
+        if let Some(tags) = tags {
+            args["tags"] = serde_json::json!(tags);
+        }
+        Ok(self.request("annotate", args).await?.message)
+    }
+
+    /// Save notes on the last finished work session to the history; an
+    /// empty text clears them
+    pub async fn note(&self, text: &str) -> Result<String, Error> {
+        let args = serde_json::json!({ "text": text });
+        Ok(self.request("note", args).await?.message)
+    }
+
+    /// Today's progress towards the daily goal
+    pub async fn goal(&self) -> Result<Goal, Error> {
+        let response = self.request("goal", serde_json::Value::Null).await?;
+        Ok(serde_json::from_value(response.data)?)
+    }
+
+    /// Set today's goal to `pomodoros` work sessions, or with None, go back
+    /// to the goal from the config
+    pub async fn set_goal(&self, pomodoros: Option<u32>) -> Result<Goal, Error> {
+        let args = match pomodoros {
+            Some(pomodoros) => serde_json::json!({ "pomodoros": pomodoros }),
+            None => serde_json::json!({ "reset": true }),
+        };
+        let response = self.request("goal", args).await?;
+        Ok(serde_json::from_value(response.data)?)
+    }
+
+    /// Add a task to the end of the day plan
+    pub async fn plan_add(&self, task: &str, pomodoros: u32) -> Result<String, Error> {
+        let args = serde_json::json!({ "action": "add", "task": task, "pomodoros": pomodoros });
+        Ok(self.request("plan", args).await?.message)
+    }
+
+    /// The planned tasks with their progress
+    pub async fn plan(&self) -> Result<Vec<PlannedTask>, Error> {
+        let response = self
+            .request("plan", serde_json::json!({ "action": "show" }))
+            .await?;
+        Ok(serde_json::from_value(response.data)?)
+    }
+
+    pub async fn plan_clear(&self) -> Result<String, Error> {
+        Ok(self
+            .request("plan", serde_json::json!({ "action": "clear" }))
+            .await?
+            .message)
+    }
+
+    pub async fn stats(&self) -> Result<Stats, Error> {
+        let response = self.request("stats", serde_json::Value::Null).await?;
+        Ok(serde_json::from_value(response.data)?)
+    }
+
+    /// Check that the daemon answers, returning its PID
+    pub async fn ping(&self) -> Result<u32, Error> {
+        let response = self.request("ping", serde_json::Value::Null).await?;
+        response
+            .data
+            .get("pid")
+            .and_then(|pid| pid.as_u64())
+            .map(|pid| pid as u32)
+            .ok_or_else(|| Error::Daemon {
+                code: None,
+                message: "Missing PID in ping response".to_string(),
+            })
+    }
+
+    /// Ask the daemon to exit
+    pub async fn shutdown(&self) -> Result<String, Error> {
+        self.simple("shutdown").await
+    }
+
+    /// Subscribe to the timer's events, which the daemon pushes as they
+    /// happen, instead of polling [`Client::status`]
+    ///
+    /// ```no_run
+    /// # async fn example() -> Result<(), tomat_client::Error> {
+    /// let mut events = tomat_client::Client::default().subscribe().await?;
+    /// while let Some(event) = events.next().await? {
+    ///     println!("{}: {:?}", event.event, event.status.phase);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn subscribe(&self) -> Result<Events, Error> {
+        let request = Request {
+            command: "subscribe".to_string(),
+            args: serde_json::Value::Null,
+        };
+        let mut lines = self
+            .connect(&serde_json::to_string(&request)?)
+            .await?
+            .lines();
+        let response = lines.next_line().await?.unwrap_or_default();
+        serde_json::from_str::<Response>(&response)?.into_result()?;
+        Ok(Events { lines })
+    }
+}
+
+/// Path of the daemon's socket, resolved the same way as by `tomat` itself
+///
+/// This is `$XDG_RUNTIME_DIR/tomat.sock` or `/run/user/<uid>/tomat.sock`, or
+/// on systems without a runtime directory, the fallback directory the daemon
+/// created.
+pub fn socket_path() -> PathBuf {
+    runtime_dir().join("tomat.sock")
+}
+
+fn runtime_dir() -> PathBuf {
+    if let Some(dir) = dirs::runtime_dir() {
+        return dir;
+    }
+
+    let uid = unsafe { libc::getuid() };
+    let system_dir = PathBuf::from(format!("/run/user/{}", uid));
+    if system_dir.is_dir() {
+        return system_dir;
+    }
+
+    dirs::cache_dir()
+        .map(|dir| dir.join("tomat").join("run"))
+        .into_iter()
+        .chain(std::iter::once(
+            std::env::temp_dir().join(format!("tomat-{}", uid)),
+        ))
+        .find(|dir| dir.is_dir())
+        .unwrap_or(system_dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::UnixListener;
+
+    /// Answer a single request with `response`, returning the request
+    async fn serve_once(listener: UnixListener, response: &str) -> Request {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        reader.read_line(&mut line).await.unwrap();
+
+        let mut stream = reader.into_inner();
+        stream.write_all(response.as_bytes()).await.unwrap();
+        stream.write_all(b"\n").await.unwrap();
+        serde_json::from_str(&line).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_start_sends_only_given_options() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("tomat.sock");
+        let listener = UnixListener::bind(&path).unwrap();
+        let client = Client::new(&path);
+
+        let options = StartOptions {
+            work: Some(50.0),
+            break_time: Some(10.0),
+            ..Default::default()
+        };
+        let (request, message) = tokio::join!(
+            serve_once(
+                listener,
+                r#"{"success":true,"data":null,"message":"Started"}"#
+            ),
+            client.start(&options)
+        );
+
+        assert_eq!(request.command, "start");
+        assert_eq!(
+            request.args,
+            serde_json::json!({ "work": 50.0, "break": 10.0 })
+        );
+        assert_eq!(message.unwrap(), "Started");
+    }
+
+    #[tokio::test]
+    async fn test_status_is_parsed() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("tomat.sock");
+        let listener = UnixListener::bind(&path).unwrap();
+        let client = Client::new(&path);
+
+        let response = serde_json::json!({
+            "success": true,
+            "message": "Status retrieved",
+            "data": {
+                "phase": "LongBreak",
+                "is_paused": true,
+                "remaining_seconds": 600,
+                "duration_minutes": 15.0,
+                "current_session": 4,
+                "sessions_until_long_break": 4
+            }
+        })
+        .to_string();
+        let (_, status) = tokio::join!(serve_once(listener, &response), client.status());
+
+        let status = status.unwrap();
+        assert_eq!(status.phase, Phase::LongBreak);
+        assert!(status.is_paused);
+        assert_eq!(status.remaining_seconds, 600);
+        assert!(status.task.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_streams_events() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("tomat.sock");
+        let listener = UnixListener::bind(&path).unwrap();
+        let client = Client::new(&path);
+
+        let status = r#"{"phase":"Work","is_paused":false,"remaining_seconds":1500,"duration_minutes":25.0,"current_session":1,"sessions_until_long_break":4}"#;
+        let response = format!(
+            "{}\n{}\n{}",
+            r#"{"success":true,"data":null,"message":"Subscribed to events"}"#,
+            format_args!(r#"{{"event":"phase_change","status":{}}}"#, status),
+            format_args!(r#"{{"event":"tick","status":{}}}"#, status),
+        );
+        let (request, events) = tokio::join!(serve_once(listener, &response), client.subscribe());
+        assert_eq!(request.command, "subscribe");
+
+        // The server closed the connection after the events
+        let mut events = events.unwrap();
+        let event = events.next().await.unwrap().unwrap();
+        assert_eq!(event.event, "phase_change");
+        assert_eq!(event.status.phase, Phase::Work);
+        assert_eq!(events.next().await.unwrap().unwrap().event, "tick");
+        assert!(events.next().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_daemon_errors() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("tomat.sock");
+        let client = Client::new(&path);
+
+        assert!(matches!(client.stop().await, Err(Error::NotRunning)));
+
+        let listener = UnixListener::bind(&path).unwrap();
+        let response = r#"{"success":false,"data":null,"message":"Cannot skip when timer is idle","error":"invalid_state"}"#;
+        let (_, result) = tokio::join!(serve_once(listener, response), client.skip(false));
+        match result {
+            Err(Error::Daemon { code, message }) => {
+                assert_eq!(code, Some(ErrorCode::InvalidState));
+                assert_eq!(message, "Cannot skip when timer is idle");
+            }
+            other => panic!("Expected a daemon error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_unknown_error_codes_are_accepted() {
+        let response: Response = serde_json::from_str(
+            r#"{"success":false,"data":null,"message":"Nope","error":"something_new"}"#,
+        )
+        .unwrap();
+        assert_eq!(response.error, Some(ErrorCode::Other));
+    }
+}